@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
-use directories::ProjectDirs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use directories::{BaseDirs, ProjectDirs};
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 pub enum BackendType {
     Kitty,
     // Sixel, // Will be added back later
@@ -16,7 +17,7 @@ impl Default for BackendType {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 pub struct Resolution {
     pub width: u32,
     pub height: u32,
@@ -28,59 +29,772 @@ impl Default for Resolution {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(default)]
 pub struct PreviewConfig {
-    #[serde(default)]
     pub backend: BackendType,
-    #[serde(default)]
     pub progressive: bool,
-    #[serde(default)]
     pub resolution: Resolution,
+    /// Show the preview pane contents at all
+    pub enabled: bool,
+    /// Files larger than this many bytes are not previewed
+    pub max_size: u64,
+    /// Lines of a text file shown in the preview; only these are read
+    pub max_lines: usize,
+    /// Add up the size of the previewed directory in the background
+    pub directory_sizes: bool,
+    /// Show the last `max_lines` lines of `.log` files instead of the first
+    pub tail_logs: bool,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            backend: BackendType::default(),
+            progressive: false,
+            resolution: Resolution::default(),
+            enabled: true,
+            max_size: 1024 * 1024,
+            max_lines: 100,
+            directory_sizes: true,
+            tail_logs: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DisplayConfig {
+    pub show_hidden_files: bool,
+    pub show_tabs: bool,
+    /// Size and modification date columns
+    pub show_file_details: bool,
+    /// Bottom line with the most useful keys for the focused pane
+    pub show_key_hints: bool,
+    /// XDG folders, bookmarks, disks and remotes on the left
+    pub show_left_pane: bool,
+    /// Tasks and info panels at the bottom
+    pub show_footer: bool,
+    /// Width of the preview (or the right list) in percent of the space
+    /// next to the left pane, the file list takes the rest
+    pub preview_width: u16,
+    /// Interface language, `en` or `ru`. Unset follows the system locale.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// `binary` for sizes in KiB and MiB, `decimal` for kB and MB
+    pub size_units: String,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            show_hidden_files: false,
+            show_tabs: false,
+            show_file_details: true,
+            show_key_hints: true,
+            show_left_pane: true,
+            show_footer: true,
+            preview_width: 50,
+            language: None,
+            size_units: "binary".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SortConfig {
+    /// `name`, `size` or `date`
+    pub field: String,
+    /// `ascending` or `descending`
+    pub order: String,
+    /// List directories before files
+    pub separate_dirs: bool,
+    /// Compare numbers in names by value, so `file2` comes before `file10`
+    pub natural: bool,
+}
+
+impl Default for SortConfig {
+    fn default() -> Self {
+        Self {
+            field: "name".to_string(),
+            order: "ascending".to_string(),
+            separate_dirs: true,
+            natural: true,
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+/// Layout version written by this build. Older files are upgraded by `migrate`.
+pub const CONFIG_VERSION: u32 = 2;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct Config {
+    /// Layout version of the file, see `CONFIG_VERSION`. Files without it are version 0.
+    #[serde(default)]
+    pub version: u32,
     #[serde(default)]
     pub keybindings: Keybindings,
     #[serde(default)]
     pub theme: Theme,
     #[serde(default)]
-    pub bookmarks: HashMap<String, PathBuf>,
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub display: DisplayConfig,
+    #[serde(default)]
+    pub sort: SortConfig,
     #[serde(default)]
     pub preview: PreviewConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub behavior: BehaviorConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    #[serde(default)]
+    pub commands: Vec<CustomCommand>,
+    /// Directories listed above everything else in their parent, by absolute path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<PathBuf>,
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    #[serde(default)]
+    pub disks: DisksConfig,
+    /// Saved remote locations by name, e.g. `nas = "me@nas.local:/srv"`.
+    /// `user@host:/path` is mounted with sshfs, `https://...` with davfs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remotes: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            keybindings: Keybindings::default(),
+            theme: Theme::default(),
+            bookmarks: Vec::new(),
+            display: DisplayConfig::default(),
+            sort: SortConfig::default(),
+            preview: PreviewConfig::default(),
+            search: SearchConfig::default(),
+            behavior: BehaviorConfig::default(),
+            archive: ArchiveConfig::default(),
+            terminal: TerminalConfig::default(),
+            commands: Vec::new(),
+            pinned: Vec::new(),
+            plugins: PluginsConfig::default(),
+            disks: DisksConfig::default(),
+            remotes: BTreeMap::new(),
+        }
+    }
+}
+
+/// A bookmarked directory, written as a `[[bookmarks]]` table.
+/// A `["name", "path"]` pair is read as well.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(from = "BookmarkEntry")]
+pub struct Bookmark {
+    pub name: String,
+    pub path: PathBuf,
+    /// Key that jumps to the bookmark after `` ` ``
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<char>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BookmarkEntry {
+    Pair(String, PathBuf),
+    Table {
+        name: String,
+        path: PathBuf,
+        #[serde(default)]
+        key: Option<char>,
+    },
+}
+
+impl From<BookmarkEntry> for Bookmark {
+    fn from(entry: BookmarkEntry) -> Self {
+        match entry {
+            BookmarkEntry::Pair(name, path) => Self { name, path, key: None },
+            BookmarkEntry::Table { name, path, key } => Self { name, path, key },
+        }
+    }
+}
+
+/// A user-defined command, run from the command palette
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct CustomCommand {
+    /// Key that runs the command in the palette
+    pub key: char,
+    pub name: String,
+    /// Shell command. `{file}`, `{files}` and `{dir}` expand to the item under
+    /// the cursor, the selection and the current directory.
+    pub cmd: String,
+    /// Run in the embedded terminal instead of as a background process
+    #[serde(default)]
+    pub run_in_terminal: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct BehaviorConfig {
+    /// How many copy, move and archive tasks may run at once
+    pub max_concurrent_tasks: usize,
+    /// How many quick metadata tasks (chmod, create, delete, ...) may run at once
+    pub max_concurrent_light_tasks: usize,
+    /// Seconds after which completed tasks disappear from the footer, 0 keeps them
+    pub completed_task_ttl_secs: u64,
+    /// Ask before deleting files
+    pub confirm_delete: bool,
+    /// Ask before a paste overwrites existing files
+    pub confirm_overwrite: bool,
+    /// Command that opens a terminal for `c T`, run with `sh -c` in the
+    /// current directory; `{dir}` is replaced by its quoted path. Unset
+    /// falls back to `$TERMINAL`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    /// Command that opens a file on Enter, run with `sh -c` in the file's
+    /// directory; `{file}` is replaced by its quoted path. Unset uses
+    /// `xdg-open {file}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opener: Option<String>,
+    /// Run `opener` in the embedded terminal, for editors such as vim
+    pub opener_in_terminal: bool,
+    /// Deleting moves files to the trash instead of removing them
+    pub use_trash: bool,
+    /// Clicks and the wheel work in the panes; off leaves the mouse to the
+    /// terminal emulator, e.g. for selecting text
+    pub mouse: bool,
+}
+
+impl Default for BehaviorConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_tasks: 4,
+            max_concurrent_light_tasks: 8,
+            completed_task_ttl_secs: 10,
+            confirm_delete: true,
+            confirm_overwrite: true,
+            terminal: None,
+            opener: None,
+            opener_in_terminal: false,
+            use_trash: false,
+            mouse: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Format preselected in the archive dialog: `zip`, `tar`, `tar.gz`, `tar.bz2` or `tar.xz`
+    pub default_format: String,
+    /// Compression level preselected in the archive dialog, from 0 (store) to 9 (best)
+    pub compression_level: u32,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self { default_format: "zip".to_string(), compression_level: 6 }
+    }
+}
+
+/// Which mounts the Disks block lists
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DisksConfig {
+    /// List block-device mounts such as `/` and `/home`, not only removable
+    /// and network ones
+    pub show_system: bool,
+    /// File system types never listed, e.g. pseudo file systems
+    pub hide_fs_types: Vec<String>,
+}
+
+impl Default for DisksConfig {
+    fn default() -> Self {
+        let hidden = [
+            "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts", "devtmpfs",
+            "efivarfs", "fusectl", "hugetlbfs", "mqueue", "nsfs", "overlay", "proc", "pstore", "ramfs",
+            "securityfs", "squashfs", "sysfs", "tmpfs", "tracefs",
+        ];
+        Self { show_system: true, hide_fs_types: hidden.iter().map(|fs_type| fs_type.to_string()).collect() }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Lines kept above the visible screen of the embedded terminal
+    pub scrollback: usize,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self { scrollback: 1000 }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct SearchConfig {
+    /// Directories indexed in the background for instant search, e.g. `["~/projects"]`
+    #[serde(default)]
+    pub index_roots: Vec<PathBuf>,
+    /// Match letter case exactly in every query syntax
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct PluginsConfig {
+    /// Names of plugins that are turned off and not started
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    /// Permissions the user agreed to, by plugin name. A plugin asking for
+    /// anything not listed here is not started until the user agrees again.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub permissions: BTreeMap<String, PluginPermissions>,
+}
+
+/// Access a plugin asks for in the `[permissions]` section of its
+/// `plugin.toml`. Only shown to the user for consent, not enforced.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct PluginPermissions {
+    /// Paths the plugin reads, e.g. `["~/Documents"]`
+    #[serde(default)]
+    pub read_paths: Vec<String>,
+    /// Paths the plugin writes to
+    #[serde(default)]
+    pub write_paths: Vec<String>,
+    #[serde(default)]
+    pub network: bool,
+}
+
+impl PluginPermissions {
+    /// The part of these permissions that `granted` does not include
+    pub fn not_covered_by(&self, granted: &PluginPermissions) -> PluginPermissions {
+        let missing = |paths: &[String], granted: &[String]| {
+            paths.iter().filter(|path| !granted.contains(path)).cloned().collect()
+        };
+        PluginPermissions {
+            read_paths: missing(&self.read_paths, &granted.read_paths),
+            write_paths: missing(&self.write_paths, &granted.write_paths),
+            network: self.network && !granted.network,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read_paths.is_empty() && self.write_paths.is_empty() && !self.network
+    }
+
+    /// One line per permission, e.g. `read ~/Documents`
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.read_paths.iter().map(|path| format!("read {}", path)).collect();
+        lines.extend(self.write_paths.iter().map(|path| format!("write {}", path)));
+        if self.network {
+            lines.push("network access".to_string());
+        }
+        lines
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
 pub struct Keybindings {
     // Add keybindings here later
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Default, PartialEq)]
 pub struct Theme {
     // Add theme settings here later
     #[serde(default)]
     pub color_scheme: Option<String>,
+    /// Per-category overrides for file name colors, e.g. `executable = "#50fa7b"`
+    #[serde(default)]
+    pub file_colors: HashMap<String, String>,
+}
+
+/// Environment variable naming a directory to use for all corvus files
+/// instead of the platform defaults, e.g. in tests
+pub const CONFIG_DIR_ENV: &str = "CORVUS_CONFIG_DIR";
+
+fn dir_override() -> Option<PathBuf> {
+    std::env::var_os(CONFIG_DIR_ENV).filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
+
+/// Directory holding `config.toml`, the `themes/` directory and the session
+pub fn config_dir() -> Option<PathBuf> {
+    dir_override().or_else(|| ProjectDirs::from("", "", "corvus").map(|proj_dirs| proj_dirs.config_dir().to_path_buf()))
+}
+
+/// Directory for state that is not configuration, such as queued tasks
+pub fn data_dir() -> Option<PathBuf> {
+    dir_override().or_else(|| ProjectDirs::from("", "", "corvus").map(|proj_dirs| proj_dirs.data_dir().to_path_buf()))
+}
+
+/// The user's trash, `$XDG_DATA_HOME/Trash`. With `CORVUS_CONFIG_DIR` set it
+/// lies in that directory instead, so a test never fills the real one.
+pub fn trash_dir() -> Option<PathBuf> {
+    match dir_override() {
+        Some(dir) => Some(dir.join("Trash")),
+        None => BaseDirs::new().map(|dirs| dirs.data_dir().join("Trash")),
+    }
+}
+
+/// Directory for files that can be recreated, such as remote mount points
+pub fn cache_dir() -> Option<PathBuf> {
+    dir_override().or_else(|| ProjectDirs::from("", "", "corvus").map(|proj_dirs| proj_dirs.cache_dir().to_path_buf()))
 }
 
-pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "rtfm", "rust-tui-fm") {
-        let config_path = proj_dirs.config_dir().join("config.toml");
+/// Path of `config.toml`, which may not exist yet
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Loads `config.toml`, falling back to defaults when there is none. Settings
+/// with invalid values are dropped one by one instead of discarding the whole
+/// file; each dropped setting is described in the returned warnings.
+pub fn load_config() -> Result<(Config, Vec<String>), Box<dyn std::error::Error>> {
+    if let Some(config_path) = config_path() {
         if config_path.exists() {
             let config_content = fs::read_to_string(config_path)?;
-            let config: Config = toml::from_str(&config_content)?;
-            return Ok(config);
+            return Ok(parse_config(&config_content)?);
+        }
+    }
+    Ok((Config::default(), Vec::new()))
+}
+
+/// Parses a config, keeping every setting that is valid on its own. Only a
+/// TOML syntax error, which carries the line and column, fails the whole file.
+/// Files written by older versions are migrated first.
+pub fn parse_config(content: &str) -> Result<(Config, Vec<String>), toml::de::Error> {
+    let mut user: toml::Table = toml::from_str(content)?;
+    migrate(&mut user);
+    let mut root = toml::Value::try_from(Config::default()).expect("default config serializes");
+    let mut warnings = Vec::new();
+    for (key, value) in user {
+        merge_valid(&mut root, &mut vec![key], value, &mut warnings);
+    }
+    let config = Config::deserialize(root).expect("merged config only holds valid settings");
+    Ok((config, warnings))
+}
+
+/// Upgrades a parsed file to `CONFIG_VERSION`, moving renamed settings to
+/// their new place so they are not dropped as unknown keys.
+fn migrate(user: &mut toml::Table) {
+    let version = user.get("version").and_then(|version| version.as_integer()).unwrap_or(0);
+    if version > CONFIG_VERSION as i64 {
+        log::warn!("config.toml is version {}, newer than the supported {}", version, CONFIG_VERSION);
+        return;
+    }
+    if version < 1 {
+        migrate_v0_archive(user);
+    }
+    if version < 2 {
+        migrate_v1_bookmarks(user);
+    }
+    if version < CONFIG_VERSION as i64 {
+        log::info!("Migrated config.toml from version {} to {}", version, CONFIG_VERSION);
+        user.insert("version".to_string(), toml::Value::Integer(CONFIG_VERSION as i64));
+    }
+}
+
+/// Version 0 kept the archive defaults in `[behavior]`
+fn migrate_v0_archive(user: &mut toml::Table) {
+    let Some(toml::Value::Table(behavior)) = user.get_mut("behavior") else {
+        return;
+    };
+    let moved: Vec<(&str, toml::Value)> = [("archive_compression_level", "compression_level"), ("default_archive_format", "default_format")]
+        .into_iter()
+        .filter_map(|(old, new)| behavior.remove(old).map(|value| (new, value)))
+        .collect();
+    if moved.is_empty() {
+        return;
+    }
+    let archive = user.entry("archive").or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let toml::Value::Table(archive) = archive {
+        for (key, value) in moved {
+            archive.entry(key).or_insert(value);
+        }
+    }
+}
+
+/// Versions 0 and 1 kept bookmarks in a `[bookmarks]` table of name to path,
+/// which had no order and no room for a jump key
+fn migrate_v1_bookmarks(user: &mut toml::Table) {
+    let Some(toml::Value::Table(bookmarks)) = user.remove("bookmarks") else {
+        return;
+    };
+    let bookmarks = bookmarks
+        .into_iter()
+        .map(|(name, path)| {
+            let mut entry = toml::Table::new();
+            entry.insert("name".to_string(), toml::Value::String(name));
+            entry.insert("path".to_string(), path);
+            toml::Value::Table(entry)
+        })
+        .collect();
+    user.insert("bookmarks".to_string(), toml::Value::Array(bookmarks));
+}
+
+fn table_at<'a>(root: &'a mut toml::Value, path: &[String]) -> &'a mut toml::Table {
+    path.iter()
+        .fold(root, |value, key| &mut value[key.as_str()])
+        .as_table_mut()
+        .expect("parent of a merged setting is a table")
+}
+
+fn check(root: &toml::Value) -> Result<(), toml::de::Error> {
+    Config::deserialize(root.clone()).map(|_| ())
+}
+
+/// Sets `value` at `path` in `root` if the result is still a valid `Config`.
+/// Otherwise tables are merged key by key and arrays keep their valid items,
+/// so a bad value only loses itself.
+fn merge_valid(root: &mut toml::Value, path: &mut Vec<String>, value: toml::Value, warnings: &mut Vec<String>) {
+    let (parent, key) = path.split_at(path.len() - 1);
+    let key = key[0].clone();
+    let previous = table_at(root, parent).insert(key.clone(), value.clone());
+    let Err(error) = check(root) else {
+        return;
+    };
+    let restore = |root: &mut toml::Value| match &previous {
+        Some(previous) => table_at(root, parent).insert(key.clone(), previous.clone()),
+        None => table_at(root, parent).remove(&key),
+    };
+    restore(root);
+    let name = path.join(".");
+    match value {
+        toml::Value::Table(entries) if previous.as_ref().is_none_or(|previous| previous.is_table()) => {
+            if previous.is_none() {
+                table_at(root, parent).insert(key.clone(), toml::Value::Table(toml::Table::new()));
+            }
+            if check(root).is_err() {
+                restore(root);
+                warnings.push(format!("{}: {}", name, error.message()));
+                return;
+            }
+            for (entry_key, entry) in entries {
+                path.push(entry_key);
+                merge_valid(root, path, entry, warnings);
+                path.pop();
+            }
+        }
+        toml::Value::Array(items) => {
+            table_at(root, parent).insert(key.clone(), toml::Value::Array(Vec::new()));
+            if check(root).is_err() {
+                restore(root);
+                warnings.push(format!("{}: {}", name, error.message()));
+                return;
+            }
+            let mut kept = Vec::new();
+            for (index, item) in items.into_iter().enumerate() {
+                kept.push(item);
+                table_at(root, parent).insert(key.clone(), toml::Value::Array(kept.clone()));
+                if let Err(error) = check(root) {
+                    kept.pop();
+                    warnings.push(format!("{}[{}]: {}", name, index, error.message()));
+                }
+            }
+            table_at(root, parent).insert(key.clone(), toml::Value::Array(kept));
         }
+        _ => warnings.push(format!("{}: {}", name, error.message())),
     }
-    Ok(Config::default())
 }
 
 pub fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "rtfm", "rust-tui-fm") {
-        let config_path = proj_dirs.config_dir();
-        fs::create_dir_all(config_path)?;
+    if let Some(config_path) = config_dir() {
+        fs::create_dir_all(&config_path)?;
         let config_file = config_path.join("config.toml");
         let toml_string = toml::to_string_pretty(config)?;
-        fs::write(config_file, toml_string)?;
+        write_atomic(&config_file, toml_string.as_bytes())?;
     }
     Ok(())
 }
+
+/// Replaces `path` with `contents` so that it never holds a partial write:
+/// the data goes to a temporary file in the same directory, is flushed to
+/// disk and then renamed over `path`. On failure the old file is untouched.
+/// A symlink is followed, so the file it points to is replaced and the link
+/// stays, and the new file keeps the permissions of the old one.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let path = resolve_symlinks(path);
+    let file_name = path.file_name().ok_or_else(|| std::io::Error::other("not a file path"))?;
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id()));
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        if let Ok(metadata) = fs::metadata(&path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp_path, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// The file that `path` leads to through any chain of symlinks. A dangling
+/// link resolves to its missing target, which the write then creates.
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    let mut resolved = path.to_path_buf();
+    // The kernel gives up with ELOOP after as many links in a row
+    for _ in 0..40 {
+        let Ok(target) = fs::read_link(&resolved) else {
+            break;
+        };
+        resolved = match resolved.parent() {
+            Some(parent) => parent.join(target),
+            None => target,
+        };
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_plugin_permissions_diff_against_the_granted_set() {
+        let requested: PluginPermissions = toml::from_str(
+            r#"
+            read_paths = ["~/Documents", "~/Music"]
+            network = true
+            "#,
+        )
+        .unwrap();
+        let granted = PluginPermissions { read_paths: vec!["~/Documents".to_string()], ..Default::default() };
+        let missing = requested.not_covered_by(&granted);
+        assert_eq!(missing.describe(), vec!["read ~/Music", "network access"]);
+        assert!(requested.not_covered_by(&requested).is_empty());
+        // Asking for less than before needs no new consent
+        assert!(granted.not_covered_by(&requested).is_empty());
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_the_file_or_leaves_it_alone() {
+        let dir = TempDir::new("write_atomic").unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "old").unwrap();
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+
+        // Renaming over a non-empty directory fails and leaves no temporary file
+        let blocked = dir.path().join("blocked");
+        fs::create_dir(&blocked).unwrap();
+        fs::write(blocked.join("inside"), "keep").unwrap();
+        assert!(write_atomic(&blocked, b"data").is_err());
+        assert_eq!(fs::read_to_string(blocked.join("inside")).unwrap(), "keep");
+        let mut names: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        names.sort();
+        assert_eq!(names, ["blocked", "config.toml"]);
+    }
+
+    #[test]
+    fn test_write_atomic_follows_symlinks_and_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new("write_atomic_link").unwrap();
+        let dotfiles = dir.path().join("dotfiles");
+        fs::create_dir(&dotfiles).unwrap();
+        let target = dotfiles.join("config.toml");
+        fs::write(&target, "old").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        let link = dir.path().join("config.toml");
+        std::os::unix::fs::symlink("dotfiles/config.toml", &link).unwrap();
+
+        write_atomic(&link, b"new").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_dropped_individually() {
+        let content = r##"
+[theme]
+color_scheme = "Nord"
+
+[bookmarks]
+projects = "/home/me/projects"
+broken = 42
+
+[behavior]
+max_concurrent_tasks = "four"
+confirm_delete = false
+
+[[commands]]
+key = "v"
+name = "Open in VLC"
+cmd = "vlc {files}"
+
+[[commands]]
+name = "No key"
+cmd = "true"
+"##;
+        let (config, warnings) = parse_config(content).unwrap();
+        assert_eq!(config.theme.color_scheme.as_deref(), Some("Nord"));
+        assert_eq!(config.bookmarks.len(), 1);
+        assert_eq!(config.behavior.max_concurrent_tasks, BehaviorConfig::default().max_concurrent_tasks);
+        assert!(!config.behavior.confirm_delete);
+        assert_eq!(config.commands.len(), 1);
+        assert_eq!(warnings.len(), 3);
+        // `broken` sorts first when the old bookmarks table becomes a list
+        assert!(warnings.iter().any(|warning| warning.starts_with("bookmarks[0]:")));
+        assert!(warnings.iter().any(|warning| warning.starts_with("behavior.max_concurrent_tasks:")));
+        assert!(warnings.iter().any(|warning| warning.starts_with("commands[1]:")));
+    }
+
+    #[test]
+    fn test_old_files_are_migrated() {
+        let content = r#"
+[bookmarks]
+projects = "/home/me/projects"
+music = "/home/me/music"
+
+[behavior]
+confirm_delete = false
+archive_compression_level = 2
+default_archive_format = "tar.xz"
+"#;
+        let (config, warnings) = parse_config(content).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(config.version, CONFIG_VERSION);
+        let music = Bookmark { name: "music".to_string(), path: PathBuf::from("/home/me/music"), key: None };
+        assert_eq!(config.bookmarks.first(), Some(&music));
+        assert_eq!(config.bookmarks.len(), 2);
+        assert!(!config.behavior.confirm_delete);
+        assert_eq!(config.archive, ArchiveConfig { default_format: "tar.xz".to_string(), compression_level: 2 });
+
+        let (config, _) = parse_config("version = 2\nbookmarks = [[\"a\", \"/a\"], { name = \"b\", path = \"/b\", key = \"b\" }]").unwrap();
+        assert_eq!(config.bookmarks[0].path, PathBuf::from("/a"));
+        assert_eq!(config.bookmarks[1].key, Some('b'));
+    }
+
+    #[test]
+    fn test_pinned_directories_round_trip() {
+        let config = Config { pinned: vec![PathBuf::from("/home/me/dev")], ..Config::default() };
+        let content = toml::to_string_pretty(&config).unwrap();
+        let (loaded, warnings) = parse_config(&content).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(loaded.pinned, config.pinned);
+        assert!(!toml::to_string_pretty(&Config::default()).unwrap().contains("pinned"));
+    }
+
+    #[test]
+    fn test_disabled_plugins_are_read() {
+        let (config, warnings) = parse_config("[plugins]\ndisabled = [\"Previewer\"]\n").unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(config.plugins.disabled, vec!["Previewer".to_string()]);
+    }
+
+    #[test]
+    fn test_syntax_error_names_the_line() {
+        let error = parse_config("[theme]\ncolor_scheme = \"Nord\n").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+}
@@ -1,25 +1,372 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::os::unix::fs::{MetadataExt, PermissionsExt};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
-use crate::plugin::{self, Plugin};
-use crate::task_manager::{TaskManager, TaskKind};
-use humansize::{format_size, BINARY};
+use crate::plugin::{self, Plugin, PluginHost, PluginManifest, PluginPermissions, PluginPreview, PluginState, PreviewResult};
+use crate::session::{LockError, SessionLock};
+use crate::task_manager::{ArchiveFormat, SavedTask, TaskEvent, TaskManager, TaskKind, TaskStatus};
+use plugin_ipc::{DirChangedParams, SelectionChangedParams, TaskCompletedParams};
+use io::fs_ops::MAX_COMPRESSION_LEVEL;
 use crate::clipboard::{Clipboard, ClipboardMode};
-use utils::fs::get_directory_size;
 use directories::UserDirs;
-use config::Config;
+use config::{Bookmark, Config};
 use log;
-use crate::search::{SearchEngine, SearchMode};
+use crate::index::FileIndexer;
+use crate::config_watch::ConfigWatcher;
+use crate::settings::{ArchiveSettings, BehaviorSettings, Language, PreviewSettings, Settings, SizeUnits, SortField, SortSettings, DEFAULT_PREVIEW_WIDTH, MAX_PREVIEW_WIDTH, MIN_PREVIEW_WIDTH, PREVIEW_WIDTH_STEP};
+use crate::view_prefs::{self, DirOverrides, DirPrefs, PinnedDirs, ViewPrefs};
+use crate::dir_size::{DirSize, DirSizer};
+use crate::usage_scan::{UsageBatch, UsageNode, UsageScanner};
+use crate::disk_usage::DiskUsageProber;
+use crate::mount_probe::{MountProber, ProbeError};
+use crate::dir_load::{DirLoader, LoadHandle, Loaded, Started};
+use crate::remote::{Remote, RemoteKind};
+use crate::command_preview::{CommandOutput, CommandPreviewer, CommandStatus};
+use crate::i18n::Msg;
+use crate::search::{BackgroundSearch, MetadataFilter, SearchEngine, SearchMatcher, SearchMode, SearchSyntax, SearchUpdate, WalkQuery};
 #[cfg(feature = "mounts")]
 use proc_mounts::MountIter;
-use portable_pty::MasterPty;
+use portable_pty::{Child, CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
+use std::cell::Cell;
+use ratatui::layout::Rect;
+use tokio::sync::mpsc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Жизненный цикл оболочки встроенного терминала
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalLifecycle {
+    /// Терминал ещё не открывали
+    NotStarted,
+    Running,
+    /// Оболочка завершилась, Enter запускает новую
+    Exited,
+}
+
+/// Вывод оболочки с номером её сеанса. Номера не повторяются ни между
+/// вкладками, ни между перезапусками, так что вывод находит свой терминал и
+/// после перестановки вкладок, а присланное прежней оболочкой отбрасывается.
+#[derive(Debug)]
+pub enum TerminalOutput {
+    Data { session: u64, bytes: Vec<u8> },
+    Exited { session: u64 },
+}
+
+impl TerminalOutput {
+    pub fn session(&self) -> u64 {
+        match self {
+            TerminalOutput::Data { session, .. } | TerminalOutput::Exited { session } => *session,
+        }
+    }
+}
+
+static NEXT_TERMINAL_SESSION: AtomicU64 = AtomicU64::new(1);
+
+/// Канал, по которому оболочки всех вкладок присылают вывод
+#[derive(Debug)]
+pub struct TerminalChannel {
+    tx: mpsc::Sender<TerminalOutput>,
+    rx: mpsc::Receiver<TerminalOutput>,
+}
+
+impl Default for TerminalChannel {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(100);
+        Self { tx, rx }
+    }
+}
+
+impl TerminalChannel {
+    pub fn sender(&self) -> mpsc::Sender<TerminalOutput> {
+        self.tx.clone()
+    }
+
+    pub async fn wait_for_output(&mut self) -> Option<TerminalOutput> {
+        self.rx.recv().await
+    }
+}
+
+/// Терминал вкладки. Запущенная оболочка завершается вместе с ним.
 pub struct TerminalState {
-    pub pty_writer: Box<dyn MasterPty + Send>,
-    pub lines: Vec<String>,
+    pub lifecycle: TerminalLifecycle,
+    pty: Option<Box<dyn MasterPty + Send>>,
+    /// Запись в PTY. PTY выдаёт её один раз, а при закрытии она посылает
+    /// оболочке конец ввода, поэтому живёт столько же, сколько оболочка.
+    writer: Option<Box<dyn Write + Send>>,
+    child: Option<Box<dyn Child + Send + Sync>>,
+    /// Сеанс текущей оболочки, 0 — оболочку ещё не запускали
+    session: u64,
+    scrollback: usize,
+    /// Эмулятор VT100: экран того же размера, что и PTY, и прокрутка
+    pub parser: vt100::Parser,
+    /// Строки и столбцы области, в которой терминал рисовался последний раз.
+    /// Её записывает отрисовка, у которой есть только `&AppState`.
+    pub drawn_size: Cell<Option<(u16, u16)>>,
+    /// Размер, сообщённый PTY
+    pub pty_size: (u16, u16),
+    /// Нажат `Ctrl+b`, следующая клавиша — команда (`[` — режим копирования)
+    pub prefix_pending: bool,
+    pub copy_mode: Option<CopyMode>,
+}
+
+/// Курсор и начало выделения режима копирования. Строки считаются от верха
+/// живого экрана, отрицательные уходят в прокрутку.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyMode {
+    pub cursor: (isize, u16),
+    pub anchor: Option<(isize, u16)>,
+}
+
+impl CopyMode {
+    /// Выделение в порядке чтения; без начала — вся строка под курсором
+    pub fn selection(&self) -> ((isize, u16), (isize, u16)) {
+        match self.anchor {
+            Some(anchor) => (anchor.min(self.cursor), anchor.max(self.cursor)),
+            None => ((self.cursor.0, 0), (self.cursor.0, u16::MAX)),
+        }
+    }
+}
+
+impl TerminalState {
+    /// Терминал без оболочки; её запускает `start`.
+    /// `scrollback` — сколько строк над экраном хранить для прокрутки
+    pub fn new(pty_size: (u16, u16), scrollback: usize) -> Self {
+        let parser = vt100::Parser::new(pty_size.0, pty_size.1, scrollback);
+        Self {
+            lifecycle: TerminalLifecycle::NotStarted,
+            pty: None,
+            writer: None,
+            child: None,
+            session: 0,
+            scrollback,
+            parser,
+            drawn_size: Cell::new(None),
+            pty_size,
+            prefix_pending: false,
+            copy_mode: None,
+        }
+    }
+
+    /// Запускает оболочку из `$SHELL` в каталоге `cwd` на чистом экране
+    /// с `scrollback` строками прокрутки. Вывод уходит в `output`.
+    pub fn start(&mut self, cwd: &Path, scrollback: usize, output: mpsc::Sender<TerminalOutput>) -> Result<(), String> {
+        let (rows, cols) = self.pty_size;
+        let pair = NativePtySystem::default()
+            .openpty(PtySize { rows, cols, ..Default::default() })
+            .map_err(|e| e.to_string())?;
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "bash".to_string());
+        let mut cmd = CommandBuilder::new(shell);
+        cmd.cwd(cwd);
+        let child = pair.slave.spawn_command(cmd).map_err(|e| e.to_string())?;
+        // Пока открыта наша копия ведомой стороны, чтение не закончится
+        // и после выхода оболочки
+        drop(pair.slave);
+        let mut reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
+        let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
+
+        self.stop();
+        let session = NEXT_TERMINAL_SESSION.fetch_add(1, Ordering::Relaxed);
+        self.session = session;
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(count) if count > 0 => {
+                        let bytes = buf[..count].to_vec();
+                        if output.blocking_send(TerminalOutput::Data { session, bytes }).is_err() {
+                            return;
+                        }
+                    }
+                    _ => break,
+                }
+            }
+            let _ = output.blocking_send(TerminalOutput::Exited { session });
+        });
+
+        self.pty = Some(pair.master);
+        self.writer = Some(writer);
+        self.child = Some(child);
+        self.scrollback = scrollback;
+        self.parser = vt100::Parser::new(rows, cols, scrollback);
+        self.copy_mode = None;
+        self.prefix_pending = false;
+        self.lifecycle = TerminalLifecycle::Running;
+        Ok(())
+    }
+
+    /// Закрывает PTY и дожидается оболочки, завершая её, если она ещё жива
+    fn stop(&mut self) {
+        self.writer = None;
+        self.pty = None;
+        if let Some(mut child) = self.child.take() {
+            if let Ok(None) = child.try_wait() {
+                let _ = child.kill();
+            }
+            let _ = child.wait();
+        }
+    }
+
+    /// Сеанс текущей оболочки, 0 — оболочку ещё не запускали
+    pub fn session(&self) -> u64 {
+        self.session
+    }
+
+    pub fn handle_output(&mut self, output: TerminalOutput) {
+        match output {
+            TerminalOutput::Data { session, bytes } if session == self.session => self.process(&bytes),
+            TerminalOutput::Exited { session } if session == self.session => {
+                self.stop();
+                self.lifecycle = TerminalLifecycle::Exited;
+            }
+            _ => {}
+        }
+    }
+
+    /// Отправляет байты оболочке
+    pub fn send(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let Some(writer) = &mut self.writer else {
+            return Err("the shell is not running".to_string());
+        };
+        writer.write_all(bytes).and_then(|_| writer.flush()).map_err(|e| e.to_string())
+    }
+
+    /// Вставляет текст из буфера обмена. Если программа в терминале включила
+    /// режим bracketed paste, текст обрамляется его маркерами, чтобы
+    /// вставленные переводы строк не выполнялись как Enter.
+    pub fn paste(&mut self, text: &str) -> Result<(), String> {
+        self.parser.set_scrollback(0);
+        if self.parser.screen().bracketed_paste() {
+            let mut bytes = b"\x1b[200~".to_vec();
+            bytes.extend_from_slice(text.as_bytes());
+            bytes.extend_from_slice(b"\x1b[201~");
+            self.send(&bytes)
+        } else {
+            self.send(text.as_bytes())
+        }
+    }
+
+    /// Передаёт эмулятору очередной вывод оболочки
+    pub fn process(&mut self, bytes: &[u8]) {
+        self.parser.process(bytes);
+    }
+
+    /// Сдвигает видимую часть на `delta` строк в прокрутку (вверх — положительные)
+    pub fn scroll(&mut self, delta: isize) {
+        let offset = self.parser.screen().scrollback().saturating_add_signed(delta);
+        // Парсер сам ограничивает смещение числом сохранённых строк
+        self.parser.set_scrollback(offset);
+    }
+
+    /// На сколько строк видимая часть сдвинута в прокрутку
+    pub fn scroll_offset(&self) -> usize {
+        self.parser.screen().scrollback()
+    }
+
+    /// Входит в режим копирования с курсором там же, где курсор оболочки
+    pub fn enter_copy_mode(&mut self) {
+        let (row, col) = self.parser.screen().cursor_position();
+        let line = row as isize - self.scroll_offset() as isize;
+        self.copy_mode = Some(CopyMode { cursor: (line, col), anchor: None });
+        self.move_copy_cursor(0, 0);
+    }
+
+    pub fn exit_copy_mode(&mut self) {
+        self.copy_mode = None;
+        self.parser.set_scrollback(0);
+    }
+
+    /// Сдвигает курсор режима копирования, прокручивая вид так, чтобы он
+    /// оставался на экране
+    pub fn move_copy_cursor(&mut self, rows: isize, cols: isize) {
+        let Some(mut mode) = self.copy_mode else {
+            return;
+        };
+        let (height, width) = self.parser.screen().size();
+        let line = mode.cursor.0.saturating_add(rows).min(height as isize - 1);
+        let offset = self.scroll_offset() as isize;
+        let wanted = offset.clamp(-line, height as isize - 1 - line).max(0);
+        self.parser.set_scrollback(wanted as usize);
+        // Выше начала прокрутки курсор не уходит
+        let line = line.max(-(self.scroll_offset() as isize));
+        let col = (mode.cursor.1 as isize).saturating_add(cols).clamp(0, width as isize - 1);
+        mode.cursor = (line, col as u16);
+        self.copy_mode = Some(mode);
+    }
+
+    /// Ставит или снимает начало выделения в позиции курсора
+    pub fn toggle_selection(&mut self) {
+        if let Some(mode) = &mut self.copy_mode {
+            mode.anchor = match mode.anchor {
+                Some(_) => None,
+                None => Some(mode.cursor),
+            };
+        }
+    }
+
+    /// Текст выделения с обрезанными справа пробелами. Выходит из режима копирования.
+    pub fn copy_selection(&mut self) -> Option<String> {
+        let mode = self.copy_mode?;
+        let ((first, first_col), (last, last_col)) = mode.selection();
+        let width = self.parser.screen().size().1;
+        let text = (first..=last)
+            .map(|line| {
+                let start = if line == first { first_col } else { 0 };
+                let end = if line == last { last_col.min(width - 1) } else { width - 1 };
+                let text = self.line_text(line, start, end.saturating_sub(start) + 1);
+                text.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.exit_copy_mode();
+        Some(text)
+    }
+
+    /// Часть строки `line` шириной `width` с колонки `start`
+    fn line_text(&mut self, line: isize, start: u16, width: u16) -> String {
+        self.parser.set_scrollback((-line).max(0) as usize);
+        let row = line + self.scroll_offset() as isize;
+        usize::try_from(row)
+            .ok()
+            .and_then(|row| self.parser.screen().rows(start, width).nth(row))
+            .unwrap_or_default()
+    }
+
+    /// Сообщает PTY и эмулятору новый размер, если область терминала изменилась
+    pub fn sync_size(&mut self) {
+        let Some((rows, cols)) = self.drawn_size.get().filter(|size| *size != self.pty_size) else {
+            return;
+        };
+        if let Some(pty) = &self.pty {
+            if let Err(e) = pty.resize(PtySize { rows, cols, ..Default::default() }) {
+                log::error!("Failed to resize terminal to {}x{}: {}", cols, rows, e);
+                return;
+            }
+        }
+        self.pty_size = (rows, cols);
+        self.parser.set_size(rows, cols);
+    }
+}
+
+/// Копия вкладки получает свой терминал, оболочка в нём запустится при открытии
+impl Clone for TerminalState {
+    fn clone(&self) -> Self {
+        Self::new(self.pty_size, self.scrollback)
+    }
+}
+
+impl Drop for TerminalState {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Default for TerminalState {
+    /// Размер до первой отрисовки терминала условный
+    fn default() -> Self {
+        Self::new((9, 80), config::TerminalConfig::default().scrollback)
+    }
 }
 
 impl std::fmt::Debug for TerminalState {
@@ -28,6 +375,47 @@ impl std::fmt::Debug for TerminalState {
     }
 }
 
+/// Расположение списков файлов
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum LayoutMode {
+    /// Один список и панель предпросмотра
+    #[default]
+    Single,
+    /// Два независимых списка рядом, как в Midnight Commander
+    Dual,
+}
+
+/// Где на экране оказались списки и предпросмотр при последней отрисовке.
+/// Записывает отрисовка, а читает обработка мыши.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PaneAreas {
+    /// Строки левого (или единственного) списка и индекс первой видимой записи
+    pub left_list: Option<(Rect, usize)>,
+    /// То же для правого списка в режиме `LayoutMode::Dual`
+    pub right_list: Option<(Rect, usize)>,
+    /// Предпросмотр, если он сейчас показан
+    pub preview: Option<Rect>,
+    /// Строки и прокрутка списков левой панели: папки, закладки, диски и
+    /// удалённые каталоги. Нет, если панель скрыта.
+    pub places: Option<[(Rect, usize); 4]>,
+    /// Полоса вкладок, если она показана
+    pub tabs: Option<Rect>,
+    /// Путь в заголовке левого (или единственного) списка и в заголовке правого
+    pub left_path: Option<Rect>,
+    pub right_path: Option<Rect>,
+}
+
+/// Два щелчка по одной записи быстрее этого считаются двойным
+pub const DOUBLE_CLICK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// Сторона активного списка в режиме `LayoutMode::Dual`
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum PanelSide {
+    #[default]
+    Left,
+    Right,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum FocusBlock {
     #[default]
@@ -35,6 +423,8 @@ pub enum FocusBlock {
     Xdg,
     Bookmarks,
     Disks,
+    /// Удалённые каталоги под блоком дисков
+    Remotes,
     Terminal,
 }
 
@@ -51,16 +441,59 @@ pub enum InputMode {
     Command,
     Settings,
     Archive,
+    /// Ввод имени активной вкладки
+    TabName,
+    /// Список пользовательских команд из `[[commands]]`
+    CommandPalette,
+    /// Ввод имени, под которым сохранить сессию
+    SessionName,
+    /// Список сохранённых сессий
+    SessionPicker,
+    /// Список каталогов выше текущего для перехода через несколько уровней
+    AncestorPicker,
+    /// Ввод адреса `user@host:/path` или WebDAV URL для подключения
+    RemoteConnect,
+    /// Первичная настройка, пока config.toml ещё нет
+    Setup,
+    /// Ввод имени новой закладки
+    Bookmark,
+    /// Ожидание клавиши закладки после `` ` ``
+    BookmarkJump,
+    /// Ожидание второй клавиши после `z`: `u` — анализ занятого места
+    Analyze,
+    /// Ввод команды, вывод которой показывается в предпросмотре
+    PreviewCommand,
+    /// Ввод команды оболочки для выделенных файлов после `c` `!`
+    ShellCommand,
+    /// Ввод glob для фильтра средней панели после `F`
+    GlobFilter,
+    /// Ввод пути или git URL плагина в настройках
+    PluginInstall,
+    /// Список всех горячих клавиш поверх интерфейса
+    Help,
 }
 
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub path: PathBuf,
-    pub size: u64,
+    /// Для каталога — `None`, пока размер считается в фоне
+    pub size: Option<u64>,
+    /// Имя владельца или его uid, если имя не найдено
     pub owner: String,
+    /// Имя группы или её gid, если имя не найдено
+    pub group: String,
+    pub uid: u32,
+    pub gid: u32,
     pub permissions: String,
     pub created: Option<SystemTime>,
     pub modified: Option<SystemTime>,
+    pub inode: u64,
+    /// Число жёстких ссылок
+    pub nlink: u64,
+    /// Куда указывает символическая ссылка, как записано в ней
+    pub target: Option<PathBuf>,
+    /// Цель ссылки не существует
+    pub broken_link: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -74,21 +507,92 @@ pub struct DirEntry {
     pub name: String,
     pub path: PathBuf,
     pub is_dir: bool,
+    #[serde(default)]
+    pub is_executable: bool,
+    #[serde(default)]
+    pub is_symlink: bool,
+    /// Цель символической ссылки
+    #[serde(default)]
+    pub target: Option<PathBuf>,
+    /// Ссылка указывает на несуществующий путь
+    #[serde(default)]
+    pub is_broken_link: bool,
+    /// Размер в байтах (для ссылок — размер цели)
+    #[serde(default)]
+    pub size: u64,
+    /// Время последнего изменения
+    #[serde(default)]
+    pub modified: Option<SystemTime>,
+}
+
+impl DirEntry {
+    /// Собирает запись для пути. Ссылка распознаётся по `symlink_metadata`,
+    /// а `is_dir` и `is_executable` описывают цель ссылки.
+    pub fn from_path(path: PathBuf) -> Self {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let is_symlink = fs::symlink_metadata(&path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        let target = if is_symlink { fs::read_link(&path).ok() } else { None };
+        let metadata = fs::metadata(&path).ok();
+        let is_broken_link = is_symlink && metadata.is_none();
+        let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+        let is_executable = !is_dir && metadata
+            .as_ref()
+            .is_some_and(|m| m.permissions().mode() & 0o111 != 0);
+        let size = metadata.as_ref().map_or(0, |m| m.len());
+        let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+        DirEntry { name, path, is_dir, is_executable, is_symlink, target, is_broken_link, size, modified }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PreviewEntry {
     pub name: String,
     pub is_dir: bool,
+    #[serde(default)]
+    pub is_symlink: bool,
+    #[serde(default)]
+    pub target: Option<PathBuf>,
+    #[serde(default)]
+    pub is_broken_link: bool,
+    /// Число записей непосредственно внутри подкаталога
+    #[serde(default)]
+    pub child_count: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PreviewContent {
-    File(String),
-    Directory(Vec<PreviewEntry>),
-    TooLarge(String),
+    /// Первые `PreviewSettings::max_lines` строк файла, а при `tail` —
+    /// последние; `truncated`, если в файле есть и другие строки
+    File { text: String, truncated: bool, tail: bool },
+    /// Первые `PREVIEW_DIR_LIMIT` записей каталога `path` из `total`.
+    /// `size` заполняется, когда фоновый подсчёт закончится.
+    Directory {
+        path: PathBuf,
+        entries: Vec<PreviewEntry>,
+        total: usize,
+        size: Option<u64>,
+    },
+    /// Файл размером `size` больше предела предпросмотра `limit`
+    TooLarge { size: u64, limit: u64 },
     Error(String),
     Binary,
+    /// Вывод разовой команды для файла `path`, заданной через `c p`.
+    /// Пока команда работает, `status` — `Running`.
+    CommandOutput {
+        path: PathBuf,
+        command: String,
+        output: String,
+        status: CommandStatus,
+    },
+    /// Предпросмотр файла, который прислал плагин
+    Plugin {
+        plugin: String,
+        result: PreviewResult,
+    },
+    /// Чтение ещё идёт в фоне, см. `TabState::loading_preview`
+    Loading,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -97,66 +601,516 @@ pub enum RightPaneView {
     Terminal,
 }
 
+/// Источник содержимого средней панели
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum ListingSource {
+    /// Содержимое `current_dir`
+    #[default]
+    Directory,
+    /// Зафиксированные результаты поиска, возможно из разных каталогов
+    SearchResults(Vec<DirEntry>),
+    /// Анализ занятого места: записи `current_dir` внутри просканированного
+    /// дерева, от больших к меньшим
+    #[serde(skip)]
+    DiskUsage(UsageView),
+}
+
+/// Дерево размеров, собранное фоновым сканированием `root`
+#[derive(Debug, Clone)]
+pub struct UsageView {
+    pub root: PathBuf,
+    pub tree: UsageNode,
+    /// Сколько байт насчитано на данный момент
+    pub total: u64,
+    /// Сканирование ещё идёт, размеры пока неполные
+    pub scanning: bool,
+    /// Сканирование прервано, размеры так и останутся неполными
+    pub stopped: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TabState {
     pub id: usize,
+    /// Имя вкладки, заданное пользователем. Без него показывается имя каталога.
+    #[serde(default)]
+    pub name: Option<String>,
     pub current_dir: PathBuf,
     pub entries: Vec<DirEntry>,
     pub filtered_entries: Vec<DirEntry>, // For search filtering
     pub cursor: usize,
     pub preview_content: Option<PreviewContent>,
     pub preview_scroll: (u16, u16),
-    pub selected_entries: HashSet<PathBuf>,
+    pub selected_entries: Selection,
     pub right_pane_view: RightPaneView,
+    #[serde(default)]
+    pub listing_source: ListingSource,
+    #[serde(default)]
+    pub sort: SortSettings,
+    /// Общая таблица запомненных настроек вида по каталогам
+    #[serde(skip)]
+    pub dir_prefs: DirPrefs,
+    /// Общий набор закреплённых каталогов
+    #[serde(skip)]
+    pub pinned: PinnedDirs,
+    /// Запомненные настройки текущего каталога, если есть
+    #[serde(skip)]
+    pub view: Option<ViewPrefs>,
+    /// Настройки из `.corvus.toml` текущего каталога, если он есть
+    #[serde(skip)]
+    pub dir_overrides: Option<DirOverrides>,
+    /// Настройки предпросмотра, раздаются из `AppState`
+    #[serde(skip)]
+    pub preview: PreviewSettings,
+    /// Посещённые каталоги для перехода назад и вперёд
+    #[serde(skip)]
+    pub history: Vec<PathBuf>,
+    /// Позиция текущего каталога в `history`
+    #[serde(skip)]
+    pub history_pos: usize,
+    /// Свой терминал вкладки, оболочка запускается при первом открытии
+    #[serde(skip)]
+    pub terminal: TerminalState,
+    /// Фильтр списка по glob; сбрасывается при смене каталога
+    #[serde(skip)]
+    pub glob_filter: Option<GlobFilter>,
+    /// Через него каталог и предпросмотр читаются в фоне. Без него, как
+    /// в тестах, всё читается сразу.
+    #[serde(skip)]
+    pub loader: Option<LoadHandle>,
+    /// Номер фонового чтения каталога, пока список не готов
+    #[serde(skip)]
+    pub loading_entries: Option<u64>,
+    /// Номер фонового чтения предпросмотра записи под курсором
+    #[serde(skip)]
+    pub loading_preview: Option<u64>,
+    /// Запись, на которую встанет курсор, когда список дочитается
+    #[serde(skip)]
+    pub pending_select: Option<PathBuf>,
+    /// Сколько скрытых файлов не попало в список
+    #[serde(skip)]
+    pub hidden_count: usize,
+    /// Свободное место на файловой системе каталога, читается вместе с ним
+    #[serde(skip)]
+    pub free_space: Option<u64>,
+}
+
+/// Выделенные записи вкладки. Размер выделенного считается по мере
+/// выделения из размеров, уже прочитанных для списка, так что выделение не
+/// обращается к диску. С диска читается только выделение из сессии.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "HashSet<PathBuf>", into = "HashSet<PathBuf>")]
+pub struct Selection {
+    /// Размер каждой выделенной записи; у каталогов его нет
+    sizes: HashMap<PathBuf, Option<u64>>,
+    bytes: u64,
+    dirs: usize,
+}
+
+impl Selection {
+    /// Добавляет запись с её размером из списка: у ссылки это размер цели
+    pub fn insert(&mut self, entry: &DirEntry) -> bool {
+        self.insert_sized(entry.path.clone(), (!entry.is_dir).then_some(entry.size))
+    }
+
+    fn insert_sized(&mut self, path: PathBuf, size: Option<u64>) -> bool {
+        if self.sizes.contains_key(&path) {
+            return false;
+        }
+        match size {
+            Some(size) => self.bytes += size,
+            None => self.dirs += 1,
+        }
+        self.sizes.insert(path, size);
+        true
+    }
+
+    pub fn remove(&mut self, path: &Path) -> bool {
+        match self.sizes.remove(path) {
+            Some(Some(size)) => self.bytes -= size,
+            Some(None) => self.dirs -= 1,
+            None => return false,
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.sizes.contains_key(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PathBuf> {
+        self.sizes.keys()
+    }
+
+    /// Суммарный размер выделенных файлов, без каталогов
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Сколько выделено каталогов
+    pub fn dirs(&self) -> usize {
+        self.dirs
+    }
+}
+
+impl<'a> Extend<&'a DirEntry> for Selection {
+    fn extend<I: IntoIterator<Item = &'a DirEntry>>(&mut self, entries: I) {
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+}
+
+/// Выделение из сессии: размеры читаются с диска так же, как для списка
+impl From<HashSet<PathBuf>> for Selection {
+    fn from(paths: HashSet<PathBuf>) -> Self {
+        let mut selection = Self::default();
+        for path in paths {
+            selection.insert(&DirEntry::from_path(path));
+        }
+        selection
+    }
+}
+
+impl From<Selection> for HashSet<PathBuf> {
+    fn from(selection: Selection) -> Self {
+        selection.sizes.into_keys().collect()
+    }
+}
+
+impl PartialEq<HashSet<PathBuf>> for Selection {
+    fn eq(&self, paths: &HashSet<PathBuf>) -> bool {
+        self.len() == paths.len() && paths.iter().all(|path| self.contains(path))
+    }
+}
+
+/// Прочитанное в фоне содержимое каталога: все записи, включая скрытые,
+/// и его `.corvus.toml`. Фильтры и сортировка применяются уже в
+/// `TabState::apply_listing`, ведь настройки вида могли смениться.
+#[derive(Debug)]
+pub struct Listing {
+    entries: Vec<DirEntry>,
+    overrides: Option<DirOverrides>,
+    free_space: Option<u64>,
+}
+
+/// Читает каталог `dir`. Может надолго зависнуть на сетевом диске.
+fn read_listing(dir: &Path) -> Listing {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|res| res.ok())
+            .map(|entry| DirEntry::from_path(entry.path()))
+            .collect(),
+        Err(e) => {
+            log::error!("Failed to read directory {:?}: {}", dir, e);
+            vec![]
+        }
+    };
+    let free_space = crate::disk_usage::stat(dir).ok().map(|usage| usage.available);
+    Listing { entries, overrides: DirOverrides::load(dir), free_space }
+}
+
+/// Записи каталога `dir` из дерева `view`, от больших к меньшим. Диск не
+/// читается, поэтому скрытые файлы видны всегда: они входят в размеры.
+fn usage_entries(view: &UsageView, dir: &Path) -> Vec<DirEntry> {
+    let node = dir.strip_prefix(&view.root).ok().and_then(|relative| view.tree.get(relative));
+    let Some(node) = node else {
+        return Vec::new();
+    };
+    let mut entries: Vec<DirEntry> = node
+        .children
+        .iter()
+        .map(|(name, child)| DirEntry {
+            name: name.to_string_lossy().into_owned(),
+            path: dir.join(name),
+            is_dir: child.is_dir,
+            is_executable: false,
+            is_symlink: false,
+            target: None,
+            is_broken_link: false,
+            size: child.size,
+            modified: None,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+/// Убирает из дерева записи каталога `dir`, которых больше нет на диске,
+/// например удалённые задачей, вместе с их размером
+fn forget_missing_entries(view: &mut UsageView, dir: &Path) {
+    let Ok(relative) = dir.strip_prefix(&view.root) else {
+        return;
+    };
+    let Some(node) = view.tree.get(relative) else {
+        return;
+    };
+    let missing: Vec<PathBuf> = node
+        .children
+        .keys()
+        .map(|name| relative.join(name))
+        .filter(|path| view.root.join(path).symlink_metadata().is_err())
+        .collect();
+    for path in missing {
+        view.tree.remove(&path);
+    }
+    view.total = view.tree.size;
+}
+
+/// Glob, которому должны соответствовать имена в средней панели
+#[derive(Debug, Clone)]
+pub struct GlobFilter {
+    pub pattern: String,
+    matcher: SearchMatcher,
+}
+
+impl GlobFilter {
+    /// Компилирует `pattern` как glob оболочки: шаблон привязан к имени
+    /// целиком и различает регистр
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let matcher = SearchMatcher::compile(pattern, SearchSyntax::Glob, true)?;
+        Ok(Self { pattern: pattern.to_string(), matcher })
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        self.matcher.is_match(name)
+    }
 }
 
+/// Число пунктов на экране первичной настройки: тема, скрытые файлы,
+/// подтверждение удаления, программа для файлов, корзина и «Готово»
+pub const SETUP_ITEMS: usize = 6;
+
+/// Команда, которой открываются файлы, если `behavior.opener` не задан
+pub const DEFAULT_OPENER: &str = "xdg-open {file}";
+
+/// Через сколько времени без ввода переход по первым буквам завершается
+pub const TYPE_AHEAD_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1500);
+
+/// Сколько каталогов хранит история вкладки
+pub const HISTORY_LIMIT: usize = 100;
+
 impl TabState {
     pub fn new(id: usize) -> Self {
         Self {
             id,
+            name: None,
             current_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")),
             entries: Vec::new(),
             filtered_entries: Vec::new(),
             cursor: 0,
             preview_content: None,
             preview_scroll: (0, 0),
-            selected_entries: HashSet::new(),
+            selected_entries: Selection::default(),
             right_pane_view: RightPaneView::Preview,
+            listing_source: ListingSource::Directory,
+            sort: SortSettings::default(),
+            dir_prefs: DirPrefs::default(),
+            pinned: PinnedDirs::default(),
+            view: None,
+            dir_overrides: None,
+            preview: PreviewSettings::default(),
+            history: Vec::new(),
+            history_pos: 0,
+            terminal: TerminalState::default(),
+            glob_filter: None,
+            loader: None,
+            loading_entries: None,
+            loading_preview: None,
+            pending_select: None,
+            hidden_count: 0,
+            free_space: None,
+        }
+    }
+
+    /// Подпись вкладки: заданное имя или имя текущего каталога
+    pub fn title(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.current_dir.file_name().unwrap_or_default().to_string_lossy().into_owned(),
         }
     }
 
     pub fn set_current_dir(&mut self, new_path: PathBuf, show_hidden: bool) {
-        self.current_dir = new_path;
+        self.change_dir(new_path);
+        self.listing_source = ListingSource::Directory;
+        self.selected_entries.clear();
+        self.update_entries(show_hidden);
+    }
+
+    pub fn is_showing_search_results(&self) -> bool {
+        matches!(self.listing_source, ListingSource::SearchResults(_))
+    }
+
+    /// Дерево размеров, если вкладка в режиме анализа занятого места
+    pub fn disk_usage(&self) -> Option<&UsageView> {
+        match &self.listing_source {
+            ListingSource::DiskUsage(view) => Some(view),
+            _ => None,
+        }
+    }
+
+    /// Узел дерева размеров для `current_dir` в анализе занятого места
+    pub fn usage_node(&self) -> Option<&UsageNode> {
+        let view = self.disk_usage()?;
+        view.tree.get(self.current_dir.strip_prefix(&view.root).ok()?)
+    }
+
+    /// Переводит среднюю панель в анализ занятого места `current_dir`.
+    /// Список заполняется по мере того, как приходят результаты сканирования.
+    pub fn show_disk_usage(&mut self) {
+        self.glob_filter = None;
+        self.selected_entries.clear();
+        self.listing_source = ListingSource::DiskUsage(UsageView {
+            root: self.current_dir.clone(),
+            tree: UsageNode::directory(),
+            total: 0,
+            scanning: true,
+            stopped: false,
+        });
+        self.update_entries(true);
+    }
+
+    /// Добавляет в дерево очередную порцию результатов сканирования.
+    /// Курсор остаётся на той же записи, хотя порядок мог измениться.
+    pub fn apply_usage_batch(&mut self, batch: &UsageBatch) {
+        let path = self.get_selected_entry_path();
+        let ListingSource::DiskUsage(view) = &mut self.listing_source else {
+            return;
+        };
+        for entry in &batch.entries {
+            view.tree.insert(&entry.path, entry.is_dir, entry.size);
+        }
+        view.total = batch.total;
+        view.scanning = !batch.done;
+        self.entries = usage_entries(view, &self.current_dir);
+        self.update_filtered_entries(None);
+        match path.and_then(|path| self.filtered_entries.iter().position(|entry| entry.path == path)) {
+            Some(index) => self.cursor = index,
+            None => {
+                self.cursor = 0;
+                self.update_preview();
+            }
+        }
+    }
+
+    /// Показывает результаты поиска в средней панели вместо содержимого каталога.
+    /// Имена записей заменяются путями относительно `current_dir`.
+    pub fn show_search_results(&mut self, results: Vec<DirEntry>) {
+        let results: Vec<DirEntry> = results
+            .into_iter()
+            .map(|mut entry| {
+                if let Ok(relative) = entry.path.strip_prefix(&self.current_dir) {
+                    entry.name = relative.to_string_lossy().to_string();
+                }
+                entry
+            })
+            .collect();
+        self.glob_filter = None;
+        self.loading_entries = None;
+        self.listing_source = ListingSource::SearchResults(results.clone());
+        self.entries = results;
+        self.filtered_entries = self.entries.clone();
+        self.cursor = 0;
+        self.update_preview();
+    }
+
+    /// Возвращает среднюю панель к содержимому `current_dir` из результатов
+    /// поиска или анализа занятого места
+    pub fn exit_search_results(&mut self, show_hidden: bool) {
+        self.listing_source = ListingSource::Directory;
         self.selected_entries.clear();
         self.update_entries(show_hidden);
     }
 
     pub fn update_entries(&mut self, show_hidden: bool) {
-        self.entries = match fs::read_dir(&self.current_dir) {
-            Ok(entries) => entries
-                .filter_map(|res| res.ok())
-                .filter(|entry| {
-                    if show_hidden {
-                        true
-                    } else {
-                        !entry.file_name().to_string_lossy().starts_with('.')
-                    }
-                })
-                .map(|entry| {
-                    let path = entry.path();
-                    let is_dir = path.is_dir();
-                    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-                    DirEntry { name, path, is_dir }
-                })
-                .collect(),
-            Err(e) => {
-                log::error!("Failed to read directory {:?}: {}", self.current_dir, e);
-                vec![]
+        self.loading_entries = None;
+        if let ListingSource::DiskUsage(view) = &mut self.listing_source {
+            forget_missing_entries(view, &self.current_dir);
+            self.entries = usage_entries(view, &self.current_dir);
+            self.update_filtered_entries(None);
+            self.cursor = self.cursor.min(self.filtered_entries.len().saturating_sub(1));
+            self.update_preview();
+            return;
+        }
+        if let ListingSource::SearchResults(results) = &mut self.listing_source {
+            // Не перечитываем каталог, только убираем исчезнувшие файлы
+            results.retain(|entry| entry.path.symlink_metadata().is_ok());
+            self.entries = results.clone();
+            self.filtered_entries = self.entries.clone();
+            self.cursor = self.cursor.min(self.filtered_entries.len().saturating_sub(1));
+            self.update_preview();
+            return;
+        }
+        self.pending_select = None;
+        let dir = self.current_dir.clone();
+        let Some(loader) = &self.loader else {
+            self.apply_listing(read_listing(&dir), show_hidden);
+            return;
+        };
+        match loader.run(move || read_listing(&dir), |id, listing| Loaded::Entries { id, listing }) {
+            Started::Done(listing) => self.apply_listing(listing, show_hidden),
+            // Пока каталог читается, список пуст, а прежнее чтение забыто
+            Started::Pending(id) => {
+                self.loading_entries = Some(id);
+                self.entries.clear();
+                self.filtered_entries.clear();
+                self.cursor = 0;
+                self.update_preview();
             }
+        }
+    }
+
+    /// Показывает прочитанный каталог с настройками вида вкладки
+    pub fn apply_listing(&mut self, listing: Listing, show_hidden: bool) {
+        self.loading_entries = None;
+        // Запомненные для каталога настройки важнее общих,
+        // а `.corvus.toml` в самом каталоге важнее запомненных
+        self.view = self.dir_prefs.get(&self.current_dir);
+        self.dir_overrides = listing.overrides;
+        let mut view = self.view.clone().unwrap_or_else(|| ViewPrefs {
+            sort: self.sort.clone(),
+            show_hidden,
+            show_file_details: true,
+        });
+        if let Some(overrides) = &self.dir_overrides {
+            overrides.apply(&mut view);
+        }
+        let filter = self.dir_overrides.as_ref().and_then(|overrides| overrides.filter_matcher());
+        self.free_space = listing.free_space;
+        self.hidden_count = if view.show_hidden {
+            0
+        } else {
+            listing.entries.iter().filter(|entry| entry.name.starts_with('.')).count()
         };
-        self.entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
-        self.filtered_entries = self.entries.clone(); // Initially, filtered entries are the same as all entries
-        self.cursor = 0;
+        self.entries = listing
+            .entries
+            .into_iter()
+            .filter(|entry| view.show_hidden || !entry.name.starts_with('.'))
+            .filter(|entry| entry.is_dir || filter.as_ref().is_none_or(|filter| filter.is_match(&entry.name)))
+            .collect();
+        // Закреплённые каталоги стоят выше остальных при любой сортировке
+        let pinned = &self.pinned;
+        self.entries.sort_by(|a, b| {
+            pinned.contains(&b.path).cmp(&pinned.contains(&a.path)).then_with(|| view.sort.compare(a, b))
+        });
+        self.update_filtered_entries(None);
+        self.cursor = self
+            .pending_select
+            .take()
+            .and_then(|path| self.filtered_entries.iter().position(|entry| entry.path == path))
+            .unwrap_or(0);
         self.update_preview();
     }
 
@@ -175,10 +1129,19 @@ impl TabState {
         self.update_preview();
     }
 
+    /// Входит в каталог под курсором. `current_dir` хранит логический путь:
+    /// при входе через символическую ссылку путь остаётся путём ссылки, а
+    /// содержимое читается из её цели (`read_dir` разыменовывает ссылку).
     pub fn enter_directory(&mut self, show_hidden: bool) {
         if let Some(entry) = self.filtered_entries.get(self.cursor) {
             if entry.is_dir {
-                self.current_dir = entry.path.clone();
+                self.change_dir(entry.path.clone());
+                // В анализе занятого места подкаталог берётся из уже
+                // просканированного дерева
+                match self.disk_usage() {
+                    Some(_) => self.cursor = 0,
+                    None => self.listing_source = ListingSource::Directory,
+                }
                 self.selected_entries.clear();
                 self.update_entries(show_hidden);
             }
@@ -186,78 +1149,158 @@ impl TabState {
         self.update_preview();
     }
 
+    /// Переходит в родительский каталог логического пути и ставит курсор на
+    /// каталог (или ссылку), из которого вышли.
     pub fn leave_directory(&mut self, show_hidden: bool) {
-        if let Some(parent) = self.current_dir.parent() {
-            self.current_dir = parent.to_path_buf();
+        if self.is_showing_search_results() {
+            self.exit_search_results(show_hidden);
+            return;
+        }
+        // Из корня анализа занятого места выходим к обычному списку
+        if self.disk_usage().is_some_and(|view| view.root == self.current_dir) {
+            self.exit_search_results(show_hidden);
+            return;
+        }
+        if let Some(parent) = self.current_dir.parent().map(|p| p.to_path_buf()) {
+            let previous_dir = self.change_dir(parent);
             self.selected_entries.clear();
             self.update_entries(show_hidden);
+            self.select_path(&previous_dir);
         }
         self.update_preview();
     }
 
-    pub fn update_preview(&mut self) {
-        self.preview_scroll = (0, 0);
-        let Some(selected_entry) = self.entries.get(self.cursor) else {
+    /// Переходит в каталог `ancestor` выше текущего и ставит курсор на
+    /// подкаталог, через который шёл путь
+    pub fn enter_ancestor(&mut self, ancestor: &Path, show_hidden: bool) {
+        let previous_dir = self.current_dir.clone();
+        self.set_current_dir(ancestor.to_path_buf(), show_hidden);
+        if let Some(child) = previous_dir.ancestors().find(|dir| dir.parent() == Some(ancestor)) {
+            self.select_path(child);
+        }
+        self.update_preview();
+    }
+
+    /// Ставит курсор на ближайшую запись, имя которой начинается с `prefix`
+    /// без учёта регистра. Поиск идёт с текущей записи и по кругу.
+    pub fn jump_to_prefix(&mut self, prefix: &str) -> bool {
+        let count = self.filtered_entries.len();
+        let found = (0..count)
+            .map(|offset| (self.cursor + offset) % count)
+            .find(|&index| self.filtered_entries[index].name.to_lowercase().starts_with(prefix));
+        match found {
+            Some(index) => {
+                self.cursor = index;
+                self.update_preview();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Переходит в `dir`, записывая его в историю. Переходы вперёд после
+    /// текущей позиции забываются. Возвращает прежний каталог.
+    fn change_dir(&mut self, dir: PathBuf) -> PathBuf {
+        self.glob_filter = None;
+        let previous_dir = std::mem::replace(&mut self.current_dir, dir);
+        if self.history.is_empty() {
+            self.history.push(previous_dir.clone());
+        }
+        if self.history.get(self.history_pos) != Some(&self.current_dir) {
+            self.history.truncate(self.history_pos + 1);
+            self.history.push(self.current_dir.clone());
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+            self.history_pos = self.history.len() - 1;
+        }
+        previous_dir
+    }
+
+    /// Возвращается к предыдущему каталогу из истории
+    pub fn go_back(&mut self, show_hidden: bool) -> bool {
+        match self.history_pos.checked_sub(1) {
+            Some(pos) => self.go_to_history(pos, show_hidden),
+            None => false,
+        }
+    }
+
+    /// Переходит к следующему каталогу из истории после `go_back`
+    pub fn go_forward(&mut self, show_hidden: bool) -> bool {
+        self.go_to_history(self.history_pos + 1, show_hidden)
+    }
+
+    /// Переходит к записи `pos` истории, не добавляя новых записей.
+    /// Курсор встаёт на каталог, из которого ушли, если он есть в списке.
+    fn go_to_history(&mut self, pos: usize, show_hidden: bool) -> bool {
+        let Some(dir) = self.history.get(pos).cloned() else {
+            return false;
+        };
+        self.history_pos = pos;
+        self.glob_filter = None;
+        let previous_dir = std::mem::replace(&mut self.current_dir, dir);
+        self.listing_source = ListingSource::Directory;
+        self.selected_entries.clear();
+        self.update_entries(show_hidden);
+        self.select_path(&previous_dir);
+        self.update_preview();
+        true
+    }
+
+    pub fn update_preview(&mut self) {
+        self.preview_scroll = (0, 0);
+        self.loading_preview = None;
+        let Some(selected_entry) = self.filtered_entries.get(self.cursor).filter(|_| self.preview.enabled) else {
             self.preview_content = None;
             return;
         };
+        let Some(loader) = &self.loader else {
+            self.preview_content = Some(load_preview(selected_entry, &self.preview));
+            return;
+        };
+        let (entry, settings) = (selected_entry.clone(), self.preview.clone());
+        self.preview_content = Some(match loader.run(move || load_preview(&entry, &settings), |id, content| Loaded::Preview { id, content }) {
+            Started::Done(content) => content,
+            Started::Pending(id) => {
+                self.loading_preview = Some(id);
+                PreviewContent::Loading
+            }
+        });
+    }
 
-        if selected_entry.is_dir {
-            self.preview_content = Some(match fs::read_dir(&selected_entry.path) {
-                Ok(entries) => {
-                    let mut preview_entries = entries
-                        .filter_map(|res| res.ok())
-                        .map(|entry| {
-                            let path = entry.path();
-                            PreviewEntry {
-                                name: entry.file_name().to_string_lossy().to_string(),
-                                is_dir: path.is_dir(),
-                            }
-                        })
-                        .collect::<Vec<_>>();
-                    preview_entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
-                    PreviewContent::Directory(preview_entries)
-                }
-                Err(e) => {
-                    PreviewContent::Error(format!("Error reading directory:\n{}", e))
-                }
-            });
-        } else {
-            const MAX_PREVIEW_SIZE: u64 = 1024 * 1024; // 1MB
-            let file_content = match fs::File::open(&selected_entry.path) {
-                Ok(mut file) => {
-                    let metadata = file.metadata().ok();
-                    let file_size = metadata.as_ref().map_or(0, |m| m.len());
-
-                    if file_size > MAX_PREVIEW_SIZE {
-                        PreviewContent::TooLarge(format_size(file_size, BINARY))
-                    } else {
-                        let mut buffer = Vec::new();
-                        match file.read_to_end(&mut buffer) {
-                            Ok(_) => match String::from_utf8(buffer) {
-                                Ok(s) => PreviewContent::File(s),
-                                Err(_) => PreviewContent::Binary,
-                            },
-                            Err(e) => PreviewContent::Error(format!("Error reading file:\n{}", e)),
-                        }
-                    }
-                }
-                Err(e) => PreviewContent::Error(format!("Error opening file:\n{}", e)),
-            };
-            self.preview_content = Some(file_content);
-        }
+    /// Оставляет в списке записи, подходящие под `matcher` и под glob-фильтр вкладки
+    pub fn update_filtered_entries(&mut self, matcher: Option<&SearchMatcher>) {
+        let glob = self.glob_filter.as_ref();
+        self.filtered_entries = self
+            .entries
+            .iter()
+            .filter(|entry| glob.is_none_or(|glob| glob.is_match(&entry.name)))
+            .filter(|entry| matcher.is_none_or(|matcher| matcher.is_match(&entry.name)))
+            .cloned()
+            .collect();
     }
 
-    pub fn update_filtered_entries(&mut self, query: &str) {
-        if query.is_empty() {
-            self.filtered_entries = self.entries.clone();
-        } else {
-            self.filtered_entries = self.entries.iter()
-                .filter(|entry| {
-                    entry.name.to_lowercase().contains(&query.to_lowercase())
-                })
-                .cloned()
-                .collect();
+    /// Включает или снимает glob-фильтр и перестраивает список
+    pub fn set_glob_filter(&mut self, filter: Option<GlobFilter>) {
+        let path = self.get_selected_entry_path();
+        self.glob_filter = filter;
+        self.update_filtered_entries(None);
+        self.cursor = path
+            .and_then(|path| self.filtered_entries.iter().position(|entry| entry.path == path))
+            .unwrap_or(0);
+        self.update_preview();
+    }
+
+    /// Ставит курсор на запись с путём `path`, если она есть в списке.
+    /// Пока каталог читается в фоне, курсор встанет на неё по готовности.
+    pub fn select_path(&mut self, path: &std::path::Path) {
+        if self.loading_entries.is_some() {
+            self.pending_select = Some(path.to_path_buf());
+            return;
+        }
+        if let Some(index) = self.filtered_entries.iter().position(|e| e.path == path) {
+            self.cursor = index;
+            self.update_preview();
         }
     }
 
@@ -270,7 +1313,7 @@ impl TabState {
             if self.selected_entries.contains(&entry.path) {
                 self.selected_entries.remove(&entry.path);
             } else {
-                self.selected_entries.insert(entry.path.clone());
+                self.selected_entries.insert(entry);
             }
         }
     }
@@ -281,33 +1324,274 @@ impl TabState {
             if self.selected_entries.contains(&entry.path) {
                 self.selected_entries.remove(&entry.path);
             } else {
-                self.selected_entries.insert(entry.path.clone());
+                self.selected_entries.insert(entry);
+            }
+        }
+    }
+}
+
+/// Готовит предпросмотр записи `entry`. Каталог и файл читаются с диска,
+/// поэтому на зависшем монтировании вызов может не вернуться.
+fn load_preview(entry: &DirEntry, settings: &PreviewSettings) -> PreviewContent {
+    if entry.is_dir {
+        match fs::read_dir(&entry.path) {
+            Ok(entries) => {
+                // Полные записи собираем только для показываемой части списка
+                let mut children = entries
+                    .filter_map(|res| res.ok())
+                    .map(|entry| {
+                        let path = entry.path();
+                        (path.is_dir(), entry.file_name().to_string_lossy().to_string(), path)
+                    })
+                    .collect::<Vec<_>>();
+                let total = children.len();
+                children.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+                let preview_entries = children
+                    .into_iter()
+                    .take(PREVIEW_DIR_LIMIT)
+                    .map(|(_, _, path)| {
+                        let entry = DirEntry::from_path(path);
+                        let child_count = entry.is_dir
+                            .then_some(&entry.path)
+                            .and_then(|path| fs::read_dir(path).ok())
+                            .map(|children| children.count());
+                        PreviewEntry {
+                            name: entry.name,
+                            is_dir: entry.is_dir,
+                            is_symlink: entry.is_symlink,
+                            target: entry.target,
+                            is_broken_link: entry.is_broken_link,
+                            child_count,
+                        }
+                    })
+                    .collect();
+                PreviewContent::Directory {
+                    path: entry.path.clone(),
+                    entries: preview_entries,
+                    total,
+                    size: None,
+                }
+            }
+            Err(e) => {
+                PreviewContent::Error(format!("Error reading directory:\n{}", e))
+            }
+        }
+    } else {
+        let max_size = settings.max_preview_size;
+        // Лог читается с конца и не дальше `max_size` байт, так что
+        // показывается и у файла больше предела
+        let tail = settings.tail_logs && entry.path.extension().is_some_and(|ext| ext == "log");
+        match fs::metadata(&entry.path) {
+            Ok(_) if tail => match io::fs_ops::load_text_tail(&entry.path, settings.max_lines, max_size) {
+                Ok(Some(preview)) => PreviewContent::File { text: preview.text, truncated: preview.truncated, tail },
+                Ok(None) => PreviewContent::Binary,
+                Err(e) => PreviewContent::Error(format!("Error reading file:\n{}", e)),
+            },
+            Ok(metadata) if metadata.len() > max_size => {
+                PreviewContent::TooLarge { size: metadata.len(), limit: max_size }
             }
+            // Читаются только показываемые строки, а не весь файл
+            Ok(_) => match io::fs_ops::load_text_preview(&entry.path, settings.max_lines) {
+                Ok(Some(preview)) => PreviewContent::File { text: preview.text, truncated: preview.truncated, tail },
+                Ok(None) => PreviewContent::Binary,
+                Err(e) => PreviewContent::Error(format!("Error reading file:\n{}", e)),
+            },
+            Err(e) => PreviewContent::Error(format!("Error opening file:\n{}", e)),
         }
     }
 }
 
+/// Сколько записей каталога показывать в предпросмотре
+pub const PREVIEW_DIR_LIMIT: usize = 500;
+
+/// Пределы размера файла для предпросмотра, между которыми переключают настройки
+pub const PREVIEW_SIZE_LIMITS: &[u64] = &[256 * 1024, 1024 * 1024, 4 * 1024 * 1024, 16 * 1024 * 1024];
+
+/// Число строк текстового предпросмотра, между которыми переключают настройки
+pub const PREVIEW_LINE_COUNTS: &[usize] = &[100, 500, 1000, 5000];
+
+/// Сколько лучших совпадений из индекса показывать в диалоге поиска
+const INDEX_RESULT_LIMIT: usize = 200;
+
+/// Описывает каждую операцию плана одной строкой, отмечая конфликты
+pub fn describe_plan(plan: &[TaskKind]) -> Vec<String> {
+    plan.iter()
+        .map(|kind| match kind {
+            TaskKind::Copy { src, dest } | TaskKind::Move { src, dest } => {
+                let verb = if matches!(kind, TaskKind::Copy { .. }) { "copy" } else { "move" };
+                let conflict = if src == dest {
+                    " [source and destination are the same]"
+                } else if dest.symlink_metadata().is_ok() {
+                    " [overwrites existing]"
+                } else {
+                    ""
+                };
+                format!("{} {} → {}{}", verb, src.display(), dest.display(), conflict)
+            }
+            TaskKind::Delete { path } if path.is_dir() => {
+                format!("delete {} (folder, {} items inside)", path.display(), count_entries(path))
+            }
+            TaskKind::Delete { path } => format!("delete {}", path.display()),
+            TaskKind::Trash { path, .. } => format!("trash {}", path.display()),
+            other => other.summary(),
+        })
+        .collect()
+}
+
+/// Число файлов и каталогов внутри `dir`, без перехода по ссылкам
+fn count_entries(dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let nested = if entry.file_type().is_ok_and(|t| t.is_dir()) { count_entries(&entry.path()) } else { 0 };
+            1 + nested
+        })
+        .sum()
+}
+
+/// Чем является диск в панели дисков, от этого зависит его значок
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountKind {
+    /// Раздел блочного устройства, например `/` или `/home`
+    System,
+    /// Съёмный носитель в `/media` или `/run/media`
+    Removable,
+    Network,
+}
+
+/// Сетевые файловые системы показываются всегда, если их тип не скрыт
+const NETWORK_FS_TYPES: &[&str] = &[
+    "cifs", "davfs", "davfs2", "fuse.rclone", "fuse.sshfs", "nfs", "nfs4", "smb3", "smbfs", "sshfs",
+];
+
+/// Вид монтирования или `None`, если его не нужно показывать. Системными
+/// считаются только устройства из `/dev`, поэтому псевдофайловые системы
+/// не попадают в список, даже если их типа нет в `hide_fs_types`.
+pub fn mount_kind(source: &Path, dest: &Path, fstype: &str, disks: &config::DisksConfig) -> Option<MountKind> {
+    if disks.hide_fs_types.iter().any(|hidden| hidden == fstype) {
+        return None;
+    }
+    if NETWORK_FS_TYPES.contains(&fstype) {
+        return Some(MountKind::Network);
+    }
+    let dest = dest.to_string_lossy();
+    if dest.starts_with("/run/media/") || dest.starts_with("/media/") {
+        return Some(MountKind::Removable);
+    }
+    (disks.show_system && source.starts_with("/dev/")).then_some(MountKind::System)
+}
+
+/// Возвращает `path` или, если он занят, `path-1`, `path-2` и так далее
+fn unique_path(path: &std::path::Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}-{}", name, n)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+/// Формат, отстоящий от `format` на `step` позиций в `ArchiveFormat::ALL`
+fn step_format(format: ArchiveFormat, step: usize) -> ArchiveFormat {
+    let index = ArchiveFormat::ALL.iter().position(|f| *f == format).unwrap_or(0);
+    ArchiveFormat::ALL[(index + step) % ArchiveFormat::ALL.len()]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_preview_width() -> u16 {
+    DEFAULT_PREVIEW_WIDTH
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppState {
     pub tabs: Vec<TabState>,
     pub active_tab_index: usize,
     pub show_tabs: bool,
+    /// Один или два списка файлов
+    #[serde(default)]
+    pub layout_mode: LayoutMode,
+    /// Активный список в двухпанельном режиме
+    #[serde(default)]
+    pub active_panel: PanelSide,
+    /// Правый список двухпанельного режима, создаётся при первом включении
+    #[serde(skip)]
+    pub second_panel: Option<TabState>,
     #[serde(skip)]
     pub task_manager: TaskManager,
     pub clipboard: Clipboard,
     pub show_hidden_files: bool, // Re-add this
+    /// Настройки сортировки, общие для всех вкладок
+    #[serde(default)]
+    pub sort_settings: SortSettings,
+    /// Настройки вида, запомненные для отдельных каталогов
+    #[serde(skip)]
+    pub dir_prefs: DirPrefs,
+    /// Каталоги, закреплённые вверху списка родителя, из `pinned` в config.toml
+    #[serde(skip)]
+    pub pinned: PinnedDirs,
+    /// Настройки предпросмотра, общие для всех вкладок
+    #[serde(skip)]
+    pub preview_settings: PreviewSettings,
+    /// Подтверждения удаления и перезаписи
+    #[serde(skip)]
+    pub behavior_settings: BehaviorSettings,
+    /// Формат и степень сжатия, выбранные в диалоге архивации по умолчанию
+    #[serde(skip)]
+    pub archive_settings: ArchiveSettings,
+    /// Показывать колонки размера и даты изменения в списке файлов
+    #[serde(default = "default_true")]
+    pub show_file_details: bool,
+    /// Строка подсказок с клавишами для панели в фокусе
+    #[serde(skip, default = "default_true")]
+    pub show_key_hints: bool,
+    /// Левая панель с папками, закладками, дисками и удалёнными ресурсами
+    #[serde(skip, default = "default_true")]
+    pub show_left_pane: bool,
+    /// Нижние панели задач и информации. Без них уведомления показываются
+    /// отдельной строкой внизу экрана.
+    #[serde(skip, default = "default_true")]
+    pub show_footer: bool,
+    /// Ширина предпросмотра или правого списка в процентах от места справа
+    /// от левой панели
+    #[serde(skip, default = "default_preview_width")]
+    pub preview_width: u16,
+    /// Язык интерфейса из config.toml или системной локали
+    #[serde(skip)]
+    pub language: Language,
+    /// Двоичные (KiB) или десятичные (kB) единицы размеров
+    #[serde(skip)]
+    pub size_units: SizeUnits,
     #[serde(skip)]
     pub focus: FocusBlock,
     pub xdg_dirs: Vec<(String, PathBuf)> ,
     pub xdg_cursor: usize,
-    pub bookmarks: Vec<(String, PathBuf)>,
+    pub bookmarks: Vec<Bookmark>,
     pub bookmarks_cursor: usize,
+    /// Закладки и XDG-каталоги, которых нет на диске.
+    /// Обновляется вместе со списком дисков, чтобы не проверять пути при каждой отрисовке.
+    #[serde(skip)]
+    pub missing_places: HashSet<PathBuf>,
     #[cfg(feature = "mounts")]
     #[serde(skip)]
     pub mounts: Vec<proc_mounts::MountInfo>,
+    /// Занятое и свободное место на смонтированных дисках
+    #[serde(skip)]
+    pub disk_usage: DiskUsageProber,
     #[cfg(feature = "mounts")]
     pub disks_cursor: usize,
+    /// Сохранённые в `[remotes]` и подключённые в этом запуске удалённые каталоги
+    #[serde(skip)]
+    pub remotes: Vec<Remote>,
+    #[serde(skip)]
+    pub remotes_cursor: usize,
     #[serde(skip)]
     pub config: Config,
     pub show_confirmation: bool,
@@ -327,6 +1611,56 @@ pub struct AppState {
     pub path_to_rename: Option<PathBuf>,
     #[serde(skip)]
     pub pending_paste: Option<(Clipboard, PathBuf)> ,
+    /// Архив, ожидающий выбора места распаковки
+    #[serde(skip)]
+    pub pending_extract: Option<PathBuf>,
+    /// Извлечение диска, ожидающее подтверждения
+    #[serde(skip)]
+    pub pending_eject: Option<TaskKind>,
+    /// Задачи прошлого запуска, ожидающие решения пользователя
+    #[serde(skip)]
+    pub pending_resume: Vec<SavedTask>,
+    /// Пользователь подтвердил выход
+    #[serde(skip)]
+    pub quit_requested: bool,
+    /// Задана переменная `NO_COLOR`: интерфейс рисуется без цветов
+    #[serde(skip)]
+    pub no_color: bool,
+    /// Выбранная строка в списке пользовательских команд
+    #[serde(skip)]
+    pub command_palette_cursor: usize,
+    /// Имя текущей сессии; `None` — сессия по умолчанию в `session.json`
+    #[serde(skip)]
+    pub session_name: Option<String>,
+    /// Сессии в списке выбора: первой идёт сессия по умолчанию
+    #[serde(skip)]
+    pub session_choices: Vec<Option<String>>,
+    #[serde(skip)]
+    pub session_cursor: usize,
+    /// Курсор в списке каталогов выше текущего
+    #[serde(skip)]
+    pub ancestor_cursor: usize,
+    /// Блокировка файла текущей сессии от других экземпляров
+    #[serde(skip)]
+    pub session_lock: Option<SessionLock>,
+    /// Сессия открыта в другом экземпляре, поэтому здесь не сохраняется
+    #[serde(skip)]
+    pub session_read_only: bool,
+    /// Первая видимая строка списка горячих клавиш
+    #[serde(skip)]
+    pub help_scroll: usize,
+    /// Текст, по которому фильтруется справка: клавиши или описание
+    #[serde(skip)]
+    pub help_filter: String,
+    /// Операции подтверждаемого действия, если пользователь попросил их показать
+    #[serde(skip)]
+    pub plan_preview: Option<Vec<String>>,
+    #[serde(skip)]
+    pub plan_scroll: usize,
+    /// Пробный режим: удаление и вставка всегда спрашивают подтверждение и
+    /// сразу показывают список операций
+    #[serde(skip)]
+    pub dry_run: bool,
     pub notification: Option<String>,
     #[serde(skip)]
     pub notification_timer: Option<std::time::Instant>,
@@ -342,13 +1676,119 @@ pub struct AppState {
     pub search_query: String,
     #[serde(skip)]
     pub search_mode: SearchMode,
+    #[serde(skip)]
+    pub search_syntax: SearchSyntax,
+    #[serde(skip)]
+    pub search_error: Option<String>,
+    #[serde(skip)]
+    pub background_search: BackgroundSearch,
+    /// Фоновый подсчёт размеров каталогов для панели информации и предпросмотра
+    #[serde(skip)]
+    pub dir_sizer: DirSizer,
+    /// Сканирование для анализа занятого места, см. `start_disk_usage`
+    #[serde(skip)]
+    pub usage_scanner: UsageScanner,
+    /// Проверка, что открываемое из левой панели монтирование отвечает
+    #[serde(skip)]
+    pub mount_prober: MountProber,
+    /// Списки и предпросмотры, которые читались дольше `LOAD_WAIT`
+    #[serde(skip)]
+    pub dir_loader: DirLoader,
+    /// Раздаётся вкладкам после `enable_background_loading`
+    #[serde(skip)]
+    load_handle: Option<LoadHandle>,
+    #[serde(skip)]
+    pub command_previewer: CommandPreviewer,
+    /// Последняя команда разового предпросмотра, подставляется в диалог
+    #[serde(skip)]
+    pub preview_command: String,
+    /// Искать во всех подкаталогах текущего каталога
+    #[serde(skip)]
+    pub search_recursive: bool,
+    /// Каталог, с которого начат рекурсивный поиск. Переход к результатам
+    /// меняет текущий каталог, а новый запрос ищет от того же корня.
+    #[serde(skip)]
+    pub search_root: Option<PathBuf>,
+    /// Фоновый индекс каталогов из `search.index_roots`
+    #[serde(skip)]
+    pub file_index: FileIndexer,
     pub search_results: Vec<DirEntry>,
     pub search_cursor: usize,
-    pub archive_format: String,
+    /// Отмеченные в диалоге поиска пути. Сохраняются при смене запроса,
+    /// чтобы собрать набор из нескольких поисков.
+    #[serde(skip)]
+    pub search_selection: HashSet<PathBuf>,
+    pub archive_format: ArchiveFormat,
+    /// Степень сжатия нового архива, от 0 (без сжатия) до 9 (максимальная)
+    #[serde(skip)]
+    pub archive_compression_level: u32,
+    /// Удалить исходные файлы после успешной архивации
+    #[serde(skip)]
+    pub archive_remove_sources: bool,
+    /// Следит за правками config.toml
+    #[serde(skip)]
+    pub config_watcher: ConfigWatcher,
+    /// Выбранный пункт экрана первичной настройки
+    #[serde(skip)]
+    pub setup_cursor: usize,
+    /// Набранное начало имени для перехода к файлу, в нижнем регистре
+    #[serde(skip)]
+    pub type_ahead: String,
+    /// Когда последний раз дополнялся `type_ahead`; `None`, если переход не начат
+    #[serde(skip)]
+    pub type_ahead_at: Option<std::time::Instant>,
     #[serde(skip)]
     pub plugins: Vec<Plugin>,
+    /// Процессы включённых плагинов
+    #[serde(skip)]
+    pub plugin_host: PluginHost,
+    /// Каталог, в который устанавливаются плагины
+    #[serde(skip)]
+    pub plugins_dir: Option<PathBuf>,
+    /// Итог установки или удаления плагина для панели плагинов в настройках
     #[serde(skip)]
-    pub terminal: Option<TerminalState>,
+    pub plugin_message: Option<String>,
+    /// Открытый в настройках журнал stderr плагина
+    #[serde(skip)]
+    pub plugin_log_view: Option<PluginLogView>,
+    /// Каталог плагина, согласие на права которого сейчас спрашивается
+    #[serde(skip)]
+    pub plugin_consent: Option<PathBuf>,
+    /// Каталог и выделение, о которых плагинам уже сообщено
+    #[serde(skip)]
+    pub plugin_dir: Option<PathBuf>,
+    #[serde(skip)]
+    pub plugin_selection: Vec<PathBuf>,
+    /// Вывод оболочек всех вкладок
+    #[serde(skip)]
+    pub terminal_channel: TerminalChannel,
+    #[serde(skip)]
+    pub pane_areas: Cell<PaneAreas>,
+    /// Последний щелчок мышью: когда, в каком списке и по какой записи
+    #[serde(skip)]
+    pub last_click: Option<(std::time::Instant, PanelSide, usize)>,
+}
+
+/// Пишет пропущенные настройки в лог и составляет уведомление о них
+fn config_warnings_notice(warnings: &[String], language: Language) -> Option<String> {
+    for warning in warnings {
+        log::warn!("Ignoring invalid setting {}", warning);
+    }
+    match warnings {
+        [] => None,
+        [warning] => Some(Msg::ConfigSettingIgnored.fill(language, &[warning])),
+        [warning, rest @ ..] => Some(Msg::ConfigSettingsIgnored.fill(language, &[&(rest.len() + 1), warning])),
+    }
+}
+
+/// Журнал stderr плагина в настройках
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginLogView {
+    /// Каталог плагина
+    pub plugin: PathBuf,
+    /// На сколько строк журнал пролистан назад от последней; при нуле
+    /// новые строки сразу видны
+    pub scroll: usize,
 }
 
 #[derive(Debug)]
@@ -356,15 +1796,42 @@ pub enum ActionToConfirm {
     Delete,
     Paste,
     Unmount,
+    /// Извлечение диска из `pending_eject`
+    Eject,
     RemoveBookmark,
+    /// Удаление каталога плагина из `path_to_delete`
+    RemovePlugin,
+    /// Согласие на права плагина из `plugin_consent`
+    GrantPluginPermissions,
+    /// Продолжить без сохранения сессии, открытой в другом экземпляре
+    SessionInUse,
+    Extract,
+    ResumeTasks,
+    Quit,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        let config = config::load_config().unwrap_or_else(|err| {
-            log::error!("Failed to load config: {}", err);
-            Config::default()
-        });
+        Self::with_session(None)
+    }
+
+    /// Состояние с вкладками из сессии `session`; `None` — сессия по умолчанию
+    pub fn with_session(session: Option<String>) -> Self {
+        // Об ошибках в конфигурации сообщаем при запуске, а не молча берём значения по умолчанию
+        // Текст составляется позже, когда известен язык из настроек
+        let (config, warnings, load_error) = match config::load_config() {
+            Ok((config, warnings)) => (config, warnings, None),
+            Err(err) => {
+                log::error!("Failed to load config: {}", err);
+                let first_line = err.to_string().lines().next().unwrap_or_default().to_string();
+                (Config::default(), Vec::new(), Some(first_line))
+            }
+        };
+        if let Some(themes_dir) = crate::paths::themes_dir() {
+            crate::settings::register_user_schemes(crate::settings::load_user_schemes(&themes_dir));
+        }
+        let file_index = FileIndexer::new(&config.search.index_roots);
+        let plugins = plugin::discover_plugins(&config.plugins.disabled);
 
         let mut xdg_dirs = Vec::new();
         if let Some(user_dirs) = UserDirs::new() {
@@ -377,25 +1844,51 @@ impl AppState {
             xdg_dirs.push(("Home".to_string(), user_dirs.home_dir().to_path_buf()));
         }
 
-        let bookmarks = config.bookmarks.clone().into_iter().collect();
+        let bookmarks = config.bookmarks.clone();
 
         // Попытка загрузить сессию
         let mut app_state = Self {
             tabs: vec![],
             active_tab_index: 0,
             show_tabs: false, // Hidden by default with one tab
-            task_manager: TaskManager::new(),
+            layout_mode: LayoutMode::Single,
+            active_panel: PanelSide::Left,
+            second_panel: None,
+            task_manager: TaskManager::with_limits(
+                config.behavior.max_concurrent_tasks,
+                config.behavior.max_concurrent_light_tasks,
+            ),
             clipboard: Clipboard::new(),
             show_hidden_files: false,
+            sort_settings: SortSettings::default(),
+            dir_prefs: view_prefs::load_view_prefs().unwrap_or_else(|err| {
+                log::error!("Failed to load view preferences: {}", err);
+                DirPrefs::default()
+            }),
+            pinned: PinnedDirs::new(&config.pinned),
+            preview_settings: PreviewSettings::default(),
+            behavior_settings: BehaviorSettings::default(),
+            archive_settings: ArchiveSettings::default(),
+            show_file_details: true,
+            show_key_hints: true,
+            show_left_pane: true,
+            show_footer: true,
+            preview_width: DEFAULT_PREVIEW_WIDTH,
+            language: Language::default(),
+            size_units: SizeUnits::default(),
             focus: FocusBlock::Middle,
             xdg_dirs,
             xdg_cursor: 0,
             bookmarks,
             bookmarks_cursor: 0,
+            missing_places: HashSet::new(),
             #[cfg(feature = "mounts")]
             mounts: Vec::new(), // Initially empty, will be populated by update_mounts
+            disk_usage: DiskUsageProber::new(),
             #[cfg(feature = "mounts")]
             disks_cursor: 0,
+            remotes: Vec::new(),
+            remotes_cursor: 0,
             config,
             show_confirmation: false,
             confirmation_message: String::new(),
@@ -408,6 +1901,23 @@ impl AppState {
             create_file_type: None,
             path_to_rename: None,
             pending_paste: None,
+            pending_extract: None,
+            pending_eject: None,
+            pending_resume: Vec::new(),
+            quit_requested: false,
+            no_color: std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+            command_palette_cursor: 0,
+            session_name: None,
+            session_choices: Vec::new(),
+            session_cursor: 0,
+            ancestor_cursor: 0,
+            session_lock: None,
+            session_read_only: false,
+            help_scroll: 0,
+            help_filter: String::new(),
+            plan_preview: None,
+            plan_scroll: 0,
+            dry_run: false,
             notification: None,
             notification_timer: None,
             input_dialog_error: None,
@@ -420,450 +1930,2294 @@ impl AppState {
             show_search_dialog: false,
             search_query: String::new(),
             search_mode: SearchMode::Name,
+            search_syntax: SearchSyntax::Substring,
+            search_error: None,
+            background_search: BackgroundSearch::new(),
+            dir_sizer: DirSizer::new(),
+            usage_scanner: UsageScanner::new(),
+            mount_prober: MountProber::new(),
+            dir_loader: DirLoader::new(),
+            load_handle: None,
+            command_previewer: CommandPreviewer::new(),
+            preview_command: String::new(),
+            search_recursive: false,
+            search_root: None,
+            file_index,
             search_results: Vec::new(),
             search_cursor: 0,
-            archive_format: "zip".to_string(),
-            plugins: plugin::discover_plugins(),
-            terminal: None,
+            search_selection: HashSet::new(),
+            archive_format: ArchiveFormat::Zip,
+            archive_compression_level: 6,
+            archive_remove_sources: false,
+            config_watcher: ConfigWatcher::for_default_config(),
+            setup_cursor: 0,
+            type_ahead: String::new(),
+            type_ahead_at: None,
+            plugins,
+            plugin_host: PluginHost::default(),
+            plugins_dir: plugin::plugins_root(),
+            plugin_message: None,
+            plugin_log_view: None,
+            plugin_consent: None,
+            plugin_dir: None,
+            plugin_selection: Vec::new(),
+            terminal_channel: TerminalChannel::default(),
+            pane_areas: Cell::new(PaneAreas::default()),
+            last_click: None,
         };
 
+        Settings::from_config(&app_state.config).apply_to_app_state(&mut app_state);
+
         // Попытка загрузить сохраненную сессию
-        match crate::session::load_session() {
+        match crate::session::load_session(session.as_deref()) {
             Ok(Some(session_state)) => {
                 log::info!("Загружена сохраненная сессия");
                 session_state.apply_to_app_state(&mut app_state);
             }
             Ok(None) => {
                 log::info!("Нет сохраненной сессии, создается новая");
-                // Создаем начальную вкладку, если сессия не была загружена
-                let show_hidden_files = app_state.show_hidden_files;
-                let mut initial_tab = TabState::new(0);
-                initial_tab.update_entries(show_hidden_files);
-                app_state.tabs = vec![initial_tab];
+                app_state.ensure_tab();
             }
             Err(e) => {
-                log::error!("Ошибка при загрузке сессии: {}", e);
-                // Создаем начальную вкладку в случае ошибки
-                let show_hidden_files = app_state.show_hidden_files;
-                let mut initial_tab = TabState::new(0);
-                initial_tab.update_entries(show_hidden_files);
-                app_state.tabs = vec![initial_tab];
+                log::warn!("Файл сессии повреждён, создается новая: {}", e);
+                app_state.ensure_tab();
             }
         }
+        app_state.session_name = session;
         
+        app_state.sync_remotes();
         app_state.update_mounts();
+        app_state.refresh_missing_places();
 
-        app_state
-    }
+        let config_problem = match load_error {
+            Some(first_line) => Some(Msg::ConfigNotLoaded.fill(app_state.language, &[&first_line])),
+            None => config_warnings_notice(&warnings, app_state.language),
+        };
+        if let Some(problem) = config_problem {
+            app_state.notification = Some(problem);
+            app_state.notification_timer = Some(std::time::Instant::now());
+        }
 
-    pub fn update_mounts(&mut self) {
-        #[cfg(feature = "mounts")]
-        {
-            const ALLOWED_FS_TYPES: &[&str] = &["davfs", "davfs2", "fuse.sshfs", "sshfs"];
-            self.mounts = match MountIter::new() {
-                Ok(iter) => iter
-                    .filter_map(|res| res.ok())
-                    .filter(|mount| {
-                        let fstype = mount.fstype.as_str();
-                        let dest_path = mount.dest.to_string_lossy();
-
-                        // Keep if it's an allowed FS type or if it's mounted in a common media path
-                        ALLOWED_FS_TYPES.contains(&fstype)
-                            || dest_path.starts_with("/run/media/")
-                            || dest_path.starts_with("/media/")
-                    })
-                    .collect(),
-                Err(e) => {
-                    log::error!("Failed to get mounts: {}", e);
-                    Vec::new()
-                }
-            };
-            // Ensure cursor is not out of bounds
-            if self.disks_cursor >= self.mounts.len() {
-                self.disks_cursor = self.mounts.len().saturating_sub(1);
-            }
+        match crate::session::load_unfinished_tasks() {
+            Ok(tasks) => app_state.offer_task_resume(tasks),
+            Err(e) => log::error!("Failed to load unfinished tasks: {}", e),
+        }
+
+        // При первом запуске предлагаем выбрать основные настройки
+        if config::config_path().is_some_and(|path| !path.exists()) {
+            app_state.input_mode = InputMode::Setup;
         }
+
+        app_state
     }
 
-    pub fn toggle_tabs(&mut self) {
-        self.show_tabs = !self.show_tabs;
+    /// Запрашивает выход. Если задачи ещё выполняются, сначала спрашивает
+    /// подтверждение и возвращает `false`; выйти можно, когда `quit_requested`.
+    pub fn request_quit(&mut self) -> bool {
+        let active = self.task_manager.active_count();
+        if active == 0 {
+            self.quit_requested = true;
+            return true;
+        }
+        let message = if active == 1 { Msg::QuitOneOperation } else { Msg::QuitOperations };
+        self.confirmation_message = message.fill(self.language, &[&active]);
+        self.show_confirmation = true;
+        self.action_to_confirm = Some(ActionToConfirm::Quit);
+        false
     }
 
-    pub fn get_active_tab_mut(&mut self) -> &mut TabState {
-        &mut self.tabs[self.active_tab_index]
+    /// Открывает внешний терминал в текущем каталоге активной вкладки:
+    /// команду `behavior.terminal` из config.toml или `$TERMINAL`
+    pub fn open_external_terminal(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let dir = self.get_active_tab().current_dir.clone();
+        let configured = self.config.behavior.terminal.as_deref().filter(|command| !command.trim().is_empty());
+        let command = match configured {
+            Some(template) => Some(crate::commands::expand_command(template, None, &[], &dir)),
+            None => std::env::var("TERMINAL").ok().filter(|command| !command.trim().is_empty()),
+        };
+        self.notification = Some(match command {
+            Some(command) => match io::fs_ops::spawn_detached(&command, &dir) {
+                Ok(()) => Msg::TerminalStarted.fill(self.language, &[&command, &dir.display()]),
+                Err(e) => Msg::TerminalStartFailed.fill(self.language, &[&command, &e]),
+            },
+            None => Msg::NoTerminal.text(self.language).to_string(),
+        });
+        self.notification_timer = Some(std::time::Instant::now());
     }
 
-    pub fn get_active_tab(&self) -> &TabState {
-        &self.tabs[self.active_tab_index]
+    /// Открывает список пользовательских команд
+    pub fn open_command_palette(&mut self) {
+        if self.config.commands.is_empty() {
+            self.input_mode = InputMode::Normal;
+            self.notification = Some(Msg::NoCustomCommands.text(self.language).to_string());
+            self.notification_timer = Some(std::time::Instant::now());
+            return;
+        }
+        self.command_palette_cursor = 0;
+        self.input_mode = InputMode::CommandPalette;
     }
 
-    pub fn cycle_focus(&mut self) {
-        self.focus = match self.focus {
-            FocusBlock::Xdg => FocusBlock::Bookmarks,
-            FocusBlock::Bookmarks => FocusBlock::Disks,
-            FocusBlock::Disks => FocusBlock::Middle,
-            FocusBlock::Middle => FocusBlock::Xdg,
-            FocusBlock::Terminal => FocusBlock::Middle,
+    /// Блокирует файл текущей сессии при запуске. Если его держит другой
+    /// экземпляр, сессия здесь только читается, а пользователь выбирает:
+    /// продолжить так или сохранить её под другим именем.
+    pub fn lock_session(&mut self) {
+        let Err(LockError::Held(pid)) = self.try_lock_session(self.session_name.clone()) else {
+            return;
         };
+        self.session_read_only = true;
+        let label = self.session_name.clone().unwrap_or_else(|| Msg::SessionDefaultLabel.text(self.language).to_string());
+        // Вопрос о прерванных задачах важнее, его не перекрываем
+        if self.show_confirmation {
+            self.notification = Some(Msg::SessionHeldNotSaved.fill(self.language, &[&label, &pid]));
+            self.notification_timer = Some(std::time::Instant::now());
+            return;
+        }
+        self.confirmation_message = Msg::SessionInUse.fill(self.language, &[&label, &pid]);
+        self.show_confirmation = true;
+        self.action_to_confirm = Some(ActionToConfirm::SessionInUse);
     }
 
-    pub fn move_left_pane_cursor_down(&mut self) {
-        match self.focus {
-            FocusBlock::Xdg => {
-                let max = self.xdg_dirs.len().saturating_sub(1);
-                if self.xdg_cursor < max { self.xdg_cursor += 1; }
-            },
-            FocusBlock::Bookmarks => {
-                let max = self.bookmarks.len().saturating_sub(1);
-                if self.bookmarks_cursor < max { self.bookmarks_cursor += 1; }
-            },
-            FocusBlock::Disks => {
-                #[cfg(feature = "mounts")]
-                {
-                    let max = self.mounts.len().saturating_sub(1);
-                    if self.disks_cursor < max { self.disks_cursor += 1; }
-                }
-            },
-            FocusBlock::Middle => {}, // Should not happen
-            FocusBlock::Terminal => {}, // Should not happen
+    /// Блокирует файл сессии `name` вместо текущей. Если блокировку
+    /// поставить не удалось по другой причине, сессия сохраняется без неё.
+    fn try_lock_session(&mut self, name: Option<String>) -> Result<(), LockError> {
+        match SessionLock::acquire(&crate::paths::session_file(name.as_deref())) {
+            Ok(lock) => self.session_lock = Some(lock),
+            Err(LockError::Io(e)) => {
+                log::warn!("Failed to lock the session: {}", e);
+                self.session_lock = None;
+            }
+            Err(held) => return Err(held),
         }
-        self.update_middle_pane_from_left_pane_selection();
+        self.session_read_only = false;
+        Ok(())
     }
 
-    pub fn move_left_pane_cursor_up(&mut self) {
-        match self.focus {
-            FocusBlock::Xdg => {
-                if self.xdg_cursor > 0 { self.xdg_cursor -= 1; }
-            },
-            FocusBlock::Bookmarks => {
-                if self.bookmarks_cursor > 0 { self.bookmarks_cursor -= 1; }
-            },
-            FocusBlock::Disks => {
-                #[cfg(feature = "mounts")]
-                {
-                    if self.disks_cursor > 0 { self.disks_cursor -= 1; }
-                }
-            },
-            FocusBlock::Middle => {}, // Should not happen
-            FocusBlock::Terminal => {}, // Should not happen
+    /// Открывает ввод имени для сохранения сессии под ним
+    pub fn start_session_save(&mut self) {
+        self.input_buffer = self.session_name.clone().unwrap_or_default();
+        self.input_mode = InputMode::SessionName;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
+    }
+
+    /// Сохраняет сессию под введённым именем и дальше пишет её туда
+    pub fn save_session_as(&mut self) {
+        let name = self.input_buffer.trim().to_string();
+        if !crate::session::is_valid_session_name(&name) {
+            self.input_dialog_error = Some(Msg::SessionNameInvalid.text(self.language).to_string());
+            return;
         }
-        self.update_middle_pane_from_left_pane_selection();
+        if self.session_name.as_ref() != Some(&name) || self.session_read_only {
+            if let Err(LockError::Held(pid)) = self.try_lock_session(Some(name.clone())) {
+                self.input_dialog_error = Some(Msg::SessionHeld.fill(self.language, &[&name, &pid]));
+                return;
+            }
+        }
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+        self.notification = Some(match crate::session::save_session(self, Some(&name)) {
+            Ok(()) => Msg::SessionSaved.fill(self.language, &[&name]),
+            Err(e) => Msg::SessionSaveFailed.fill(self.language, &[&name, &e]),
+        });
+        self.notification_timer = Some(std::time::Instant::now());
+        self.session_name = Some(name);
     }
 
-    pub fn update_middle_pane_from_left_pane_selection(&mut self) {
-        let path = match self.focus {
-            FocusBlock::Xdg => self.xdg_dirs.get(self.xdg_cursor).map(|(_, path)| path.clone()),
-            FocusBlock::Bookmarks => self.bookmarks.get(self.bookmarks_cursor).map(|(_, path)| path.clone()),
-            FocusBlock::Disks => {
-                #[cfg(feature = "mounts")]
-                {
-                    self.mounts.get(self.disks_cursor).map(|mount| mount.dest.clone())
-                }
-                #[cfg(not(feature = "mounts"))]
-                {
-                    None
-                }
-            },
-            FocusBlock::Middle => None, // No-op
-            FocusBlock::Terminal => None, // No-op
-        };
-
-        if let Some(path) = path {
-            let show_hidden = self.show_hidden_files;
-            let active_tab = self.get_active_tab_mut();
-            active_tab.set_current_dir(path, show_hidden);
-            active_tab.update_preview();
-            self.show_info_panel = false;
-            
-            // Cancel any active search when changing directories
-            self.cancel_search();
-        }
+    /// Открывает список сессий: по умолчанию и сохранённых под именами
+    pub fn open_session_picker(&mut self) {
+        self.session_choices = std::iter::once(None).chain(crate::session::list_sessions().into_iter().map(Some)).collect();
+        self.session_cursor = self.session_choices.iter().position(|name| *name == self.session_name).unwrap_or(0);
+        self.input_mode = InputMode::SessionPicker;
     }
 
-    pub fn toggle_hidden_files(&mut self) {
-        self.show_hidden_files = !self.show_hidden_files;
-        for tab in &mut self.tabs {
-            tab.update_entries(self.show_hidden_files);
-        }
+    pub fn move_session_cursor(&mut self, delta: isize) {
+        let last = self.session_choices.len().saturating_sub(1);
+        self.session_cursor = self.session_cursor.saturating_add_signed(delta).min(last);
     }
 
-    pub fn yank_selection(&mut self) {
-        let active_tab = self.get_active_tab();
-        let paths_to_yank = if !active_tab.selected_entries.is_empty() {
-            active_tab.selected_entries.iter().cloned().collect()
-        } else if let Some(path) = active_tab.get_selected_entry_path() {
-            vec![path]
-        } else {
-            Vec::new()
+    /// Сохраняет текущую сессию и открывает вкладки выбранной в списке
+    pub fn load_selected_session(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let Some(name) = self.session_choices.get(self.session_cursor).cloned() else {
+            return;
         };
-
-        if !paths_to_yank.is_empty() {
-            self.clipboard.yank(paths_to_yank);
+        if name == self.session_name {
+            return;
         }
-    }
-
-    pub fn cut_selection(&mut self) {
-        let active_tab = self.get_active_tab();
-        let paths_to_cut = if !active_tab.selected_entries.is_empty() {
-            active_tab.selected_entries.iter().cloned().collect()
-        } else if let Some(path) = active_tab.get_selected_entry_path() {
-            vec![path]
-        } else {
-            Vec::new()
+        let label = name.clone().unwrap_or_else(|| Msg::SessionDefaultLabel.text(self.language).to_string());
+        self.notification_timer = Some(std::time::Instant::now());
+        let session_state = match crate::session::load_session(name.as_deref()) {
+            Ok(Some(session_state)) => session_state,
+            Ok(None) => {
+                self.notification = Some(Msg::SessionNotSaved.fill(self.language, &[&label]));
+                return;
+            }
+            Err(e) => {
+                self.notification = Some(Msg::SessionLoadFailed.fill(self.language, &[&label, &e]));
+                return;
+            }
         };
-
-        if !paths_to_cut.is_empty() {
-            self.clipboard.cut(paths_to_cut);
+        if !self.session_read_only {
+            if let Err(e) = crate::session::save_session(self, self.session_name.as_deref()) {
+                log::error!("Failed to save session before switching: {}", e);
+            }
         }
-    }
-
-    pub fn paste(&mut self) {
-        if self.clipboard.paths.is_empty() {
+        // Блокировка текущей сессии снимается, только если новую удалось взять
+        if let Err(LockError::Held(pid)) = self.try_lock_session(name.clone()) {
+            self.notification = Some(Msg::SessionHeld.fill(self.language, &[&label, &pid]));
             return;
         }
+        session_state.apply_to_app_state(self);
+        self.session_name = name;
+        self.notification = Some(Msg::SessionLoaded.fill(self.language, &[&label]));
+    }
 
-        let destination = self.get_active_tab().current_dir.clone();
-        let conflict = self.clipboard.paths.iter().any(|src_path| {
-            let dest_path = destination.join(src_path.file_name().unwrap());
-            dest_path.exists()
-        });
+    pub fn move_command_palette_cursor(&mut self, delta: isize) {
+        let last = self.config.commands.len().saturating_sub(1);
+        self.command_palette_cursor = self.command_palette_cursor.saturating_add_signed(delta).min(last);
+    }
 
-        if conflict {
-            self.confirmation_message = "A file with the same name already exists. Overwrite? (y/n)".to_string();
-            self.show_confirmation = true;
-            self.action_to_confirm = Some(ActionToConfirm::Paste);
-            self.pending_paste = Some((self.clipboard.clone(), destination));
-        } else {
-            self.execute_paste(self.clipboard.clone(), destination);
+    /// Запускает команду с клавишей `key`. Возвращает `false`, если такой нет.
+    pub fn run_custom_command_by_key(&mut self, key: char) -> bool {
+        match self.config.commands.iter().position(|command| command.key == key) {
+            Some(index) => {
+                self.run_custom_command(index);
+                true
+            }
+            None => false,
         }
     }
 
-    fn execute_paste(&mut self, clipboard: Clipboard, destination: PathBuf) {
-        let mode = clipboard.mode.clone().unwrap();
-        for src_path in &clipboard.paths {
-            let dest_path = destination.join(src_path.file_name().unwrap());
-            let description = format!("{:?} {:?} -> {:?}", mode, src_path.file_name().unwrap(), destination);
-            let task_kind = match mode {
-                ClipboardMode::Copy => TaskKind::Copy { src: src_path.clone(), dest: dest_path },
-                ClipboardMode::Move => TaskKind::Move { src: src_path.clone(), dest: dest_path },
-            };
-            self.task_manager.add_task(task_kind, description);
+    /// Показывает терминал в правой панели. Оболочка запускается при
+    /// первом открытии.
+    pub fn show_terminal(&mut self) {
+        self.get_active_tab_mut().right_pane_view = RightPaneView::Terminal;
+        self.focus = FocusBlock::Terminal;
+        if self.get_active_tab().terminal.lifecycle == TerminalLifecycle::NotStarted {
+            self.start_terminal();
         }
+    }
 
-        if mode == ClipboardMode::Move {
-            self.clipboard.clear();
+    /// Запускает новую оболочку в терминале активной вкладки, в её каталоге
+    pub fn start_terminal(&mut self) {
+        let scrollback = self.config.terminal.scrollback;
+        let output = self.terminal_channel.sender();
+        let tab = self.get_active_tab_mut();
+        let dir = tab.current_dir.clone();
+        if let Err(e) = tab.terminal.start(&dir, scrollback, output) {
+            log::error!("Failed to start the shell: {}", e);
+            self.notification = Some(Msg::ShellStartFailed.fill(self.language, &[&e]));
+            self.notification_timer = Some(std::time::Instant::now());
         }
     }
 
-    pub fn confirm_paste(&mut self) {
-        if let Some((clipboard, destination)) = self.pending_paste.take() {
-            self.execute_paste(clipboard, destination);
+    /// Отправляет байты оболочке активной вкладки; ошибку записи показывает
+    pub fn send_to_terminal(&mut self, bytes: &[u8]) {
+        if let Err(e) = self.get_active_tab_mut().terminal.send(bytes) {
+            self.report_terminal_error(e);
         }
-        self.show_confirmation = false;
-        self.action_to_confirm = None;
     }
 
-    pub fn cancel_paste(&mut self) {
-        self.pending_paste = None;
-        self.show_confirmation = false;
-        self.action_to_confirm = None;
+    /// Вставляет текст в терминал активной вкладки
+    pub fn paste_to_terminal(&mut self, text: &str) {
+        if let Err(e) = self.get_active_tab_mut().terminal.paste(text) {
+            self.report_terminal_error(e);
+        }
     }
 
-    pub fn next_tab(&mut self) {
-        self.active_tab_index = (self.active_tab_index + 1) % self.tabs.len();
+    fn report_terminal_error(&mut self, error: String) {
+        log::error!("Failed to write to the shell: {}", error);
+        self.notification = Some(Msg::ShellWriteFailed.fill(self.language, &[&error]));
+        self.notification_timer = Some(std::time::Instant::now());
     }
 
-    pub fn previous_tab(&mut self) {
-        if self.active_tab_index > 0 {
-            self.active_tab_index -= 1;
-        } else {
-            self.active_tab_index = self.tabs.len() - 1;
+    /// Печатает в строке оболочки пути выделенных файлов или файла под
+    /// курсором, не нажимая Enter. Терминал открывается и при необходимости
+    /// запускается.
+    pub fn send_selection_to_terminal(&mut self) {
+        let active_tab = self.get_active_tab();
+        let mut files: Vec<PathBuf> = active_tab.selected_entries.iter().cloned().collect();
+        files.sort();
+        if files.is_empty() {
+            files.extend(active_tab.get_selected_entry_path());
         }
-    }
-
-    pub fn new_tab(&mut self) {
-        if self.tabs.len() >= 10 {
+        if files.is_empty() {
             return;
         }
-        log::info!("new_tab called. Current tab count: {}", self.tabs.len());
-        let new_id = self.tabs.len();
-        let mut new_tab = TabState::new(new_id);
-        new_tab.update_entries(self.show_hidden_files);
-        self.tabs.push(new_tab);
-        self.active_tab_index = new_id;
-        self.show_tabs = true; // Show tabs when a new one is created
-        log::info!("new_tab finished. New tab count: {}. Active index: {}", self.tabs.len(), self.active_tab_index);
+        self.show_terminal();
+        if self.get_active_tab().terminal.lifecycle == TerminalLifecycle::Exited {
+            self.start_terminal();
+        }
+        let mut text: Vec<String> = files
+            .iter()
+            .map(|path| crate::commands::prompt_quote(&path.to_string_lossy()))
+            .collect();
+        // Пробел в конце, чтобы сразу продолжить команду
+        text.push(String::new());
+        self.send_to_terminal(text.join(" ").as_bytes());
     }
 
-    pub fn close_tab(&mut self) {
-        if self.tabs.len() > 1 {
-            self.tabs.remove(self.active_tab_index);
-            if self.active_tab_index >= self.tabs.len() {
-                self.active_tab_index = self.tabs.len() - 1;
-            }
-            if self.tabs.len() == 1 {
-                self.show_tabs = false; // Hide tabs when only one is left
-            }
+    /// Передаёт вывод оболочки терминалу вкладки, которой она принадлежит.
+    /// Вывод закрытых вкладок и прежних оболочек отбрасывается.
+    pub fn handle_terminal_output(&mut self, output: TerminalOutput) {
+        let session = output.session();
+        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.terminal.session() == session) {
+            tab.terminal.handle_output(output);
         }
     }
 
-    pub fn add_bookmark(&mut self) {
-        let path = self.get_active_tab().current_dir.clone();
-        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-        if !name.is_empty() {
-            self.config.bookmarks.insert(name.clone(), path.clone());
-            self.bookmarks.push((name, path));
-            if let Err(e) = config::save_config(&self.config) {
-                log::error!("Failed to save config: {}", e);
-            }
+    /// Подставляет выделение в команду и запускает её фоновой задачей
+    /// или во встроенном терминале
+    pub fn run_custom_command(&mut self, index: usize) {
+        self.input_mode = InputMode::Normal;
+        let Some(command) = self.config.commands.get(index).cloned() else {
+            return;
+        };
+        let active_tab = self.get_active_tab();
+        let dir = active_tab.current_dir.clone();
+        let file = active_tab.get_selected_entry_path();
+        let mut files: Vec<PathBuf> = active_tab.selected_entries.iter().cloned().collect();
+        files.sort();
+        if files.is_empty() {
+            files.extend(file.clone());
         }
-    }
+        let expanded = crate::commands::expand_command(&command.cmd, file.as_deref(), &files, &dir);
 
-    pub fn remove_bookmark(&mut self) {
-        if self.focus == FocusBlock::Bookmarks {
-            if let Some((name, _path)) = self.bookmarks.get(self.bookmarks_cursor) {
-                self.confirmation_message = format!("Remove bookmark '{}'? (y/n)", name);
-                self.show_confirmation = true;
-                self.action_to_confirm = Some(ActionToConfirm::RemoveBookmark);
-            }
+        if command.run_in_terminal && self.run_in_embedded_terminal(&dir, &expanded) {
+            return;
         }
+        let task_kind = TaskKind::RunCommand { name: command.name.clone(), command: expanded, dir };
+        self.task_manager.add_task(task_kind, command.name);
     }
 
-    fn confirm_remove_bookmark(&mut self) {
-        if let Some((name, _path)) = self.bookmarks.get(self.bookmarks_cursor).cloned() {
-            self.bookmarks.remove(self.bookmarks_cursor);
-            self.config.bookmarks.remove(&name);
-
-            if let Err(e) = config::save_config(&self.config) {
-                log::error!("Failed to save config after removing bookmark: {}", e);
-            }
-
-            if self.bookmarks_cursor >= self.bookmarks.len() {
-                self.bookmarks_cursor = self.bookmarks.len().saturating_sub(1);
-            }
+    /// Выполняет команду в каталоге `dir` во встроенном терминале активной
+    /// вкладки. Возвращает `false`, если оболочку запустить не удалось.
+    fn run_in_embedded_terminal(&mut self, dir: &Path, command: &str) -> bool {
+        self.show_terminal();
+        if self.get_active_tab().terminal.lifecycle == TerminalLifecycle::Exited {
+            self.start_terminal();
         }
+        if self.get_active_tab().terminal.lifecycle != TerminalLifecycle::Running {
+            return false;
+        }
+        let line = format!("cd {} && {}\r", crate::commands::shell_quote(&dir.to_string_lossy()), command);
+        self.send_to_terminal(line.as_bytes());
+        true
     }
 
-    pub fn delete_selection(&mut self) {
-        let active_tab = self.get_active_tab();
-        let paths_to_delete: Vec<PathBuf> = if !active_tab.selected_entries.is_empty() {
-            active_tab.selected_entries.iter().cloned().collect()
-        } else if let Some(path) = active_tab.get_selected_entry_path() {
-            vec![path]
-        } else {
-            Vec::new()
-        };
-
-        if !paths_to_delete.is_empty() {
-            self.path_to_delete = Some(paths_to_delete[0].clone()); // A bit of a hack for now
-            self.confirmation_message = if paths_to_delete.len() > 1 {
-                format!("Are you sure you want to delete {} items? (y/n)", paths_to_delete.len())
-            } else {
-                format!("Are you sure you want to delete {:?}? (y/n)", paths_to_delete[0].file_name().unwrap())
-            };
-            self.show_confirmation = true;
-            self.action_to_confirm = Some(ActionToConfirm::Delete);
+    /// Предлагает продолжить задачи, не завершённые в прошлый раз
+    pub fn offer_task_resume(&mut self, tasks: Vec<SavedTask>) {
+        if tasks.is_empty() {
+            return;
         }
+        let message = if tasks.len() == 1 { Msg::ResumeOneTask } else { Msg::ResumeTasks };
+        self.confirmation_message = message.fill(self.language, &[&tasks.len()]);
+        self.pending_resume = tasks;
+        self.show_confirmation = true;
+        self.action_to_confirm = Some(ActionToConfirm::ResumeTasks);
     }
 
-    fn confirm_delete(&mut self) {
-        // Сначала собираем пути для удаления
-        let paths_to_delete: Vec<PathBuf> = {
-            let active_tab = self.get_active_tab();
-            if !active_tab.selected_entries.is_empty() {
-                active_tab.selected_entries.iter().cloned().collect()
-            } else if let Some(path) = self.path_to_delete.take() {
-                vec![path]
-            } else {
-                Vec::new()
-            }
-        };
-
-        // Затем добавляем задачи на удаление
-        for path in paths_to_delete {
-            let description = format!("Delete {:?}", path.file_name().unwrap());
-            let task_kind = TaskKind::Delete { path };
-            self.task_manager.add_task(task_kind, description);
+    fn confirm_resume_tasks(&mut self) {
+        let tasks = std::mem::take(&mut self.pending_resume);
+        let total = tasks.len();
+        let restored = self.task_manager.restore(tasks);
+        if restored < total {
+            self.notification = Some(Msg::TasksSkipped.fill(self.language, &[&restored, &(total - restored)]));
+            self.notification_timer = Some(std::time::Instant::now());
         }
-        
-        // И наконец очищаем выделение
-        self.get_active_tab_mut().selected_entries.clear();
     }
 
-    pub fn unmount_selection(&mut self) {
+    pub fn update_mounts(&mut self) {
         #[cfg(feature = "mounts")]
-        if self.focus == FocusBlock::Disks {
-            if let Some(mount_info) = self.mounts.get(self.disks_cursor) {
-                self.path_to_delete = Some(mount_info.dest.clone()); // Re-use path_to_delete for unmount path
-                self.confirmation_message = format!("Are you sure you want to unmount {:?}? (y/n)", mount_info.dest);
-                self.show_confirmation = true;
-                self.action_to_confirm = Some(ActionToConfirm::Unmount);
+        {
+            let all_mounts: Vec<_> = match MountIter::new() {
+                Ok(iter) => iter.filter_map(|res| res.ok()).collect(),
+                Err(e) => {
+                    log::error!("Failed to get mounts: {}", e);
+                    Vec::new()
+                }
+            };
+            self.mark_connected_remotes(&all_mounts);
+            let disks = &self.config.disks;
+            let mut seen = HashSet::new();
+            self.mounts = all_mounts
+                .into_iter()
+                // Точка автомонтирования сама ничего не содержит: то, что на
+                // ней смонтировано, идёт в таблице отдельной строкой
+                .filter(|mount| mount.fstype != "autofs")
+                // Одно и то же монтирование бывает в таблице несколько раз
+                .filter(|mount| seen.insert((mount.source.clone(), mount.dest.clone())))
+                .filter(|mount| mount_kind(&mount.source, &mount.dest, &mount.fstype, disks).is_some())
+                .collect();
+            self.disk_usage.request(self.mounts.iter().map(|mount| mount.dest.as_path()));
+            // Ensure cursor is not out of bounds
+            if self.disks_cursor >= self.mounts.len() {
+                self.disks_cursor = self.mounts.len().saturating_sub(1);
             }
         }
     }
 
-    fn confirm_unmount(&mut self) {
-        if let Some(path) = self.path_to_delete.take() {
-            let description = format!("Unmount {:?}", path);
-            let task_kind = TaskKind::Unmount { path };
-            self.task_manager.add_task(task_kind, description);
+    /// Сверяет удалённые каталоги с таблицей монтирования. Каталоги,
+    /// оставшиеся смонтированными с прошлого запуска, добавляются в список.
+    #[cfg(feature = "mounts")]
+    fn mark_connected_remotes(&mut self, mounts: &[proc_mounts::MountInfo]) {
+        for remote in &mut self.remotes {
+            let mountpoint = remote.mountpoint();
+            remote.connected = mounts.iter().any(|mount| mount.dest == mountpoint);
+        }
+        let dir = crate::paths::remote_mounts_dir();
+        for mount in mounts.iter().filter(|mount| mount.dest.parent() == Some(dir.as_path())) {
+            if self.remotes.iter().any(|remote| remote.mountpoint() == mount.dest) {
+                continue;
+            }
+            let name = mount.dest.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            let mut remote = Remote::new(&name, &mount.source.to_string_lossy(), false);
+            remote.connected = true;
+            self.remotes.push(remote);
         }
     }
 
-    pub fn confirm(&mut self) {
-        if let Some(action) = self.action_to_confirm.take() {
-            match action {
-                ActionToConfirm::Delete => self.confirm_delete(),
-                ActionToConfirm::Paste => self.confirm_paste(),
-                ActionToConfirm::Unmount => self.confirm_unmount(),
-                ActionToConfirm::RemoveBookmark => self.confirm_remove_bookmark(),
+    /// Пересобирает список удалённых каталогов из `[remotes]`. Подключённые
+    /// в этом запуске остаются в списке, пока их не отключат.
+    fn sync_remotes(&mut self) {
+        let mut remotes: Vec<Remote> =
+            self.config.remotes.iter().map(|(name, url)| Remote::new(name, url, true)).collect();
+        for old in self.remotes.drain(..) {
+            match remotes.iter_mut().find(|remote| remote.name == old.name) {
+                Some(remote) => remote.connected = old.connected,
+                None if !old.saved || old.connected => remotes.push(Remote { saved: false, ..old }),
+                None => {}
             }
         }
-        self.show_confirmation = false;
+        self.remotes = remotes;
+        self.remotes_cursor = self.remotes_cursor.min(self.remotes.len().saturating_sub(1));
     }
 
-    pub fn cancel(&mut self) {
-        self.show_confirmation = false;
-        self.action_to_confirm = None;
-        self.path_to_delete = None;
-        self.pending_paste = None;
+    /// Открывает окно быстрого подключения к удалённому каталогу
+    pub fn start_remote_connect(&mut self) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::RemoteConnect;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
     }
 
-    pub fn create_item(&mut self) {
-        if self.input_buffer.is_empty() {
+    /// Подключает адрес из окна быстрого подключения. Адрес из `[remotes]`
+    /// подключается под своим именем, новый — под именем хоста.
+    pub fn connect_remote_from_input(&mut self) {
+        let url = self.input_buffer.trim().to_string();
+        if RemoteKind::of(&url).is_none() {
+            self.input_dialog_error = Some(Msg::RemoteAddressInvalid.text(self.language).to_string());
             return;
         }
-
-        let new_item_name = self.input_buffer.clone();
+        self.input_mode = InputMode::Normal;
         self.input_buffer.clear();
+        let index = match self.remotes.iter().position(|remote| remote.url == url) {
+            Some(index) => index,
+            None => {
+                let host = crate::remote::host_of(&url);
+                let mut name = host.to_string();
+                let mut number = 1;
+                while self.remotes.iter().any(|remote| remote.name == name) {
+                    number += 1;
+                    name = format!("{}-{}", host, number);
+                }
+                self.remotes.push(Remote::new(&name, &url, false));
+                self.remotes.len() - 1
+            }
+        };
+        self.remotes_cursor = index;
+        self.connect_remote(index);
+    }
 
-        let current_dir = self.get_active_tab().current_dir.clone();
-        let new_item_path = current_dir.join(&new_item_name);
+    /// Enter в блоке удалённых каталогов: открывает подключённый каталог
+    /// или подключает его
+    pub fn activate_remote(&mut self) {
+        let connected = self.remotes.get(self.remotes_cursor).is_some_and(|remote| remote.connected);
+        self.connect_remote(self.remotes_cursor);
+        if connected {
+            self.focus = FocusBlock::Middle;
+        }
+    }
 
-        if new_item_path.exists() {
-            self.input_dialog_error = Some("A file with this name already exists.".to_string());
-            self.show_input_dialog = true;
-            self.input_buffer = new_item_name;
+    /// Монтирует удалённый каталог в фоне, после монтирования активная
+    /// вкладка переходит в него. Подключённый каталог просто открывается.
+    fn connect_remote(&mut self, index: usize) {
+        let Some(remote) = self.remotes.get(index) else {
+            return;
+        };
+        let mountpoint = remote.mountpoint();
+        if remote.connected {
+            self.mount_prober.request(mountpoint);
+            return;
+        }
+        // Повторный Enter, пока идёт подключение, ничего не запускает
+        let connecting = self.task_manager.get_tasks().iter().any(|task| {
+            !task.status.is_finished() && matches!(&task.kind, TaskKind::MountRemote { mountpoint: path, .. } if *path == mountpoint)
+        });
+        if connecting {
             return;
         }
+        let url = remote.url.clone();
+        let description = format!("Connect {}", url);
+        self.task_manager.add_task(TaskKind::MountRemote { url, mountpoint }, description);
+    }
 
-        let task_kind = match self.create_file_type.as_ref().unwrap() {
-            CreateFileType::File => TaskKind::CreateFile {
-                path: new_item_path.clone(),
-            },
-            CreateFileType::Directory => TaskKind::CreateDirectory {
-                path: new_item_path.clone(),
-            },
+    /// Отключает выбранный удалённый каталог. Вкладки, открытые в нём,
+    /// переходят в домашний каталог.
+    pub fn disconnect_remote(&mut self) {
+        let Some(remote) = self.remotes.get(self.remotes_cursor) else {
+            return;
         };
-
-        let description = format!("Create {:?}", new_item_path);
-        self.task_manager.add_task(task_kind, description);
-
-        self.create_file_type = None;
+        if !remote.connected {
+            self.notification = Some(Msg::RemoteNotConnected.fill(self.language, &[&remote.name]));
+            self.notification_timer = Some(std::time::Instant::now());
+            return;
+        }
+        let mountpoint = remote.mountpoint();
+        let url = remote.url.clone();
+        let home = directories::BaseDirs::new().map_or_else(|| PathBuf::from("/"), |dirs| dirs.home_dir().to_path_buf());
+        let show_hidden = self.show_hidden_files;
+        for tab in self.panels_mut() {
+            if tab.current_dir.starts_with(&mountpoint) {
+                tab.set_current_dir(home.clone(), show_hidden);
+            }
+        }
+        let description = format!("Disconnect {}", url);
+        self.task_manager.add_task(TaskKind::UnmountRemote { url, mountpoint }, description);
     }
 
-    pub fn rename_selection(&mut self) {
-        if let Some(path) = self.get_active_tab().get_selected_entry_path() {
+    /// Отмечает итог подключения или отключения удалённого каталога.
+    /// Несохранённый каталог убирается из списка после отключения или
+    /// неудачного подключения.
+    fn finish_remote_task(&mut self, kind: &TaskKind, succeeded: bool) {
+        let (mountpoint, connected) = match kind {
+            TaskKind::MountRemote { mountpoint, .. } => (mountpoint, succeeded),
+            TaskKind::UnmountRemote { mountpoint, .. } if succeeded => (mountpoint, false),
+            _ => return,
+        };
+        let Some(index) = self.remotes.iter().position(|remote| remote.mountpoint() == *mountpoint) else {
+            return;
+        };
+        self.remotes[index].connected = connected;
+        if !connected && !self.remotes[index].saved {
+            self.remotes.remove(index);
+            self.remotes_cursor = self.remotes_cursor.min(self.remotes.len().saturating_sub(1));
+        }
+        if connected {
+            self.open_place(mountpoint.clone());
+        }
+    }
+
+    pub fn toggle_tabs(&mut self) {
+        self.show_tabs = !self.show_tabs;
+    }
+
+    /// Индекс выбранной вкладки. Устаревший индекс не должен ронять приложение,
+    /// поэтому он ограничивается числом вкладок.
+    fn selected_tab_index(&self) -> usize {
+        self.active_tab_index.min(self.tabs.len().saturating_sub(1))
+    }
+
+    /// Вкладка, выбранная в панели вкладок, независимо от активной стороны
+    pub fn get_selected_tab(&self) -> &TabState {
+        &self.tabs[self.selected_tab_index()]
+    }
+
+    /// Новая вкладка с общими настройками сортировки и предпросмотра
+    pub(crate) fn new_tab_state(&self, id: usize) -> TabState {
+        let mut tab = TabState::new(id);
+        tab.sort = self.sort_settings.clone();
+        tab.dir_prefs = self.dir_prefs.clone();
+        tab.pinned = self.pinned.clone();
+        tab.preview = self.preview_settings.clone();
+        tab.loader = self.load_handle.clone();
+        tab
+    }
+
+    /// Дальше каталоги и предпросмотры всех вкладок читаются в фоне, а
+    /// медленные приходят через `dir_loader`. Нужен работающий цикл событий,
+    /// который передаёт их в `apply_loaded`.
+    pub fn enable_background_loading(&mut self) {
+        let handle = self.dir_loader.handle();
+        for tab in self.panels_mut() {
+            tab.loader = Some(handle.clone());
+        }
+        self.load_handle = Some(handle);
+    }
+
+    /// Показывает дочитанный каталог или предпросмотр во вкладке, которая
+    /// его ждёт. Если вкладка успела перейти дальше, результат не нужен.
+    pub fn apply_loaded(&mut self, loaded: Loaded) {
+        let show_hidden = self.show_hidden_files;
+        match loaded {
+            Loaded::Entries { id, listing } => {
+                if let Some(tab) = self.panels_mut().find(|tab| tab.loading_entries == Some(id)) {
+                    tab.apply_listing(listing, show_hidden);
+                }
+            }
+            Loaded::Preview { id, content } => {
+                if let Some(tab) = self.panels_mut().find(|tab| tab.loading_preview == Some(id)) {
+                    tab.loading_preview = None;
+                    // Вывод команды `c p` и ответ плагина важнее встроенного
+                    if !matches!(tab.preview_content, Some(PreviewContent::CommandOutput { .. } | PreviewContent::Plugin { .. })) {
+                        tab.preview_content = Some(content);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Создаёт вкладку по умолчанию, если вкладок нет
+    pub fn ensure_tab(&mut self) {
+        if self.tabs.is_empty() {
+            let mut tab = self.new_tab_state(0);
+            tab.update_entries(self.show_hidden_files);
+            self.tabs.push(tab);
+            self.active_tab_index = 0;
+        }
+    }
+
+    /// Активная вкладка, а в двухпанельном режиме — список активной стороны
+    pub fn get_active_tab_mut(&mut self) -> &mut TabState {
+        let index = self.selected_tab_index();
+        match (self.layout_mode, self.active_panel, &mut self.second_panel) {
+            (LayoutMode::Dual, PanelSide::Right, Some(panel)) => panel,
+            _ => &mut self.tabs[index],
+        }
+    }
+
+    pub fn get_active_tab(&self) -> &TabState {
+        match (self.layout_mode, self.active_panel, &self.second_panel) {
+            (LayoutMode::Dual, PanelSide::Right, Some(panel)) => panel,
+            _ => self.get_selected_tab(),
+        }
+    }
+
+    /// Неактивный список в двухпанельном режиме
+    pub fn get_other_panel(&self) -> Option<&TabState> {
+        if self.layout_mode != LayoutMode::Dual {
+            return None;
+        }
+        match self.active_panel {
+            PanelSide::Left => self.second_panel.as_ref(),
+            PanelSide::Right => Some(self.get_selected_tab()),
+        }
+    }
+
+    /// Список на стороне `side`: правый есть только в двухпанельном режиме
+    fn panel_mut(&mut self, side: PanelSide) -> Option<&mut TabState> {
+        match side {
+            PanelSide::Left => {
+                let index = self.selected_tab_index();
+                Some(&mut self.tabs[index])
+            }
+            PanelSide::Right if self.layout_mode == LayoutMode::Dual => self.second_panel.as_mut(),
+            PanelSide::Right => None,
+        }
+    }
+
+    /// Щелчок по записи `index` списка `side`: список становится активным, а
+    /// курсор встаёт на запись. Второй щелчок по ней же открывает её, как `Enter`.
+    pub fn click_entry(&mut self, side: PanelSide, index: usize) {
+        let show_hidden = self.show_hidden_files;
+        let Some(panel) = self.panel_mut(side).filter(|panel| index < panel.filtered_entries.len()) else {
+            return;
+        };
+        panel.cursor = index;
+        panel.update_preview();
+        if self.layout_mode == LayoutMode::Dual {
+            self.active_panel = side;
+        }
+        self.focus = FocusBlock::Middle;
+        self.show_info_panel = false;
+
+        let double = self.last_click.is_some_and(|(at, last_side, last_index)| {
+            (last_side, last_index) == (side, index) && at.elapsed() < DOUBLE_CLICK_INTERVAL
+        });
+        if double {
+            self.last_click = None;
+            match self.get_active_tab().filtered_entries.get(index) {
+                Some(entry) if !entry.is_dir => self.open_file(entry.path.clone()),
+                _ => self.get_active_tab_mut().enter_directory(show_hidden),
+            }
+        } else {
+            self.last_click = Some((std::time::Instant::now(), side, index));
+        }
+    }
+
+    /// Открывает файл командой `behavior.opener`, по умолчанию `xdg-open`.
+    /// Редактор с `opener_in_terminal` запускается во встроенном терминале.
+    pub fn open_file(&mut self, path: PathBuf) {
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_else(|| self.get_active_tab().current_dir.clone());
+        let template = self.config.behavior.opener.as_deref().filter(|command| !command.trim().is_empty()).unwrap_or(DEFAULT_OPENER);
+        let command = crate::commands::expand_command(template, Some(&path), std::slice::from_ref(&path), &dir);
+        if self.config.behavior.opener_in_terminal && self.run_in_embedded_terminal(&dir, &command) {
+            return;
+        }
+        if let Err(e) = io::fs_ops::spawn_detached(&command, &dir) {
+            self.notification = Some(Msg::OpenFailed.fill(self.language, &[&path.display(), &e]));
+            self.notification_timer = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Щелчок по записи `index` блока `block` левой панели выбирает её и
+    /// открывает, как Enter
+    pub fn click_place(&mut self, block: FocusBlock, index: usize) {
+        let count = match block {
+            FocusBlock::Xdg => self.xdg_dirs.len(),
+            FocusBlock::Bookmarks => self.bookmarks.len(),
+            #[cfg(feature = "mounts")]
+            FocusBlock::Disks => self.mounts.len(),
+            FocusBlock::Remotes => self.remotes.len(),
+            _ => 0,
+        };
+        if index >= count {
+            return;
+        }
+        self.focus = block;
+        self.last_click = None;
+        match block {
+            FocusBlock::Xdg => self.xdg_cursor = index,
+            FocusBlock::Bookmarks => self.bookmarks_cursor = index,
+            #[cfg(feature = "mounts")]
+            FocusBlock::Disks => self.disks_cursor = index,
+            FocusBlock::Remotes => self.remotes_cursor = index,
+            _ => {}
+        }
+        if block == FocusBlock::Remotes {
+            self.activate_remote();
+        } else {
+            self.update_middle_pane_from_left_pane_selection();
+        }
+    }
+
+    /// Каталоги выше текущего в активном списке, от ближайшего к корню
+    pub fn ancestor_dirs(&self) -> Vec<PathBuf> {
+        self.get_active_tab().current_dir.ancestors().skip(1).map(Path::to_path_buf).collect()
+    }
+
+    /// Открывает список каталогов выше текущего; в корне открывать нечего
+    pub fn open_ancestor_picker(&mut self) {
+        if self.ancestor_dirs().is_empty() {
+            return;
+        }
+        self.ancestor_cursor = 0;
+        self.input_mode = InputMode::AncestorPicker;
+    }
+
+    pub fn move_ancestor_cursor(&mut self, delta: isize) {
+        let last = self.ancestor_dirs().len().saturating_sub(1);
+        self.ancestor_cursor = self.ancestor_cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Переходит на `index` + 1 уровней вверх и закрывает список
+    pub fn jump_to_ancestor(&mut self, index: usize) {
+        self.input_mode = InputMode::Normal;
+        if let Some(dir) = self.ancestor_dirs().into_iter().nth(index) {
+            self.enter_ancestor_dir(dir);
+        }
+    }
+
+    /// Щелчок по части пути в заголовке списка `side` переходит в этот каталог
+    pub fn click_breadcrumb(&mut self, side: PanelSide, dir: PathBuf) {
+        if self.panel_mut(side).is_none() {
+            return;
+        }
+        if self.layout_mode == LayoutMode::Dual {
+            self.active_panel = side;
+        }
+        self.last_click = None;
+        if dir != self.get_active_tab().current_dir {
+            self.enter_ancestor_dir(dir);
+        }
+    }
+
+    fn enter_ancestor_dir(&mut self, dir: PathBuf) {
+        let show_hidden = self.show_hidden_files;
+        self.get_active_tab_mut().enter_ancestor(&dir, show_hidden);
+        self.focus = FocusBlock::Middle;
+        self.show_info_panel = false;
+        self.close_search_on_navigation();
+    }
+
+    /// Щелчок по названию вкладки делает её активной
+    pub fn click_tab(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.active_tab_index = index;
+        }
+    }
+
+    /// Нужен ли захват мыши сейчас. Пока фокус во встроенном терминале,
+    /// мышь достаётся запущенным в нём программам.
+    pub fn mouse_capture_wanted(&self) -> bool {
+        self.config.behavior.mouse && self.focus != FocusBlock::Terminal
+    }
+
+    /// Колесо над списком `side` сдвигает его курсор на `delta` записей,
+    /// не меняя активного списка
+    pub fn scroll_entries(&mut self, side: PanelSide, delta: isize) {
+        if let Some(panel) = self.panel_mut(side) {
+            let last = panel.filtered_entries.len().saturating_sub(1);
+            panel.cursor = panel.cursor.saturating_add_signed(delta).min(last);
+            panel.update_preview();
+        }
+    }
+
+    /// Колесо над предпросмотром прокручивает его на `delta` строк
+    pub fn scroll_preview(&mut self, delta: i16) {
+        let active_tab = self.get_active_tab_mut();
+        active_tab.preview_scroll.0 = active_tab.preview_scroll.0.saturating_add_signed(delta);
+    }
+
+    /// Все списки файлов: вкладки и правая панель
+    fn panels_mut(&mut self) -> impl Iterator<Item = &mut TabState> {
+        self.tabs.iter_mut().chain(self.second_panel.iter_mut())
+    }
+
+    /// Переключает один и два списка. Правая панель при первом показе
+    /// открывается в каталоге активной вкладки и дальше живёт сама по себе.
+    pub fn toggle_layout_mode(&mut self) {
+        match self.layout_mode {
+            LayoutMode::Single => {
+                if self.second_panel.is_none() {
+                    let mut panel = self.new_tab_state(0);
+                    panel.set_current_dir(self.get_active_tab().current_dir.clone(), self.show_hidden_files);
+                    self.second_panel = Some(panel);
+                }
+                self.layout_mode = LayoutMode::Dual;
+            }
+            LayoutMode::Dual => {
+                self.layout_mode = LayoutMode::Single;
+                self.active_panel = PanelSide::Left;
+            }
+        }
+    }
+
+    pub fn cycle_focus(&mut self) {
+        self.focus = match self.focus {
+            FocusBlock::Xdg => FocusBlock::Bookmarks,
+            FocusBlock::Bookmarks => FocusBlock::Disks,
+            FocusBlock::Disks => FocusBlock::Remotes,
+            FocusBlock::Remotes => {
+                self.active_panel = PanelSide::Left;
+                FocusBlock::Middle
+            }
+            // В двухпанельном режиме Tab сначала переходит на правый список
+            FocusBlock::Middle if self.layout_mode == LayoutMode::Dual && self.active_panel == PanelSide::Left => {
+                self.active_panel = PanelSide::Right;
+                FocusBlock::Middle
+            }
+            FocusBlock::Middle => FocusBlock::Xdg,
+            FocusBlock::Terminal => FocusBlock::Middle,
+        };
+        // Блоки скрытой левой панели пропускаются
+        if !self.show_left_pane && self.focus_in_left_pane() {
+            self.active_panel = PanelSide::Left;
+            self.focus = FocusBlock::Middle;
+        }
+    }
+
+    pub fn move_left_pane_cursor_down(&mut self) {
+        match self.focus {
+            FocusBlock::Xdg => {
+                let max = self.xdg_dirs.len().saturating_sub(1);
+                if self.xdg_cursor < max { self.xdg_cursor += 1; }
+            },
+            FocusBlock::Bookmarks => {
+                let max = self.bookmarks.len().saturating_sub(1);
+                if self.bookmarks_cursor < max { self.bookmarks_cursor += 1; }
+            },
+            FocusBlock::Disks => {
+                #[cfg(feature = "mounts")]
+                {
+                    let max = self.mounts.len().saturating_sub(1);
+                    if self.disks_cursor < max { self.disks_cursor += 1; }
+                }
+            },
+            FocusBlock::Remotes => {
+                let max = self.remotes.len().saturating_sub(1);
+                if self.remotes_cursor < max { self.remotes_cursor += 1; }
+            },
+            FocusBlock::Middle => {}, // Should not happen
+            FocusBlock::Terminal => {}, // Should not happen
+        }
+        self.update_middle_pane_from_left_pane_selection();
+    }
+
+    pub fn move_left_pane_cursor_up(&mut self) {
+        match self.focus {
+            FocusBlock::Xdg => {
+                if self.xdg_cursor > 0 { self.xdg_cursor -= 1; }
+            },
+            FocusBlock::Bookmarks => {
+                if self.bookmarks_cursor > 0 { self.bookmarks_cursor -= 1; }
+            },
+            FocusBlock::Disks => {
+                #[cfg(feature = "mounts")]
+                {
+                    if self.disks_cursor > 0 { self.disks_cursor -= 1; }
+                }
+            },
+            FocusBlock::Remotes => {
+                if self.remotes_cursor > 0 { self.remotes_cursor -= 1; }
+            },
+            FocusBlock::Middle => {}, // Should not happen
+            FocusBlock::Terminal => {}, // Should not happen
+        }
+        self.update_middle_pane_from_left_pane_selection();
+    }
+
+    pub fn update_middle_pane_from_left_pane_selection(&mut self) {
+        // Зависшее сетевое монтирование заблокировало бы чтение каталога,
+        // поэтому диски и удалённые каталоги сначала проверяются в фоне
+        let probe = matches!(self.focus, FocusBlock::Disks | FocusBlock::Remotes);
+        let path = match self.focus {
+            FocusBlock::Xdg => self.xdg_dirs.get(self.xdg_cursor).map(|(_, path)| path.clone()),
+            FocusBlock::Bookmarks => self.bookmarks.get(self.bookmarks_cursor).map(|bookmark| bookmark.path.clone()),
+            FocusBlock::Disks => {
+                #[cfg(feature = "mounts")]
+                {
+                    self.mounts.get(self.disks_cursor).map(|mount| mount.dest.clone())
+                }
+                #[cfg(not(feature = "mounts"))]
+                {
+                    None
+                }
+            },
+            // Неподключённый каталог подключается только по Enter
+            FocusBlock::Remotes => {
+                self.remotes.get(self.remotes_cursor).filter(|remote| remote.connected).map(|remote| remote.mountpoint())
+            }
+            FocusBlock::Middle => None, // No-op
+            FocusBlock::Terminal => None, // No-op
+        };
+
+        match path {
+            Some(path) if probe => self.mount_prober.request(path),
+            Some(path) => self.open_place(path),
+            None => {}
+        }
+    }
+
+    /// Открывает проверенное монтирование или сообщает, почему не вышло
+    pub fn apply_mount_probe(&mut self, path: PathBuf, result: Result<(), ProbeError>) {
+        match result {
+            Ok(()) => self.open_place(path),
+            Err(e) => {
+                log::warn!("Cannot open {}: {}", path.display(), e);
+                self.notification = Some(format!("{}: {}", path.display(), e));
+                self.notification_timer = Some(std::time::Instant::now());
+            }
+        }
+    }
+
+    /// Открывает в активной вкладке каталог из левой панели или закладки.
+    /// Если каталога больше нет, показывает уведомление.
+    fn open_place(&mut self, path: PathBuf) {
+        if !path.is_dir() {
+            self.missing_places.insert(path.clone());
+            self.notification = Some(Msg::PlaceMissing.fill(self.language, &[&path.display()]));
+            self.notification_timer = Some(std::time::Instant::now());
+            return;
+        }
+        self.missing_places.remove(&path);
+        let show_hidden = self.show_hidden_files;
+        let active_tab = self.get_active_tab_mut();
+        active_tab.set_current_dir(path, show_hidden);
+        active_tab.update_preview();
+        self.show_info_panel = false;
+        self.close_search_on_navigation();
+    }
+
+    /// Закрывает поиск при переходе в другой каталог. Результаты
+    /// рекурсивного поиска остаются, пока их не закроют явно.
+    fn close_search_on_navigation(&mut self) {
+        if !self.search_recursive {
+            self.cancel_search();
+        }
+    }
+
+    /// Переходит к закладке с клавишей `key`
+    pub fn jump_to_bookmark(&mut self, key: char) {
+        match self.bookmarks.iter().find(|bookmark| bookmark.key == Some(key)) {
+            Some(bookmark) => {
+                let path = bookmark.path.clone();
+                self.focus = FocusBlock::Middle;
+                self.open_place(path);
+            }
+            None => {
+                self.notification = Some(Msg::NoBookmarkOnKey.fill(self.language, &[&key]));
+                self.notification_timer = Some(std::time::Instant::now());
+            }
+        }
+    }
+
+    /// Переходит по истории активной вкладки назад или вперёд (`forward`)
+    pub fn navigate_history(&mut self, forward: bool) {
+        let show_hidden = self.show_hidden_files;
+        let tab = self.get_active_tab_mut();
+        let moved = if forward { tab.go_forward(show_hidden) } else { tab.go_back(show_hidden) };
+        if moved {
+            self.focus = FocusBlock::Middle;
+            self.show_info_panel = false;
+            self.close_search_on_navigation();
+        }
+    }
+
+    /// Начинает переход к файлу по первым буквам имени
+    pub fn start_type_ahead(&mut self) {
+        self.type_ahead.clear();
+        self.type_ahead_at = Some(std::time::Instant::now());
+    }
+
+    /// Идёт ли сейчас набор начала имени
+    pub fn is_typing_ahead(&self) -> bool {
+        self.type_ahead_at.is_some_and(|at| at.elapsed() < TYPE_AHEAD_TIMEOUT)
+    }
+
+    /// Дописывает `c` к набранному началу имени и переходит к подходящей записи.
+    /// Если с новой буквой ничего не нашлось, буква не добавляется.
+    pub fn type_ahead_char(&mut self, c: char) {
+        self.type_ahead_at = Some(std::time::Instant::now());
+        let mut prefix = self.type_ahead.clone();
+        prefix.extend(c.to_lowercase());
+        if self.get_active_tab_mut().jump_to_prefix(&prefix) {
+            self.type_ahead = prefix;
+            self.show_info_panel = false;
+        }
+    }
+
+    /// Завершает набор начала имени
+    pub fn cancel_type_ahead(&mut self) {
+        self.type_ahead.clear();
+        self.type_ahead_at = None;
+    }
+
+    /// Заново проверяет, какие закладки и XDG-каталоги указывают на несуществующие пути
+    pub fn refresh_missing_places(&mut self) {
+        self.missing_places = self
+            .xdg_dirs
+            .iter()
+            .map(|(_, path)| path)
+            .chain(self.bookmarks.iter().map(|bookmark| &bookmark.path))
+            .filter(|path| !path.is_dir())
+            .cloned()
+            .collect();
+    }
+
+    pub fn toggle_hidden_files(&mut self) {
+        self.show_hidden_files = !self.show_hidden_files;
+        let show_hidden = self.show_hidden_files;
+        for tab in self.panels_mut() {
+            tab.update_entries(show_hidden);
+        }
+    }
+
+    /// Переключает отделение каталогов от файлов при сортировке
+    pub fn toggle_separate_dirs(&mut self) {
+        self.sort_settings.separate_dirs = !self.sort_settings.separate_dirs;
+        self.apply_sort_settings();
+    }
+
+    /// Переключает общее поле сортировки
+    pub fn cycle_sort_field(&mut self) {
+        self.sort_settings.field = self.sort_settings.field.next();
+        self.apply_sort_settings();
+    }
+
+    /// Меняет общий порядок сортировки на обратный
+    pub fn reverse_sort_order(&mut self) {
+        self.sort_settings.order = self.sort_settings.order.reversed();
+        self.apply_sort_settings();
+    }
+
+    /// Переключает естественную и строго лексикографическую сортировку имён
+    pub fn toggle_natural_sort(&mut self) {
+        self.sort_settings.natural = !self.sort_settings.natural;
+        self.apply_sort_settings();
+    }
+
+    /// Раздаёт настройки сортировки и предпросмотра вкладкам и пересортировывает их
+    pub fn apply_sort_settings(&mut self) {
+        let (sort, dir_prefs, show_hidden) = (self.sort_settings.clone(), self.dir_prefs.clone(), self.show_hidden_files);
+        let (preview, pinned) = (self.preview_settings.clone(), self.pinned.clone());
+        for tab in self.panels_mut() {
+            tab.sort = sort.clone();
+            tab.dir_prefs = dir_prefs.clone();
+            tab.pinned = pinned.clone();
+            tab.preview = preview.clone();
+            // Курсор остаётся на той же записи, а не на том же месте списка
+            let selected = tab.get_selected_entry_path();
+            tab.update_entries(show_hidden);
+            if let Some(path) = selected {
+                tab.select_path(&path);
+            }
+        }
+    }
+
+    /// Общие настройки вида, действующие в незапомненных каталогах
+    fn global_view(&self) -> ViewPrefs {
+        ViewPrefs {
+            sort: self.sort_settings.clone(),
+            show_hidden: self.show_hidden_files,
+            show_file_details: self.show_file_details,
+        }
+    }
+
+    /// Настройки вида, действующие в активной вкладке
+    pub fn current_view(&self) -> ViewPrefs {
+        self.view_of(self.get_active_tab())
+    }
+
+    /// Настройки вида, действующие в списке `tab`
+    pub fn view_of(&self, tab: &TabState) -> ViewPrefs {
+        tab.view.clone().unwrap_or_else(|| self.global_view())
+    }
+
+    /// Меняет настройки вида текущего каталога: запомненные, если они есть, иначе общие
+    fn edit_view(&mut self, edit: impl FnOnce(&mut ViewPrefs)) {
+        let mut view = self.current_view();
+        edit(&mut view);
+        let active_tab = self.get_active_tab();
+        if active_tab.view.is_some() {
+            self.dir_prefs.set(active_tab.current_dir.clone(), view);
+        } else {
+            self.sort_settings = view.sort;
+            self.show_hidden_files = view.show_hidden;
+            self.show_file_details = view.show_file_details;
+        }
+        self.apply_sort_settings();
+    }
+
+    pub fn toggle_view_hidden_files(&mut self) {
+        self.edit_view(|view| view.show_hidden = !view.show_hidden);
+    }
+
+    pub fn toggle_view_file_details(&mut self) {
+        self.edit_view(|view| view.show_file_details = !view.show_file_details);
+    }
+
+    pub fn cycle_view_sort_field(&mut self) {
+        self.edit_view(|view| view.sort.field = view.sort.field.next());
+        let message = match self.current_view().sort.field {
+            SortField::Name => Msg::SortedByName,
+            SortField::Size => Msg::SortedBySize,
+            SortField::Modified => Msg::SortedByDate,
+        };
+        self.notification = Some(message.text(self.language).to_string());
+        self.notification_timer = Some(std::time::Instant::now());
+    }
+
+    pub fn reverse_view_sort_order(&mut self) {
+        self.edit_view(|view| view.sort.order = view.sort.order.reversed());
+    }
+
+    /// Запоминает текущие настройки вида для каталога или забывает их
+    pub fn toggle_remember_view(&mut self) {
+        let view = self.current_view();
+        let active_tab = self.get_active_tab();
+        let dir = active_tab.current_dir.clone();
+        let message = if active_tab.view.is_some() {
+            self.dir_prefs.remove(&dir);
+            Msg::ViewForgotten.fill(self.language, &[&dir.display()])
+        } else {
+            self.dir_prefs.set(dir.clone(), view);
+            Msg::ViewRemembered.fill(self.language, &[&dir.display()])
+        };
+        self.apply_sort_settings();
+        self.notification = Some(message);
+        self.notification_timer = Some(std::time::Instant::now());
+    }
+
+    /// Записывает действующие настройки вида активной вкладки в `.corvus.toml`
+    /// её каталога. Фильтр из существующего файла сохраняется.
+    pub fn write_dir_overrides(&mut self) {
+        let mut view = self.current_view();
+        let active_tab = self.get_active_tab();
+        let filter = active_tab.dir_overrides.as_ref().and_then(|overrides| overrides.filter.clone());
+        if let Some(overrides) = &active_tab.dir_overrides {
+            overrides.apply(&mut view);
+        }
+        let message = match DirOverrides::from_view(&view, filter).save(&active_tab.current_dir) {
+            Ok(path) => Msg::ViewSaved.fill(self.language, &[&path.display()]),
+            Err(e) => Msg::ViewSaveFailed.fill(self.language, &[&e]),
+        };
+        self.apply_sort_settings();
+        self.notification = Some(message);
+        self.notification_timer = Some(std::time::Instant::now());
+    }
+
+    /// Убирает из списка задач успешно завершённые задачи по истечении
+    /// `behavior.completed_task_ttl_secs`
+    pub fn expire_finished_tasks(&mut self) {
+        let ttl = self.config.behavior.completed_task_ttl_secs;
+        if ttl > 0 {
+            self.task_manager.expire_completed(std::time::Duration::from_secs(ttl));
+        }
+    }
+
+    pub fn clear_finished_tasks(&mut self) {
+        self.task_manager.clear_finished();
+    }
+
+    /// Реагирует на завершение задачи: показывает уведомление и перечитывает
+    /// только те вкладки, каталоги которых задача затронула
+    pub fn handle_task_event(&mut self, event: TaskEvent) {
+        let kind = match event {
+            TaskEvent::Progress { .. } => return,
+            TaskEvent::Completed { kind, summary, .. } => {
+                self.notification = Some(summary);
+                self.notification_timer = Some(std::time::Instant::now());
+                self.notify_task_completed(&kind);
+                if let TaskKind::InstallPlugin { dest, .. } = &kind {
+                    self.finish_plugin_install(dest);
+                }
+                self.finish_remote_task(&kind, true);
+                kind
+            }
+            TaskEvent::Failed { kind, description, error, .. } => {
+                self.notification = Some(Msg::TaskFailed.fill(self.language, &[&description, &error]));
+                self.notification_timer = Some(std::time::Instant::now());
+                if let TaskKind::InstallPlugin { url, .. } = &kind {
+                    self.plugin_message = Some(Msg::PluginCloneFailed.fill(self.language, &[url, &error]));
+                }
+                self.finish_remote_task(&kind, false);
+                kind
+            }
+            // О задачах группы сообщаем одним уведомлением, когда завершится вся группа
+            TaskEvent::GroupItemFinished { id, kind, report } => {
+                if self.task_manager.get_tasks().iter().any(|task| task.id == id && task.status == TaskStatus::Completed) {
+                    self.notify_task_completed(&kind);
+                }
+                if let Some(report) = report {
+                    for error in &report.errors {
+                        log::error!("{}", error);
+                    }
+                    self.notification = Some(
+                        std::iter::once(report.summary).chain(report.errors).collect::<Vec<_>>().join("\n"),
+                    );
+                    self.notification_timer = Some(std::time::Instant::now());
+                }
+                kind
+            }
+        };
+
+        // Закрепления следуют за переименованным или перенесённым каталогом
+        if let TaskKind::Move { src, dest } = &kind {
+            if !src.exists() && dest.exists() && self.pinned.move_paths(src, dest) {
+                self.save_pinned();
+            }
+        }
+        self.refresh_dirs(&kind.affected_dirs());
+        // После распаковки в новый каталог переходим курсором на него
+        if let TaskKind::Extract { dest, .. } = &kind {
+            self.get_active_tab_mut().select_path(dest);
+        }
+        self.update_mounts();
+        self.file_index.rebuild();
+    }
+
+    /// Перечитывает вкладки, открытые в одном из `dirs`.
+    /// Результаты поиска только освобождаются от исчезнувших файлов.
+    pub fn refresh_dirs(&mut self, dirs: &[PathBuf]) {
+        let show_hidden = self.show_hidden_files;
+        for tab in self.panels_mut() {
+            let affected = match tab.listing_source {
+                ListingSource::Directory => dirs.contains(&tab.current_dir),
+                ListingSource::SearchResults(_) => true,
+                ListingSource::DiskUsage(_) => dirs.contains(&tab.current_dir),
+            };
+            if affected {
+                tab.update_entries(show_hidden);
+            }
+        }
+    }
+
+    pub fn toggle_file_details(&mut self) {
+        self.show_file_details = !self.show_file_details;
+    }
+
+    pub fn toggle_key_hints(&mut self) {
+        self.show_key_hints = !self.show_key_hints;
+    }
+
+    /// Скрывает или показывает левую панель и запоминает это в config.toml.
+    /// Фокус со скрытой панели переходит на список файлов.
+    pub fn toggle_left_pane(&mut self) {
+        self.show_left_pane = !self.show_left_pane;
+        if !self.show_left_pane && self.focus_in_left_pane() {
+            self.focus = FocusBlock::Middle;
+        }
+        self.save_settings();
+    }
+
+    /// Скрывает или показывает нижние панели и запоминает это в config.toml
+    pub fn toggle_footer(&mut self) {
+        self.show_footer = !self.show_footer;
+        self.save_settings();
+    }
+
+    /// Расширяет или сужает предпросмотр за счёт списка на `PREVIEW_WIDTH_STEP`
+    pub fn resize_preview(&mut self, wider: bool) {
+        let width = if wider {
+            self.preview_width + PREVIEW_WIDTH_STEP
+        } else {
+            self.preview_width.saturating_sub(PREVIEW_WIDTH_STEP)
+        };
+        let width = width.clamp(MIN_PREVIEW_WIDTH, MAX_PREVIEW_WIDTH);
+        if width != self.preview_width {
+            self.preview_width = width;
+            self.save_settings();
+        }
+    }
+
+    /// Фокус на одном из блоков левой панели
+    fn focus_in_left_pane(&self) -> bool {
+        matches!(self.focus, FocusBlock::Xdg | FocusBlock::Bookmarks | FocusBlock::Disks | FocusBlock::Remotes)
+    }
+
+    pub fn cycle_language(&mut self) {
+        self.language = self.language.next();
+    }
+
+    /// Переключает двоичные и десятичные единицы размеров
+    pub fn cycle_size_units(&mut self) {
+        self.size_units = self.size_units.next();
+    }
+
+    pub fn yank_selection(&mut self) {
+        let active_tab = self.get_active_tab();
+        let paths_to_yank = if !active_tab.selected_entries.is_empty() {
+            active_tab.selected_entries.iter().cloned().collect()
+        } else if let Some(path) = active_tab.get_selected_entry_path() {
+            vec![path]
+        } else {
+            Vec::new()
+        };
+
+        if !paths_to_yank.is_empty() {
+            self.clipboard.yank(paths_to_yank);
+        }
+    }
+
+    pub fn cut_selection(&mut self) {
+        let active_tab = self.get_active_tab();
+        let paths_to_cut = if !active_tab.selected_entries.is_empty() {
+            active_tab.selected_entries.iter().cloned().collect()
+        } else if let Some(path) = active_tab.get_selected_entry_path() {
+            vec![path]
+        } else {
+            Vec::new()
+        };
+
+        if !paths_to_cut.is_empty() {
+            self.clipboard.cut(paths_to_cut);
+        }
+    }
+
+    pub fn paste(&mut self) {
+        if self.clipboard.paths.is_empty() {
+            return;
+        }
+
+        let destination = self.get_active_tab().current_dir.clone();
+        self.paste_into(self.clipboard.clone(), destination);
+    }
+
+    /// Копирует или перемещает выделение активного списка в каталог другого.
+    /// Работает только в двухпанельном режиме.
+    pub fn transfer_to_other_panel(&mut self, mode: ClipboardMode) {
+        let Some(destination) = self.get_other_panel().map(|panel| panel.current_dir.clone()) else {
+            return;
+        };
+        let active_tab = self.get_active_tab();
+        let paths: Vec<PathBuf> = if !active_tab.selected_entries.is_empty() {
+            active_tab.selected_entries.iter().cloned().collect()
+        } else if let Some(path) = active_tab.get_selected_entry_path() {
+            vec![path]
+        } else {
+            return;
+        };
+        self.get_active_tab_mut().selected_entries.clear();
+        self.paste_into(Clipboard { paths, mode: Some(mode) }, destination);
+    }
+
+    /// Вставляет `clipboard` в `destination`, спрашивая подтверждение при конфликте имён
+    fn paste_into(&mut self, clipboard: Clipboard, destination: PathBuf) {
+        // Каталог нельзя вставить в него самого или в его подкаталог
+        if let Some(src) = clipboard.paths.iter().find(|src| io::fs_ops::is_within(&destination, src)) {
+            self.notification = Some(format!(
+                "Cannot paste {:?} into itself",
+                src.file_name().unwrap_or_default()
+            ));
+            self.notification_timer = Some(std::time::Instant::now());
+            return;
+        }
+        let conflict = clipboard.paths.iter().any(|src_path| {
+            let dest_path = destination.join(src_path.file_name().unwrap());
+            dest_path.exists()
+        });
+
+        if (conflict && self.behavior_settings.confirm_overwrite) || self.dry_run {
+            self.confirmation_message = if conflict {
+                Msg::PasteOverwrite.fill(self.language, &[&self.plan_key_hint()])
+            } else {
+                Msg::PasteConfirm.fill(self.language, &[&clipboard.paths.len(), &destination.display(), &self.plan_key_hint()])
+            };
+            self.show_confirmation = true;
+            self.action_to_confirm = Some(ActionToConfirm::Paste);
+            self.pending_paste = Some((clipboard, destination));
+            if self.dry_run {
+                self.toggle_plan_preview();
+            }
+        } else {
+            self.execute_paste(clipboard, destination);
+        }
+    }
+
+    /// Задачи, которые поставит вставка `clipboard` в `destination`
+    pub fn paste_plan(clipboard: &Clipboard, destination: &Path) -> Vec<TaskKind> {
+        let Some(mode) = &clipboard.mode else {
+            return Vec::new();
+        };
+        clipboard
+            .paths
+            .iter()
+            .filter_map(|src_path| {
+                let dest = destination.join(src_path.file_name()?);
+                let src = src_path.clone();
+                Some(match mode {
+                    ClipboardMode::Copy => TaskKind::Copy { src, dest },
+                    ClipboardMode::Move => TaskKind::Move { src, dest },
+                })
+            })
+            .collect()
+    }
+
+    fn execute_paste(&mut self, clipboard: Clipboard, destination: PathBuf) {
+        let Some(mode) = clipboard.mode.clone() else {
+            return;
+        };
+        let mut tasks: Vec<(TaskKind, String)> = Self::paste_plan(&clipboard, &destination)
+            .into_iter()
+            .filter_map(|task_kind| {
+                let (TaskKind::Copy { src, .. } | TaskKind::Move { src, .. }) = &task_kind else {
+                    return None;
+                };
+                let description = format!("{:?} {:?} -> {:?}", mode, src.file_name().unwrap(), destination);
+                Some((task_kind, description))
+            })
+            .collect();
+        // Несколько файлов идут одной группой, чтобы не занимать по строке на каждый
+        if tasks.len() > 1 {
+            self.task_manager.add_group(tasks);
+        } else if let Some((task_kind, description)) = tasks.pop() {
+            self.task_manager.add_task(task_kind, description);
+        }
+
+        // Вырезанное уже перемещено, а перенос между панелями буфер не трогает
+        if mode == ClipboardMode::Move && self.clipboard.paths == clipboard.paths {
+            self.clipboard.clear();
+        }
+    }
+
+    pub fn confirm_paste(&mut self) {
+        if let Some((clipboard, destination)) = self.pending_paste.take() {
+            self.execute_paste(clipboard, destination);
+        }
+        self.show_confirmation = false;
+        self.action_to_confirm = None;
+    }
+
+    pub fn cancel_paste(&mut self) {
+        self.pending_paste = None;
+        self.show_confirmation = false;
+        self.action_to_confirm = None;
+    }
+
+    pub fn next_tab(&mut self) {
+        self.active_tab_index = (self.active_tab_index + 1) % self.tabs.len();
+    }
+
+    pub fn previous_tab(&mut self) {
+        if self.active_tab_index > 0 {
+            self.active_tab_index -= 1;
+        } else {
+            self.active_tab_index = self.tabs.len() - 1;
+        }
+    }
+
+    pub fn new_tab(&mut self) {
+        if self.tabs.len() >= 10 {
+            return;
+        }
+        log::info!("new_tab called. Current tab count: {}", self.tabs.len());
+        let new_id = self.tabs.len();
+        let mut new_tab = self.new_tab_state(new_id);
+        new_tab.update_entries(self.show_hidden_files);
+        self.tabs.push(new_tab);
+        self.active_tab_index = new_id;
+        self.show_tabs = true; // Show tabs when a new one is created
+        log::info!("new_tab finished. New tab count: {}. Active index: {}", self.tabs.len(), self.active_tab_index);
+    }
+
+    /// Открывает копию активной вкладки: тот же каталог, курсор и вид.
+    /// Выделение не копируется, чтобы операции не задели его дважды.
+    pub fn duplicate_tab(&mut self) {
+        if self.tabs.len() >= 10 {
+            return;
+        }
+        let new_id = self.tabs.len();
+        let mut new_tab = self.get_active_tab().clone();
+        new_tab.id = new_id;
+        // Имя должно отличать вкладку, поэтому копия его не наследует
+        new_tab.name = None;
+        new_tab.selected_entries.clear();
+        self.tabs.push(new_tab);
+        self.active_tab_index = new_id;
+        self.active_panel = PanelSide::Left;
+        self.show_tabs = true;
+    }
+
+    pub fn close_tab(&mut self) {
+        if self.tabs.len() > 1 {
+            // Терминал вкладки при удалении завершает и дожидается её оболочки
+            self.tabs.remove(self.active_tab_index);
+            self.renumber_tabs();
+            if self.active_tab_index >= self.tabs.len() {
+                self.active_tab_index = self.tabs.len() - 1;
+            }
+            if self.tabs.len() == 1 {
+                self.show_tabs = false; // Hide tabs when only one is left
+            }
+        }
+    }
+
+    /// Меняет активную вкладку местами с соседней слева или справа.
+    /// Номера вкладок следуют их положению.
+    pub fn move_tab(&mut self, forward: bool) {
+        let index = self.selected_tab_index();
+        let target = if forward { index + 1 } else { index.wrapping_sub(1) };
+        if target >= self.tabs.len() {
+            return;
+        }
+        self.tabs.swap(index, target);
+        self.renumber_tabs();
+        self.active_tab_index = target;
+    }
+
+    fn renumber_tabs(&mut self) {
+        for (id, tab) in self.tabs.iter_mut().enumerate() {
+            tab.id = id;
+        }
+    }
+
+    /// Открывает диалог имени закладки на текущий каталог, заполненный его именем
+    pub fn add_bookmark(&mut self) {
+        let path = self.get_active_tab().current_dir.clone();
+        self.input_buffer = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        self.input_mode = InputMode::Bookmark;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
+    }
+
+    /// Добавляет закладку на текущий каталог с именем из поля ввода.
+    /// Окончание ` #k` назначает клавишу для быстрого перехода.
+    pub fn save_bookmark(&mut self) {
+        let input = self.input_buffer.trim();
+        let (name, key) = match input.rsplit_once(" #") {
+            Some((name, key)) if key.chars().count() == 1 => (name.trim().to_string(), key.chars().next()),
+            _ => (input.to_string(), None),
+        };
+        if name.is_empty() {
+            self.input_dialog_error = Some(Msg::BookmarkNameEmpty.text(self.language).to_string());
+            return;
+        }
+        if self.bookmarks.iter().any(|bookmark| bookmark.name == name) {
+            self.input_dialog_error = Some(Msg::BookmarkExists.fill(self.language, &[&name]));
+            return;
+        }
+        if let Some(taken) = key.and_then(|key| self.bookmarks.iter().find(|bookmark| bookmark.key == Some(key))) {
+            self.input_dialog_error = Some(Msg::BookmarkKeyTaken.fill(self.language, &[&key.unwrap_or_default(), &taken.name]));
+            return;
+        }
+
+        let path = self.get_active_tab().current_dir.clone();
+        self.bookmarks.push(Bookmark { name, path, key });
+        self.save_bookmarks();
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+    }
+
+    /// Записывает список закладок в config.toml
+    fn save_bookmarks(&mut self) {
+        self.config.bookmarks = self.bookmarks.clone();
+        if let Err(e) = config::save_config(&self.config) {
+            log::error!("Failed to save bookmarks: {}", e);
+        }
+    }
+
+    /// Закрепляет каталог под курсором вверху списка его родителя или
+    /// снимает закрепление
+    pub fn toggle_pin(&mut self) {
+        let active_tab = self.get_active_tab();
+        let Some(entry) = active_tab.filtered_entries.get(active_tab.cursor).cloned() else {
+            return;
+        };
+        if !entry.is_dir {
+            self.notification = Some(Msg::PinDirsOnly.text(self.language).to_string());
+            self.notification_timer = Some(std::time::Instant::now());
+            return;
+        }
+        let pinned = self.pinned.toggle(entry.path.clone());
+        self.save_pinned();
+        self.refresh_dirs(&entry.path.parent().map(Path::to_path_buf).into_iter().collect::<Vec<_>>());
+        self.get_active_tab_mut().select_path(&entry.path);
+        let message = if pinned { Msg::Pinned } else { Msg::Unpinned };
+        self.notification = Some(message.fill(self.language, &[&entry.name]));
+        self.notification_timer = Some(std::time::Instant::now());
+    }
+
+    /// Запускает процессы включённых плагинов. Плагины, которым нужно
+    /// согласие на права, ждут его в настройках.
+    pub fn start_plugins(&mut self) {
+        for index in 0..self.plugins.len() {
+            if self.plugins[index].enabled {
+                self.start_plugin(index);
+            }
+        }
+        let waiting: Vec<&str> = self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.state == PluginState::NeedsConsent)
+            .map(|plugin| plugin.manifest.name.as_str())
+            .collect();
+        if !waiting.is_empty() {
+            self.notification = Some(Msg::PluginsWaiting.fill(self.language, &[&waiting.join(", ")]));
+            self.notification_timer = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Права из манифеста плагина, на которые пользователь ещё не согласился.
+    /// У плагина без процесса прав нет.
+    pub fn missing_plugin_permissions(&self, plugin: &Plugin) -> PluginPermissions {
+        if plugin.manifest.command.is_none() {
+            return PluginPermissions::default();
+        }
+        let granted = self.config.plugins.permissions.get(&plugin.manifest.name).cloned().unwrap_or_default();
+        plugin.manifest.permissions.not_covered_by(&granted)
+    }
+
+    /// Запускает плагин `index`, если на все его права есть согласие,
+    /// иначе останавливает его и оставляет ждать согласия
+    fn start_plugin(&mut self, index: usize) {
+        let Some(plugin) = self.plugins.get(index) else {
+            return;
+        };
+        let needs_consent = !self.missing_plugin_permissions(plugin).is_empty();
+        let plugin = &mut self.plugins[index];
+        if needs_consent {
+            self.plugin_host.stop(plugin);
+            plugin.state = PluginState::NeedsConsent;
+        } else {
+            self.plugin_host.start(plugin);
+        }
+    }
+
+    /// Спрашивает согласия на права плагина `index`. Права, на которые
+    /// согласия ещё не было, помечены как новые.
+    pub fn ask_plugin_consent(&mut self, index: usize) {
+        let Some(plugin) = self.plugins.get(index) else {
+            return;
+        };
+        let missing = self.missing_plugin_permissions(plugin);
+        let first_time = !self.config.plugins.permissions.contains_key(&plugin.manifest.name);
+        let mut message = Msg::PluginAsks.fill(self.language, &[&plugin.manifest.name]);
+        message.push('\n');
+        for line in plugin.manifest.permissions.describe() {
+            let new = !first_time && missing.describe().contains(&line);
+            let mark = if new { Msg::PermissionNew.text(self.language) } else { "" };
+            message.push_str(&format!("  {}{}\n", line, mark));
+        }
+        message.push_str(Msg::PluginAllow.text(self.language));
+        self.confirmation_message = message;
+        self.plugin_consent = Some(plugin.path.clone());
+        self.show_confirmation = true;
+        self.action_to_confirm = Some(ActionToConfirm::GrantPluginPermissions);
+    }
+
+    /// Запоминает права плагина как разрешённые в config.toml и запускает
+    /// его. Права пока только записываются в лог, процесс ими не ограничен.
+    fn confirm_plugin_consent(&mut self) {
+        let Some(path) = self.plugin_consent.take() else {
+            return;
+        };
+        let Some(index) = self.plugins.iter().position(|plugin| plugin.path == path) else {
+            return;
+        };
+        let manifest = &self.plugins[index].manifest;
+        log::info!("Plugin {} is allowed: {}", manifest.name, manifest.permissions.describe().join(", "));
+        self.config.plugins.permissions.insert(manifest.name.clone(), manifest.permissions.clone());
+        if let Err(e) = config::save_config(&self.config) {
+            log::error!("Failed to save plugin permissions: {}", e);
+        }
+        if self.plugins[index].enabled {
+            self.start_plugin(index);
+        }
+    }
+
+    /// Обновляет состояние плагинов, процессы которых завершились сами,
+    /// и сообщает о новых строках в их stderr, если журнал не открыт
+    pub fn poll_plugins(&mut self) {
+        self.plugin_host.poll(&mut self.plugins);
+        let open_log = self.plugin_log_view.as_ref().map(|view| view.plugin.clone());
+        let reports: Vec<String> = self
+            .plugin_host
+            .take_unseen_log_lines()
+            .into_iter()
+            .filter(|(path, _)| open_log.as_ref() != Some(path))
+            .filter_map(|(path, count)| {
+                let plugin = self.plugins.iter().find(|plugin| plugin.path == path)?;
+                let message = if count == 1 { Msg::PluginWroteError } else { Msg::PluginWroteErrors };
+                Some(message.fill(self.language, &[&plugin.manifest.name, &count]))
+            })
+            .collect();
+        if !reports.is_empty() {
+            self.notification = Some(reports.join("; "));
+            self.notification_timer = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Открывает журнал stderr плагина `index` на последних строках
+    pub fn open_plugin_log(&mut self, index: usize) {
+        if let Some(plugin) = self.plugins.get(index) {
+            self.plugin_log_view = Some(PluginLogView { plugin: plugin.path.clone(), scroll: 0 });
+        }
+    }
+
+    pub fn close_plugin_log(&mut self) {
+        self.plugin_log_view = None;
+    }
+
+    /// Листает журнал плагина: положительный `delta` — к более старым строкам
+    pub fn scroll_plugin_log(&mut self, delta: isize) {
+        let Some(view) = &mut self.plugin_log_view else {
+            return;
+        };
+        let len = self.plugin_host.log_lines(&view.plugin).len();
+        view.scroll = view.scroll.saturating_add_signed(delta).min(len.saturating_sub(1));
+    }
+
+    /// Сообщает плагинам о смене каталога или выделения в активной вкладке
+    pub fn notify_plugins(&mut self) {
+        if !self.plugin_host.has_listeners() {
+            return;
+        }
+        let tab = self.get_active_tab();
+        let dir = tab.current_dir.clone();
+        let mut selection: Vec<PathBuf> = tab.selected_entries.iter().cloned().collect();
+        selection.sort();
+        if self.plugin_dir.as_ref() != Some(&dir) {
+            let params = serde_json::to_value(DirChangedParams { path: dir.clone() }).unwrap_or_default();
+            self.plugin_host.broadcast(&mut self.plugins, "on_dir_changed", params);
+            self.plugin_dir = Some(dir);
+        }
+        if self.plugin_selection != selection {
+            let params = serde_json::to_value(SelectionChangedParams { paths: selection.clone() }).unwrap_or_default();
+            self.plugin_host.broadcast(&mut self.plugins, "on_selection_changed", params);
+            self.plugin_selection = selection;
+        }
+    }
+
+    fn notify_task_completed(&mut self, kind: &TaskKind) {
+        if !self.plugin_host.has_listeners() {
+            return;
+        }
+        let params = TaskCompletedParams { kind: kind.name().to_string(), paths: kind.paths() };
+        let params = serde_json::to_value(params).unwrap_or_default();
+        self.plugin_host.broadcast(&mut self.plugins, "on_task_completed", params);
+    }
+
+    /// Включает или выключает плагин, запуская или останавливая его
+    /// процесс, и запоминает выбор в config.toml
+    pub fn toggle_plugin(&mut self, index: usize) {
+        let Some(plugin) = self.plugins.get(index) else {
+            return;
+        };
+        if plugin.enabled && plugin.state == PluginState::NeedsConsent {
+            self.ask_plugin_consent(index);
+            return;
+        }
+        let enabled = !plugin.enabled;
+        self.set_plugin_enabled(index, enabled);
+        if self.plugins[index].state == PluginState::NeedsConsent {
+            self.ask_plugin_consent(index);
+        }
+        self.config.plugins.disabled = self
+            .plugins
+            .iter()
+            .filter(|plugin| !plugin.enabled)
+            .map(|plugin| plugin.manifest.name.clone())
+            .collect();
+        if let Err(e) = config::save_config(&self.config) {
+            log::error!("Failed to save plugin settings: {}", e);
+        }
+    }
+
+    /// Открывает ввод пути или git URL устанавливаемого плагина
+    pub fn start_plugin_install(&mut self) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::PluginInstall;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
+    }
+
+    /// Устанавливает плагин из введённого источника. Локальный каталог
+    /// копируется сразу, git-репозиторий клонируется фоновой задачей.
+    /// Итог пишется в `plugin_message`, ввод возвращается к настройкам.
+    pub fn install_plugin(&mut self) {
+        let source = self.input_buffer.trim().to_string();
+        self.input_buffer.clear();
+        self.input_mode = InputMode::Settings;
+        if source.is_empty() {
+            return;
+        }
+        let Some(plugins_dir) = self.plugins_dir.clone() else {
+            self.plugin_message = Some(Msg::NoPluginsDir.text(self.language).to_string());
+            return;
+        };
+        if plugin::is_git_url(&source) {
+            let Some(dest) = plugin::install_dir(&plugins_dir, &source) else {
+                self.plugin_message = Some(Msg::PluginNameUnknown.fill(self.language, &[&source]));
+                return;
+            };
+            if dest.exists() {
+                self.plugin_message = Some(Msg::AlreadyExists.fill(self.language, &[&dest.display()]));
+                return;
+            }
+            if let Err(e) = fs::create_dir_all(&plugins_dir) {
+                self.plugin_message = Some(e.to_string());
+                return;
+            }
+            self.plugin_message = Some(Msg::PluginCloning.fill(self.language, &[&source]));
+            let description = format!("Install plugin {}", source);
+            self.task_manager.add_task(TaskKind::InstallPlugin { url: source, dest }, description);
+            return;
+        }
+        // Относительный путь считается от каталога активной вкладки
+        let src = self.get_active_tab().current_dir.join(&source);
+        match plugin::install_from_dir(&src, &plugins_dir) {
+            Ok((manifest, dest)) => self.add_installed_plugin(manifest, dest),
+            Err(e) => self.plugin_message = Some(Msg::PluginNotInstalled.fill(self.language, &[&e])),
+        }
+    }
+
+    /// Проверяет склонированный плагин; несовместимый удаляется
+    fn finish_plugin_install(&mut self, dest: &Path) {
+        match PluginManifest::validate(dest) {
+            Ok(manifest) => self.add_installed_plugin(manifest, dest.to_path_buf()),
+            Err(e) => {
+                if let Err(e) = fs::remove_dir_all(dest) {
+                    log::error!("Failed to remove {}: {}", dest.display(), e);
+                }
+                self.plugin_message = Some(Msg::PluginNotInstalled.fill(self.language, &[&e]));
+            }
+        }
+    }
+
+    fn add_installed_plugin(&mut self, manifest: PluginManifest, path: PathBuf) {
+        self.plugin_message = Some(Msg::PluginInstalled.fill(self.language, &[&manifest.name, &manifest.version]));
+        self.plugins.push(Plugin::new(manifest, path));
+        let index = self.plugins.len() - 1;
+        self.start_plugin(index);
+        if self.plugins[index].state == PluginState::NeedsConsent {
+            self.ask_plugin_consent(index);
+        }
+    }
+
+    /// Спрашивает, удалить ли каталог плагина `index`
+    pub fn remove_plugin(&mut self, index: usize) {
+        let Some(plugin) = self.plugins.get(index) else {
+            return;
+        };
+        self.confirmation_message = Msg::PluginRemove.fill(self.language, &[&plugin.manifest.name, &plugin.path.display()]);
+        self.path_to_delete = Some(plugin.path.clone());
+        self.show_confirmation = true;
+        self.action_to_confirm = Some(ActionToConfirm::RemovePlugin);
+    }
+
+    fn confirm_remove_plugin(&mut self) {
+        let Some(path) = self.path_to_delete.take() else {
+            return;
+        };
+        let Some(index) = self.plugins.iter().position(|plugin| plugin.path == path) else {
+            return;
+        };
+        let mut plugin = self.plugins.remove(index);
+        self.plugin_host.stop(&mut plugin);
+        self.plugin_message = Some(match fs::remove_dir_all(&path) {
+            Ok(()) => Msg::PluginRemoved.fill(self.language, &[&plugin.manifest.name]),
+            Err(e) => Msg::DeleteFailed.fill(self.language, &[&path.display(), &e]),
+        });
+    }
+
+    fn set_plugin_enabled(&mut self, index: usize, enabled: bool) {
+        let Some(plugin) = self.plugins.get_mut(index) else {
+            return;
+        };
+        if plugin.enabled == enabled {
+            return;
+        }
+        plugin.enabled = enabled;
+        if enabled {
+            self.start_plugin(index);
+        } else {
+            self.plugin_host.stop(plugin);
+        }
+    }
+
+    /// Записывает закреплённые каталоги в config.toml
+    fn save_pinned(&mut self) {
+        self.config.pinned = self.pinned.to_vec();
+        if let Err(e) = config::save_config(&self.config) {
+            log::error!("Failed to save pinned directories: {}", e);
+        }
+    }
+
+    pub fn remove_bookmark(&mut self) {
+        if self.focus == FocusBlock::Bookmarks {
+            if let Some(bookmark) = self.bookmarks.get(self.bookmarks_cursor) {
+                self.confirmation_message = Msg::BookmarkRemove.fill(self.language, &[&bookmark.name]);
+                self.show_confirmation = true;
+                self.action_to_confirm = Some(ActionToConfirm::RemoveBookmark);
+            }
+        }
+    }
+
+    fn confirm_remove_bookmark(&mut self) {
+        if self.bookmarks_cursor < self.bookmarks.len() {
+            self.bookmarks.remove(self.bookmarks_cursor);
+            self.save_bookmarks();
+
+            if self.bookmarks_cursor >= self.bookmarks.len() {
+                self.bookmarks_cursor = self.bookmarks.len().saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn delete_selection(&mut self) {
+        let active_tab = self.get_active_tab();
+        let paths_to_delete: Vec<PathBuf> = if !active_tab.selected_entries.is_empty() {
+            active_tab.selected_entries.iter().cloned().collect()
+        } else if let Some(path) = active_tab.get_selected_entry_path() {
+            vec![path]
+        } else {
+            Vec::new()
+        };
+
+        if !paths_to_delete.is_empty() {
+            self.path_to_delete = Some(paths_to_delete[0].clone()); // A bit of a hack for now
+            if !self.behavior_settings.confirm_delete && !self.dry_run {
+                self.confirm_delete();
+                return;
+            }
+            let to_trash = self.trash_dir().is_some();
+            self.confirmation_message = if paths_to_delete.len() > 1 {
+                let message = if to_trash { Msg::TrashItems } else { Msg::DeleteItems };
+                message.fill(self.language, &[&paths_to_delete.len(), &self.plan_key_hint()])
+            } else {
+                let message = if to_trash { Msg::TrashItem } else { Msg::DeleteItem };
+                let name = format!("{:?}", paths_to_delete[0].file_name().unwrap());
+                message.fill(self.language, &[&name, &self.plan_key_hint()])
+            };
+            self.show_confirmation = true;
+            self.action_to_confirm = Some(ActionToConfirm::Delete);
+            if self.dry_run {
+                self.toggle_plan_preview();
+            }
+        }
+    }
+
+    /// Что делает `v` в подтверждении: в пробном режиме список операций
+    /// уже открыт
+    fn plan_key_hint(&self) -> &'static str {
+        let hint = if self.dry_run { Msg::PlanHide } else { Msg::PlanShow };
+        hint.text(self.language)
+    }
+
+    /// Включает или выключает пробный режим для удаления и вставки
+    pub fn toggle_dry_run(&mut self) {
+        self.dry_run = !self.dry_run;
+        let message = if self.dry_run { Msg::DryRunOn } else { Msg::DryRunOff };
+        self.notification = Some(message.text(self.language).to_string());
+        self.notification_timer = Some(std::time::Instant::now());
+    }
+
+    /// Задачи, которые поставит подтверждение удаления
+    pub fn delete_plan(&self) -> Vec<TaskKind> {
+        let active_tab = self.get_active_tab();
+        let paths_to_delete: Vec<PathBuf> = if !active_tab.selected_entries.is_empty() {
+            active_tab.selected_entries.iter().cloned().collect()
+        } else if let Some(path) = &self.path_to_delete {
+            vec![path.clone()]
+        } else {
+            Vec::new()
+        };
+        let trash = self.trash_dir();
+        paths_to_delete
+            .into_iter()
+            .map(|path| match &trash {
+                Some(trash) => TaskKind::Trash { path, trash: trash.clone() },
+                None => TaskKind::Delete { path },
+            })
+            .collect()
+    }
+
+    /// Корзина, если удаление настроено перемещать туда файлы
+    fn trash_dir(&self) -> Option<PathBuf> {
+        if !self.config.behavior.use_trash {
+            return None;
+        }
+        let trash = crate::paths::trash_dir();
+        if trash.is_none() {
+            log::warn!("No trash directory, files are deleted instead");
+        }
+        trash
+    }
+
+    fn confirm_delete(&mut self) {
+        for task_kind in self.delete_plan() {
+            let description = match &task_kind {
+                TaskKind::Delete { path } => format!("Delete {:?}", path.file_name().unwrap()),
+                TaskKind::Trash { path, .. } => format!("Trash {:?}", path.file_name().unwrap()),
+                _ => continue,
+            };
+            self.task_manager.add_task(task_kind, description);
+        }
+        self.path_to_delete = None;
+
+        // И наконец очищаем выделение
+        self.get_active_tab_mut().selected_entries.clear();
+    }
+
+    /// Показывает или скрывает список операций, которые выполнит
+    /// подтверждаемое действие. Ничего не ставится в очередь.
+    pub fn toggle_plan_preview(&mut self) {
+        if self.plan_preview.take().is_some() {
+            return;
+        }
+        let plan = match self.action_to_confirm {
+            Some(ActionToConfirm::Delete) => self.delete_plan(),
+            Some(ActionToConfirm::Paste) => match &self.pending_paste {
+                Some((clipboard, destination)) => Self::paste_plan(clipboard, destination),
+                None => Vec::new(),
+            },
+            _ => return,
+        };
+        self.plan_preview = Some(describe_plan(&plan));
+        self.plan_scroll = 0;
+    }
+
+    pub fn scroll_plan_preview(&mut self, delta: isize) {
+        let len = self.plan_preview.as_ref().map_or(0, |lines| lines.len());
+        self.plan_scroll = self.plan_scroll.saturating_add_signed(delta).min(len.saturating_sub(1));
+    }
+
+    pub fn unmount_selection(&mut self) {
+        #[cfg(feature = "mounts")]
+        if self.focus == FocusBlock::Disks {
+            if let Some(mount_info) = self.mounts.get(self.disks_cursor) {
+                self.path_to_delete = Some(mount_info.dest.clone()); // Re-use path_to_delete for unmount path
+                self.confirmation_message = Msg::UnmountConfirm.fill(self.language, &[&format!("{:?}", mount_info.dest)]);
+                self.show_confirmation = true;
+                self.action_to_confirm = Some(ActionToConfirm::Unmount);
+            }
+        }
+    }
+
+    /// Спрашивает, извлечь ли диск выбранного в панели дисков раздела:
+    /// отмонтировать все его разделы и отключить питание. Диск, на котором
+    /// открыта какая-нибудь вкладка, не извлекается.
+    pub fn eject_selection(&mut self) {
+        #[cfg(feature = "mounts")]
+        if self.focus == FocusBlock::Disks {
+            let Some(mount) = self.mounts.get(self.disks_cursor) else {
+                return;
+            };
+            let Some(disk) = crate::block_device::disk_of(&mount.source) else {
+                self.notification = Some(Msg::NotBlockDevice.fill(self.language, &[&mount.dest.display()]));
+                self.notification_timer = Some(std::time::Instant::now());
+                return;
+            };
+            // Разделы ищутся среди всех монтирований, а не только показанных
+            let all_mounts: Vec<_> = MountIter::new().map(|iter| iter.filter_map(|res| res.ok()).collect()).unwrap_or_default();
+            let mounts = crate::block_device::mounts_of(
+                &disk,
+                all_mounts.iter().map(|mount| (mount.source.as_path(), mount.dest.as_path())),
+            );
+            let mountpoints: Vec<PathBuf> = mounts.iter().map(|(_, mountpoint)| mountpoint.clone()).collect();
+            let mut partitions: Vec<PathBuf> = mounts.into_iter().map(|(partition, _)| partition).collect();
+            partitions.dedup();
+            // Правая панель двухпанельного режима тоже держит диск
+            let open_in = |tab: &TabState| mountpoints.iter().find(|mountpoint| tab.current_dir.starts_with(mountpoint));
+            let busy = self
+                .tabs
+                .iter()
+                .enumerate()
+                .find_map(|(index, tab)| open_in(tab).map(|mountpoint| (Msg::TabHolder.fill(self.language, &[&(index + 1)]), mountpoint)))
+                .or_else(|| {
+                    let holder = Msg::RightPanelHolder.text(self.language).to_string();
+                    self.second_panel.as_ref().and_then(open_in).map(|mountpoint| (holder, mountpoint))
+                });
+            if let Some((holder, mountpoint)) = busy {
+                self.notification = Some(Msg::EjectBusy.fill(self.language, &[&disk.display(), &holder, &mountpoint.display()]));
+                self.notification_timer = Some(std::time::Instant::now());
+                return;
+            }
+            let list: Vec<String> = mountpoints.iter().map(|path| path.display().to_string()).collect();
+            let list = if list.is_empty() { Msg::NothingMounted.text(self.language).to_string() } else { list.join(", ") };
+            self.confirmation_message = Msg::EjectConfirm.fill(self.language, &[&disk.display(), &list]);
+            self.pending_eject = Some(TaskKind::Eject { device: disk, partitions, mountpoints });
+            self.show_confirmation = true;
+            self.action_to_confirm = Some(ActionToConfirm::Eject);
+        }
+    }
+
+    fn confirm_eject(&mut self) {
+        if let Some(kind) = self.pending_eject.take() {
+            if let TaskKind::Eject { device, .. } = &kind {
+                let description = format!("Eject {}", device.display());
+                self.task_manager.add_task(kind, description);
+            }
+        }
+    }
+
+    fn confirm_unmount(&mut self) {
+        if let Some(path) = self.path_to_delete.take() {
+            let description = format!("Unmount {:?}", path);
+            let task_kind = TaskKind::Unmount { path };
+            self.task_manager.add_task(task_kind, description);
+        }
+    }
+
+    pub fn confirm(&mut self) {
+        self.plan_preview = None;
+        if let Some(action) = self.action_to_confirm.take() {
+            match action {
+                ActionToConfirm::Delete => self.confirm_delete(),
+                ActionToConfirm::Paste => self.confirm_paste(),
+                ActionToConfirm::Unmount => self.confirm_unmount(),
+                ActionToConfirm::Eject => self.confirm_eject(),
+                ActionToConfirm::RemoveBookmark => self.confirm_remove_bookmark(),
+                ActionToConfirm::RemovePlugin => self.confirm_remove_plugin(),
+                ActionToConfirm::GrantPluginPermissions => self.confirm_plugin_consent(),
+                ActionToConfirm::SessionInUse => {}
+                ActionToConfirm::Extract => self.confirm_extract(true),
+                ActionToConfirm::ResumeTasks => self.confirm_resume_tasks(),
+                ActionToConfirm::Quit => self.quit_requested = true,
+            }
+        }
+        self.show_confirmation = false;
+    }
+
+    pub fn cancel(&mut self) {
+        self.plan_preview = None;
+        self.pending_resume.clear();
+        self.show_confirmation = false;
+        self.action_to_confirm = None;
+        self.path_to_delete = None;
+        self.pending_paste = None;
+        self.pending_extract = None;
+        self.pending_eject = None;
+        self.plugin_consent = None;
+    }
+
+    /// Спрашивает, куда распаковать выбранный архив
+    pub fn extract_selection(&mut self) {
+        let Some(path) = self.get_active_tab().get_selected_entry_path() else {
+            return;
+        };
+        let Some(stem) = io::fs_ops::archive_stem(&path) else {
+            self.notification = Some(Msg::NotArchive.text(self.language).to_string());
+            self.notification_timer = Some(std::time::Instant::now());
+            return;
+        };
+        let folder = unique_path(&path.with_file_name(&stem));
+        let archive = format!("{:?}", path.file_name().unwrap());
+        let folder = format!("{:?}", folder.file_name().unwrap());
+        self.confirmation_message = Msg::ExtractConfirm.fill(self.language, &[&archive, &folder]);
+        self.pending_extract = Some(path);
+        self.show_confirmation = true;
+        self.action_to_confirm = Some(ActionToConfirm::Extract);
+    }
+
+    /// Ставит задачу распаковки в новый подкаталог или в текущий каталог
+    pub fn confirm_extract(&mut self, into_subfolder: bool) {
+        if let Some(archive) = self.pending_extract.take() {
+            let current_dir = archive.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+            let dest = match io::fs_ops::archive_stem(&archive) {
+                Some(stem) if into_subfolder => unique_path(&current_dir.join(stem)),
+                _ => current_dir,
+            };
+            let description = format!("Extract {:?} to {:?}", archive.file_name().unwrap(), dest);
+            self.task_manager.add_task(TaskKind::Extract { archive, dest }, description);
+        }
+        self.show_confirmation = false;
+        self.action_to_confirm = None;
+    }
+
+    pub fn create_item(&mut self) {
+        if self.input_buffer.is_empty() {
+            return;
+        }
+
+        let new_item_name = self.input_buffer.clone();
+        self.input_buffer.clear();
+
+        let current_dir = self.get_active_tab().current_dir.clone();
+        let new_item_path = current_dir.join(&new_item_name);
+
+        if new_item_path.exists() {
+            self.input_dialog_error = Some(Msg::NameTaken.text(self.language).to_string());
+            self.show_input_dialog = true;
+            self.input_buffer = new_item_name;
+            return;
+        }
+
+        let task_kind = match self.create_file_type.as_ref().unwrap() {
+            CreateFileType::File => TaskKind::CreateFile {
+                path: new_item_path.clone(),
+            },
+            CreateFileType::Directory => TaskKind::CreateDirectory {
+                path: new_item_path.clone(),
+            },
+        };
+
+        let description = format!("Create {:?}", new_item_path);
+        self.task_manager.add_task(task_kind, description);
+
+        self.create_file_type = None;
+    }
+
+    /// Открывает диалог ввода имени активной вкладки
+    pub fn start_tab_rename(&mut self) {
+        self.input_buffer = self.get_active_tab().name.clone().unwrap_or_default();
+        self.input_mode = InputMode::TabName;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
+    }
+
+    /// Задаёт имя активной вкладки из поля ввода. Пустой ввод убирает имя.
+    pub fn set_tab_name(&mut self) {
+        let name = self.input_buffer.trim().to_string();
+        self.get_active_tab_mut().name = (!name.is_empty()).then_some(name);
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+    }
+
+    pub fn rename_selection(&mut self) {
+        if let Some(path) = self.get_active_tab().get_selected_entry_path() {
             self.path_to_rename = Some(path.clone());
             self.input_buffer = path.file_name().unwrap_or_default().to_string_lossy().to_string();
             self.input_mode = InputMode::Rename;
@@ -884,7 +4238,13 @@ impl AppState {
             }
             let new_path = path_to_rename.with_file_name(&new_name);
             if new_path.exists() {
-                self.input_dialog_error = Some("A file with this name already exists.".to_string());
+                self.input_dialog_error = Some(Msg::NameTaken.text(self.language).to_string());
+                self.show_input_dialog = true;
+                return;
+            }
+            // Имя с `/` может переместить каталог внутрь него самого
+            if io::fs_ops::is_within(&new_path, &path_to_rename) {
+                self.input_dialog_error = Some(Msg::MoveIntoItself.text(self.language).to_string());
                 self.show_input_dialog = true;
                 return;
             }
@@ -972,27 +4332,47 @@ impl AppState {
         self.show_input_dialog = true;
         self.input_buffer.clear();
         self.input_dialog_error = None;
+        self.archive_remove_sources = false;
+        self.archive_format = self.archive_settings.default_format;
+        self.archive_compression_level = self.archive_settings.compression_level;
+    }
+
+    /// Переключает удаление исходных файлов после архивации
+    pub fn toggle_archive_remove_sources(&mut self) {
+        self.archive_remove_sources = !self.archive_remove_sources;
     }
     
+    /// Выбирает следующий формат архива из `ArchiveFormat::ALL`
     pub fn select_archive_format(&mut self) {
-        // For now, we'll just cycle through formats
-        self.archive_format = match self.archive_format.as_str() {
-            "zip" => "tar".to_string(),
-            "tar" => "tar.gz".to_string(),
-            "tar.gz" => "zip".to_string(),
-            _ => "zip".to_string(),
-        };
+        self.step_archive_format(1);
+    }
+
+    /// Выбирает предыдущий формат архива из `ArchiveFormat::ALL`
+    pub fn select_previous_archive_format(&mut self) {
+        self.step_archive_format(ArchiveFormat::ALL.len() - 1);
+    }
+
+    /// Меняет степень сжатия на `delta` в пределах 0..=9
+    pub fn adjust_compression_level(&mut self, delta: i32) {
+        self.archive_compression_level = self
+            .archive_compression_level
+            .saturating_add_signed(delta)
+            .min(MAX_COMPRESSION_LEVEL);
+    }
+
+    fn step_archive_format(&mut self, step: usize) {
+        self.archive_format = step_format(self.archive_format, step);
     }
     
     pub fn archive_item(&mut self) {
         if self.input_buffer.is_empty() {
-            self.input_dialog_error = Some("Archive name cannot be empty".to_string());
+            self.input_dialog_error = Some(Msg::ArchiveNameEmpty.text(self.language).to_string());
             self.show_input_dialog = true;
             return;
         }
 
         let archive_name = self.input_buffer.clone();
-        let format = self.archive_format.clone();
+        let format = self.archive_format;
         
         let active_tab = self.get_active_tab();
         let paths_to_archive: Vec<PathBuf> = if !active_tab.selected_entries.is_empty() {
@@ -1003,40 +4383,83 @@ impl AppState {
             Vec::new()
         };
 
-        if !paths_to_archive.is_empty() {
+        if !paths_to_archive.is_empty() && io::fs_ops::common_ancestor(&paths_to_archive).is_none() {
+            self.notification = Some(Msg::NoCommonParent.text(self.language).to_string());
+            self.notification_timer = Some(std::time::Instant::now());
+        } else if !paths_to_archive.is_empty() {
             let current_dir = active_tab.current_dir.clone();
-            let extension = match format.as_str() {
-                "zip" => ".zip",
-                "tar" => ".tar",
-                "tar.gz" => ".tar.gz",
-                _ => ".zip", // Default to zip
-            };
-            
-            let archive_path = current_dir.join(format!("{}{}", archive_name, extension));
+            let archive_path = current_dir.join(format!("{}{}", archive_name, format.extension()));
             
             let description = format!("Archive {} items to {:?}", paths_to_archive.len(), archive_path);
             let task_kind = TaskKind::Archive { 
-                paths: paths_to_archive, 
+                paths: paths_to_archive.clone(), 
                 dest: archive_path, 
-                format 
+                format,
+                level: self.archive_compression_level,
             };
-            self.task_manager.add_task(task_kind, description);
+            let archive_id = self.task_manager.add_task(task_kind, description);
+
+            // Исходники удаляются, только если архив создан успешно
+            if self.archive_remove_sources {
+                for path in paths_to_archive {
+                    let description = format!("Delete {:?}", path.file_name().unwrap_or_default());
+                    self.task_manager.add_task_after(archive_id, TaskKind::Delete { path }, description);
+                }
+                self.get_active_tab_mut().selected_entries.clear();
+            }
         }
         
         self.input_mode = InputMode::Normal;
         self.input_buffer.clear();
     }
 
+    /// `Esc` в обычном режиме: сначала закрывает то, что показано поверх
+    /// списка (панель информации, результаты поиска или анализ занятого
+    /// места, оставшийся фильтр), и только когда закрывать нечего, снимает
+    /// выделение. Идущее сканирование занятого места первый `Esc` только
+    /// останавливает, уже насчитанное остаётся на экране.
+    pub fn escape(&mut self) {
+        if self.show_info_panel {
+            self.show_info_panel = false;
+            return;
+        }
+        if let ListingSource::DiskUsage(view) = &mut self.get_active_tab_mut().listing_source {
+            if view.scanning {
+                view.scanning = false;
+                view.stopped = true;
+                let root = view.root.clone();
+                if self.usage_scanner.scanning() == Some(root.as_path()) {
+                    self.usage_scanner.cancel();
+                }
+                return;
+            }
+        }
+        let show_hidden = self.show_hidden_files;
+        let active_tab = self.get_active_tab_mut();
+        if active_tab.is_showing_search_results() || active_tab.disk_usage().is_some() {
+            active_tab.exit_search_results(show_hidden);
+        } else if active_tab.glob_filter.is_some() {
+            active_tab.set_glob_filter(None);
+        } else if active_tab.filtered_entries.len() != active_tab.entries.len() {
+            active_tab.update_filtered_entries(None);
+        } else {
+            active_tab.selected_entries.clear();
+        }
+    }
+
     pub fn show_info_panel(&mut self) {
         self.show_info_panel = true;
         if let Some(path) = self.get_active_tab().get_selected_entry_path() {
-            if let Ok(metadata) = fs::metadata(&path) {
+            // Для ссылки показываются сведения о ней самой, цель — отдельной строкой
+            if let Ok(metadata) = fs::symlink_metadata(&path) {
+                let target = if metadata.file_type().is_symlink() { fs::read_link(&path).ok() } else { None };
+                let broken_link = target.is_some() && !path.exists();
                 let uid = metadata.uid();
                 let gid = metadata.gid();
                 let owner = users::get_user_by_uid(uid)
                     .map(|u| u.name().to_string_lossy().into_owned())
                     .unwrap_or_else(|| uid.to_string());
-                let _group = users::get_group_by_gid(gid)
+                let group = users::get_group_by_gid(gid)
                     .map(|g| g.name().to_string_lossy().into_owned())
                     .unwrap_or_else(|| gid.to_string());
 
@@ -1055,19 +4478,23 @@ impl AppState {
                     if mode & 0o001 != 0 { "x" } else { "-" }
                 );
 
-                let size = if metadata.is_dir() {
-                    get_directory_size(&path)
-                } else {
-                    metadata.len()
-                };
+                // Размер каталога досчитает `dir_sizer`, см. `request_dir_sizes`
+                let size = (!metadata.is_dir()).then_some(metadata.len());
 
                 self.file_info = Some(FileInfo {
                     path,
                     size,
                     owner,
+                    group,
+                    uid,
+                    gid,
                     permissions,
                     created: metadata.created().ok(),
                     modified: metadata.modified().ok(),
+                    inode: metadata.ino(),
+                    nlink: metadata.nlink(),
+                    target,
+                    broken_link,
                 });
             }
         }
@@ -1081,18 +4508,41 @@ impl AppState {
     }
     
     pub fn update_search(&mut self) {
+        self.search_error = None;
+        self.background_search.cancel();
         if self.search_query.is_empty() {
             self.search_results.clear();
             return;
         }
         
-        // Clone the search query to avoid borrowing issues
-        let search_query = self.search_query.clone();
+        if matches!(self.search_mode, SearchMode::Index) {
+            self.start_index_search();
+            return;
+        }
+        
+        if self.search_recursive {
+            self.start_recursive_search();
+            return;
+        }
+        
+        if matches!(self.search_mode, SearchMode::Metadata) {
+            self.start_metadata_search();
+            return;
+        }
+        
+        let matcher = match SearchMatcher::compile(&self.search_query, self.search_syntax, self.config.search.case_sensitive) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                // Keep the previous listing but make the broken pattern visible
+                self.search_error = Some(e);
+                return;
+            }
+        };
         
         let active_tab = self.get_active_tab();
         self.search_results = SearchEngine::search_entries(
             &active_tab.entries, 
-            &search_query, 
+            &matcher, 
             &self.search_mode
         );
             
@@ -1100,7 +4550,244 @@ impl AppState {
         
         // Update filtered entries in the active tab
         let active_tab = self.get_active_tab_mut();
-        active_tab.update_filtered_entries(&search_query);
+        active_tab.update_filtered_entries(Some(&matcher));
+    }
+    
+    /// Разбирает запрос режима метаданных на фильтр имени и условия
+    fn parse_metadata_search(&self) -> Result<(Option<SearchMatcher>, Vec<MetadataFilter>), String> {
+        let query = SearchEngine::parse_metadata_query(&self.search_query, SystemTime::now())?;
+        let name_matcher = match &query.name {
+            Some(name) => {
+                // Шаблоны вида `*.log` понимаются как glob даже в режиме подстроки
+                let syntax = if self.search_syntax == SearchSyntax::Substring && name.contains(['*', '?']) {
+                    SearchSyntax::Glob
+                } else {
+                    self.search_syntax
+                };
+                Some(SearchMatcher::compile(name, syntax, self.config.search.case_sensitive)?)
+            }
+            None => None,
+        };
+        Ok((name_matcher, query.filters))
+    }
+    
+    /// Запускает фоновую фильтрацию по размеру и дате изменения.
+    /// Результаты приходят через `apply_search_update`.
+    fn start_metadata_search(&mut self) {
+        self.search_results.clear();
+        self.search_cursor = 0;
+        
+        let (name_matcher, filters) = match self.parse_metadata_search() {
+            Ok(parts) => parts,
+            Err(e) => {
+                self.search_error = Some(e);
+                return;
+            }
+        };
+        
+        let active_tab = self.get_active_tab_mut();
+        active_tab.update_filtered_entries(None);
+        let candidates: Vec<DirEntry> = active_tab.entries.iter()
+            .filter(|entry| name_matcher.as_ref().is_none_or(|m| m.is_match(&entry.name)))
+            .cloned()
+            .collect();
+        
+        if filters.is_empty() {
+            self.search_results = candidates;
+        } else {
+            self.background_search.start_metadata_filter(candidates, filters);
+        }
+    }
+    
+    /// Запускает рекурсивный обход текущего каталога в фоне
+    fn start_recursive_search(&mut self) {
+        self.search_results.clear();
+        self.search_cursor = 0;
+        
+        let compiled = match self.search_mode {
+            SearchMode::Name | SearchMode::Index => SearchMatcher::compile(&self.search_query, self.search_syntax, self.config.search.case_sensitive)
+                .map(|matcher| WalkQuery { name: Some(matcher), ..Default::default() }),
+            SearchMode::Content => SearchMatcher::compile(&self.search_query, self.search_syntax, self.config.search.case_sensitive)
+                .map(|matcher| WalkQuery { content: Some(matcher), ..Default::default() }),
+            SearchMode::Metadata => self.parse_metadata_search()
+                .map(|(name, filters)| WalkQuery { name, filters, ..Default::default() }),
+        };
+        let query = match compiled {
+            Ok(query) => WalkQuery { show_hidden: self.show_hidden_files, ..query },
+            Err(e) => {
+                self.search_error = Some(e);
+                return;
+            }
+        };
+        
+        let current_dir = self.get_active_tab().current_dir.clone();
+        let root = self.search_root.get_or_insert(current_dir).clone();
+        self.get_active_tab_mut().update_filtered_entries(None);
+        self.background_search.start_walk(root, query);
+    }
+    
+    /// Нечёткий поиск по индексу в пределах текущего каталога.
+    /// Вне проиндексированных каталогов выполняется обычный обход.
+    fn start_index_search(&mut self) {
+        self.search_results.clear();
+        self.search_cursor = 0;
+        
+        let current_dir = self.get_active_tab().current_dir.clone();
+        if self.file_index.covers(&current_dir) {
+            self.search_results = self.file_index.query(
+                &self.search_query,
+                &current_dir,
+                self.show_hidden_files,
+                INDEX_RESULT_LIMIT,
+            );
+            self.get_active_tab_mut().update_filtered_entries(None);
+            return;
+        }
+        
+        let matcher = match SearchMatcher::compile(&self.search_query, self.search_syntax, self.config.search.case_sensitive) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                self.search_error = Some(e);
+                return;
+            }
+        };
+        let query = WalkQuery { name: Some(matcher), show_hidden: self.show_hidden_files, ..Default::default() };
+        self.get_active_tab_mut().update_filtered_entries(None);
+        self.background_search.start_walk(current_dir, query);
+    }
+    
+    /// Подхватывает свежий индекс в открытом диалоге поиска
+    pub fn apply_index_update(&mut self) {
+        if self.show_search_dialog && matches!(self.search_mode, SearchMode::Index) {
+            self.update_search();
+        }
+    }
+    
+    /// Переключает учёт регистра во всех видах запросов
+    pub fn toggle_search_case_sensitive(&mut self) {
+        self.config.search.case_sensitive = !self.config.search.case_sensitive;
+        if self.show_search_dialog {
+            self.update_search();
+        }
+    }
+
+    /// Включает и выключает рекурсивный поиск
+    pub fn toggle_search_recursive(&mut self) {
+        self.search_recursive = !self.search_recursive;
+        self.search_root = None;
+        self.update_search();
+    }
+    
+    /// Пометка для уведомлений, если поиск ещё идёт
+    fn incomplete_search_suffix(&self) -> &'static str {
+        if self.background_search.running {
+            Msg::SearchIncomplete.text(self.language)
+        } else {
+            ""
+        }
+    }
+    
+    /// Сохраняет найденные пути в текстовый файл в текущем каталоге,
+    /// по одному абсолютному пути в строке
+    pub fn export_search_results(&mut self) {
+        if self.search_results.is_empty() {
+            return;
+        }
+        let current_dir = self.get_active_tab().current_dir.clone();
+        let mut export_path = current_dir.join("search-results.txt");
+        let mut counter = 1;
+        while export_path.exists() {
+            export_path = current_dir.join(format!("search-results-{}.txt", counter));
+            counter += 1;
+        }
+        
+        let mut content = String::new();
+        for entry in &self.search_results {
+            let path = fs::canonicalize(&entry.path).unwrap_or_else(|_| entry.path.clone());
+            content.push_str(&path.to_string_lossy());
+            content.push('\n');
+        }
+        
+        self.notification = Some(match fs::write(&export_path, content) {
+            Ok(_) => Msg::SearchExported.fill(
+                self.language,
+                &[
+                    &self.search_results.len(),
+                    &export_path.file_name().unwrap_or_default().to_string_lossy(),
+                    &self.incomplete_search_suffix(),
+                ],
+            ),
+            Err(e) => Msg::SearchExportFailed.fill(self.language, &[&e]),
+        });
+        self.notification_timer = Some(std::time::Instant::now());
+    }
+    
+    /// Добавляет порцию результатов фонового поиска
+    pub fn apply_search_update(&mut self, update: SearchUpdate) {
+        if !self.show_search_dialog {
+            return;
+        }
+        self.search_results.extend(update.entries);
+    }
+    
+    /// Переключает режим поиска: имя -> содержимое -> метаданные
+    pub fn cycle_search_mode(&mut self) {
+        self.search_mode = self.search_mode.next();
+        self.update_search();
+    }
+    
+    /// Переносит результаты поиска в среднюю панель как обычный список
+    pub fn materialize_search_results(&mut self) {
+        if self.search_results.is_empty() {
+            return;
+        }
+        let results = self.search_results.clone();
+        self.get_active_tab_mut().show_search_results(results);
+        self.cancel_search();
+    }
+    
+    /// Выделяет все найденные элементы, чтобы передать их в удаление или архивирование
+    pub fn select_all_search_results(&mut self) {
+        // Поиск после этого закрывается, результаты можно забрать
+        let results = std::mem::take(&mut self.search_results);
+        let count = results.len();
+        if count == 0 {
+            return;
+        }
+        self.get_active_tab_mut().selected_entries.extend(&results);
+        self.notification = Some(Msg::SelectedItems.fill(self.language, &[&count, &self.incomplete_search_suffix()]));
+        self.notification_timer = Some(std::time::Instant::now());
+        self.cancel_search();
+    }
+    
+    /// Отмечает результат под курсором или снимает отметку и переходит
+    /// к следующему
+    pub fn toggle_search_result(&mut self) {
+        let Some(entry) = self.search_results.get(self.search_cursor) else {
+            return;
+        };
+        if !self.search_selection.remove(&entry.path) {
+            self.search_selection.insert(entry.path.clone());
+        }
+        self.move_search_cursor_down();
+    }
+
+    /// Выделяет во вкладке отмеченные результаты, чтобы скопировать,
+    /// переместить или удалить их как обычное выделение
+    pub fn select_marked_search_results(&mut self) {
+        let marked = std::mem::take(&mut self.search_selection);
+        let results = std::mem::take(&mut self.search_results);
+        let count = marked.len();
+        self.get_active_tab_mut().selected_entries.extend(results.iter().filter(|entry| marked.contains(&entry.path)));
+        self.notification = Some(Msg::SelectedItems.fill(self.language, &[&count, &""]));
+        self.notification_timer = Some(std::time::Instant::now());
+        self.cancel_search();
+    }
+
+    /// Переключает интерпретацию запроса: подстрока -> glob -> regex
+    pub fn cycle_search_syntax(&mut self) {
+        self.search_syntax = self.search_syntax.next();
+        self.update_search();
     }
     
     pub fn move_search_cursor_down(&mut self) {
@@ -1116,6 +4803,8 @@ impl AppState {
         }
     }
     
+    /// Ставит курсор вкладки на выбранный результат. Для результата
+    /// рекурсивного поиска сначала открывается каталог, где он лежит.
     pub fn select_search_result(&mut self) {
         // Clone the selected entry path to avoid borrowing issues
         let selected_path = if let Some(selected_entry) = self.search_results.get(self.search_cursor) {
@@ -1125,8 +4814,13 @@ impl AppState {
         };
         
         if let Some(selected_path) = selected_path {
-            // Find the corresponding entry in the active tab's filtered entries
+            let recursive = self.search_recursive;
+            let show_hidden = self.show_hidden_files;
             let active_tab = self.get_active_tab_mut();
+            if let Some(parent) = selected_path.parent().filter(|parent| recursive && *parent != active_tab.current_dir) {
+                active_tab.set_current_dir(parent.to_path_buf(), show_hidden);
+            }
+            // Find the corresponding entry in the active tab's filtered entries
             if let Some(index) = active_tab.filtered_entries.iter().position(|e| e.path == selected_path) {
                 active_tab.cursor = index;
                 active_tab.update_preview();
@@ -1139,9 +4833,23 @@ impl AppState {
         self.search_query = String::new();
         self.search_results.clear();
         self.search_cursor = 0;
+        self.search_selection.clear();
+        self.search_root = None;
+        self.search_error = None;
+        self.background_search.cancel();
     }
     
+    /// Схема, которой рисуется интерфейс. При `NO_COLOR` это всегда
+    /// монохромная схема, что бы ни было выбрано в настройках.
     pub fn get_current_color_scheme(&self) -> crate::settings::ColorScheme {
+        if self.no_color {
+            return crate::settings::ColorScheme::monochrome();
+        }
+        self.configured_color_scheme()
+    }
+
+    /// Схема, выбранная в config.toml
+    pub fn configured_color_scheme(&self) -> crate::settings::ColorScheme {
         // Получаем имя текущей схемы из конфигурации
         if let Some(scheme_name) = &self.config.theme.color_scheme {
             // Пытаемся создать ColorScheme из имени
@@ -1150,12 +4858,489 @@ impl AppState {
             }
         }
         // Если не удалось получить схему из конфигурации, используем схему по умолчанию
-        crate::settings::ColorScheme::Dracula
+        crate::settings::ColorScheme::dracula()
+    }
+    
+    /// Палитра цветов имён файлов для текущей схемы
+    pub fn get_file_type_palette(&self) -> crate::settings::FileTypePalette {
+        crate::settings::FileTypePalette::new(
+            &self.get_current_color_scheme(),
+            &self.config.theme.file_colors,
+        )
     }
     
     pub fn set_color_scheme(&mut self, color_scheme: crate::settings::ColorScheme) {
         // Обновляем тему в конфигурации
         self.config.theme.color_scheme = Some(color_scheme.name().to_string());
-        println!("Color scheme updated to: {}", color_scheme.name());
+        self.save_settings();
+    }
+
+    /// Включает или выключает предпросмотр
+    pub fn toggle_preview(&mut self) {
+        self.preview_settings.enabled = !self.preview_settings.enabled;
+        self.apply_sort_settings();
+        for tab in self.panels_mut() {
+            tab.update_preview();
+        }
+    }
+
+    /// Переключает предел размера файла для предпросмотра по кругу из `PREVIEW_SIZE_LIMITS`
+    pub fn cycle_preview_size_limit(&mut self) {
+        let current = self.preview_settings.max_preview_size;
+        self.preview_settings.max_preview_size = PREVIEW_SIZE_LIMITS
+            .iter()
+            .copied()
+            .find(|&limit| limit > current)
+            .unwrap_or(PREVIEW_SIZE_LIMITS[0]);
+        self.apply_sort_settings();
+        for tab in self.panels_mut() {
+            tab.update_preview();
+        }
+    }
+
+    /// Переключает число строк текстового предпросмотра по кругу из `PREVIEW_LINE_COUNTS`
+    pub fn cycle_preview_line_count(&mut self) {
+        let current = self.preview_settings.max_lines;
+        self.preview_settings.max_lines = PREVIEW_LINE_COUNTS
+            .iter()
+            .copied()
+            .find(|&count| count > current)
+            .unwrap_or(PREVIEW_LINE_COUNTS[0]);
+        self.apply_sort_settings();
+        for tab in self.panels_mut() {
+            tab.update_preview();
+        }
+    }
+
+    /// Включает и выключает показ конца файлов `.log`
+    pub fn toggle_tail_logs(&mut self) {
+        self.preview_settings.tail_logs = !self.preview_settings.tail_logs;
+        self.apply_sort_settings();
+        for tab in self.panels_mut() {
+            tab.update_preview();
+        }
+    }
+
+    /// Включает и выключает фоновый подсчёт размера каталога в предпросмотре
+    pub fn toggle_directory_sizes(&mut self) {
+        self.preview_settings.directory_sizes = !self.preview_settings.directory_sizes;
+        self.apply_sort_settings();
+    }
+
+    /// Отдаёт `dir_sizer` каталог, размер которого ещё не известен: сначала
+    /// для панели информации, потом для предпросмотра активной вкладки.
+    /// Если считать нечего, прерывает начатый подсчёт.
+    pub fn request_dir_sizes(&mut self) {
+        let info = self.file_info
+            .as_ref()
+            .filter(|info| self.show_info_panel && info.size.is_none())
+            .map(|info| info.path.clone());
+        let tab = self.get_active_tab();
+        let preview = match &tab.preview_content {
+            Some(PreviewContent::Directory { path, size: None, .. }) if tab.preview.directory_sizes => Some(path.clone()),
+            _ => None,
+        };
+        match info.or(preview) {
+            Some(path) => self.dir_sizer.request(path),
+            None => self.dir_sizer.cancel(),
+        }
+    }
+
+    /// Открывает диалог команды, вывод которой для файла под курсором
+    /// заменит предпросмотр
+    pub fn start_preview_command(&mut self) {
+        if self.get_active_tab().get_selected_entry_path().is_none() {
+            return;
+        }
+        self.input_buffer = self.preview_command.clone();
+        self.input_mode = InputMode::PreviewCommand;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
+    }
+
+    /// Открывает ввод glob для фильтра средней панели с текущим шаблоном
+    pub fn start_glob_filter(&mut self) {
+        self.input_buffer = self.get_active_tab().glob_filter.as_ref().map(|filter| filter.pattern.clone()).unwrap_or_default();
+        self.input_mode = InputMode::GlobFilter;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
+    }
+
+    /// Фильтрует список активной вкладки по введённому glob. Пустой ввод
+    /// снимает фильтр, ошибка в шаблоне показывается в диалоге.
+    pub fn apply_glob_filter(&mut self) {
+        let pattern = self.input_buffer.trim().to_string();
+        let filter = if pattern.is_empty() {
+            None
+        } else {
+            match GlobFilter::new(&pattern) {
+                Ok(filter) => Some(filter),
+                Err(e) => {
+                    self.input_dialog_error = Some(format!("Invalid glob: {}", e));
+                    return;
+                }
+            }
+        };
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+        self.get_active_tab_mut().set_glob_filter(filter);
+    }
+
+    /// Открывает ввод команды оболочки для выделенных файлов
+    pub fn start_shell_command(&mut self) {
+        self.input_buffer.clear();
+        self.input_mode = InputMode::ShellCommand;
+        self.show_input_dialog = true;
+        self.input_dialog_error = None;
+    }
+
+    /// Запускает введённую команду фоновой задачей в текущем каталоге.
+    /// Если в ней есть `{}`, она выполняется для каждого выделенного файла
+    /// (или файла под курсором) с путём вместо `{}`.
+    pub fn run_shell_command(&mut self) {
+        let command = self.input_buffer.trim().to_string();
+        if command.is_empty() {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        let active_tab = self.get_active_tab();
+        let dir = active_tab.current_dir.clone();
+        if !command.contains("{}") {
+            self.input_mode = InputMode::Normal;
+            self.input_buffer.clear();
+            let task_kind = TaskKind::RunCommand { name: command.clone(), command: command.clone(), dir };
+            self.task_manager.add_task(task_kind, command);
+            return;
+        }
+        let mut files: Vec<PathBuf> = active_tab.selected_entries.iter().cloned().collect();
+        files.sort();
+        if files.is_empty() {
+            files.extend(active_tab.get_selected_entry_path());
+        }
+        if files.is_empty() {
+            self.input_dialog_error = Some(Msg::NoFilesForPlaceholder.text(self.language).to_string());
+            return;
+        }
+        self.input_mode = InputMode::Normal;
+        self.input_buffer.clear();
+        let mut tasks: Vec<(TaskKind, String)> = files
+            .iter()
+            .map(|file| {
+                let task_kind = TaskKind::RunCommand {
+                    name: command.clone(),
+                    command: crate::commands::substitute_path(&command, file),
+                    dir: dir.clone(),
+                };
+                let name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                (task_kind, format!("{} ({})", command, name))
+            })
+            .collect();
+        if tasks.len() == 1 {
+            let (task_kind, description) = tasks.remove(0);
+            self.task_manager.add_task(task_kind, description);
+        } else {
+            self.task_manager.add_group(tasks);
+        }
+    }
+
+    /// Запускает введённую команду для файла под курсором. Вывод показывается
+    /// вместо предпросмотра, пока курсор не сдвинется.
+    pub fn run_preview_command(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let command = std::mem::take(&mut self.input_buffer).trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        self.preview_command = command.clone();
+        let tab = self.get_active_tab_mut();
+        let Some(path) = tab.get_selected_entry_path() else {
+            return;
+        };
+        tab.right_pane_view = RightPaneView::Preview;
+        tab.preview_scroll = (0, 0);
+        tab.preview_content = Some(PreviewContent::CommandOutput {
+            path,
+            command,
+            output: String::new(),
+            status: CommandStatus::Running,
+        });
+        if self.focus == FocusBlock::Terminal {
+            self.focus = FocusBlock::Middle;
+        }
+    }
+
+    /// Отдаёт `command_previewer` команду, которую ждёт предпросмотр
+    /// активной вкладки, или прерывает ставшую ненужной
+    pub fn request_command_preview(&mut self) {
+        let running = match &self.get_active_tab().preview_content {
+            Some(PreviewContent::CommandOutput { path, command, status: CommandStatus::Running, .. }) => {
+                Some((path.clone(), command.clone()))
+            }
+            _ => None,
+        };
+        match running {
+            Some((path, command)) => self.command_previewer.request(path, command),
+            None => self.command_previewer.cancel(),
+        }
+    }
+
+    /// Спрашивает плагины о файле под курсором. Если встроенный
+    /// предпросмотр того же файла построился заново, снова показывает
+    /// уже полученный ответ.
+    pub fn request_plugin_preview(&mut self) {
+        let tab = self.get_active_tab();
+        let path = tab
+            .filtered_entries
+            .get(tab.cursor)
+            .filter(|entry| !entry.is_dir && tab.preview.enabled)
+            .map(|entry| entry.path.clone());
+        self.plugin_host.request_preview(&self.plugins, path.as_deref());
+        if let Some(preview) = path.as_deref().and_then(|path| self.plugin_host.latest_preview(path)).cloned() {
+            self.apply_plugin_preview(preview);
+        }
+    }
+
+    /// Показывает ответ плагина вместо встроенного предпросмотра файла.
+    /// Вывод команды `c p` и ответ другого плагина не заменяются.
+    pub fn apply_plugin_preview(&mut self, preview: PluginPreview) {
+        for tab in self.panels_mut() {
+            if tab.get_selected_entry_path().as_ref() != Some(&preview.path) {
+                continue;
+            }
+            if !matches!(tab.preview_content, Some(PreviewContent::CommandOutput { .. } | PreviewContent::Plugin { .. })) {
+                tab.preview_content = Some(PreviewContent::Plugin {
+                    plugin: preview.plugin.clone(),
+                    result: preview.result.clone(),
+                });
+            }
+        }
+    }
+
+    /// Показывает вывод команды там, где его ждут
+    pub fn apply_command_output(&mut self, update: CommandOutput) {
+        for tab in self.panels_mut() {
+            if let Some(PreviewContent::CommandOutput { path, command, output, status }) = &mut tab.preview_content {
+                if *status == CommandStatus::Running && *path == update.path && *command == update.command {
+                    *output = update.output.clone();
+                    *status = update.status;
+                }
+            }
+        }
+    }
+
+    /// Начинает анализ занятого места в текущем каталоге активной вкладки.
+    /// Начатое в другой вкладке сканирование прерывается, и её размеры так и
+    /// остаются неполными.
+    pub fn start_disk_usage(&mut self) {
+        self.input_mode = InputMode::Normal;
+        for tab in self.panels_mut() {
+            if let ListingSource::DiskUsage(view) = &mut tab.listing_source {
+                view.stopped |= view.scanning;
+                view.scanning = false;
+            }
+        }
+        let active_tab = self.get_active_tab_mut();
+        active_tab.show_disk_usage();
+        let root = active_tab.current_dir.clone();
+        self.usage_scanner.request(root);
+    }
+
+    /// Передаёт порцию результатов сканирования вкладке, которая их ждёт.
+    /// Если такой уже нет, сканирование больше не нужно.
+    pub fn apply_usage_batch(&mut self, batch: UsageBatch) {
+        let mut wanted = false;
+        for tab in self.panels_mut() {
+            if tab.disk_usage().is_some_and(|view| view.scanning && view.root == batch.root) {
+                tab.apply_usage_batch(&batch);
+                wanted = true;
+            }
+        }
+        if !wanted {
+            self.usage_scanner.cancel();
+        }
+    }
+
+    /// Подставляет посчитанный размер туда, где его ждут
+    pub fn apply_dir_size(&mut self, update: DirSize) {
+        if let Some(info) = self.file_info.as_mut().filter(|info| info.path == update.path) {
+            info.size = Some(update.bytes);
+        }
+        for tab in self.panels_mut() {
+            if let Some(PreviewContent::Directory { path, size, .. }) = &mut tab.preview_content {
+                if *path == update.path {
+                    *size = Some(update.bytes);
+                }
+            }
+        }
+    }
+
+    pub fn toggle_confirm_delete(&mut self) {
+        self.behavior_settings.confirm_delete = !self.behavior_settings.confirm_delete;
+    }
+
+    pub fn toggle_confirm_overwrite(&mut self) {
+        self.behavior_settings.confirm_overwrite = !self.behavior_settings.confirm_overwrite;
+    }
+
+    /// Выбирает следующий формат архива по умолчанию из `ArchiveFormat::ALL`
+    pub fn cycle_default_archive_format(&mut self) {
+        self.archive_settings.default_format = step_format(self.archive_settings.default_format, 1);
+    }
+
+    /// Меняет степень сжатия по умолчанию по кругу от 0 до 9
+    pub fn cycle_default_compression_level(&mut self) {
+        self.archive_settings.compression_level = (self.archive_settings.compression_level + 1) % (MAX_COMPRESSION_LEVEL + 1);
+    }
+
+    /// Перечитывает config.toml после правки. При синтаксической ошибке
+    /// остаётся прежняя конфигурация, а ошибка показывается в уведомлении.
+    pub fn reload_config(&mut self) {
+        let notice = match config::load_config() {
+            Ok((config, warnings)) => {
+                if config == self.config && warnings.is_empty() {
+                    return;
+                }
+                self.apply_config(config);
+                config_warnings_notice(&warnings, self.language).unwrap_or_else(|| Msg::ConfigReloaded.text(self.language).to_string())
+            }
+            Err(e) => {
+                log::error!("Failed to reload config: {}", e);
+                let first_line = e.to_string().lines().next().unwrap_or_default().to_string();
+                Msg::ConfigNotReloaded.fill(self.language, &[&first_line])
+            }
+        };
+        self.notification = Some(notice);
+        self.notification_timer = Some(std::time::Instant::now());
+    }
+
+    /// Применяет изменившиеся разделы новой конфигурации, не трогая вкладки и задачи.
+    /// Тема, пользовательские команды и время жизни задач читаются из `config`
+    /// при каждом использовании, поэтому их достаточно заменить.
+    pub fn apply_config(&mut self, config: Config) {
+        let settings = Settings::from_config(&config);
+        if config.bookmarks != self.config.bookmarks {
+            self.bookmarks = config.bookmarks.clone();
+            self.bookmarks_cursor = self.bookmarks_cursor.min(self.bookmarks.len().saturating_sub(1));
+            self.refresh_missing_places();
+        }
+        if config.display != self.config.display {
+            self.show_tabs = settings.display.show_tabs;
+            self.show_hidden_files = settings.display.show_hidden_files;
+            self.show_file_details = settings.display.show_file_details;
+            self.show_key_hints = settings.display.show_key_hints;
+            self.show_left_pane = settings.display.show_left_pane;
+            self.show_footer = settings.display.show_footer;
+            self.preview_width = settings.display.preview_width;
+            self.language = settings.display.language;
+            self.size_units = settings.display.size_units;
+        }
+        if config.sort != self.config.sort {
+            self.sort_settings = settings.sort;
+        }
+        if config.pinned != self.config.pinned {
+            self.pinned.replace(&config.pinned);
+        }
+        let plugins_changed = config.plugins != self.config.plugins;
+        let preview_changed = config.preview != self.config.preview;
+        if preview_changed {
+            self.preview_settings = settings.preview;
+        }
+        if config.behavior != self.config.behavior {
+            self.behavior_settings = settings.behavior;
+        }
+        if config.archive != self.config.archive {
+            self.archive_settings = settings.archive;
+        }
+        let disks_changed = config.disks != self.config.disks;
+        let remotes_changed = config.remotes != self.config.remotes;
+        if config.search.index_roots != self.config.search.index_roots {
+            self.file_index = FileIndexer::new(&config.search.index_roots);
+            self.file_index.rebuild();
+        }
+        self.config = config;
+        if remotes_changed {
+            self.sync_remotes();
+        }
+        if disks_changed || remotes_changed {
+            self.update_mounts();
+        }
+        if plugins_changed {
+            // Права сверяются с новой конфигурацией, поэтому после её замены
+            for index in 0..self.plugins.len() {
+                let enabled = !self.config.plugins.disabled.contains(&self.plugins[index].manifest.name);
+                if self.plugins[index].enabled != enabled {
+                    self.set_plugin_enabled(index, enabled);
+                } else if enabled {
+                    self.start_plugin(index);
+                }
+            }
+        }
+        self.apply_sort_settings();
+        if preview_changed {
+            for tab in self.panels_mut() {
+                tab.update_preview();
+            }
+        }
+    }
+
+    /// Сдвигает курсор экрана первичной настройки на `delta` пунктов
+    pub fn move_setup_cursor(&mut self, delta: i32) {
+        self.setup_cursor = self.setup_cursor.saturating_add_signed(delta as isize).min(SETUP_ITEMS - 1);
+    }
+
+    /// Меняет выбранную настройку на экране первичной настройки.
+    /// Последний пункт завершает настройку.
+    pub fn change_setup_item(&mut self) {
+        match self.setup_cursor {
+            0 => {
+                let schemes = crate::settings::ColorScheme::all();
+                let current = self.configured_color_scheme();
+                let index = schemes.iter().position(|scheme| scheme.name() == current.name()).map_or(0, |i| (i + 1) % schemes.len());
+                self.config.theme.color_scheme = Some(schemes[index].name().to_string());
+            }
+            1 => self.toggle_hidden_files(),
+            2 => self.toggle_confirm_delete(),
+            3 => self.cycle_opener(),
+            4 => self.config.behavior.use_trash = !self.config.behavior.use_trash,
+            _ => self.finish_setup(),
+        }
+    }
+
+    /// Переключает программу для файлов: `xdg-open`, затем редакторы из
+    /// `$VISUAL` и `$EDITOR` и `vi` во встроенном терминале
+    fn cycle_opener(&mut self) {
+        let mut choices = vec![(None, false)];
+        let editors = ["VISUAL", "EDITOR"].into_iter().filter_map(|name| std::env::var(name).ok());
+        for editor in editors.chain(std::iter::once("vi".to_string())) {
+            let choice = (Some(format!("{} {{file}}", editor.trim())), true);
+            if !editor.trim().is_empty() && !choices.contains(&choice) {
+                choices.push(choice);
+            }
+        }
+        let behavior = &mut self.config.behavior;
+        let current = (behavior.opener.clone(), behavior.opener_in_terminal);
+        let index = choices.iter().position(|choice| *choice == current).map_or(0, |i| (i + 1) % choices.len());
+        (behavior.opener, behavior.opener_in_terminal) = choices.swap_remove(index);
+    }
+
+    /// Записывает выбранные при первом запуске настройки в config.toml
+    pub fn finish_setup(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.setup_cursor = 0;
+        self.save_settings();
+        if self.notification.is_none() {
+            self.notification = Some("Saved config.toml".to_string());
+            self.notification_timer = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Переносит текущие настройки в конфигурацию и сохраняет её в config.toml
+    pub fn save_settings(&mut self) {
+        Settings::from_app_state(self).store_in(&mut self.config);
+        if let Err(e) = config::save_config(&self.config) {
+            log::error!("Failed to save config: {}", e);
+            self.notification = Some(format!("Failed to save settings: {}", e));
+            self.notification_timer = Some(std::time::Instant::now());
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+/// Where the kernel lists whole disks, each with a subdirectory per partition
+const SYS_BLOCK: &str = "/sys/block";
+
+/// The whole disk a mount source such as `/dev/sdb1` belongs to, e.g.
+/// `/dev/sdb`. Links like `/dev/disk/by-uuid/...` are resolved first.
+pub fn disk_of(source: &Path) -> Option<PathBuf> {
+    let source = source.canonicalize().ok()?;
+    if !source.starts_with("/dev") {
+        return None;
+    }
+    let name = source.file_name()?.to_str()?;
+    disk_in(Path::new(SYS_BLOCK), name).map(|disk| Path::new("/dev").join(disk))
+}
+
+/// Name of the disk in `sys_block` that is `device` itself or has it as a
+/// partition
+fn disk_in(sys_block: &Path, device: &str) -> Option<String> {
+    std::fs::read_dir(sys_block)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .find(|disk| disk == device || sys_block.join(disk).join(device).is_dir())
+}
+
+/// The mounts of all partitions of `disk` among `(source, mount point)`
+/// pairs, so every partition of a multi-partition stick is unmounted. Each
+/// comes with its partition device, links like `/dev/disk/by-uuid/...`
+/// resolved.
+pub fn mounts_of<'a>(disk: &Path, mounts: impl IntoIterator<Item = (&'a Path, &'a Path)>) -> Vec<(PathBuf, PathBuf)> {
+    mounts
+        .into_iter()
+        .filter(|(source, _)| disk_of(source).as_deref() == Some(disk))
+        .map(|(source, dest)| (source.canonicalize().unwrap_or_else(|_| source.to_path_buf()), dest.to_path_buf()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_partitions_resolve_to_their_disk() {
+        let dir = TempDir::new("sys_block").unwrap();
+        std::fs::create_dir_all(dir.path().join("sdb/sdb1")).unwrap();
+        std::fs::create_dir_all(dir.path().join("sdb/sdb2")).unwrap();
+        std::fs::create_dir_all(dir.path().join("nvme0n1/nvme0n1p3")).unwrap();
+
+        assert_eq!(disk_in(dir.path(), "sdb2").as_deref(), Some("sdb"));
+        assert_eq!(disk_in(dir.path(), "sdb").as_deref(), Some("sdb"));
+        assert_eq!(disk_in(dir.path(), "nvme0n1p3").as_deref(), Some("nvme0n1"));
+        assert_eq!(disk_in(dir.path(), "sdc1"), None);
+    }
+}
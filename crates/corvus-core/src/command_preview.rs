@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// At most this much output is kept for the preview
+pub const OUTPUT_LIMIT: usize = 64 * 1024;
+
+/// A preview command still running after this long is killed
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a preview command ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandStatus {
+    Running,
+    Exited(i32),
+    /// Killed by a signal other than the timeout or a cancel
+    Signaled,
+    TimedOut,
+    /// Stopped by a newer request or `cancel`
+    Cancelled,
+    /// The shell could not be started
+    Failed,
+}
+
+/// Combined stdout and stderr of a command run on `path`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandOutput {
+    pub path: PathBuf,
+    pub command: String,
+    pub output: String,
+    pub status: CommandStatus,
+}
+
+/// Runs one-off preview commands on a worker thread. Like `DirSizer`, only
+/// the latest request matters: a new request or `cancel` bumps the
+/// generation, which kills the previous command and drops its output.
+#[derive(Debug)]
+pub struct CommandPreviewer {
+    generation: Arc<AtomicU64>,
+    pending: Option<(PathBuf, String)>,
+    tx: mpsc::UnboundedSender<(u64, CommandOutput)>,
+    rx: mpsc::UnboundedReceiver<(u64, CommandOutput)>,
+}
+
+impl CommandPreviewer {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            pending: None,
+            tx,
+            rx,
+        }
+    }
+
+    /// Starts `command` for `path` unless that is already under way.
+    /// `{file}` in the command is replaced with the quoted path, otherwise
+    /// the path is appended.
+    pub fn request(&mut self, path: PathBuf, command: String) {
+        let request = (path, command);
+        if self.pending.as_ref() == Some(&request) {
+            return;
+        }
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.pending = Some(request.clone());
+        let current = self.generation.clone();
+        let tx = self.tx.clone();
+
+        std::thread::spawn(move || {
+            let (path, command) = request;
+            let (output, status) = run(&path, &command, || current.load(Ordering::SeqCst) != generation);
+            let _ = tx.send((generation, CommandOutput { path, command, output, status }));
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        if self.pending.take().is_some() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Waits for the output of the latest request, skipping stale ones
+    pub async fn wait_for_output(&mut self) -> Option<CommandOutput> {
+        loop {
+            let (generation, output) = self.rx.recv().await?;
+            if generation == self.generation.load(Ordering::SeqCst) {
+                self.pending = None;
+                return Some(output);
+            }
+        }
+    }
+}
+
+impl Default for CommandPreviewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The shell line for `command` applied to `path`
+fn command_line(path: &Path, command: &str) -> String {
+    if command.contains("{file}") {
+        let dir = path.parent().unwrap_or(Path::new("/"));
+        crate::commands::expand_command(command, Some(path), &[path.to_path_buf()], dir)
+    } else {
+        format!("{} {}", command, crate::commands::shell_quote(&path.to_string_lossy()))
+    }
+}
+
+/// Runs the command in the file's directory until it exits, times out or
+/// `cancelled` returns true. Then its whole process group is killed, so
+/// that nothing it started keeps running. Output past `OUTPUT_LIMIT` is
+/// discarded.
+fn run(path: &Path, command: &str, cancelled: impl Fn() -> bool) -> (String, CommandStatus) {
+    let dir = path.parent().unwrap_or(Path::new("/"));
+    // stderr goes into the same pipe so the two stay interleaved
+    let script = format!("exec 2>&1\n{}", command_line(path, command));
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .process_group(0)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return (e.to_string(), CommandStatus::Failed),
+    };
+
+    // The reader is left behind if a background process keeps the pipe open
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    if let Some(mut stdout) = child.stdout.take() {
+        let output = output.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            while let Ok(count @ 1..) = stdout.read(&mut buf) {
+                let mut output = output.lock().unwrap();
+                let room = OUTPUT_LIMIT.saturating_sub(output.len());
+                output.extend_from_slice(&buf[..count.min(room)]);
+            }
+            let _ = done_tx.send(());
+        });
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status.code().map_or(CommandStatus::Signaled, CommandStatus::Exited),
+            Ok(None) if cancelled() || started.elapsed() >= TIMEOUT => {
+                let status = if cancelled() { CommandStatus::Cancelled } else { CommandStatus::TimedOut };
+                let group = nix::unistd::Pid::from_raw(child.id() as i32);
+                let _ = nix::sys::signal::killpg(group, nix::sys::signal::Signal::SIGKILL);
+                let _ = child.wait();
+                break status;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return (e.to_string(), CommandStatus::Failed),
+        }
+    };
+    let _ = done_rx.recv_timeout(Duration::from_millis(200));
+    let output = String::from_utf8_lossy(&output.lock().unwrap()).into_owned();
+    (output, status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[tokio::test]
+    async fn test_output_and_exit_status_are_reported() {
+        let dir = TempDir::new("command_preview").unwrap();
+        let file = dir.path().join("it's here.txt");
+        std::fs::write(&file, "hello\n").unwrap();
+
+        let mut previewer = CommandPreviewer::new();
+        previewer.request(file.clone(), "cat {file}; echo oops >&2; exit 3".to_string());
+        let output = previewer.wait_for_output().await.unwrap();
+        assert_eq!(output.path, file);
+        assert_eq!(output.output, "hello\noops\n");
+        assert_eq!(output.status, CommandStatus::Exited(3));
+    }
+
+    #[test]
+    fn test_long_output_is_cut() {
+        let (output, status) = run(Path::new("/"), "yes | head -c 200000; true", || false);
+        assert_eq!(output.len(), OUTPUT_LIMIT);
+        assert_eq!(status, CommandStatus::Exited(0));
+    }
+
+    #[test]
+    fn test_cancel_stops_the_command_and_what_it_started() {
+        let dir = TempDir::new("command_cancel").unwrap();
+        let marker = dir.path().join("marker");
+        let command = format!("(sleep 0.5; touch {}) & sleep 10; true", crate::commands::shell_quote(&marker.to_string_lossy()));
+        let started = Instant::now();
+        let (_, status) = run(dir.path(), &command, || started.elapsed() >= Duration::from_millis(200));
+        assert_eq!(status, CommandStatus::Cancelled);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        std::thread::sleep(Duration::from_millis(800));
+        assert!(!marker.exists());
+    }
+}
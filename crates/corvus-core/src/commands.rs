@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+/// Quotes `value` for a POSIX shell. Everything is wrapped in single quotes,
+/// with embedded single quotes written as `'\''`.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quotes `value` to be typed at an interactive shell prompt. Control
+/// characters such as newlines would act as keys there, so a value with
+/// them is written as `$'...'` with escapes instead of plain single quotes.
+pub fn prompt_quote(value: &str) -> String {
+    if !value.chars().any(char::is_control) {
+        return shell_quote(value);
+    }
+    let mut quoted = String::from("$'");
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str(r"\\"),
+            '\'' => quoted.push_str(r"\'"),
+            '\n' => quoted.push_str(r"\n"),
+            '\t' => quoted.push_str(r"\t"),
+            '\r' => quoted.push_str(r"\r"),
+            c if c.is_ascii_control() => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+fn quote_path(path: &Path) -> String {
+    shell_quote(&path.to_string_lossy())
+}
+
+/// Expands the placeholders of a custom command template:
+/// `{file}` is the item under the cursor, `{files}` the selection and `{dir}`
+/// the current directory. Paths are shell-quoted, and text that a path
+/// brings in is never expanded again.
+pub fn expand_command(template: &str, file: Option<&Path>, files: &[PathBuf], dir: &Path) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let (replacement, len) = if rest.starts_with("{files}") {
+            let quoted: Vec<String> = files.iter().map(|path| quote_path(path)).collect();
+            (quoted.join(" "), "{files}".len())
+        } else if rest.starts_with("{file}") {
+            (file.map(quote_path).unwrap_or_else(|| shell_quote("")), "{file}".len())
+        } else if rest.starts_with("{dir}") {
+            (quote_path(dir), "{dir}".len())
+        } else {
+            ("{".to_string(), 1)
+        };
+        expanded.push_str(&replacement);
+        rest = &rest[len..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Replaces every `{}` in an ad-hoc shell command with the quoted `path`
+pub fn substitute_path(command: &str, path: &Path) -> String {
+    command.replace("{}", &quote_path(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("plain"), "'plain'");
+        assert_eq!(shell_quote("it's $HOME"), r"'it'\''s $HOME'");
+    }
+
+    #[test]
+    fn test_prompt_quote_escapes_control_characters() {
+        assert_eq!(prompt_quote("a b's"), r"'a b'\''s'");
+        assert_eq!(prompt_quote("two\nlines 'q' \\"), r"$'two\nlines \'q\' \\'");
+        assert_eq!(prompt_quote("bell\u{7}"), r"$'bell\x07'");
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let dir = Path::new("/music/new albums");
+        let files = vec![dir.join("a b.mp3"), dir.join("c.mp3")];
+        assert_eq!(
+            expand_command("vlc {files}", Some(&files[0]), &files, dir),
+            "vlc '/music/new albums/a b.mp3' '/music/new albums/c.mp3'"
+        );
+        assert_eq!(
+            expand_command("cd {dir} && du -sh {file} {x}", Some(&files[1]), &files, dir),
+            "cd '/music/new albums' && du -sh '/music/new albums/c.mp3' {x}"
+        );
+    }
+
+    #[test]
+    fn test_substitute_path() {
+        let path = Path::new("/tmp/it's {}");
+        assert_eq!(substitute_path("chmod -x {} && ls {}", path), r"chmod -x '/tmp/it'\''s {}' && ls '/tmp/it'\''s {}'");
+    }
+
+    #[test]
+    fn test_expanded_paths_are_not_expanded_again() {
+        let file = PathBuf::from("/tmp/{dir}");
+        assert_eq!(expand_command("cat {file}", Some(&file), &[], Path::new("/x")), "cat '/tmp/{dir}'");
+    }
+}
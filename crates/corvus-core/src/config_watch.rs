@@ -0,0 +1,86 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use tokio::sync::mpsc;
+
+/// Watches `config.toml` and tells the main loop when it was edited
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    // Dropping the watcher stops the notifications
+    _watcher: Option<RecommendedWatcher>,
+    rx: mpsc::Receiver<()>,
+}
+
+impl ConfigWatcher {
+    /// Watches the directory holding `config_file`, because editors often
+    /// save by replacing the file rather than writing into it
+    pub fn new(config_file: &Path) -> Self {
+        // A single slot coalesces the bursts of events one save produces
+        let (tx, rx) = mpsc::channel(1);
+        let Some(dir) = config_file.parent() else {
+            return Self { _watcher: None, rx };
+        };
+        let file_name = config_file.file_name().map(|name| name.to_os_string());
+        let handler = move |result: notify::Result<Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            let is_config = event.paths.iter().any(|path| path.file_name() == file_name.as_deref());
+            if is_config && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                let _ = tx.try_send(());
+            }
+        };
+        let watcher = std::fs::create_dir_all(dir)
+            .map_err(notify::Error::io)
+            .and_then(|_| notify::recommended_watcher(handler))
+            .and_then(|mut watcher| watcher.watch(dir, RecursiveMode::NonRecursive).map(|_| watcher));
+        match watcher {
+            Ok(watcher) => Self { _watcher: Some(watcher), rx },
+            Err(e) => {
+                log::error!("Failed to watch {}: {}", dir.display(), e);
+                Self { _watcher: None, rx }
+            }
+        }
+    }
+
+    /// Watches the default config location
+    pub fn for_default_config() -> Self {
+        match config::config_dir() {
+            Some(dir) => Self::new(&dir.join("config.toml")),
+            None => Self::default(),
+        }
+    }
+
+    /// Waits until the config file changes. Events queued meanwhile are
+    /// folded into this one, so a save triggers a single reload.
+    pub async fn wait_for_change(&mut self) -> Option<()> {
+        self.rx.recv().await?;
+        while self.rx.try_recv().is_ok() {}
+        Some(())
+    }
+}
+
+impl Default for ConfigWatcher {
+    fn default() -> Self {
+        let (_, rx) = mpsc::channel(1);
+        Self { _watcher: None, rx }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_reports_config_edits() {
+        let dir = tempdir::TempDir::new("config_watch").unwrap();
+        let config_file = dir.path().join("config.toml");
+        let mut watcher = ConfigWatcher::new(&config_file);
+
+        std::fs::write(dir.path().join("other.toml"), "x = 1").unwrap();
+        std::fs::write(&config_file, "[behavior]\nconfirm_delete = false\n").unwrap();
+
+        let change = tokio::time::timeout(Duration::from_secs(5), watcher.wait_for_change()).await;
+        assert_eq!(change.unwrap(), Some(()));
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::app_state::{Listing, PreviewContent};
+
+/// How long the UI thread waits for a listing or a preview before it shows
+/// "Loading…" and lets the result arrive later. Local directories are done
+/// well within this, so they never flicker.
+pub const LOAD_WAIT: Duration = Duration::from_millis(50);
+
+/// Ids of loads, unique across all tabs
+static NEXT_LOAD: AtomicU64 = AtomicU64::new(1);
+
+/// A load that took longer than `LOAD_WAIT`, for the tab waiting on `id`
+#[derive(Debug)]
+pub enum Loaded {
+    Entries { id: u64, listing: Listing },
+    Preview { id: u64, content: PreviewContent },
+}
+
+/// Result of `LoadHandle::run`
+#[derive(Debug)]
+pub enum Started<T> {
+    /// Finished within `LOAD_WAIT`
+    Done(T),
+    /// Still running, comes through `DirLoader::wait_for_update` with this id
+    Pending(u64),
+}
+
+/// Hand-over between a worker and the thread waiting for it
+enum Slot<T> {
+    Waiting,
+    Ready(T),
+    /// The waiter gave up, the worker sends the result to the loader instead
+    Abandoned,
+}
+
+/// Collects loads that outlived `LOAD_WAIT`. Tabs start loads through a
+/// `LoadHandle`; a tab that navigates again simply waits for another id, so
+/// the superseded result matches no tab and is dropped.
+#[derive(Debug)]
+pub struct DirLoader {
+    tx: mpsc::UnboundedSender<Loaded>,
+    rx: mpsc::UnboundedReceiver<Loaded>,
+}
+
+impl DirLoader {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self { tx, rx }
+    }
+
+    pub fn handle(&self) -> LoadHandle {
+        LoadHandle { tx: self.tx.clone() }
+    }
+
+    /// Waits for a load that finished late
+    pub async fn wait_for_update(&mut self) -> Option<Loaded> {
+        self.rx.recv().await
+    }
+}
+
+impl Default for DirLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lets a tab run loads on worker threads
+#[derive(Debug, Clone)]
+pub struct LoadHandle {
+    tx: mpsc::UnboundedSender<Loaded>,
+}
+
+impl LoadHandle {
+    /// Runs `load` on a worker thread and waits up to `LOAD_WAIT` for it. If
+    /// it takes longer, its result is sent to the loader as `wrap(id, ..)`.
+    /// A worker stuck on a dead mount is left behind, nothing waits for it.
+    pub fn run<T: Send + 'static>(
+        &self,
+        load: impl FnOnce() -> T + Send + 'static,
+        wrap: fn(u64, T) -> Loaded,
+    ) -> Started<T> {
+        let id = NEXT_LOAD.fetch_add(1, Ordering::SeqCst);
+        let slot = Arc::new((Mutex::new(Slot::Waiting), Condvar::new()));
+        let worker_slot = slot.clone();
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let value = load();
+            let (lock, ready) = &*worker_slot;
+            let mut state = lock.lock().unwrap();
+            match *state {
+                Slot::Abandoned => {
+                    let _ = tx.send(wrap(id, value));
+                }
+                _ => {
+                    *state = Slot::Ready(value);
+                    ready.notify_one();
+                }
+            }
+        });
+
+        let (lock, ready) = &*slot;
+        let (mut state, _) = ready
+            .wait_timeout_while(lock.lock().unwrap(), LOAD_WAIT, |state| matches!(state, Slot::Waiting))
+            .unwrap();
+        match std::mem::replace(&mut *state, Slot::Abandoned) {
+            Slot::Ready(value) => Started::Done(value),
+            _ => Started::Pending(id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrap_preview(id: u64, text: String) -> Loaded {
+        Loaded::Preview { id, content: PreviewContent::Error(text) }
+    }
+
+    #[tokio::test]
+    async fn test_slow_loads_arrive_through_the_loader() {
+        let mut loader = DirLoader::new();
+        let handle = loader.handle();
+        assert!(matches!(handle.run(|| "quick".to_string(), wrap_preview), Started::Done(text) if text == "quick"));
+
+        let slow = || {
+            std::thread::sleep(LOAD_WAIT * 4);
+            "slow".to_string()
+        };
+        let Started::Pending(id) = handle.run(slow, wrap_preview) else {
+            panic!("a slow load must not block");
+        };
+        match loader.wait_for_update().await.unwrap() {
+            Loaded::Preview { id: loaded, content: PreviewContent::Error(text) } => {
+                assert_eq!(loaded, id);
+                assert_eq!(text, "slow");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
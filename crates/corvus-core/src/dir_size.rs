@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use utils::fs::get_directory_size_until;
+
+/// Total size of the files under `path`
+#[derive(Debug)]
+pub struct DirSize {
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Sums up directory sizes on a worker thread so that moving the cursor
+/// never waits for a large tree. Only the latest request matters: asking
+/// for another directory bumps the generation, which stops the previous
+/// walk and discards its result.
+#[derive(Debug)]
+pub struct DirSizer {
+    generation: Arc<AtomicU64>,
+    pending: Option<PathBuf>,
+    tx: mpsc::UnboundedSender<(u64, DirSize)>,
+    rx: mpsc::UnboundedReceiver<(u64, DirSize)>,
+}
+
+impl DirSizer {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            pending: None,
+            tx,
+            rx,
+        }
+    }
+
+    /// The directory currently being sized
+    pub fn pending(&self) -> Option<&Path> {
+        self.pending.as_deref()
+    }
+
+    /// Starts sizing `path` unless that is already under way
+    pub fn request(&mut self, path: PathBuf) {
+        if self.pending.as_ref() == Some(&path) {
+            return;
+        }
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.pending = Some(path.clone());
+        let current = self.generation.clone();
+        let tx = self.tx.clone();
+
+        std::thread::spawn(move || {
+            let bytes = get_directory_size_until(&path, || current.load(Ordering::SeqCst) != generation);
+            if let Some(bytes) = bytes {
+                let _ = tx.send((generation, DirSize { path, bytes }));
+            }
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        if self.pending.take().is_some() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Waits for the size of the latest requested directory, skipping stale ones
+    pub async fn wait_for_update(&mut self) -> Option<DirSize> {
+        loop {
+            let (generation, size) = self.rx.recv().await?;
+            if generation == self.generation.load(Ordering::SeqCst) {
+                self.pending = None;
+                return Some(size);
+            }
+        }
+    }
+}
+
+impl Default for DirSizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[tokio::test]
+    async fn test_only_the_latest_request_is_reported() {
+        let first = TempDir::new("sizer_first").unwrap();
+        let second = TempDir::new("sizer_second").unwrap();
+        std::fs::create_dir(second.path().join("nested")).unwrap();
+        std::fs::write(second.path().join("nested/file"), vec![0; 1000]).unwrap();
+        std::fs::write(second.path().join("top"), vec![0; 24]).unwrap();
+
+        let mut sizer = DirSizer::new();
+        sizer.request(first.path().to_path_buf());
+        sizer.request(second.path().to_path_buf());
+        let size = sizer.wait_for_update().await.unwrap();
+        assert_eq!(size.path, second.path());
+        assert_eq!(size.bytes, 1024);
+        assert!(sizer.pending().is_none());
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use crate::settings::SizeUnits;
+
+/// A mount whose statvfs has not returned after this long is shown
+/// without usage until it does
+pub const STAT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Space on a mounted file system, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+}
+
+impl DiskUsage {
+    /// Share of the space in use, from 0 to 1
+    pub fn used_ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.used as f64 / self.total as f64
+    }
+}
+
+/// Reads the usage of the file system mounted at `path`
+pub fn stat(path: &Path) -> nix::Result<DiskUsage> {
+    let stat = nix::sys::statvfs::statvfs(path)?;
+    let block = stat.fragment_size() as u64;
+    let blocks = stat.blocks() as u64;
+    Ok(DiskUsage {
+        total: blocks * block,
+        used: blocks.saturating_sub(stat.blocks_free() as u64) * block,
+        available: stat.blocks_available() as u64 * block,
+    })
+}
+
+/// A size with one decimal and a one-letter unit, e.g. `12.3G` or `50G`,
+/// counted in powers of 1024 or 1000 as `units` says
+pub fn short_size(bytes: u64, units: SizeUnits) -> String {
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+    let base = units.base() as f64;
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= base && unit < UNITS.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    let text = format!("{:.1}", value);
+    format!("{}{}", text.strip_suffix(".0").unwrap_or(&text), UNITS[unit])
+}
+
+/// Calls statvfs for each mount on its own thread. A dead network mount
+/// can block the call indefinitely, so nothing ever waits for it: a mount
+/// whose call is still running is not asked again, and after
+/// `STAT_TIMEOUT` its old usage is dropped.
+#[derive(Debug)]
+pub struct DiskUsageProber {
+    usage: HashMap<PathBuf, DiskUsage>,
+    in_flight: HashMap<PathBuf, Instant>,
+    tx: mpsc::UnboundedSender<(PathBuf, Option<DiskUsage>)>,
+    rx: mpsc::UnboundedReceiver<(PathBuf, Option<DiskUsage>)>,
+}
+
+impl DiskUsageProber {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            usage: HashMap::new(),
+            in_flight: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Starts a statvfs for every mount not already being read and forgets
+    /// mounts that are gone
+    pub fn request<'a>(&mut self, mounts: impl IntoIterator<Item = &'a Path>) {
+        let mounts: Vec<&Path> = mounts.into_iter().collect();
+        self.usage.retain(|path, _| mounts.contains(&path.as_path()));
+        for mount in mounts {
+            if let Some(started) = self.in_flight.get(mount) {
+                if started.elapsed() >= STAT_TIMEOUT && self.usage.remove(mount).is_some() {
+                    log::warn!("statvfs on {} does not return", mount.display());
+                }
+                continue;
+            }
+            self.in_flight.insert(mount.to_path_buf(), Instant::now());
+            let path = mount.to_path_buf();
+            let tx = self.tx.clone();
+            std::thread::spawn(move || {
+                let usage = match stat(&path) {
+                    Ok(usage) => Some(usage),
+                    Err(e) => {
+                        log::warn!("statvfs on {} failed: {}", path.display(), e);
+                        None
+                    }
+                };
+                let _ = tx.send((path, usage));
+            });
+        }
+    }
+
+    /// Usage of the mount at `path`, if known and not timed out
+    pub fn get(&self, path: &Path) -> Option<&DiskUsage> {
+        self.usage.get(path)
+    }
+
+    /// Waits for a statvfs call to return and records its result
+    pub async fn wait_for_update(&mut self) -> Option<()> {
+        let (path, usage) = self.rx.recv().await?;
+        let timed_out = self.in_flight.remove(&path).is_some_and(|started| started.elapsed() >= STAT_TIMEOUT);
+        match usage {
+            // A late answer still counts, the next tick asks again anyway
+            Some(usage) => {
+                if timed_out {
+                    log::info!("statvfs on {} returned after {:?}", path.display(), STAT_TIMEOUT);
+                }
+                self.usage.insert(path, usage);
+            }
+            None => {
+                self.usage.remove(&path);
+            }
+        }
+        Some(())
+    }
+}
+
+impl Default for DiskUsageProber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_size_keeps_one_decimal() {
+        assert_eq!(short_size(512, SizeUnits::Binary), "512B");
+        assert_eq!(short_size(50 * 1024 * 1024 * 1024, SizeUnits::Binary), "50G");
+        assert_eq!(short_size(12_300 * 1024 * 1024, SizeUnits::Binary), "12G");
+        assert_eq!(short_size(12 * 1024 * 1024 * 1024 + 300 * 1024 * 1024, SizeUnits::Binary), "12.3G");
+        assert_eq!(short_size(1000, SizeUnits::Decimal), "1K");
+        assert_eq!(short_size(50 * 1024 * 1024 * 1024, SizeUnits::Decimal), "53.7G");
+    }
+
+    #[tokio::test]
+    async fn test_usage_of_a_mount_is_read_in_the_background() {
+        let mut prober = DiskUsageProber::new();
+        prober.request([Path::new("/")]);
+        // Asking again while the first call runs starts nothing new
+        prober.request([Path::new("/")]);
+        prober.wait_for_update().await.unwrap();
+        let usage = prober.get(Path::new("/")).unwrap();
+        assert!(usage.total > 0);
+        assert!(usage.used <= usage.total);
+        assert!(prober.in_flight.is_empty());
+
+        prober.request(std::iter::empty());
+        assert!(prober.get(Path::new("/")).is_none());
+    }
+}
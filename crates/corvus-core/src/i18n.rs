@@ -0,0 +1,709 @@
+//! Строки интерфейса на английском и русском.
+//!
+//! Каждое сообщение — вариант `Msg`; переводы стоят рядом в одном `match`,
+//! чтобы новый текст нельзя было добавить только на одном языке. Места для
+//! значений отмечены `{}` и заполняются `Msg::fill` по порядку.
+//!
+//! Каталог лежит в ядре, потому что уведомления и подтверждения
+//! составляет `AppState`; интерфейс берёт его отсюда же.
+
+use std::fmt::Display;
+
+pub use crate::settings::Language;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    // Экран настроек
+    SettingsTitle,
+    Categories,
+    SettingsItems,
+    HotkeysTitle,
+    HelpTitle,
+    HelpFiltered,
+    HelpNoMatches,
+    AncestorsTitle,
+    // Строка состояния
+    StatusHidden,
+    StatusSelected,
+    StatusSelectedDirs,
+    StatusSort,
+    StatusFilter,
+    StatusFree,
+    CategoryInterface,
+    CategoryColorSchemes,
+    CategorySorting,
+    CategoryPreview,
+    CategoryBehavior,
+    CategoryBookmarks,
+    CategorySearch,
+    CategoryHotkeys,
+    CategoryPlugins,
+    ShowLeftPane,
+    ShowTabs,
+    ShowFooter,
+    ShowHiddenFiles,
+    ShowFileDetails,
+    ColorSchemeValue,
+    ShowKeyHints,
+    LanguageValue,
+    SizeUnitsValue,
+    SortFieldValue,
+    SortByName,
+    SortBySize,
+    SortByDate,
+    SortOrderValue,
+    Ascending,
+    Descending,
+    SeparateDirsValue,
+    Yes,
+    No,
+    NaturalSort,
+    EnablePreview,
+    MaxPreviewSizeValue,
+    PreviewLinesValue,
+    PreviewTruncated,
+    PreviewTail,
+    TailLogs,
+    DirectorySizes,
+    ImageResolutionValue,
+    ConfirmDelete,
+    ConfirmOverwrite,
+    AutoRefresh,
+    DefaultArchiveFormatValue,
+    DefaultCompressionValue,
+    ManageBookmarks,
+    ContentSearchDefault,
+    CaseSensitivity,
+    IndexRootsValue,
+    IndexStatusValue,
+    IndexRootsUnset,
+    IndexOff,
+    IndexFiles,
+    IndexNotBuilt,
+    IndexRefreshing,
+    HotkeysPrompt,
+    NoPlugins,
+    PluginActions,
+    PluginLogTitle,
+    PluginLogEmpty,
+    PluginInstall,
+    PluginRunning,
+    PluginStopped,
+    PluginNeedsConsent,
+    PluginFailed,
+    PluginEventsDropped,
+    ChooseCategory,
+
+    // Первый запуск
+    SetupTitle,
+    SetupIntro,
+    SetupConfirmDelete,
+    SetupOpener,
+    SetupInTerminal,
+    SetupUseTrash,
+    SetupDone,
+
+    // Панели
+    XdgDirs,
+    Bookmarks,
+    Disks,
+    MountsUnsupported,
+    Remotes,
+    RemotesEmpty,
+    SearchResultsIn,
+    DiskUsageIn,
+    DiskUsageScanning,
+    DiskUsageStopped,
+    Preview,
+    PluginPreviewTitle,
+    PluginImage,
+    Terminal,
+    TerminalCopy,
+    ShellNotStarted,
+    ShellExited,
+    DirItems,
+    DirSizing,
+    DirFirstShown,
+    FileTooLarge,
+    BinaryFile,
+    Loading,
+    NoItemSelected,
+    CommandOutputTitle,
+    CommandRunning,
+    CommandExited,
+    CommandSignaled,
+    CommandTimedOut,
+    CommandCancelled,
+    CommandFailed,
+    Tasks,
+    TaskQueued,
+    TaskDone,
+    TasksRunning,
+    TasksQueued,
+    TasksFailed,
+    TasksDone,
+    TasksClearHint,
+    Info,
+    BufferFiles,
+    BufferEmpty,
+    BufferCopy,
+    BufferMove,
+    BufferNone,
+    JumpTo,
+    InfoPath,
+    InfoSize,
+    InfoOwner,
+    InfoGroup,
+    InfoTarget,
+    InfoTargetMissing,
+    InfoInode,
+    InfoPermissions,
+    InfoCreated,
+    InfoModified,
+    Calculating,
+
+    // Диалоги
+    CommandsTitle,
+    SessionsTitle,
+    SessionDefault,
+    SessionName,
+    SessionReadOnly,
+    RemoteConnect,
+    CommandInTerminal,
+    CreateFile,
+    CreateDirectory,
+    Rename,
+    Chmod,
+    Chown,
+    PreviewCommand,
+    ShellCommand,
+    GlobFilter,
+    GlobFilterActive,
+    TabName,
+    BookmarkName,
+    ArchiveTitle,
+    Input,
+    ArchiveDialogTitle,
+    ArchiveName,
+    CompressionValue,
+    CompressionStore,
+    CompressionFast,
+    CompressionBest,
+    RemoveSources,
+    ArchiveFormat,
+    SearchTitle,
+    Searching,
+    SearchRecursive,
+    SearchQuery,
+    InvalidPattern,
+    NoResults,
+    SearchResults,
+    SearchMarked,
+    Confirmation,
+    ConfirmationPlan,
+
+    // Уведомления и подтверждения
+    ConfigSettingIgnored,
+    ConfigSettingsIgnored,
+    ConfigNotLoaded,
+    ConfigReloaded,
+    ConfigNotReloaded,
+    QuitOneOperation,
+    QuitOperations,
+    TerminalStarted,
+    TerminalStartFailed,
+    NoTerminal,
+    NoCustomCommands,
+    SessionDefaultLabel,
+    SessionHeldNotSaved,
+    SessionInUse,
+    SessionNameInvalid,
+    SessionHeld,
+    SessionSaved,
+    SessionSaveFailed,
+    SessionNotSaved,
+    SessionLoadFailed,
+    SessionLoaded,
+    ShellStartFailed,
+    ShellWriteFailed,
+    ResumeOneTask,
+    ResumeTasks,
+    TasksSkipped,
+    RemoteAddressInvalid,
+    RemoteNotConnected,
+    OpenFailed,
+    PlaceMissing,
+    NoBookmarkOnKey,
+    SortedByName,
+    SortedBySize,
+    SortedByDate,
+    ViewForgotten,
+    ViewRemembered,
+    ViewSaved,
+    ViewSaveFailed,
+    TaskFailed,
+    PasteOverwrite,
+    PasteConfirm,
+    PlanShow,
+    PlanHide,
+    DryRunOn,
+    DryRunOff,
+    DeleteItems,
+    DeleteItem,
+    TrashItems,
+    TrashItem,
+    DeleteFailed,
+    AlreadyExists,
+    NameTaken,
+    MoveIntoItself,
+    NoFilesForPlaceholder,
+    BookmarkNameEmpty,
+    BookmarkExists,
+    BookmarkKeyTaken,
+    BookmarkRemove,
+    PinDirsOnly,
+    Pinned,
+    Unpinned,
+    PluginsWaiting,
+    PluginAsks,
+    PermissionNew,
+    PluginAllow,
+    PluginWroteError,
+    PluginWroteErrors,
+    NoPluginsDir,
+    PluginNameUnknown,
+    PluginCloning,
+    PluginCloneFailed,
+    PluginNotInstalled,
+    PluginInstalled,
+    PluginRemove,
+    PluginRemoved,
+    UnmountConfirm,
+    NotBlockDevice,
+    TabHolder,
+    RightPanelHolder,
+    EjectBusy,
+    EjectConfirm,
+    NothingMounted,
+    NotArchive,
+    ExtractConfirm,
+    ArchiveNameEmpty,
+    NoCommonParent,
+    SearchIncomplete,
+    SearchExported,
+    SearchExportFailed,
+    SelectedItems,
+}
+
+impl Msg {
+    /// Текст сообщения на языке `language`
+    pub fn text(self, language: Language) -> &'static str {
+        use Msg::*;
+        let (english, russian) = match self {
+            SettingsTitle => (
+                "Settings (↑/↓ - navigate, Tab - switch, Enter - select, Esc - exit)",
+                "Настройки приложения (↑/↓ - навигация, Tab - переключение, Enter - выбор, Esc - выход)",
+            ),
+            Categories => ("Categories", "Категории"),
+            SettingsItems => ("Settings", "Настройки"),
+            HotkeysTitle => ("Hotkeys", "Горячие клавиши"),
+            HelpTitle => ("Hotkeys — type to filter, Esc to close", "Горячие клавиши — набирайте для фильтра, Esc закрывает"),
+            HelpFiltered => ("Hotkeys matching \"{}\" — Esc clears", "Горячие клавиши по «{}» — Esc сбрасывает"),
+            HelpNoMatches => ("No keys match", "Ничего не найдено"),
+            AncestorsTitle => ("Go up — number or Enter", "Перейти выше — цифра или Enter"),
+            StatusHidden => ("{} hidden", "скрыто: {}"),
+            StatusSelected => ("{} selected, {}", "выделено: {}, {}"),
+            StatusSelectedDirs => ("+{} dirs", "+{} кат."),
+            StatusSort => ("sort: {} {}", "сортировка: {} {}"),
+            StatusFilter => ("filter: {}", "фильтр: {}"),
+            StatusFree => ("{} free", "свободно: {}"),
+            CategoryInterface => ("Interface", "Интерфейс"),
+            CategoryColorSchemes => ("Color schemes", "Цветовые схемы"),
+            CategorySorting => ("Sorting", "Сортировка"),
+            CategoryPreview => ("Preview", "Предпросмотр"),
+            CategoryBehavior => ("Behavior", "Поведение"),
+            CategoryBookmarks => ("Bookmarks", "Закладки"),
+            CategorySearch => ("Search", "Поиск"),
+            CategoryHotkeys => ("Hotkeys", "Горячие клавиши"),
+            CategoryPlugins => ("Plugins", "Плагины"),
+            ShowLeftPane => ("Show the left pane", "Отображать левую панель"),
+            ShowTabs => ("Show tabs", "Отображать вкладки"),
+            ShowFooter => ("Show the bottom panel", "Отображать нижнюю панель"),
+            ShowHiddenFiles => ("Show hidden files", "Показывать скрытые файлы"),
+            ShowFileDetails => ("Show size and modification date", "Показывать размер и дату изменения"),
+            ColorSchemeValue => ("Color scheme: {}", "Цветовая схема: {}"),
+            ShowKeyHints => ("Key hints at the bottom of the screen", "Подсказки клавиш внизу экрана"),
+            LanguageValue => ("Language: {}", "Язык: {}"),
+            SizeUnitsValue => ("Size units: {}", "Единицы размеров: {}"),
+            SortFieldValue => ("Sort by: {}", "Поле сортировки: {}"),
+            SortByName => ("Name", "По имени"),
+            SortBySize => ("Size", "По размеру"),
+            SortByDate => ("Modification date", "По дате изменения"),
+            SortOrderValue => ("Order: {}", "Порядок: {}"),
+            Ascending => ("Ascending", "По возрастанию"),
+            Descending => ("Descending", "По убыванию"),
+            SeparateDirsValue => ("Directories before files: {}", "Разделение файлов и каталогов: {}"),
+            Yes => ("Yes", "Да"),
+            No => ("No", "Нет"),
+            NaturalSort => ("Natural sort (file2 before file10)", "Естественная сортировка (file2 перед file10)"),
+            EnablePreview => ("Enable preview", "Включить предпросмотр"),
+            MaxPreviewSizeValue => ("Maximum file size: {}", "Максимальный размер файла: {}"),
+            PreviewLinesValue => ("Text preview lines: {}", "Строк в предпросмотре текста: {}"),
+            PreviewTruncated => ("… (truncated)", "… (обрезано)"),
+            PreviewTail => ("Preview (tail)", "Предпросмотр (конец)"),
+            TailLogs => ("Show the end of .log files", "Показывать конец файлов .log"),
+            DirectorySizes => ("Compute directory sizes", "Считать размер каталогов"),
+            ImageResolutionValue => ("Image resolution: {}x{}", "Разрешение изображений: {}x{}"),
+            ConfirmDelete => ("Confirm file deletion", "Подтверждение удаления файлов"),
+            ConfirmOverwrite => ("Confirm overwriting", "Подтверждение перезаписи"),
+            AutoRefresh => ("Automatic refresh", "Автоматическое обновление"),
+            DefaultArchiveFormatValue => ("Default archive format: {}", "Формат архива по умолчанию: {}"),
+            DefaultCompressionValue => ("Default compression level: {}", "Степень сжатия по умолчанию: {}"),
+            ManageBookmarks => ("Manage bookmarks...", "Управление закладками..."),
+            ContentSearchDefault => ("Search file contents by default", "Поиск по содержимому файлов по умолчанию"),
+            CaseSensitivity => ("Case-sensitive search", "Поиск с учётом регистра"),
+            IndexRootsValue => ("Indexed directories: {}", "Индексируемые каталоги: {}"),
+            IndexStatusValue => ("Index: {}", "Индекс: {}"),
+            IndexRootsUnset => ("not set (search.index_roots)", "не заданы (search.index_roots)"),
+            IndexOff => ("off", "выключен"),
+            IndexFiles => ("{} files, updated {}", "{} файлов, обновлён {}"),
+            IndexNotBuilt => ("not built yet", "ещё не построен"),
+            IndexRefreshing => ("{} (refreshing...)", "{} (обновляется...)"),
+            HotkeysPrompt => ("Press Enter to see the full list...", "Нажмите Enter для просмотра полного списка..."),
+            NoPlugins => ("No plugins found.", "Плагины не найдены."),
+            PluginActions => (
+                "Enter: enable/disable, l: stderr log, a: install, d: remove",
+                "Enter: включить/выключить, l: журнал stderr, a: установить, d: удалить",
+            ),
+            PluginLogTitle => (
+                "stderr of {}, {} lines (j/k, PageUp/PageDown to scroll, Esc to close)",
+                "stderr плагина {}, строк: {} (j/k, PageUp/PageDown — листать, Esc — закрыть)",
+            ),
+            PluginLogEmpty => ("The plugin has not written anything to stderr.", "Плагин ничего не писал в stderr."),
+            PluginInstall => (
+                "Install a plugin from a directory or git URL",
+                "Установить плагин из каталога или git URL",
+            ),
+            PluginRunning => ("running", "работает"),
+            PluginStopped => ("stopped", "остановлен"),
+            PluginNeedsConsent => ("waiting for permission, Enter to review", "ждёт разрешения, Enter — просмотреть"),
+            PluginFailed => ("failed: {}", "ошибка: {}"),
+            PluginEventsDropped => ("{} events dropped", "потеряно событий: {}"),
+            ChooseCategory => ("Choose a category", "Выберите категорию"),
+
+            SetupTitle => (
+                "First run (↑/↓ - navigate, Enter - change, Esc - save and exit)",
+                "Первый запуск (↑/↓ - навигация, Enter - изменить, Esc - сохранить и выйти)",
+            ),
+            SetupIntro => (
+                "Choose the basic settings. You can change them later in config.toml or on the settings screen (F2).",
+                "Выберите основные настройки. Их можно изменить позже в config.toml или на экране настроек (F2).",
+            ),
+            SetupConfirmDelete => ("Ask before deleting", "Подтверждать удаление"),
+            SetupOpener => ("Open files with: {}{}", "Открывать файлы: {}{}"),
+            SetupInTerminal => (" (in the terminal)", " (во встроенном терминале)"),
+            SetupUseTrash => ("Move deleted files to the trash", "Перемещать удаляемое в корзину"),
+            SetupDone => ("Done: save config.toml", "Готово: сохранить config.toml"),
+
+            XdgDirs => ("XDG Dirs", "Каталоги XDG"),
+            Bookmarks => ("Bookmarks", "Закладки"),
+            Disks => ("Disks", "Диски"),
+            MountsUnsupported => ("Mounts (unsupported)", "Диски (не поддерживается)"),
+            Remotes => ("Remotes", "Удалённые"),
+            RemotesEmpty => ("c r: connect", "c r — подключить"),
+            SearchResultsIn => ("Search results in {}: {} (Esc or h to exit)", "Найдено в {}: {} (Esc или h — выход)"),
+            DiskUsageIn => ("Disk usage of {}: {} (Esc to exit)", "Занято в {}: {} (Esc — выход)"),
+            DiskUsageScanning => ("Disk usage of {}: {} so far, scanning… (Esc to stop)", "Занято в {}: пока {}, сканирование… (Esc — прервать)"),
+            DiskUsageStopped => ("Disk usage of {}: at least {}, scan stopped (Esc to exit)", "Занято в {}: не меньше {}, сканирование прервано (Esc — выход)"),
+            Preview => ("Preview", "Предпросмотр"),
+            PluginPreviewTitle => ("Preview ({})", "Предпросмотр ({})"),
+            PluginImage => ("Image: {}", "Изображение: {}"),
+            Terminal => ("Terminal", "Терминал"),
+            TerminalCopy => ("copy", "копирование"),
+            ShellNotStarted => ("[press Enter to start the shell]", "[Enter — запустить оболочку]"),
+            ShellExited => ("[process exited — press Enter to restart]", "[процесс завершён — Enter для перезапуска]"),
+            DirItems => ("{} items", "элементов: {}"),
+            DirSizing => (", sizing...", ", размер считается..."),
+            DirFirstShown => (" (first {} shown)", " (показаны первые {})"),
+            FileTooLarge => ("File is too large to preview ({})", "Файл слишком велик для предпросмотра ({})"),
+            BinaryFile => ("[Binary File]", "[Двоичный файл]"),
+            Loading => ("Loading…", "Загрузка…"),
+            NoItemSelected => ("No item selected", "Ничего не выбрано"),
+            CommandOutputTitle => ("{} [{}]", "{} [{}]"),
+            CommandRunning => ("running...", "выполняется..."),
+            CommandExited => ("exit {}", "код {}"),
+            CommandSignaled => ("killed by a signal", "завершена сигналом"),
+            CommandTimedOut => ("stopped after {} s", "остановлена через {} с"),
+            CommandCancelled => ("cancelled", "отменена"),
+            CommandFailed => ("failed to start", "не запустилась"),
+            Tasks => ("Tasks", "Задачи"),
+            TaskQueued => ("{} (queued)", "{} (в очереди)"),
+            TaskDone => ("{} (done)", "{} (готово)"),
+            TasksRunning => ("running", "выполняется"),
+            TasksQueued => ("queued", "в очереди"),
+            TasksFailed => ("failed", "с ошибкой"),
+            TasksDone => ("done", "готово"),
+            TasksClearHint => ("{} (C: clear finished)", "{} (C: убрать завершённые)"),
+            Info => ("Info", "Сведения"),
+            BufferFiles => ("Buffer: {} files ({})", "Буфер: файлов {} ({})"),
+            BufferEmpty => ("Buffer: Empty", "Буфер: пуст"),
+            BufferCopy => ("Copy", "копирование"),
+            BufferMove => ("Move", "перемещение"),
+            BufferNone => ("None", "нет"),
+            JumpTo => ("Jump to: {}_", "Переход к: {}_"),
+            InfoPath => ("Path: {}", "Путь: {}"),
+            InfoSize => ("Size: {}", "Размер: {}"),
+            InfoOwner => ("Owner: {} ({})", "Владелец: {} ({})"),
+            InfoGroup => ("Group: {} ({})", "Группа: {} ({})"),
+            InfoTarget => ("Target: {}", "Цель: {}"),
+            InfoTargetMissing => ("Target: {} (missing)", "Цель: {} (не существует)"),
+            InfoInode => ("Inode: {}, hard links: {}", "Inode: {}, жёстких ссылок: {}"),
+            InfoPermissions => ("Permissions: {}", "Права: {}"),
+            InfoCreated => ("Created: {}", "Создан: {}"),
+            InfoModified => ("Modified: {}", "Изменён: {}"),
+            Calculating => ("calculating...", "считается..."),
+
+            CommandsTitle => ("Commands (key or Enter: run, Esc: close)", "Команды (клавиша или Enter — запуск, Esc — закрыть)"),
+            SessionsTitle => ("Sessions (Enter: load, Esc: close)", "Сессии (Enter — открыть, Esc — закрыть)"),
+            SessionDefault => ("(default)", "(по умолчанию)"),
+            SessionReadOnly => (
+                "Session open in another corvus, not saved here (c s: save under another name)",
+                "Сессия открыта в другом corvus и здесь не сохраняется (c s — сохранить под другим именем)",
+            ),
+            SessionName => ("Save the session as (letters, digits, - and _)", "Сохранить сессию под именем (буквы, цифры, - и _)"),
+            RemoteConnect => ("Connect to user@host:/path or https://...", "Подключиться к user@host:/path или https://..."),
+            CommandInTerminal => (" (terminal)", " (терминал)"),
+            CreateFile => ("Create new file", "Новый файл"),
+            CreateDirectory => ("Create new directory", "Новый каталог"),
+            Rename => ("Rename", "Переименовать"),
+            Chmod => ("Chmod (e.g. 755)", "Права доступа (например, 755)"),
+            Chown => ("Chown (e.g. user:group)", "Владелец (например, user:group)"),
+            PreviewCommand => (
+                "Command to preview the file with ({file} or appended)",
+                "Команда для предпросмотра файла ({file} или в конце)",
+            ),
+            ShellCommand => (
+                "! Shell command ({} is each selected path)",
+                "! Команда оболочки ({} — путь каждого выделенного файла)",
+            ),
+            GlobFilter => (
+                "Filter by glob, e.g. *.rs or test_? (empty to clear)",
+                "Фильтр по glob, например *.rs или test_? (пустой — снять)",
+            ),
+            GlobFilterActive => ("filter: {} (Esc to clear)", "фильтр: {} (Esc — снять)"),
+            TabName => ("Tab name (empty to reset)", "Имя вкладки (пустое — сбросить)"),
+            BookmarkName => (
+                "Bookmark name (append \" #x\" for a jump key)",
+                "Имя закладки (\" #x\" в конце задаёт клавишу перехода)",
+            ),
+            ArchiveTitle => ("Archive (Format: {})", "Архив (формат: {})"),
+            Input => ("Input", "Ввод"),
+            ArchiveDialogTitle => (
+                "Archive (Format: {}) ↑/↓ or Tab: format, Enter: create",
+                "Архив (формат: {}) ↑/↓ или Tab — формат, Enter — создать",
+            ),
+            ArchiveName => ("Name: {}", "Имя: {}"),
+            CompressionValue => ("Compression: {}{}  ←/→: change", "Сжатие: {}{}  ←/→ — изменить"),
+            CompressionStore => (" (store)", " (без сжатия)"),
+            CompressionFast => (" (fast)", " (быстрое)"),
+            CompressionBest => (" (best)", " (наилучшее)"),
+            RemoveSources => (
+                "[{}] Remove sources after archiving  Ctrl+d: toggle",
+                "[{}] Удалить исходные файлы после упаковки  Ctrl+d — переключить",
+            ),
+            ArchiveFormat => ("Format", "Формат"),
+            SearchTitle => (
+                "Search [{} · {}{}]{} (Tab: syntax, Shift+Tab: mode, Ctrl+r: recursive, Ctrl+a: select all, Ctrl+e: export, Ctrl+l: list)",
+                "Поиск [{} · {}{}]{} (Tab — синтаксис, Shift+Tab — режим, Ctrl+r — в подкаталогах, Ctrl+a — выделить все, Ctrl+e — экспорт, Ctrl+l — список)",
+            ),
+            Searching => (" searching...", " идёт поиск..."),
+            SearchRecursive => (" · Recursive", " · в подкаталогах"),
+            SearchQuery => ("Search query: {}", "Запрос: {}"),
+            InvalidPattern => ("Invalid pattern: {}", "Неверный шаблон: {}"),
+            NoResults => ("No results found", "Ничего не найдено"),
+            SearchResults => ("Search results:", "Результаты поиска:"),
+            SearchMarked => (
+                "Marked: {}, Enter selects them in the tab",
+                "Отмечено: {}, Enter выделит их во вкладке",
+            ),
+            Confirmation => ("Confirmation", "Подтверждение"),
+            ConfirmationPlan => (
+                "Confirmation: {} operations (↑/↓: scroll, v: hide)",
+                "Подтверждение: операций {} (↑/↓ — прокрутка, v — скрыть)",
+            ),
+            ConfigSettingIgnored => (
+                "Ignored invalid setting in config.toml: {}",
+                "Пропущена неверная настройка в config.toml: {}",
+            ),
+            ConfigSettingsIgnored => (
+                "Ignored {} invalid settings in config.toml, first: {} (see log)",
+                "Пропущено неверных настроек в config.toml: {}, первая: {} (подробности в логе)",
+            ),
+            ConfigNotLoaded => (
+                "config.toml not loaded, using defaults: {}",
+                "config.toml не загружен, действуют значения по умолчанию: {}",
+            ),
+            ConfigReloaded => ("Reloaded config.toml", "config.toml перечитан"),
+            ConfigNotReloaded => ("config.toml not reloaded: {}", "config.toml не перечитан: {}"),
+            QuitOneOperation => (
+                "{} operation in progress, quit anyway? (y/n)",
+                "Идёт операций: {}. Всё равно выйти? (y/n)",
+            ),
+            QuitOperations => (
+                "{} operations in progress, quit anyway? (y/n)",
+                "Идёт операций: {}. Всё равно выйти? (y/n)",
+            ),
+            TerminalStarted => ("Started {} in {}", "Запущен {} в {}"),
+            TerminalStartFailed => ("Failed to start {}: {}", "Не удалось запустить {}: {}"),
+            NoTerminal => (
+                "No terminal to open: set $TERMINAL or terminal in [behavior]",
+                "Нет терминала: задайте $TERMINAL или terminal в [behavior]",
+            ),
+            NoCustomCommands => (
+                "No custom commands, add [[commands]] to the config",
+                "Своих команд нет, добавьте [[commands]] в config.toml",
+            ),
+            SessionDefaultLabel => ("default", "по умолчанию"),
+            SessionHeldNotSaved => (
+                "Session {} is open in corvus PID {}, it will not be saved here",
+                "Сессия {} открыта в corvus с PID {}, здесь она не сохранится",
+            ),
+            SessionInUse => (
+                "Session {} is open in another corvus (PID {}).\ny: continue without saving it, n: save it under another name",
+                "Сессия {} открыта в другом corvus (PID {}).\ny — продолжить без сохранения, n — сохранить под другим именем",
+            ),
+            SessionNameInvalid => ("Use letters, digits, - and _", "Допустимы буквы, цифры, - и _"),
+            SessionHeld => ("Session {} is open in corvus PID {}", "Сессия {} открыта в corvus с PID {}"),
+            SessionSaved => ("Saved session {}", "Сессия {} сохранена"),
+            SessionSaveFailed => ("Failed to save session {}: {}", "Не удалось сохранить сессию {}: {}"),
+            SessionNotSaved => ("Session {} is not saved yet", "Сессия {} ещё не сохранена"),
+            SessionLoadFailed => ("Failed to load session {}: {}", "Не удалось загрузить сессию {}: {}"),
+            SessionLoaded => ("Loaded session {}", "Загружена сессия {}"),
+            ShellStartFailed => ("Failed to start the shell: {}", "Не удалось запустить оболочку: {}"),
+            ShellWriteFailed => ("Failed to write to the shell: {}", "Не удалось передать ввод оболочке: {}"),
+            ResumeOneTask => (
+                "Resume {} unfinished task from the previous session? (y/n)",
+                "Продолжить незавершённые в прошлый раз задачи ({})? (y/n)",
+            ),
+            ResumeTasks => (
+                "Resume {} unfinished tasks from the previous session? (y/n)",
+                "Продолжить незавершённые в прошлый раз задачи ({})? (y/n)",
+            ),
+            TasksSkipped => (
+                "Resumed {} tasks, {} skipped because their files are gone",
+                "Продолжено задач: {}, пропущено: {} — их файлов больше нет",
+            ),
+            RemoteAddressInvalid => (
+                "Enter user@host:/path or an https:// address",
+                "Введите user@host:/path или адрес https://",
+            ),
+            RemoteNotConnected => ("{} is not connected", "{} не подключён"),
+            OpenFailed => ("Failed to open {}: {}", "Не удалось открыть {}: {}"),
+            PlaceMissing => ("{} no longer exists", "{} больше не существует"),
+            NoBookmarkOnKey => ("No bookmark on key {}", "На клавише {} нет закладки"),
+            SortedByName => ("Sort by name", "Сортировка по имени"),
+            SortedBySize => ("Sort by size", "Сортировка по размеру"),
+            SortedByDate => ("Sort by date", "Сортировка по дате"),
+            ViewForgotten => ("Forgot view settings for {}", "Настройки вида для {} забыты"),
+            ViewRemembered => ("Remembered view settings for {}", "Настройки вида для {} запомнены"),
+            ViewSaved => ("Saved view settings to {}", "Настройки вида сохранены в {}"),
+            ViewSaveFailed => ("Failed to save view settings: {}", "Не удалось сохранить настройки вида: {}"),
+            TaskFailed => ("{} failed: {}", "{} — ошибка: {}"),
+            PasteOverwrite => (
+                "A file with the same name already exists. Overwrite? (y/n, {})",
+                "Файл с таким именем уже есть. Перезаписать? (y/n, {})",
+            ),
+            PasteConfirm => ("Paste {} item(s) into {}? (y/n, {})", "Вставить элементы ({}) в {}? (y/n, {})"),
+            PlanShow => ("v: show operations", "v — показать операции"),
+            PlanHide => ("v: hide operations", "v — скрыть операции"),
+            DryRunOn => (
+                "Dry run on: deletes and pastes list their operations before anything runs",
+                "Пробный режим включён: удаление и вставка сначала показывают список операций",
+            ),
+            DryRunOff => ("Dry run off", "Пробный режим выключен"),
+            DeleteItems => ("Are you sure you want to delete {} items? (y/n, {})", "Удалить элементы ({})? (y/n, {})"),
+            DeleteItem => ("Are you sure you want to delete {}? (y/n, {})", "Удалить {}? (y/n, {})"),
+            TrashItems => ("Move {} items to the trash? (y/n, {})", "Переместить элементы ({}) в корзину? (y/n, {})"),
+            TrashItem => ("Move {} to the trash? (y/n, {})", "Переместить {} в корзину? (y/n, {})"),
+            DeleteFailed => ("Could not delete {}: {}", "Не удалось удалить {}: {}"),
+            AlreadyExists => ("{} already exists", "{} уже существует"),
+            NameTaken => ("A file with this name already exists.", "Файл с таким именем уже существует."),
+            MoveIntoItself => (
+                "A folder cannot be moved inside itself.",
+                "Каталог нельзя переместить внутрь него самого.",
+            ),
+            NoFilesForPlaceholder => ("No files to substitute for {}", "Нет файлов, чтобы подставить вместо {}"),
+            BookmarkNameEmpty => ("Bookmark name cannot be empty", "Имя закладки не может быть пустым"),
+            BookmarkExists => ("A bookmark named '{}' already exists", "Закладка '{}' уже есть"),
+            BookmarkKeyTaken => ("Key {} already opens '{}'", "Клавиша {} уже открывает '{}'"),
+            BookmarkRemove => ("Remove bookmark '{}'? (y/n)", "Удалить закладку '{}'? (y/n)"),
+            PinDirsOnly => ("Only directories can be pinned", "Закрепить можно только каталог"),
+            Pinned => ("Pinned {}", "Закреплён {}"),
+            Unpinned => ("Unpinned {}", "Откреплён {}"),
+            PluginsWaiting => (
+                "Plugins waiting for permission in settings (F2): {}",
+                "Плагины ждут разрешения в настройках (F2): {}",
+            ),
+            PluginAsks => ("Plugin '{}' asks for:", "Плагин '{}' запрашивает:"),
+            PermissionNew => (" (new)", " (новое)"),
+            PluginAllow => ("Allow and start it? (y/n)", "Разрешить и запустить? (y/n)"),
+            PluginWroteError => ("plugin {} wrote {} error", "плагин {} записал ошибок: {}"),
+            PluginWroteErrors => ("plugin {} wrote {} errors", "плагин {} записал ошибок: {}"),
+            NoPluginsDir => ("No plugins directory", "Нет каталога плагинов"),
+            PluginNameUnknown => ("Cannot tell the plugin name from {}", "Не удалось понять имя плагина по {}"),
+            PluginCloning => ("Cloning {}…", "Клонирование {}…"),
+            PluginCloneFailed => ("Could not clone {}: {}", "Не удалось клонировать {}: {}"),
+            PluginNotInstalled => ("Not installed: {}", "Не установлен: {}"),
+            PluginInstalled => ("Installed {} {}", "Установлен {} {}"),
+            PluginRemove => ("Remove plugin '{}' and delete {}? (y/n)", "Удалить плагин '{}' вместе с {}? (y/n)"),
+            PluginRemoved => ("Removed {}", "Удалён {}"),
+            UnmountConfirm => ("Are you sure you want to unmount {}? (y/n)", "Отмонтировать {}? (y/n)"),
+            NotBlockDevice => ("{} is not on a block device", "{} не на блочном устройстве"),
+            TabHolder => ("tab {}", "вкладка {}"),
+            RightPanelHolder => ("the right panel", "правая панель"),
+            EjectBusy => (
+                "Cannot eject {}: {} is open in {}, leave it first",
+                "Нельзя извлечь {}: {} открыта в {}, сначала уйдите оттуда",
+            ),
+            EjectConfirm => (
+                "Eject {}? This unmounts {} and powers the drive off. (y/n)",
+                "Извлечь {}? Будет отмонтировано: {}, затем питание диска отключится. (y/n)",
+            ),
+            NothingMounted => ("nothing", "ничего"),
+            NotArchive => ("Not a supported archive", "Архив этого формата не поддерживается"),
+            ExtractConfirm => (
+                "Extract {} into new folder {}? (y: new folder, n: current directory, Esc: cancel)",
+                "Распаковать {} в новый каталог {}? (y — новый каталог, n — текущий, Esc — отмена)",
+            ),
+            ArchiveNameEmpty => ("Archive name cannot be empty", "Имя архива не может быть пустым"),
+            NoCommonParent => (
+                "Selected items have no common parent directory, nothing archived",
+                "У выделенного нет общего родительского каталога, архив не создан",
+            ),
+            SearchIncomplete => (
+                " (search still running, results may be incomplete)",
+                " (поиск ещё идёт, результаты могут быть неполными)",
+            ),
+            SearchExported => ("Exported {} paths to {}{}", "Пути ({}) сохранены в {}{}"),
+            SearchExportFailed => (
+                "Failed to export search results: {}",
+                "Не удалось сохранить результаты поиска: {}",
+            ),
+            SelectedItems => ("Selected {} items{}", "Выделено: {}{}"),
+        };
+        language.pick(english, russian)
+    }
+
+    /// Текст сообщения с `args` на местах `{}`
+    pub fn fill(self, language: Language, args: &[&dyn Display]) -> String {
+        let mut parts = self.text(language).split("{}");
+        let mut text = parts.next().unwrap_or_default().to_string();
+        for (index, part) in parts.enumerate() {
+            if let Some(arg) = args.get(index) {
+                text.push_str(&arg.to_string());
+            }
+            text.push_str(part);
+        }
+        text
+    }
+}
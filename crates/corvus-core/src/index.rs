@@ -0,0 +1,284 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+use crate::app_state::DirEntry;
+use directories::BaseDirs;
+use tokio::sync::mpsc;
+
+/// File names under the indexed roots. Names are stored once and point at
+/// their parent directory, so a large tree costs little more than its names.
+#[derive(Debug, Default)]
+pub struct FileIndex {
+    dirs: Vec<PathBuf>,
+    names: Vec<Box<str>>,
+    parents: Vec<u32>,
+}
+
+impl FileIndex {
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    fn push_dir(&mut self, dir: PathBuf) -> u32 {
+        self.dirs.push(dir);
+        (self.dirs.len() - 1) as u32
+    }
+
+    fn push_name(&mut self, parent: u32, name: String) {
+        self.names.push(name.into_boxed_str());
+        self.parents.push(parent);
+    }
+
+    /// Fuzzy-matches `pattern` against the names under `scope` and returns the
+    /// best `limit` hits, best first. Entry names are paths relative to `scope`.
+    pub fn query(&self, pattern: &str, scope: &Path, show_hidden: bool, limit: usize) -> Vec<DirEntry> {
+        let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+        let mut scored: Vec<(i64, usize)> = Vec::new();
+        for (i, name) in self.names.iter().enumerate() {
+            let Some(score) = fuzzy_score(&pattern, name) else {
+                continue;
+            };
+            let parent = &self.dirs[self.parents[i] as usize];
+            let Ok(relative) = parent.strip_prefix(scope) else {
+                continue;
+            };
+            if !show_hidden && (name.starts_with('.') || has_hidden_component(relative)) {
+                continue;
+            }
+            scored.push((score, i));
+        }
+        // Shorter names win ties: they are usually the thing being looked for
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| self.names[a.1].len().cmp(&self.names[b.1].len())));
+
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, i)| {
+                let path = self.dirs[self.parents[i] as usize].join(&*self.names[i]);
+                let mut entry = DirEntry::from_path(path);
+                if let Ok(relative) = entry.path.strip_prefix(scope) {
+                    entry.name = relative.to_string_lossy().to_string();
+                }
+                entry
+            })
+            .collect()
+    }
+}
+
+fn has_hidden_component(path: &Path) -> bool {
+    path.components().any(|c| matches!(c, Component::Normal(part) if part.to_string_lossy().starts_with('.')))
+}
+
+/// Scores `name` against a lowercased `pattern` whose characters must all
+/// appear in order. Consecutive runs and matches at word starts score higher.
+pub fn fuzzy_score(pattern: &[char], name: &str) -> Option<i64> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut previous_char: Option<char> = None;
+    for (i, c) in name.chars().enumerate() {
+        if next < pattern.len() && c.to_lowercase().eq(std::iter::once(pattern[next])) {
+            score += 1;
+            if previous_match == Some(i.wrapping_sub(1)) {
+                score += 5;
+            }
+            if previous_char.is_none_or(|p| matches!(p, '.' | '_' | '-' | ' ' | '/')) {
+                score += 8;
+            }
+            previous_match = Some(i);
+            next += 1;
+        }
+        previous_char = Some(c);
+    }
+    if next < pattern.len() {
+        return None;
+    }
+    // Prefer compact matches
+    let spread = previous_match.unwrap_or(0) as i64 + 1 - pattern.len() as i64;
+    Some(score * 4 - spread)
+}
+
+/// Keeps a `FileIndex` of the configured roots fresh in the background
+#[derive(Debug)]
+pub struct FileIndexer {
+    roots: Vec<PathBuf>,
+    index: FileIndex,
+    pub building: bool,
+    pub last_refresh: Option<SystemTime>,
+    generation: Arc<AtomicU64>,
+    tx: mpsc::Sender<(u64, FileIndex)>,
+    rx: mpsc::Receiver<(u64, FileIndex)>,
+}
+
+impl FileIndexer {
+    /// `~` at the start of a root is expanded to the home directory
+    pub fn new(roots: &[PathBuf]) -> Self {
+        let home = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+        let roots = roots
+            .iter()
+            .map(|root| match (root.strip_prefix("~"), &home) {
+                (Ok(rest), Some(home)) => home.join(rest),
+                _ => root.clone(),
+            })
+            .collect();
+        let (tx, rx) = mpsc::channel(4);
+        Self {
+            roots,
+            index: FileIndex::default(),
+            building: false,
+            last_refresh: None,
+            generation: Arc::new(AtomicU64::new(0)),
+            tx,
+            rx,
+        }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Whether queries under `path` can be answered from the index
+    pub fn covers(&self, path: &Path) -> bool {
+        self.last_refresh.is_some() && self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    pub fn query(&self, pattern: &str, scope: &Path, show_hidden: bool, limit: usize) -> Vec<DirEntry> {
+        self.index.query(pattern, scope, show_hidden, limit)
+    }
+
+    pub fn cancel(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.building = false;
+    }
+
+    /// Starts a fresh walk of all roots, abandoning any build in progress.
+    /// The old index keeps answering queries until the new one is ready.
+    pub fn rebuild(&mut self) {
+        if self.roots.is_empty() {
+            return;
+        }
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.building = true;
+        let current = self.generation.clone();
+        let tx = self.tx.clone();
+        let roots = self.roots.clone();
+
+        tokio::spawn(async move {
+            let mut index = FileIndex::default();
+            let mut pending = roots;
+            while let Some(dir) = pending.pop() {
+                if current.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                let Ok(mut read_dir) = tokio::fs::read_dir(&dir).await else {
+                    continue;
+                };
+                let parent = index.push_dir(dir);
+                while let Ok(Some(entry)) = read_dir.next_entry().await {
+                    // `file_type` does not follow symlinks, so linked directories are not descended into
+                    if entry.file_type().await.is_ok_and(|t| t.is_dir()) {
+                        pending.push(entry.path());
+                    }
+                    index.push_name(parent, entry.file_name().to_string_lossy().to_string());
+                }
+                // Stay out of the way of the UI and file operations
+                tokio::task::yield_now().await;
+            }
+            let _ = tx.send((generation, index)).await;
+        });
+    }
+
+    /// Waits for a finished build and swaps it in, skipping cancelled ones
+    pub async fn wait_for_update(&mut self) -> Option<()> {
+        loop {
+            let (generation, index) = self.rx.recv().await?;
+            if generation == self.generation.load(Ordering::SeqCst) {
+                self.index = index;
+                self.building = false;
+                self.last_refresh = Some(SystemTime::now());
+                return Some(());
+            }
+        }
+    }
+}
+
+impl Default for FileIndexer {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(pattern: &str, name: &str) -> Option<i64> {
+        let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+        fuzzy_score(&pattern, name)
+    }
+
+    #[test]
+    fn test_fuzzy_requires_ordered_subsequence() {
+        assert!(score("apst", "app_state.rs").is_some());
+        assert!(score("APST", "app_state.rs").is_some());
+        assert!(score("tspa", "app_state.rs").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_prefers_word_starts_and_runs() {
+        let word_starts = score("as", "app_state.rs").unwrap();
+        let scattered = score("as", "bananas").unwrap();
+        assert!(word_starts > scattered);
+
+        let run = score("main", "main.rs").unwrap();
+        let gaps = score("main", "my_animation.rs").unwrap();
+        assert!(run > gaps);
+    }
+
+    #[tokio::test]
+    async fn test_index_build_and_scoped_query() {
+        let dir = tempdir::TempDir::new("index").unwrap();
+        std::fs::create_dir_all(dir.path().join("src/ui")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join("src/ui/layout.rs"), "").unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(dir.path().join(".git/config"), "").unwrap();
+
+        let mut indexer = FileIndexer::new(&[dir.path().to_path_buf()]);
+        assert!(!indexer.covers(dir.path()));
+        indexer.rebuild();
+        indexer.wait_for_update().await.unwrap();
+        assert!(!indexer.building);
+        assert!(indexer.covers(&dir.path().join("src")));
+        assert_eq!(indexer.len(), 6);
+
+        let results = indexer.query("lay", dir.path(), false, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "src/ui/layout.rs");
+
+        // Hidden directories are indexed but only shown on request
+        assert!(indexer.query("config", dir.path(), false, 10).is_empty());
+        assert_eq!(indexer.query("config", dir.path(), true, 10).len(), 1);
+
+        // Queries are limited to the scope directory
+        let scoped = indexer.query("rs", &dir.path().join("src/ui"), false, 10);
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].name, "layout.rs");
+    }
+}
@@ -2,6 +2,20 @@ pub mod app_state;
 pub mod task_manager;
 pub mod clipboard;
 pub mod search;
+pub mod dir_size;
+pub mod usage_scan;
+pub mod disk_usage;
+pub mod block_device;
+pub mod mount_probe;
+pub mod dir_load;
+pub mod remote;
+pub mod command_preview;
+pub mod index;
 pub mod settings;
+pub mod i18n;
+pub mod config_watch;
+pub mod paths;
 pub mod session;
+pub mod view_prefs;
+pub mod commands;
 pub mod plugin;
@@ -0,0 +1,139 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// How long opening a mount may take before it counts as not responding
+pub const PROBE_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// Why a mount cannot be opened
+#[derive(Debug)]
+pub enum ProbeError {
+    /// Listing did not return in time, e.g. a dead network mount or an
+    /// autofs map whose server is gone
+    NotResponding,
+    Unreadable(std::io::Error),
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProbeError::NotResponding => write!(f, "mount not responding"),
+            ProbeError::Unreadable(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Checks that `path` opens and lists within `timeout`. The check runs on
+/// a detached thread: a call stuck in the kernel cannot be interrupted, so
+/// it is left behind instead of being waited for.
+pub async fn probe_readable(path: PathBuf, timeout: Duration) -> Result<(), ProbeError> {
+    let (tx, rx) = oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(open_and_list(&path));
+    });
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(result)) => result.map_err(ProbeError::Unreadable),
+        Ok(Err(_)) | Err(_) => Err(ProbeError::NotResponding),
+    }
+}
+
+/// A plain open comes first: it is the call that hangs on a dead mount,
+/// and unlike `read_dir` it also blocks on a fifo with no writer
+fn open_and_list(path: &Path) -> std::io::Result<()> {
+    std::fs::File::open(path)?;
+    std::fs::read_dir(path)?.next().transpose()?;
+    Ok(())
+}
+
+/// Probes the mount the user opens from the left pane before the tab goes
+/// there. Only the latest request is answered, an earlier answer that
+/// arrives late is dropped.
+#[derive(Debug)]
+pub struct MountProber {
+    pending: Option<PathBuf>,
+    tx: mpsc::UnboundedSender<(PathBuf, Result<(), ProbeError>)>,
+    rx: mpsc::UnboundedReceiver<(PathBuf, Result<(), ProbeError>)>,
+}
+
+impl MountProber {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self { pending: None, tx, rx }
+    }
+
+    /// The mount being probed
+    pub fn pending(&self) -> Option<&Path> {
+        self.pending.as_deref()
+    }
+
+    /// Starts probing `path`. Needs a running tokio runtime.
+    pub fn request(&mut self, path: PathBuf) {
+        self.pending = Some(path.clone());
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let result = probe_readable(path.clone(), PROBE_TIMEOUT).await;
+            let _ = tx.send((path, result));
+        });
+    }
+
+    /// Waits for the answer about the latest requested mount
+    pub async fn wait_for_result(&mut self) -> Option<(PathBuf, Result<(), ProbeError>)> {
+        loop {
+            let (path, result) = self.rx.recv().await?;
+            if self.pending.as_ref() == Some(&path) {
+                self.pending = None;
+                return Some((path, result));
+            }
+        }
+    }
+}
+
+impl Default for MountProber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[tokio::test]
+    async fn test_probe_tells_readable_unreadable_and_hung_paths_apart() {
+        let dir = TempDir::new("mount_probe").unwrap();
+        std::fs::write(dir.path().join("file"), "").unwrap();
+        let fifo = dir.path().join("fifo");
+        nix::unistd::mkfifo(&fifo, nix::sys::stat::Mode::S_IRWXU).unwrap();
+        let timeout = Duration::from_millis(200);
+
+        assert!(probe_readable(dir.path().to_path_buf(), timeout).await.is_ok());
+        // Not a directory, so it cannot be listed
+        assert!(matches!(
+            probe_readable(dir.path().join("file"), timeout).await,
+            Err(ProbeError::Unreadable(_))
+        ));
+        assert!(matches!(
+            probe_readable(dir.path().join("missing"), timeout).await,
+            Err(ProbeError::Unreadable(_))
+        ));
+        // Permissions do not stop root, so this part only runs for users
+        if users::get_effective_uid() != 0 {
+            let locked = dir.path().join("locked");
+            std::fs::create_dir(&locked).unwrap();
+            std::fs::set_permissions(&locked, std::os::unix::fs::PermissionsExt::from_mode(0o000)).unwrap();
+            assert!(matches!(probe_readable(locked, timeout).await, Err(ProbeError::Unreadable(_))));
+        }
+        // Opening a fifo waits for a writer forever, like a dead mount
+        assert!(matches!(probe_readable(fifo, timeout).await, Err(ProbeError::NotResponding)));
+
+        let mut prober = MountProber::new();
+        prober.request(dir.path().join("missing"));
+        prober.request(dir.path().to_path_buf());
+        let (path, result) = prober.wait_for_result().await.unwrap();
+        assert_eq!(path, dir.path());
+        assert!(result.is_ok());
+        assert!(prober.pending().is_none());
+    }
+}
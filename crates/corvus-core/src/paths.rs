@@ -0,0 +1,98 @@
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+
+pub use config::{cache_dir, config_dir, data_dir, trash_dir};
+
+/// Directory scanned for plugins
+pub fn plugins_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("plugins"))
+}
+
+/// Directory with user color schemes
+pub fn themes_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("themes"))
+}
+
+/// Open tabs and bookmarks from the last run, next to `config.toml`.
+/// A named session is kept in `session-<name>.json` beside the default one.
+pub fn session_file(name: Option<&str>) -> PathBuf {
+    sessions_dir().join(session_file_name(name))
+}
+
+/// Directory holding the default and the named sessions
+pub fn sessions_dir() -> PathBuf {
+    config_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn session_file_name(name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("session-{}.json", name),
+        None => "session.json".to_string(),
+    }
+}
+
+/// Tasks that were still queued or running when corvus quit
+pub fn tasks_file() -> PathBuf {
+    data_dir().unwrap_or_else(|| PathBuf::from(".")).join("tasks.json")
+}
+
+/// Remembered per-directory view settings
+pub fn view_prefs_file() -> PathBuf {
+    data_dir().unwrap_or_else(|| PathBuf::from(".")).join("view_prefs.json")
+}
+
+/// Mount points of remote locations, one directory per connection
+pub fn remote_mounts_dir() -> PathBuf {
+    cache_dir().unwrap_or_else(|| PathBuf::from(".")).join("mounts")
+}
+
+/// Copies files from the directories older versions used into the current
+/// ones. A file is only copied when it does not exist in the new place yet,
+/// so this runs on every start and does nothing once migrated. The old
+/// files are left in place.
+pub fn migrate_legacy_files() {
+    if std::env::var_os(config::CONFIG_DIR_ENV).is_some() {
+        return;
+    }
+    let legacy_dir = |qualifier, organization, application| {
+        ProjectDirs::from(qualifier, organization, application).map(|dirs| dirs.config_dir().to_path_buf())
+    };
+    let mut moves = Vec::new();
+    if let (Some(old), Some(new)) = (legacy_dir("com", "rtfm", "rust-tui-fm"), config_dir()) {
+        moves.push((old.join("config.toml"), new.join("config.toml")));
+        moves.push((old.join("themes"), new.join("themes")));
+    }
+    if let Some(old) = legacy_dir("org", "rust-tui-fm", "rtfm") {
+        moves.push((old.join("session.json"), session_file(None)));
+        moves.push((old.join("tasks.json"), tasks_file()));
+        moves.push((old.join("view_prefs.json"), view_prefs_file()));
+    }
+    if let (Some(old), Some(new)) = (legacy_dir("com", "Corvus", "Corvus"), plugins_dir()) {
+        moves.push((old.join("plugins"), new));
+    }
+    for (from, to) in moves {
+        if from == to || !from.exists() || to.exists() {
+            continue;
+        }
+        match copy_recursive(&from, &to) {
+            Ok(()) => log::info!("Migrated {} to {}", from.display(), to.display()),
+            Err(e) => log::error!("Failed to migrate {} to {}: {}", from.display(), to.display(), e),
+        }
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
@@ -1,8 +1,29 @@
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::fs;
-use directories::ProjectDirs;
 use log;
+use plugin_ipc::{InitParams, InitResult, Notification, OnSelectParams, Request, Response, API_VERSION, EVENTS_CAPABILITY, MAX_MESSAGE_SIZE, MIN_API_VERSION};
+pub use plugin_ipc::PreviewResult;
+use tokio::sync::mpsc;
+pub use config::PluginPermissions;
+
+/// How long a plugin has to exit after the shutdown request before it is killed
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Lines waiting for a plugin's stdin; further events are dropped
+const QUEUE_LIMIT: usize = 64;
+
+/// Lines of stderr kept per plugin
+pub const LOG_LINES: usize = 500;
+
+/// Longest stderr line kept; longer ones are replaced with a note
+const LOG_LINE_LIMIT: usize = 4096;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct PluginManifest {
@@ -10,6 +31,61 @@ pub struct PluginManifest {
     pub author: String,
     pub version: String,
     pub description: String,
+    /// Program started for the plugin, relative to its directory. Plugins
+    /// without one have no process.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Protocol version the plugin was written for, see
+    /// `plugin_ipc::is_compatible`. Required for installing.
+    #[serde(default)]
+    pub api_version: Option<String>,
+    /// Access the plugin asks for; it is started only after the user agrees
+    #[serde(default)]
+    pub permissions: PluginPermissions,
+}
+
+impl PluginManifest {
+    /// Reads and checks `plugin.toml` in `dir`: the name and version must
+    /// be set, the API version supported and the command present
+    pub fn validate(dir: &Path) -> Result<PluginManifest, String> {
+        let manifest_path = dir.join("plugin.toml");
+        let content = fs::read_to_string(&manifest_path).map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+        let manifest: PluginManifest = toml::from_str(&content).map_err(|e| format!("invalid plugin.toml: {}", e.message()))?;
+        if manifest.name.trim().is_empty() {
+            return Err("plugin.toml has an empty name".to_string());
+        }
+        if manifest.version.trim().is_empty() {
+            return Err("plugin.toml has an empty version".to_string());
+        }
+        match &manifest.api_version {
+            None => return Err("plugin.toml does not name an api_version".to_string()),
+            Some(version) if !plugin_ipc::is_compatible(version) => {
+                return Err(format!(
+                    "{} needs API {}, supported are {} to {}",
+                    manifest.name, version, MIN_API_VERSION, API_VERSION
+                ));
+            }
+            Some(_) => {}
+        }
+        if let Some(command) = &manifest.command {
+            if !dir.join(command).is_file() {
+                return Err(format!("command {} is missing", command));
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// Whether the plugin's process is alive
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum PluginState {
+    #[default]
+    Stopped,
+    Running,
+    /// The process could not be started or exited on its own
+    Failed(String),
+    /// Enabled, but not started until the user agrees to its permissions
+    NeedsConsent,
 }
 
 #[derive(Debug, Clone)]
@@ -17,21 +93,83 @@ pub struct Plugin {
     pub manifest: PluginManifest,
     pub path: PathBuf,
     pub enabled: bool,
+    pub state: PluginState,
+    pub stats: PluginStats,
 }
 
-pub fn discover_plugins() -> Vec<Plugin> {
-    let mut plugins = Vec::new();
+impl Plugin {
+    /// An enabled, not yet started plugin installed at `path`
+    pub fn new(manifest: PluginManifest, path: PathBuf) -> Self {
+        Self {
+            manifest,
+            path,
+            enabled: true,
+            state: PluginState::Stopped,
+            stats: PluginStats::default(),
+        }
+    }
+}
 
+/// Where plugins are installed: `./test-plugins` when run from the
+/// source tree, the plugins directory of the config otherwise
+pub fn plugins_root() -> Option<PathBuf> {
     let local_plugins_dir = PathBuf::from("./test-plugins");
-
-    let plugins_dir = if local_plugins_dir.exists() {
-        local_plugins_dir
+    if local_plugins_dir.exists() {
+        Some(local_plugins_dir)
     } else {
-        if let Some(proj_dirs) = ProjectDirs::from("com", "Corvus", "Corvus") {
-            proj_dirs.config_dir().join("plugins")
+        crate::paths::plugins_dir()
+    }
+}
+
+/// Whether an install source is a git repository rather than a local path
+pub fn is_git_url(source: &str) -> bool {
+    ["https://", "http://", "ssh://", "git://", "file://", "git@"].iter().any(|prefix| source.starts_with(prefix))
+}
+
+/// Directory a plugin from `source` is installed to: the last path
+/// component without a `.git` suffix
+pub fn install_dir(plugins_dir: &Path, source: &str) -> Option<PathBuf> {
+    let name = source.trim_end_matches('/').rsplit(['/', ':']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty() && name != "." && name != "..").then(|| plugins_dir.join(name))
+}
+
+/// Copies the plugin in `src` into `plugins_dir` after checking its
+/// manifest. An already installed plugin of the same name is refused.
+pub fn install_from_dir(src: &Path, plugins_dir: &Path) -> Result<(PluginManifest, PathBuf), String> {
+    let manifest = PluginManifest::validate(src)?;
+    let dest = install_dir(plugins_dir, &src.to_string_lossy()).ok_or("not a plugin directory")?;
+    if dest.exists() {
+        return Err(format!("{} already exists", dest.display()));
+    }
+    fs::create_dir_all(plugins_dir).map_err(|e| e.to_string())?;
+    if let Err(e) = copy_dir(src, &dest) {
+        let _ = fs::remove_dir_all(&dest);
+        return Err(e.to_string());
+    }
+    Ok((manifest, dest))
+}
+
+fn copy_dir(src: &Path, dest: &Path) -> std::io::Result<()> {
+    fs::create_dir(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
         } else {
-            return plugins;
+            fs::copy(entry.path(), &target)?;
         }
+    }
+    Ok(())
+}
+
+/// Finds the installed plugins. Those named in `disabled` are turned off.
+pub fn discover_plugins(disabled: &[String]) -> Vec<Plugin> {
+    let mut plugins = Vec::new();
+
+    let Some(plugins_dir) = plugins_root() else {
+        return plugins;
     };
 
     if !plugins_dir.exists() {
@@ -52,11 +190,8 @@ pub fn discover_plugins() -> Vec<Plugin> {
                             Ok(content) => {
                                 match toml::from_str::<PluginManifest>(&content) {
                                     Ok(manifest) => {
-                                        plugins.push(Plugin {
-                                            manifest,
-                                            path: path.clone(),
-                                            enabled: true, // Default to enabled
-                                        });
+                                        let enabled = !disabled.contains(&manifest.name);
+                                        plugins.push(Plugin { enabled, ..Plugin::new(manifest, path.clone()) });
                                     }
                                     Err(e) => log::error!("Failed to parse plugin manifest at {:?}: {}", manifest_path, e),
                                 }
@@ -73,3 +208,580 @@ pub fn discover_plugins() -> Vec<Plugin> {
 
     plugins
 }
+
+/// A preview a plugin returned for `path`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginPreview {
+    pub plugin: String,
+    pub path: PathBuf,
+    pub result: PreviewResult,
+}
+
+/// Sent and dropped notifications of one plugin
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PluginStats {
+    pub events_sent: u64,
+    /// Events lost because the plugin was not reading its stdin fast enough
+    pub events_dropped: u64,
+}
+
+/// A running plugin. Lines for its stdin go through a bounded queue and are
+/// written by a separate thread, so a plugin that stops reading never
+/// blocks the interface.
+#[derive(Debug)]
+struct PluginProcess {
+    child: Child,
+    queue: SyncSender<String>,
+    /// The plugin listed `events` among its capabilities in reply to `init`
+    events: bool,
+}
+
+impl PluginProcess {
+    fn push(&self, line: String) -> Result<(), String> {
+        self.queue.try_send(line).map_err(|e| match e {
+            TrySendError::Full(_) => "the plugin is not reading its input".to_string(),
+            TrySendError::Disconnected(_) => "the plugin has closed its input".to_string(),
+        })
+    }
+}
+
+/// A line a plugin wrote to stderr
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub time: chrono::DateTime<chrono::Local>,
+    pub text: String,
+}
+
+/// The last `LOG_LINES` stderr lines of a plugin, kept across restarts
+#[derive(Debug, Default)]
+struct PluginLog {
+    lines: VecDeque<LogLine>,
+    /// Lines written since `take_unseen_log_lines` last looked
+    unseen: usize,
+}
+
+/// Owns the processes of running plugins, keyed by plugin directory.
+/// Requests are written to a plugin's stdin and responses read from its
+/// stdout, one JSON object per line.
+#[derive(Debug)]
+pub struct PluginHost {
+    processes: HashMap<PathBuf, PluginProcess>,
+    next_id: u64,
+    responses_tx: mpsc::UnboundedSender<Response>,
+    responses_rx: mpsc::UnboundedReceiver<Response>,
+    /// `init` requests waiting for the capabilities of the plugin
+    pending_init: HashMap<u64, PathBuf>,
+    /// `on_select` requests still waiting for an answer: plugin name and file
+    pending: HashMap<u64, (String, PathBuf)>,
+    /// File the plugins were last asked about
+    selected: Option<PathBuf>,
+    /// The latest preview for `selected`
+    latest: Option<PluginPreview>,
+    /// Stderr of every plugin started so far, filled by reader threads
+    logs: HashMap<PathBuf, Arc<Mutex<PluginLog>>>,
+}
+
+impl PluginHost {
+    pub fn new() -> Self {
+        let (responses_tx, responses_rx) = mpsc::unbounded_channel();
+        Self {
+            processes: HashMap::new(),
+            next_id: 0,
+            responses_tx,
+            responses_rx,
+            pending_init: HashMap::new(),
+            pending: HashMap::new(),
+            selected: None,
+            latest: None,
+            logs: HashMap::new(),
+        }
+    }
+
+    /// Starts the plugin's process and sends it `init`. A plugin without a
+    /// command or one that is already running is left alone.
+    pub fn start(&mut self, plugin: &mut Plugin) {
+        let Some(command) = &plugin.manifest.command else {
+            return;
+        };
+        if self.processes.contains_key(&plugin.path) {
+            return;
+        }
+        let spawned = Command::new(plugin.path.join(command))
+            .current_dir(&plugin.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                log::error!("Failed to start plugin {}: {}", plugin.manifest.name, e);
+                plugin.state = PluginState::Failed(e.to_string());
+                return;
+            }
+        };
+        if let Some(stdout) = child.stdout.take() {
+            let name = plugin.manifest.name.clone();
+            let responses = self.responses_tx.clone();
+            std::thread::spawn(move || read_responses(name, stdout, responses));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let name = plugin.manifest.name.clone();
+            let log = self.logs.entry(plugin.path.clone()).or_default().clone();
+            std::thread::spawn(move || read_log(name, stderr, log));
+        }
+        let (queue, lines) = std::sync::mpsc::sync_channel(QUEUE_LIMIT);
+        if let Some(stdin) = child.stdin.take() {
+            std::thread::spawn(move || write_lines(stdin, lines));
+        }
+        let process = PluginProcess { child, queue, events: false };
+        let params = serde_json::to_value(InitParams { api_version: API_VERSION.to_string() }).unwrap_or_default();
+        match self.send(&process, "init", params) {
+            Ok(id) => {
+                self.pending_init.insert(id, plugin.path.clone());
+            }
+            Err(e) => log::warn!("Failed to send init to plugin {}: {}", plugin.manifest.name, e),
+        }
+        self.processes.insert(plugin.path.clone(), process);
+        plugin.state = PluginState::Running;
+        plugin.stats = PluginStats::default();
+    }
+
+    /// Asks the plugin to shut down and kills it if it does not exit in
+    /// time. The waiting happens on a separate thread.
+    pub fn stop(&mut self, plugin: &mut Plugin) {
+        if let Some(process) = self.processes.remove(&plugin.path) {
+            let _ = self.send(&process, "shutdown", serde_json::Value::Null);
+            std::thread::spawn(move || shut_down(process));
+        }
+        plugin.state = PluginState::Stopped;
+    }
+
+    /// Notices plugins whose process has exited since the last call
+    pub fn poll(&mut self, plugins: &mut [Plugin]) {
+        for plugin in plugins.iter_mut() {
+            let Some(process) = self.processes.get_mut(&plugin.path) else {
+                continue;
+            };
+            let state = match process.child.try_wait() {
+                Ok(None) => continue,
+                Ok(Some(status)) => PluginState::Failed(format!("exited with {}", status)),
+                Err(e) => PluginState::Failed(e.to_string()),
+            };
+            log::warn!("Plugin {} stopped: {:?}", plugin.manifest.name, state);
+            self.processes.remove(&plugin.path);
+            plugin.state = state;
+        }
+    }
+
+    /// Stderr lines of the plugin at `plugin`, oldest first
+    pub fn log_lines(&self, plugin: &Path) -> Vec<LogLine> {
+        self.logs.get(plugin).map_or_else(Vec::new, |log| log.lock().unwrap().lines.iter().cloned().collect())
+    }
+
+    /// Plugins that wrote to stderr since the last call, with the number
+    /// of lines each
+    pub fn take_unseen_log_lines(&self) -> Vec<(PathBuf, usize)> {
+        self.logs
+            .iter()
+            .filter_map(|(path, log)| {
+                let unseen = std::mem::take(&mut log.lock().unwrap().unseen);
+                (unseen > 0).then(|| (path.clone(), unseen))
+            })
+            .collect()
+    }
+
+    /// Whether any running plugin wants event notifications
+    pub fn has_listeners(&self) -> bool {
+        self.processes.values().any(|process| process.events)
+    }
+
+    /// Sends a notification to every plugin that asked for events. A plugin
+    /// whose queue is full misses it, which is counted in its stats.
+    pub fn broadcast(&mut self, plugins: &mut [Plugin], method: &str, params: serde_json::Value) {
+        let notification = Notification { method: method.to_string(), params };
+        let Ok(line) = serde_json::to_string(&notification) else {
+            return;
+        };
+        for plugin in plugins.iter_mut() {
+            let Some(process) = self.processes.get(&plugin.path).filter(|process| process.events) else {
+                continue;
+            };
+            match process.push(line.clone()) {
+                Ok(()) => plugin.stats.events_sent += 1,
+                Err(_) => plugin.stats.events_dropped += 1,
+            }
+        }
+    }
+
+    /// Sends `on_select` for `path` to every running plugin, once per
+    /// file. `None` (a directory or nothing under the cursor) forgets the
+    /// previous file so its late answers are dropped.
+    pub fn request_preview(&mut self, plugins: &[Plugin], path: Option<&Path>) {
+        if self.selected.as_deref() == path {
+            return;
+        }
+        self.selected = path.map(Path::to_path_buf);
+        self.pending.clear();
+        self.latest = None;
+        let Some(path) = path else {
+            return;
+        };
+        let params = serde_json::to_value(OnSelectParams { path: path.to_path_buf(), mime_type: None }).unwrap_or_default();
+        for plugin in plugins {
+            let Some(process) = self.processes.get(&plugin.path) else {
+                continue;
+            };
+            self.next_id += 1;
+            let request = Request { id: self.next_id, method: "on_select".to_string(), params: params.clone() };
+            match serde_json::to_string(&request).map_err(|e| e.to_string()).and_then(|line| process.push(line)) {
+                Ok(()) => {
+                    self.pending.insert(request.id, (plugin.manifest.name.clone(), path.to_path_buf()));
+                }
+                Err(e) => log::warn!("Failed to send on_select to plugin {}: {}", plugin.manifest.name, e),
+            }
+        }
+    }
+
+    /// The preview already received for `path`, if any
+    pub fn latest_preview(&self, path: &Path) -> Option<&PluginPreview> {
+        self.latest.as_ref().filter(|preview| preview.path == path)
+    }
+
+    /// Waits for a plugin to answer `on_select` for the current file with
+    /// something to show. Errors and `null` results are skipped. Replies to
+    /// `init` are handled on the way.
+    pub async fn wait_for_preview(&mut self) -> Option<PluginPreview> {
+        loop {
+            let response = self.responses_rx.recv().await?;
+            if let Some(plugin_path) = self.pending_init.remove(&response.id) {
+                self.handle_init(&plugin_path, response);
+                continue;
+            }
+            let Some((plugin, path)) = self.pending.remove(&response.id) else {
+                continue;
+            };
+            if let Some(error) = response.error {
+                log::warn!("Plugin {} could not preview {}: {}", plugin, path.display(), error.message);
+                continue;
+            }
+            let Some(result) = response.result.filter(|result| !result.is_null()) else {
+                continue;
+            };
+            match serde_json::from_value::<PreviewResult>(result) {
+                Ok(result) => {
+                    let preview = PluginPreview { plugin, path, result };
+                    self.latest = Some(preview.clone());
+                    return Some(preview);
+                }
+                Err(e) => log::warn!("Plugin {} sent an invalid preview: {}", plugin, e),
+            }
+        }
+    }
+
+    /// Remembers whether the plugin wants event notifications
+    fn handle_init(&mut self, plugin_path: &Path, response: Response) {
+        let Some(process) = self.processes.get_mut(plugin_path) else {
+            return;
+        };
+        match response.result.map(serde_json::from_value::<InitResult>) {
+            Some(Ok(init)) => process.events = init.capabilities.iter().any(|capability| capability == EVENTS_CAPABILITY),
+            Some(Err(e)) => log::warn!("Plugin at {} sent an invalid init reply: {}", plugin_path.display(), e),
+            None => {}
+        }
+    }
+
+    /// Queues a request and returns its id
+    fn send(&mut self, process: &PluginProcess, method: &str, params: serde_json::Value) -> Result<u64, String> {
+        self.next_id += 1;
+        let request = Request { id: self.next_id, method: method.to_string(), params };
+        process.push(serde_json::to_string(&request).map_err(|e| e.to_string())?)?;
+        Ok(self.next_id)
+    }
+}
+
+impl Default for PluginHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Forwards the plugin's responses until its stdout closes. Lines that are
+/// not responses or are longer than `MAX_MESSAGE_SIZE` are dropped.
+fn read_responses(plugin: String, stdout: impl std::io::Read, responses: mpsc::UnboundedSender<Response>) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        match read_line_limited(&mut reader, MAX_MESSAGE_SIZE) {
+            Ok(Some(Ok(line))) => match serde_json::from_slice::<Response>(&line) {
+                Ok(response) => {
+                    if responses.send(response).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => log::warn!("Plugin {} wrote something that is not a response: {}", plugin, e),
+            },
+            Ok(Some(Err(length))) => log::warn!("Dropped a {} byte message from plugin {}", length, plugin),
+            Ok(None) | Err(_) => return,
+        }
+    }
+}
+
+/// Keeps the plugin's stderr lines in `log` and copies them to the main log
+fn read_log(plugin: String, stderr: impl std::io::Read, log: Arc<Mutex<PluginLog>>) {
+    let mut reader = BufReader::new(stderr);
+    loop {
+        let text = match read_line_limited(&mut reader, LOG_LINE_LIMIT) {
+            Ok(Some(Ok(line))) => String::from_utf8_lossy(&line).into_owned(),
+            Ok(Some(Err(length))) => format!("[a {} byte line was dropped]", length),
+            Ok(None) | Err(_) => return,
+        };
+        log::warn!("[plugin {}] {}", plugin, text);
+        let mut log = log.lock().unwrap();
+        if log.lines.len() == LOG_LINES {
+            log.lines.pop_front();
+        }
+        log.lines.push_back(LogLine { time: chrono::Local::now(), text });
+        log.unseen += 1;
+    }
+}
+
+/// Reads one line without its newline, keeping at most `limit` bytes.
+/// A longer line is still consumed but comes back as `Err(length)`.
+/// `None` means the stream has ended.
+fn read_line_limited(reader: &mut impl BufRead, limit: usize) -> std::io::Result<Option<Result<Vec<u8>, usize>>> {
+    let mut line = Vec::new();
+    let mut length = 0;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok((length > 0).then_some(if length > limit { Err(length) } else { Ok(line) }));
+        }
+        let end = buf.iter().position(|&byte| byte == b'\n');
+        let chunk = &buf[..end.unwrap_or(buf.len())];
+        length += chunk.len();
+        if length <= limit {
+            line.extend_from_slice(chunk);
+        }
+        let consumed = chunk.len() + usize::from(end.is_some());
+        reader.consume(consumed);
+        if end.is_some() {
+            return Ok(Some(if length > limit { Err(length) } else { Ok(line) }));
+        }
+    }
+}
+
+impl Drop for PluginHost {
+    fn drop(&mut self) {
+        let processes: Vec<PluginProcess> = self.processes.drain().map(|(_, process)| process).collect();
+        for process in processes {
+            let _ = self.send(&process, "shutdown", serde_json::Value::Null);
+            shut_down(process);
+        }
+    }
+}
+
+/// Writes queued lines to the plugin's stdin. Once the host drops the
+/// queue, stdin is closed, which tells plugins that only read requests to
+/// stop as well.
+fn write_lines(mut stdin: ChildStdin, lines: Receiver<String>) {
+    for line in lines {
+        if writeln!(stdin, "{}", line).and_then(|_| stdin.flush()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Waits for a plugin that was asked to shut down, killing it after
+/// `SHUTDOWN_TIMEOUT`
+fn shut_down(process: PluginProcess) {
+    let PluginProcess { mut child, queue, .. } = process;
+    drop(queue);
+    let started = Instant::now();
+    while started.elapsed() < SHUTDOWN_TIMEOUT {
+        match child.try_wait() {
+            Ok(Some(_)) | Err(_) => return,
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn script_plugin(dir: &TempDir, script: &str) -> Plugin {
+        use std::os::unix::fs::PermissionsExt;
+        let program = dir.path().join("run.sh");
+        fs::write(&program, script).unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+        Plugin {
+            manifest: PluginManifest {
+                name: "test".to_string(),
+                author: String::new(),
+                version: "0.1.0".to_string(),
+                description: String::new(),
+                command: Some("run.sh".to_string()),
+                api_version: Some(API_VERSION.to_string()),
+                permissions: PluginPermissions::default(),
+            },
+            path: dir.path().to_path_buf(),
+            enabled: true,
+            state: PluginState::Stopped,
+            stats: PluginStats::default(),
+        }
+    }
+
+    #[test]
+    fn test_plugin_is_started_stopped_and_failures_are_noticed() {
+        let dir = TempDir::new("plugin").unwrap();
+        // Writes the requests it gets and exits on shutdown
+        let mut plugin = script_plugin(
+            &dir,
+            "#!/bin/sh\nwhile read line; do echo \"$line\" >> requests; case \"$line\" in *shutdown*) exit 0;; esac; done\n",
+        );
+        let mut host = PluginHost::default();
+        host.start(&mut plugin);
+        assert_eq!(plugin.state, PluginState::Running);
+        host.stop(&mut plugin);
+        assert_eq!(plugin.state, PluginState::Stopped);
+        let started = Instant::now();
+        while fs::read_to_string(dir.path().join("requests")).map_or(true, |r| r.lines().count() < 2) {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let requests = fs::read_to_string(dir.path().join("requests")).unwrap();
+        assert!(requests.contains("\"init\"") && requests.contains("\"shutdown\""), "{}", requests);
+
+        let dir = TempDir::new("plugin_failing").unwrap();
+        let mut plugin = script_plugin(&dir, "#!/bin/sh\nexit 4\n");
+        host.start(&mut plugin);
+        let started = Instant::now();
+        while plugin.state == PluginState::Running {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(20));
+            host.poll(std::slice::from_mut(&mut plugin));
+        }
+        assert_eq!(plugin.state, PluginState::Failed("exited with exit status: 4".to_string()));
+    }
+
+    #[test]
+    fn test_manifest_validation_and_install_names() {
+        let dir = TempDir::new("plugin_manifest").unwrap();
+        let manifest = |extra: &str| {
+            fs::write(dir.path().join("plugin.toml"), format!("name = \"p\"\nauthor = \"\"\nversion = \"1\"\ndescription = \"\"\n{}", extra)).unwrap();
+            PluginManifest::validate(dir.path())
+        };
+        assert_eq!(manifest("").unwrap_err(), "plugin.toml does not name an api_version");
+        assert!(manifest("api_version = \"0.1\"").is_ok());
+        assert_eq!(manifest("api_version = \"0.1\"\ncommand = \"run.sh\"").unwrap_err(), "command run.sh is missing");
+        fs::write(dir.path().join("plugin.toml"), "name = \"p\"\n").unwrap();
+        assert!(PluginManifest::validate(dir.path()).unwrap_err().starts_with("invalid plugin.toml"));
+
+        let root = Path::new("/plugins");
+        assert!(is_git_url("https://example.com/me/preview.git") && is_git_url("git@example.com:me/preview"));
+        assert!(!is_git_url("/home/me/preview"));
+        assert_eq!(install_dir(root, "https://example.com/me/preview.git/"), Some(root.join("preview")));
+        assert_eq!(install_dir(root, "git@example.com:preview"), Some(root.join("preview")));
+        assert_eq!(install_dir(root, "/"), None);
+    }
+
+    #[test]
+    fn test_stderr_is_kept_in_a_ring_buffer() {
+        let dir = TempDir::new("plugin_log").unwrap();
+        let mut plugin = script_plugin(&dir, "#!/bin/sh\nseq 1 600 >&2\n");
+        let mut host = PluginHost::new();
+        host.start(&mut plugin);
+        let started = Instant::now();
+        while host.log_lines(&plugin.path).last().is_none_or(|line| line.text != "600") {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let lines = host.log_lines(&plugin.path);
+        assert_eq!(lines.len(), LOG_LINES);
+        assert_eq!(lines[0].text, "101");
+        assert_eq!(host.take_unseen_log_lines(), vec![(plugin.path.clone(), 600)]);
+        assert!(host.take_unseen_log_lines().is_empty());
+    }
+
+    #[test]
+    fn test_long_lines_are_rejected() {
+        let mut input = std::io::Cursor::new(b"short\nthis one is too long\nlast".to_vec());
+        assert_eq!(read_line_limited(&mut input, 8).unwrap(), Some(Ok(b"short".to_vec())));
+        assert_eq!(read_line_limited(&mut input, 8).unwrap(), Some(Err(20)));
+        assert_eq!(read_line_limited(&mut input, 8).unwrap(), Some(Ok(b"last".to_vec())));
+        assert_eq!(read_line_limited(&mut input, 8).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_preview_comes_from_on_select_response() {
+        let dir = TempDir::new("plugin_preview").unwrap();
+        // Answers on_select with a table holding the file name
+        let mut plugin = script_plugin(
+            &dir,
+            r#"#!/bin/sh
+while read line; do
+    case "$line" in *on_select*)
+        id=${line#*\"id\":}; id=${id%%,*}
+        echo "{\"id\":$id,\"result\":{\"Table\":[[\"name\",\"x.flac\"]]}}";;
+    esac
+done
+"#,
+        );
+        let mut host = PluginHost::new();
+        host.start(&mut plugin);
+        let file = dir.path().join("x.flac");
+        host.request_preview(std::slice::from_ref(&plugin), Some(&file));
+        let preview = tokio::time::timeout(Duration::from_secs(5), host.wait_for_preview()).await.unwrap().unwrap();
+        assert_eq!(preview.path, file);
+        assert_eq!(preview.result, PreviewResult::Table(vec![("name".to_string(), "x.flac".to_string())]));
+        assert_eq!(host.latest_preview(&file), Some(&preview));
+        host.stop(&mut plugin);
+    }
+
+    #[tokio::test]
+    async fn test_events_reach_listeners_and_are_dropped_when_stdin_blocks() {
+        const INIT_REPLY: &str = r#"id=${line#*\"id\":}; id=${id%%,*}
+echo "{\"id\":$id,\"result\":{\"plugin_name\":\"t\",\"plugin_version\":\"0\",\"capabilities\":[\"events\"]}}""#;
+        let dir = TempDir::new("plugin_events").unwrap();
+        // Writes every notification it gets after init
+        let mut listener = script_plugin(
+            &dir,
+            &format!("#!/bin/sh\nread line\n{}\nwhile read line; do echo \"$line\" >> events; done\n", INIT_REPLY),
+        );
+        let stuck_dir = TempDir::new("plugin_stuck").unwrap();
+        // Answers init, then never reads again
+        let mut stuck = script_plugin(&stuck_dir, &format!("#!/bin/sh\nread line\n{}\nsleep 30\n", INIT_REPLY));
+        let mut host = PluginHost::new();
+        host.start(&mut listener);
+        host.start(&mut stuck);
+        let started = Instant::now();
+        while host.processes.values().any(|process| !process.events) {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            let _ = tokio::time::timeout(Duration::from_millis(20), host.wait_for_preview()).await;
+        }
+        assert!(host.has_listeners());
+
+        let mut plugins = [listener, stuck];
+        let params = serde_json::json!({ "path": "x".repeat(4096) });
+        for _ in 0..200 {
+            host.broadcast(&mut plugins, "on_dir_changed", params.clone());
+        }
+        let [listener, stuck] = &mut plugins;
+        assert_eq!(listener.stats.events_sent + listener.stats.events_dropped, 200);
+        assert!(stuck.stats.events_dropped > 0);
+        assert_eq!(stuck.stats.events_sent + stuck.stats.events_dropped, 200);
+
+        let started = Instant::now();
+        while fs::read_to_string(dir.path().join("events")).map_or(0, |events| events.lines().count()) < listener.stats.events_sent as usize {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let events = fs::read_to_string(dir.path().join("events")).unwrap();
+        assert!(events.lines().all(|line| line.starts_with(r#"{"method":"on_dir_changed""#)), "{}", events);
+        host.stop(listener);
+        host.stop(stuck);
+    }
+}
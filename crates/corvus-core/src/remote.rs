@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+/// How a remote location is mounted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteKind {
+    /// `user@host:/path`, mounted with sshfs
+    Sftp,
+    /// `http://` or `https://` URL, mounted with mount.davfs
+    WebDav,
+}
+
+impl RemoteKind {
+    /// Kind of an address, `None` when it is neither SFTP nor WebDAV
+    pub fn of(url: &str) -> Option<Self> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return url.split_once("://").filter(|(_, rest)| !rest.is_empty()).map(|_| RemoteKind::WebDav);
+        }
+        // scp-style `[user@]host:[path]`, the host before the first colon
+        let (host, _) = url.split_once(':')?;
+        let host = host.rsplit('@').next().unwrap_or(host);
+        let valid = !host.is_empty() && !host.contains('/') && !url.contains("://") && !url.chars().any(char::is_whitespace);
+        valid.then_some(RemoteKind::Sftp)
+    }
+}
+
+/// A remote location in the left pane: saved in `[remotes]` or connected
+/// from the quick-connect dialog for this run only
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remote {
+    pub name: String,
+    pub url: String,
+    /// Listed in `[remotes]`, so it stays in the list while disconnected
+    pub saved: bool,
+    pub connected: bool,
+}
+
+impl Remote {
+    pub fn new(name: &str, url: &str, saved: bool) -> Self {
+        Self { name: name.to_string(), url: url.to_string(), saved, connected: false }
+    }
+
+    /// Managed directory the location is mounted on
+    pub fn mountpoint(&self) -> PathBuf {
+        crate::paths::remote_mounts_dir().join(dir_name(&self.name))
+    }
+}
+
+/// Host part of an address, used to name a quick connection
+pub fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', ':']).next().unwrap_or(rest);
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    if host.is_empty() {
+        "remote"
+    } else {
+        host
+    }
+}
+
+/// `name` reduced to characters that are safe in a single path component
+fn dir_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    match cleaned.trim_start_matches('.') {
+        "" => "remote".to_string(),
+        cleaned => cleaned.to_string(),
+    }
+}
+
+/// Program and arguments that mount `url` on `mountpoint`. sshfs runs with
+/// `BatchMode` so a missing key fails with a message instead of waiting for
+/// a password on the terminal corvus draws on.
+pub fn mount_command(url: &str, mountpoint: &Path) -> Option<(&'static str, Vec<String>)> {
+    let mountpoint = mountpoint.to_string_lossy().into_owned();
+    Some(match RemoteKind::of(url)? {
+        RemoteKind::Sftp => ("sshfs", vec![url.to_string(), mountpoint, "-o".to_string(), "BatchMode=yes".to_string()]),
+        RemoteKind::WebDav => ("mount.davfs", vec![url.to_string(), mountpoint]),
+    })
+}
+
+/// Program and arguments that unmount a location mounted by `mount_command`
+pub fn unmount_command(url: &str, mountpoint: &Path) -> Option<(&'static str, Vec<String>)> {
+    let mountpoint = mountpoint.to_string_lossy().into_owned();
+    Some(match RemoteKind::of(url)? {
+        RemoteKind::Sftp => ("fusermount", vec!["-u".to_string(), mountpoint]),
+        RemoteKind::WebDav => ("umount", vec![mountpoint]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_addresses_are_told_apart() {
+        assert_eq!(RemoteKind::of("me@nas.local:/srv/media"), Some(RemoteKind::Sftp));
+        assert_eq!(RemoteKind::of("nas:"), Some(RemoteKind::Sftp));
+        assert_eq!(RemoteKind::of("https://dav.example.com/files/me"), Some(RemoteKind::WebDav));
+        assert_eq!(RemoteKind::of("ftp://example.com/pub"), None);
+        assert_eq!(RemoteKind::of("/home/me"), None);
+        assert_eq!(RemoteKind::of("https://"), None);
+
+        assert_eq!(host_of("me@nas.local:/srv/media"), "nas.local");
+        assert_eq!(host_of("https://me@dav.example.com:8443/files"), "dav.example.com");
+        assert_eq!(dir_name("../etc"), "_etc");
+        assert_eq!(dir_name("my box"), "my_box");
+    }
+}
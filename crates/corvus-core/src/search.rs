@@ -1,6 +1,14 @@
+use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use crate::app_state::DirEntry;
+use chrono::{Local, NaiveDate};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
 use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
 
 #[derive(Debug, Clone, Default)]
 pub enum SearchMode {
@@ -8,18 +16,317 @@ pub enum SearchMode {
     Name,
     Content,
     Metadata,
+    /// Fuzzy lookup in the background file index
+    Index,
+}
+
+impl SearchMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchMode::Name => "Name",
+            SearchMode::Content => "Content",
+            SearchMode::Metadata => "Metadata",
+            SearchMode::Index => "Index",
+        }
+    }
+
+    pub fn next(&self) -> SearchMode {
+        match self {
+            SearchMode::Name => SearchMode::Content,
+            SearchMode::Content => SearchMode::Metadata,
+            SearchMode::Metadata => SearchMode::Index,
+            SearchMode::Index => SearchMode::Name,
+        }
+    }
+}
+
+/// How the search query is interpreted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchSyntax {
+    #[default]
+    Substring,
+    Glob,
+    Regex,
+}
+
+impl SearchSyntax {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchSyntax::Substring => "Substring",
+            SearchSyntax::Glob => "Glob",
+            SearchSyntax::Regex => "Regex",
+        }
+    }
+
+    pub fn next(&self) -> SearchSyntax {
+        match self {
+            SearchSyntax::Substring => SearchSyntax::Glob,
+            SearchSyntax::Glob => SearchSyntax::Regex,
+            SearchSyntax::Regex => SearchSyntax::Substring,
+        }
+    }
+}
+
+/// Compiled regexes larger than this are rejected, so a pattern such as
+/// `(a{1000}){1000}` cannot eat memory or stall every keystroke
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// A query compiled for one of the `SearchSyntax` variants
+#[derive(Debug, Clone)]
+pub enum SearchMatcher {
+    /// The needle is lowercase unless the search is case-sensitive
+    Substring { needle: String, case_sensitive: bool },
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl SearchMatcher {
+    /// Compiles `query` according to `syntax`. Unless `case_sensitive` is
+    /// set, matching ignores case; a regex can still override that with
+    /// `(?-i)` or `(?i)`.
+    pub fn compile(query: &str, syntax: SearchSyntax, case_sensitive: bool) -> Result<SearchMatcher, String> {
+        match syntax {
+            SearchSyntax::Substring => Ok(SearchMatcher::Substring {
+                needle: if case_sensitive { query.to_string() } else { query.to_lowercase() },
+                case_sensitive,
+            }),
+            SearchSyntax::Glob => GlobBuilder::new(query)
+                .case_insensitive(!case_sensitive)
+                .literal_separator(true)
+                .build()
+                .map(|glob| SearchMatcher::Glob(glob.compile_matcher()))
+                .map_err(|e| e.to_string()),
+            SearchSyntax::Regex => RegexBuilder::new(query)
+                .case_insensitive(!case_sensitive)
+                .size_limit(REGEX_SIZE_LIMIT)
+                .dfa_size_limit(REGEX_SIZE_LIMIT)
+                .build()
+                .map(SearchMatcher::Regex)
+                .map_err(|e| match e {
+                    regex::Error::CompiledTooBig(_) => "pattern is too complex".to_string(),
+                    e => e.to_string(),
+                }),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            SearchMatcher::Substring { needle, case_sensitive: true } => text.contains(needle.as_str()),
+            SearchMatcher::Substring { needle, case_sensitive: false } => text.to_lowercase().contains(needle.as_str()),
+            SearchMatcher::Glob(glob) => glob.is_match(text),
+            SearchMatcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// A single size or date condition of a metadata query
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataFilter {
+    LargerThan(u64),
+    SmallerThan(u64),
+    NewerThan(SystemTime),
+    OlderThan(SystemTime),
+}
+
+impl MetadataFilter {
+    /// Size conditions never match directories
+    pub fn matches(&self, metadata: &std::fs::Metadata) -> bool {
+        match self {
+            MetadataFilter::LargerThan(size) => metadata.is_file() && metadata.len() > *size,
+            MetadataFilter::SmallerThan(size) => metadata.is_file() && metadata.len() < *size,
+            MetadataFilter::NewerThan(time) => metadata.modified().is_ok_and(|m| m > *time),
+            MetadataFilter::OlderThan(time) => metadata.modified().is_ok_and(|m| m < *time),
+        }
+    }
+}
+
+/// A parsed metadata query such as `*.log >100M older:30d`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataQuery {
+    /// The words that are not size or date conditions, joined by spaces
+    pub name: Option<String>,
+    pub filters: Vec<MetadataFilter>,
+}
+
+/// What a recursive search looks for. All present conditions must match.
+#[derive(Debug, Clone, Default)]
+pub struct WalkQuery {
+    pub name: Option<SearchMatcher>,
+    pub content: Option<SearchMatcher>,
+    pub filters: Vec<MetadataFilter>,
+    pub show_hidden: bool,
+}
+
+impl WalkQuery {
+    async fn matches(&self, path: &PathBuf, name: &str, is_dir: bool) -> bool {
+        if let Some(matcher) = &self.name {
+            if !matcher.is_match(name) {
+                return false;
+            }
+        }
+        if !self.filters.is_empty() {
+            match tokio::fs::metadata(path).await {
+                Ok(metadata) if self.filters.iter().all(|f| f.matches(&metadata)) => {}
+                _ => return false,
+            }
+        }
+        if let Some(matcher) = &self.content {
+            if is_dir || !SearchEngine::search_content(path, matcher).await.unwrap_or(false) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Incremental results of a background search
+#[derive(Debug)]
+pub struct SearchUpdate {
+    pub generation: u64,
+    pub entries: Vec<DirEntry>,
+    pub done: bool,
+}
+
+/// Runs searches that need file system access off the UI thread. Starting a
+/// new search or cancelling bumps the generation, which stops the previous
+/// worker and discards anything it already sent.
+#[derive(Debug)]
+pub struct BackgroundSearch {
+    generation: Arc<AtomicU64>,
+    pub running: bool,
+    tx: mpsc::Sender<SearchUpdate>,
+    rx: mpsc::Receiver<SearchUpdate>,
+}
+
+const METADATA_BATCH_SIZE: usize = 64;
+
+impl BackgroundSearch {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel(100);
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            running: false,
+            tx,
+            rx,
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.running = false;
+    }
+
+    /// Stats `candidates` in batches and streams back the ones matching all `filters`
+    pub fn start_metadata_filter(&mut self, candidates: Vec<DirEntry>, filters: Vec<MetadataFilter>) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.running = true;
+        let current = self.generation.clone();
+        let tx = self.tx.clone();
+
+        tokio::spawn(async move {
+            for batch in candidates.chunks(METADATA_BATCH_SIZE) {
+                if current.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                let mut matches = Vec::new();
+                for entry in batch {
+                    if let Ok(metadata) = tokio::fs::metadata(&entry.path).await {
+                        if filters.iter().all(|filter| filter.matches(&metadata)) {
+                            matches.push(entry.clone());
+                        }
+                    }
+                }
+                if !matches.is_empty() {
+                    let update = SearchUpdate { generation, entries: matches, done: false };
+                    if tx.send(update).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            let _ = tx.send(SearchUpdate { generation, entries: Vec::new(), done: true }).await;
+        });
+    }
+
+    /// Walks the tree under `root` and streams back matching entries one
+    /// directory at a time. Entry names are paths relative to `root`.
+    /// Symlinked directories are not descended into to avoid loops.
+    pub fn start_walk(&mut self, root: PathBuf, query: WalkQuery) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.running = true;
+        let current = self.generation.clone();
+        let tx = self.tx.clone();
+
+        tokio::spawn(async move {
+            let mut pending = vec![root.clone()];
+            while let Some(dir) = pending.pop() {
+                if current.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                let Ok(mut read_dir) = tokio::fs::read_dir(&dir).await else {
+                    continue;
+                };
+                let mut matches = Vec::new();
+                while let Ok(Some(entry)) = read_dir.next_entry().await {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if !query.show_hidden && name.starts_with('.') {
+                        continue;
+                    }
+                    let Ok(file_type) = entry.file_type().await else {
+                        continue;
+                    };
+                    let path = entry.path();
+                    if file_type.is_dir() {
+                        pending.push(path.clone());
+                    }
+                    if query.matches(&path, &name, file_type.is_dir()).await {
+                        let mut found = DirEntry::from_path(path);
+                        if let Ok(relative) = found.path.strip_prefix(&root) {
+                            found.name = relative.to_string_lossy().to_string();
+                        }
+                        matches.push(found);
+                    }
+                }
+                if !matches.is_empty() {
+                    let update = SearchUpdate { generation, entries: matches, done: false };
+                    if tx.send(update).await.is_err() {
+                        return;
+                    }
+                }
+                // Let the UI breathe between directories
+                tokio::task::yield_now().await;
+            }
+            let _ = tx.send(SearchUpdate { generation, entries: Vec::new(), done: true }).await;
+        });
+    }
+
+    /// Waits for the next update of the current search, skipping stale ones
+    pub async fn wait_for_update(&mut self) -> Option<SearchUpdate> {
+        loop {
+            let update = self.rx.recv().await?;
+            if update.generation == self.generation.load(Ordering::SeqCst) {
+                if update.done {
+                    self.running = false;
+                }
+                return Some(update);
+            }
+        }
+    }
+}
+
+impl Default for BackgroundSearch {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub struct SearchEngine;
 
 impl SearchEngine {
-    pub fn search_entries(entries: &[DirEntry], query: &str, mode: &SearchMode) -> Vec<DirEntry> {
+    pub fn search_entries(entries: &[DirEntry], matcher: &SearchMatcher, mode: &SearchMode) -> Vec<DirEntry> {
         match mode {
-            SearchMode::Name => {
+            SearchMode::Name | SearchMode::Index => {
                 entries.iter()
-                    .filter(|entry| {
-                        entry.name.to_lowercase().contains(&query.to_lowercase())
-                    })
+                    .filter(|entry| matcher.is_match(&entry.name))
                     .cloned()
                     .collect()
             },
@@ -27,13 +334,7 @@ impl SearchEngine {
                 // For content search, we would need to read file contents
                 // This is a simplified implementation for now
                 entries.iter()
-                    .filter(|entry| {
-                        if entry.is_dir {
-                            false
-                        } else {
-                            entry.name.to_lowercase().contains(&query.to_lowercase())
-                        }
-                    })
+                    .filter(|entry| !entry.is_dir && matcher.is_match(&entry.name))
                     .cloned()
                     .collect()
             },
@@ -41,57 +342,91 @@ impl SearchEngine {
                 // For metadata search, we would check file metadata
                 // This is a simplified implementation for now
                 entries.iter()
-                    .filter(|entry| {
-                        entry.name.to_lowercase().contains(&query.to_lowercase())
-                    })
+                    .filter(|entry| matcher.is_match(&entry.name))
                     .cloned()
                     .collect()
             }
         }
     }
-    
-    pub async fn search_content(path: &PathBuf, query: &str) -> Result<bool, std::io::Error> {
+
+    /// Parses a metadata query. Supported conditions:
+    /// `>SIZE`, `<SIZE` with an optional `k`, `M`, `G` or `T` suffix (powers of 1024),
+    /// `newer:AGE`, `older:AGE` where AGE is `N` followed by `h`, `d`, `w`, `m` (30 days)
+    /// or `y` (365 days), or an absolute `YYYY-MM-DD` date.
+    pub fn parse_metadata_query(query: &str, now: SystemTime) -> Result<MetadataQuery, String> {
+        let mut name_parts = Vec::new();
+        let mut filters = Vec::new();
+
+        for token in query.split_whitespace() {
+            if let Some(size) = token.strip_prefix('>') {
+                filters.push(MetadataFilter::LargerThan(parse_size(size)?));
+            } else if let Some(size) = token.strip_prefix('<') {
+                filters.push(MetadataFilter::SmallerThan(parse_size(size)?));
+            } else if let Some(time) = token.strip_prefix("newer:") {
+                filters.push(MetadataFilter::NewerThan(parse_time(time, now)?));
+            } else if let Some(time) = token.strip_prefix("older:") {
+                filters.push(MetadataFilter::OlderThan(parse_time(time, now)?));
+            } else {
+                name_parts.push(token);
+            }
+        }
+
+        let name = if name_parts.is_empty() { None } else { Some(name_parts.join(" ")) };
+        Ok(MetadataQuery { name, filters })
+    }
+
+    pub async fn search_content(path: &PathBuf, matcher: &SearchMatcher) -> Result<bool, std::io::Error> {
         // Check if the path is a file
         let metadata = tokio::fs::metadata(path).await?;
         if metadata.is_dir() {
             return Ok(false);
         }
-        
+
         // Read the file content
         let mut file = tokio::fs::File::open(path).await?;
         let mut contents = String::new();
         file.read_to_string(&mut contents).await?;
-        
-        // Search for the query in the content
-        Ok(contents.to_lowercase().contains(&query.to_lowercase()))
+
+        // Search for the query line by line so that anchored patterns work
+        Ok(contents.lines().any(|line| matcher.is_match(line)))
     }
-    
+
     pub async fn search_in_directory(
-        directory: &PathBuf, 
-        query: &str, 
+        directory: &PathBuf,
+        matcher: &SearchMatcher,
         mode: &SearchMode
     ) -> Result<Vec<DirEntry>, std::io::Error> {
         let mut results = Vec::new();
-        
+
         // Read directory entries
         let mut entries = tokio::fs::read_dir(directory).await?;
-        
+
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
             let name = entry.file_name().to_string_lossy().to_string();
-            let is_dir = entry.file_type().await?.is_dir();
-            
+            // `file_type` does not follow symlinks, `metadata` does
+            let is_symlink = entry.file_type().await?.is_symlink();
+            let metadata = tokio::fs::metadata(&path).await.ok();
+            let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+            let is_executable = !is_dir && metadata
+                .as_ref()
+                .is_some_and(|m| m.permissions().mode() & 0o111 != 0);
+            let target = if is_symlink { tokio::fs::read_link(&path).await.ok() } else { None };
+            let is_broken_link = is_symlink && metadata.is_none();
+            let size = metadata.as_ref().map_or(0, |m| m.len());
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+
             match mode {
-                SearchMode::Name => {
-                    if name.to_lowercase().contains(&query.to_lowercase()) {
-                        results.push(DirEntry { name, path, is_dir });
+                SearchMode::Name | SearchMode::Index => {
+                    if matcher.is_match(&name) {
+                        results.push(DirEntry { name, path, is_dir, is_executable, is_symlink, target, is_broken_link, size, modified });
                     }
                 },
                 SearchMode::Content => {
                     if !is_dir {
-                        if let Ok(matches) = Self::search_content(&path, query).await {
+                        if let Ok(matches) = Self::search_content(&path, matcher).await {
                             if matches {
-                                results.push(DirEntry { name, path, is_dir });
+                                results.push(DirEntry { name, path, is_dir, is_executable, is_symlink, target, is_broken_link, size, modified });
                             }
                         }
                     }
@@ -99,13 +434,229 @@ impl SearchEngine {
                 SearchMode::Metadata => {
                     // For metadata search, we would check file metadata
                     // This is a simplified implementation for now
-                    if name.to_lowercase().contains(&query.to_lowercase()) {
-                        results.push(DirEntry { name, path, is_dir });
+                    if matcher.is_match(&name) {
+                        results.push(DirEntry { name, path, is_dir, is_executable, is_symlink, target, is_broken_link, size, modified });
                     }
                 }
             }
         }
-        
+
         Ok(results)
     }
-}
\ No newline at end of file
+}
+
+fn parse_size(text: &str) -> Result<u64, String> {
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (digits, suffix) = text.split_at(split);
+    let value: u64 = digits.parse().map_err(|_| format!("Invalid size: {}", text))?;
+    let multiplier: u64 = match suffix.to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        "t" | "tb" => 1024 * 1024 * 1024 * 1024,
+        _ => return Err(format!("Unknown size suffix: {}", suffix)),
+    };
+    value.checked_mul(multiplier).ok_or_else(|| format!("Size is too large: {}", text))
+}
+
+fn parse_time(text: &str, now: SystemTime) -> Result<SystemTime, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        let midnight = date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|dt| dt.and_local_timezone(Local).earliest())
+            .ok_or_else(|| format!("Invalid date: {}", text))?;
+        return Ok(midnight.into());
+    }
+
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (digits, unit) = text.split_at(split);
+    let value: u64 = digits.parse().map_err(|_| format!("Invalid age: {}", text))?;
+    let seconds: u64 = match unit {
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "m" => 30 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return Err(format!("Unknown age unit: {}", unit)),
+    };
+    let age = value
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Age is too large: {}", text))?;
+    now.checked_sub(age).ok_or_else(|| format!("Age is too large: {}", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(names: &[&str]) -> Vec<DirEntry> {
+        names
+            .iter()
+            .map(|name| DirEntry {
+                name: name.to_string(),
+                path: PathBuf::from(name),
+                is_dir: false,
+                is_executable: false,
+                is_symlink: false,
+                target: None,
+                is_broken_link: false,
+                size: 0,
+                modified: None,
+            })
+            .collect()
+    }
+
+    fn names(results: &[DirEntry]) -> Vec<&str> {
+        results.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_substring_is_case_insensitive() {
+        let matcher = SearchMatcher::compile("READ", SearchSyntax::Substring, false).unwrap();
+        let results = SearchEngine::search_entries(&entries(&["README.md", "main.rs"]), &matcher, &SearchMode::Name);
+        assert_eq!(names(&results), vec!["README.md"]);
+    }
+
+    #[test]
+    fn test_glob_matches_extension() {
+        let matcher = SearchMatcher::compile("*.RS", SearchSyntax::Glob, false).unwrap();
+        let results = SearchEngine::search_entries(&entries(&["main.rs", "lib.rs", "Cargo.toml"]), &matcher, &SearchMode::Name);
+        assert_eq!(names(&results), vec!["main.rs", "lib.rs"]);
+    }
+
+    #[test]
+    fn test_regex_is_anchored_by_pattern() {
+        let matcher = SearchMatcher::compile(r"^test_.*\.py$", SearchSyntax::Regex, false).unwrap();
+        let results = SearchEngine::search_entries(&entries(&["test_app.py", "app_test.py", "test_app.pyc"]), &matcher, &SearchMode::Name);
+        assert_eq!(names(&results), vec!["test_app.py"]);
+    }
+
+    #[test]
+    fn test_invalid_regex_reports_error() {
+        assert!(SearchMatcher::compile("(unclosed", SearchSyntax::Regex, false).is_err());
+        assert_eq!(SearchMatcher::compile("(a{1000}){1000}", SearchSyntax::Regex, false).unwrap_err(), "pattern is too complex");
+    }
+
+    #[test]
+    fn test_case_sensitive_matching() {
+        let names_in = ["README.md", "readme_test.rs"];
+        for syntax in [SearchSyntax::Substring, SearchSyntax::Glob, SearchSyntax::Regex] {
+            let query = if syntax == SearchSyntax::Glob { "read*" } else { "read" };
+            let matcher = SearchMatcher::compile(query, syntax, true).unwrap();
+            let results = SearchEngine::search_entries(&entries(&names_in), &matcher, &SearchMode::Name);
+            assert_eq!(names(&results), vec!["readme_test.rs"], "{:?}", syntax);
+        }
+    }
+
+    #[test]
+    fn test_parse_size_suffixes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("1k"), Ok(1024));
+        assert_eq!(parse_size("10M"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1T"), Ok(1024 * 1024 * 1024 * 1024));
+        assert!(parse_size("10Q").is_err());
+        assert!(parse_size("M").is_err());
+    }
+
+    #[test]
+    fn test_parse_size_operators() {
+        let now = SystemTime::now();
+        let query = SearchEngine::parse_metadata_query(">10M <1k", now).unwrap();
+        assert_eq!(query.name, None);
+        assert_eq!(
+            query.filters,
+            vec![MetadataFilter::LargerThan(10 * 1024 * 1024), MetadataFilter::SmallerThan(1024)]
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_ages() {
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        let query = SearchEngine::parse_metadata_query("newer:7d older:2w newer:3h older:1m newer:1y", now).unwrap();
+        assert_eq!(
+            query.filters,
+            vec![
+                MetadataFilter::NewerThan(now - day * 7),
+                MetadataFilter::OlderThan(now - day * 14),
+                MetadataFilter::NewerThan(now - Duration::from_secs(3 * 60 * 60)),
+                MetadataFilter::OlderThan(now - day * 30),
+                MetadataFilter::NewerThan(now - day * 365),
+            ]
+        );
+        assert!(SearchEngine::parse_metadata_query("newer:7x", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_absolute_date() {
+        let now = SystemTime::now();
+        let query = SearchEngine::parse_metadata_query("older:2024-01-01", now).unwrap();
+        let expected: SystemTime = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap()
+            .into();
+        assert_eq!(query.filters, vec![MetadataFilter::OlderThan(expected)]);
+    }
+
+    #[test]
+    fn test_parse_keeps_name_fragment() {
+        let query = SearchEngine::parse_metadata_query("*.log >100M older:30d", SystemTime::now()).unwrap();
+        assert_eq!(query.name.as_deref(), Some("*.log"));
+        assert_eq!(query.filters.len(), 2);
+    }
+
+    #[test]
+    fn test_size_filter_skips_directories() {
+        let dir = tempdir::TempDir::new("metadata_filter").unwrap();
+        let file = dir.path().join("big.bin");
+        std::fs::write(&file, vec![0u8; 2048]).unwrap();
+
+        let file_meta = std::fs::metadata(&file).unwrap();
+        let dir_meta = std::fs::metadata(dir.path()).unwrap();
+        assert!(MetadataFilter::LargerThan(1024).matches(&file_meta));
+        assert!(!MetadataFilter::SmallerThan(1024).matches(&file_meta));
+        assert!(!MetadataFilter::SmallerThan(u64::MAX).matches(&dir_meta));
+    }
+
+    #[tokio::test]
+    async fn test_walk_streams_nested_matches() {
+        let dir = tempdir::TempDir::new("walk").unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("top.log"), "").unwrap();
+        std::fs::write(dir.path().join("a/b/deep.log"), "").unwrap();
+        std::fs::write(dir.path().join("a/other.txt"), "").unwrap();
+
+        let mut search = BackgroundSearch::new();
+        let query = WalkQuery {
+            name: Some(SearchMatcher::compile("*.log", SearchSyntax::Glob, false).unwrap()),
+            ..Default::default()
+        };
+        search.start_walk(dir.path().to_path_buf(), query);
+
+        let mut found = Vec::new();
+        while let Some(update) = search.wait_for_update().await {
+            found.extend(update.entries.into_iter().map(|e| e.name));
+            if update.done {
+                break;
+            }
+        }
+        found.sort();
+        assert_eq!(found, vec!["a/b/deep.log".to_string(), "top.log".to_string()]);
+        assert!(!search.running);
+    }
+
+    #[test]
+    fn test_syntax_cycles() {
+        let syntax = SearchSyntax::default();
+        assert_eq!(syntax.next(), SearchSyntax::Glob);
+        assert_eq!(syntax.next().next(), SearchSyntax::Regex);
+        assert_eq!(syntax.next().next().next(), SearchSyntax::Substring);
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use crate::app_state::{AppState, TabState};
+use std::path::{Path, PathBuf};
+use crate::app_state::{AppState, LayoutMode, PanelSide};
+use config::Bookmark;
+use crate::settings::SortSettings;
+use crate::task_manager::{SavedTask, TaskManager};
 
 /// Структура для хранения данных сессии
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,10 +14,22 @@ pub struct SessionState {
     pub active_tab_index: usize,
     /// Показывать ли вкладки
     pub show_tabs: bool,
-    /// Закладки
-    pub bookmarks: Vec<(String, PathBuf)>,
+    /// Закладки. Старые сессии хранили их парами `[имя, путь]`, они тоже читаются.
+    pub bookmarks: Vec<Bookmark>,
     /// Показывать ли скрытые файлы
     pub show_hidden_files: bool,
+    /// Настройки сортировки
+    #[serde(default)]
+    pub sort: SortSettings,
+    /// Один или два списка файлов
+    #[serde(default)]
+    pub layout_mode: LayoutMode,
+    /// Активный список в двухпанельном режиме
+    #[serde(default)]
+    pub active_panel: PanelSide,
+    /// Каталог правого списка, если его уже открывали
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub second_panel: Option<PathBuf>,
 }
 
 /// Структура для хранения пути вкладки
@@ -24,6 +39,9 @@ pub struct TabPath {
     pub id: usize,
     /// Текущий каталог вкладки
     pub current_dir: PathBuf,
+    /// Имя вкладки, если оно задано
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 impl SessionState {
@@ -35,6 +53,7 @@ impl SessionState {
             .map(|tab| TabPath {
                 id: tab.id,
                 current_dir: tab.current_dir.clone(),
+                name: tab.name.clone(),
             })
             .collect();
 
@@ -44,6 +63,10 @@ impl SessionState {
             show_tabs: app_state.show_tabs,
             bookmarks: app_state.bookmarks.clone(),
             show_hidden_files: app_state.show_hidden_files,
+            sort: app_state.sort_settings.clone(),
+            layout_mode: app_state.layout_mode,
+            active_panel: app_state.active_panel,
+            second_panel: app_state.second_panel.as_ref().map(|panel| panel.current_dir.clone()),
         }
     }
 
@@ -52,11 +75,18 @@ impl SessionState {
         // Обновляем вкладки
         app_state.tabs.clear();
         for (_index, tab_path) in self.tabs.iter().enumerate() {
-            let mut tab = TabState::new(tab_path.id);
+            let mut tab = app_state.new_tab_state(tab_path.id);
+            tab.name = tab_path.name.clone();
             tab.set_current_dir(tab_path.current_dir.clone(), self.show_hidden_files);
             app_state.tabs.push(tab);
         }
 
+        // Сессия без вкладок повреждена: начинаем с вкладки по умолчанию
+        if app_state.tabs.is_empty() {
+            log::warn!("Saved session has no tabs, opening a default tab");
+            app_state.ensure_tab();
+        }
+
         // Устанавливаем индекс активной вкладки
         app_state.active_tab_index = self.active_tab_index.min(app_state.tabs.len().saturating_sub(1));
 
@@ -68,40 +98,202 @@ impl SessionState {
 
         // Обновляем показ скрытых файлов
         app_state.show_hidden_files = self.show_hidden_files;
-        // Обновляем записи во всех вкладках в соответствии с настройкой показа скрытых файлов
-        for tab in &mut app_state.tabs {
-            tab.update_entries(app_state.show_hidden_files);
+        // Обновляем записи во всех вкладках в соответствии с настройками показа и сортировки
+        app_state.sort_settings = self.sort.clone();
+
+        // Без сохранённого правого списка двухпанельный режим не включается
+        app_state.second_panel = self.second_panel.as_ref().map(|dir| {
+            let mut panel = app_state.new_tab_state(0);
+            panel.set_current_dir(dir.clone(), self.show_hidden_files);
+            panel
+        });
+        let dual = self.layout_mode == LayoutMode::Dual && app_state.second_panel.is_some();
+        app_state.layout_mode = if dual { LayoutMode::Dual } else { LayoutMode::Single };
+        app_state.active_panel = if dual { self.active_panel } else { PanelSide::Left };
+        app_state.apply_sort_settings();
+    }
+}
+
+/// Сохраняет сессию в файл. `None` — сессия по умолчанию, `session.json`.
+pub fn save_session(app_state: &AppState, name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    save_session_in(&crate::paths::sessions_dir(), app_state, name)
+}
+
+/// Сохраняет сессию в каталог `dir`
+fn save_session_in(dir: &Path, app_state: &AppState, name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(&SessionState::from_app_state(app_state))?;
+    write_session(&dir.join(crate::paths::session_file_name(name)), &json)
+}
+
+/// Имя сессии становится частью имени файла, поэтому допускаются только
+/// буквы, цифры, `-` и `_`
+pub fn is_valid_session_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Файл с PID процесса, у которого открыта сессия `session_file`.
+/// Пока он есть, другой экземпляр не пишет в ту же сессию. Файл удаляется,
+/// когда блокировка выходит из области видимости.
+#[derive(Debug)]
+pub struct SessionLock {
+    path: PathBuf,
+}
+
+/// Почему не удалось заблокировать сессию
+#[derive(Debug)]
+pub enum LockError {
+    /// Сессию держит живой процесс с этим PID
+    Held(u32),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockError::Held(pid) => write!(f, "the session is open in corvus with PID {}", pid),
+            LockError::Io(e) => e.fmt(f),
         }
     }
 }
 
-/// Получает путь к файлу сессии
-pub fn get_session_file_path() -> PathBuf {
-    let config_dir = directories::ProjectDirs::from("org", "rust-tui-fm", "rtfm")
-        .map(|dirs| dirs.config_dir().to_path_buf())
-        .unwrap_or_else(|| PathBuf::from("."));
+impl SessionLock {
+    /// Создаёт `<файл сессии>.lock` со своим PID. Блокировку умершего
+    /// процесса, как и оставшуюся от этого же процесса, снимает.
+    pub fn acquire(session_file: &Path) -> Result<SessionLock, LockError> {
+        let mut file_name = session_file.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".lock");
+        let path = session_file.with_file_name(file_name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(LockError::Io)?;
+        }
+        let own_pid = std::process::id();
+        loop {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    use std::io::Write;
+                    write!(file, "{}", own_pid).map_err(LockError::Io)?;
+                    return Ok(SessionLock { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let pid = std::fs::read_to_string(&path).ok().and_then(|text| text.trim().parse::<u32>().ok());
+                    if let Some(pid) = pid.filter(|pid| *pid != own_pid && is_alive(*pid)) {
+                        return Err(LockError::Held(pid));
+                    }
+                    log::info!("Removing stale session lock {} (PID {:?})", path.display(), pid);
+                    std::fs::remove_file(&path).map_err(LockError::Io)?;
+                }
+                Err(e) => return Err(LockError::Io(e)),
+            }
+        }
+    }
+}
 
-    config_dir.join("session.json")
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        // Чужую блокировку, поставленную после снятия нашей как устаревшей, не трогаем
+        let own = std::fs::read_to_string(&self.path).is_ok_and(|text| text.trim() == std::process::id().to_string());
+        if own {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
 }
 
-/// Сохраняет сессию в файл
-pub fn save_session(app_state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
-    let session_state = SessionState::from_app_state(app_state);
-    let session_file_path = get_session_file_path();
+fn is_alive(pid: u32) -> bool {
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    // Сигнал 0 только проверяет, есть ли процесс; EPERM — процесс есть, но чужой
+    match nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None) {
+        Ok(()) => true,
+        Err(errno) => errno == nix::errno::Errno::EPERM,
+    }
+}
 
+/// Имена сохранённых именованных сессий по алфавиту
+pub fn list_sessions() -> Vec<String> {
+    sessions_in(&crate::paths::sessions_dir())
+}
+
+fn sessions_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let name = file_name.strip_prefix("session-")?.strip_suffix(".json")?;
+            is_valid_session_name(name).then(|| name.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+fn write_session(path: &Path, json: &str) -> Result<(), Box<dyn std::error::Error>> {
     // Создаем директорию конфигурации, если она не существует
-    if let Some(parent) = session_file_path.parent() {
+    if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-
-    let json = serde_json::to_string_pretty(&session_state)?;
-    std::fs::write(session_file_path, json)?;
+    config::write_atomic(path, json.as_bytes())?;
     Ok(())
 }
 
-/// Загружает сессию из файла
-pub fn load_session() -> Result<Option<SessionState>, Box<dyn std::error::Error>> {
-    let session_file_path = get_session_file_path();
+/// Периодическое сохранение сессии в каталог `dir`, в файл текущей
+/// сессии `AppState::session_name`. Сессия считается изменённой, если её
+/// имя или JSON отличаются от последних записанных, поэтому файл не
+/// переписывается, пока вкладки, закладки и настройки те же.
+#[derive(Debug)]
+pub struct SessionSaver {
+    dir: PathBuf,
+    saved: Option<(Option<String>, String)>,
+}
+
+impl SessionSaver {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, saved: None }
+    }
+
+    fn snapshot(app_state: &AppState) -> Option<(Option<String>, String)> {
+        let json = serde_json::to_string_pretty(&SessionState::from_app_state(app_state)).ok()?;
+        Some((app_state.session_name.clone(), json))
+    }
+
+    /// Считает текущее состояние уже сохранённым, например только что
+    /// загруженным из файла
+    pub fn mark_saved(&mut self, app_state: &AppState) {
+        self.saved = Self::snapshot(app_state);
+    }
+
+    pub fn is_dirty(&self, app_state: &AppState) -> bool {
+        Self::snapshot(app_state) != self.saved
+    }
+
+    /// Записывает сессию, если она изменилась. Возвращает, была ли запись.
+    pub fn save_if_changed(&mut self, app_state: &AppState) -> Result<bool, Box<dyn std::error::Error>> {
+        if app_state.session_read_only {
+            return Ok(false);
+        }
+        let json = serde_json::to_string_pretty(&SessionState::from_app_state(app_state))?;
+        let snapshot = (app_state.session_name.clone(), json);
+        if self.saved.as_ref() == Some(&snapshot) {
+            return Ok(false);
+        }
+        let path = self.dir.join(crate::paths::session_file_name(snapshot.0.as_deref()));
+        write_session(&path, &snapshot.1)?;
+        self.saved = Some(snapshot);
+        Ok(true)
+    }
+}
+
+/// Загружает сессию из файла. `None` — сессия по умолчанию.
+pub fn load_session(name: Option<&str>) -> Result<Option<SessionState>, Box<dyn std::error::Error>> {
+    load_session_in(&crate::paths::sessions_dir(), name)
+}
+
+/// Загружает сессию из каталога `dir`
+fn load_session_in(dir: &Path, name: Option<&str>) -> Result<Option<SessionState>, Box<dyn std::error::Error>> {
+    let session_file_path = dir.join(crate::paths::session_file_name(name));
 
     if !session_file_path.exists() {
         return Ok(None);
@@ -112,37 +304,185 @@ pub fn load_session() -> Result<Option<SessionState>, Box<dyn std::error::Error>
     Ok(Some(session_state))
 }
 
+/// Сохраняет незавершённые задачи. Если их нет, файл удаляется.
+pub fn save_unfinished_tasks(task_manager: &TaskManager) -> Result<(), Box<dyn std::error::Error>> {
+    let tasks = task_manager.unfinished_tasks();
+    let tasks_file_path = crate::paths::tasks_file();
+    if tasks.is_empty() {
+        if tasks_file_path.exists() {
+            std::fs::remove_file(tasks_file_path)?;
+        }
+        return Ok(());
+    }
+    if let Some(parent) = tasks_file_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    config::write_atomic(&tasks_file_path, serde_json::to_string_pretty(&tasks)?.as_bytes())?;
+    Ok(())
+}
+
+/// Загружает задачи, не завершённые в прошлый раз
+pub fn load_unfinished_tasks() -> Result<Vec<SavedTask>, Box<dyn std::error::Error>> {
+    let tasks_file_path = crate::paths::tasks_file();
+    if !tasks_file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = std::fs::read_to_string(tasks_file_path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempdir::TempDir;
-    use std::env;
+
+    #[test]
+    fn test_session_without_tabs_opens_default_tab() {
+        let json = r#"{"tabs": [], "active_tab_index": 3, "show_tabs": true, "bookmarks": [], "show_hidden_files": false}"#;
+        let session_state: SessionState = serde_json::from_str(json).unwrap();
+
+        let mut app_state = AppState::new();
+        session_state.apply_to_app_state(&mut app_state);
+        assert_eq!(app_state.tabs.len(), 1);
+        assert_eq!(app_state.active_tab_index, 0);
+        assert!(app_state.get_active_tab().current_dir.is_dir());
+    }
+
+    #[test]
+    fn test_tab_names_survive_session_round_trip() {
+        let mut app_state = AppState::new();
+        app_state.start_tab_rename();
+        app_state.input_buffer = "  work  ".to_string();
+        app_state.set_tab_name();
+        assert_eq!(app_state.get_active_tab().title(), "work");
+
+        let json = serde_json::to_string(&SessionState::from_app_state(&app_state)).unwrap();
+        let mut restored = AppState::new();
+        serde_json::from_str::<SessionState>(&json).unwrap().apply_to_app_state(&mut restored);
+        assert_eq!(restored.get_active_tab().name.as_deref(), Some("work"));
+
+        // Empty input falls back to the directory name
+        restored.start_tab_rename();
+        assert_eq!(restored.input_buffer, "work");
+        restored.input_buffer.clear();
+        restored.set_tab_name();
+        assert_eq!(restored.get_active_tab().name, None);
+    }
+
+    #[tokio::test]
+    async fn test_restored_tabs_load_in_the_background() {
+        let mut app_state = AppState::new();
+        app_state.toggle_layout_mode();
+        app_state.new_tab();
+        let json = serde_json::to_string(&SessionState::from_app_state(&app_state)).unwrap();
+
+        let mut restored = AppState::new();
+        restored.enable_background_loading();
+        serde_json::from_str::<SessionState>(&json).unwrap().apply_to_app_state(&mut restored);
+        assert_eq!(restored.tabs.len(), 2);
+        assert!(restored.tabs.iter().all(|tab| tab.loader.is_some()));
+        assert!(restored.second_panel.as_ref().unwrap().loader.is_some());
+    }
+
+    #[test]
+    fn test_dual_layout_survives_session_round_trip() {
+        let temp_dir = TempDir::new("dual_session").unwrap();
+        let mut app_state = AppState::new();
+        app_state.toggle_layout_mode();
+        app_state.cycle_focus();
+        app_state.get_active_tab_mut().set_current_dir(temp_dir.path().to_path_buf(), false);
+
+        let json = serde_json::to_string(&SessionState::from_app_state(&app_state)).unwrap();
+        let mut restored = AppState::new();
+        serde_json::from_str::<SessionState>(&json).unwrap().apply_to_app_state(&mut restored);
+        assert_eq!(restored.layout_mode, LayoutMode::Dual);
+        assert_eq!(restored.active_panel, PanelSide::Right);
+        assert_eq!(restored.get_active_tab().current_dir, temp_dir.path());
+        assert_eq!(restored.get_other_panel().unwrap().current_dir, app_state.tabs[0].current_dir);
+
+        // Older sessions have no layout and open with a single list
+        let json = r#"{"tabs": [], "active_tab_index": 0, "show_tabs": true, "bookmarks": [], "show_hidden_files": false}"#;
+        serde_json::from_str::<SessionState>(json).unwrap().apply_to_app_state(&mut restored);
+        assert_eq!(restored.layout_mode, LayoutMode::Single);
+        assert!(restored.second_panel.is_none());
+    }
+
+    #[test]
+    fn test_session_is_written_only_when_it_changed() {
+        let temp_dir = TempDir::new("session_saver").unwrap();
+        let path = temp_dir.path().join("session.json");
+        let mut app_state = AppState::new();
+        let mut saver = SessionSaver::new(temp_dir.path().to_path_buf());
+        saver.mark_saved(&app_state);
+        assert!(!saver.save_if_changed(&app_state).unwrap());
+        assert!(!path.exists());
+
+        app_state.new_tab();
+        assert!(saver.is_dirty(&app_state));
+        assert!(saver.save_if_changed(&app_state).unwrap());
+        let saved: SessionState = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.tabs.len(), app_state.tabs.len());
+        assert!(!saver.save_if_changed(&app_state).unwrap());
+
+        // A new name is a change even with the same tabs
+        app_state.session_name = Some("work".to_string());
+        assert!(saver.save_if_changed(&app_state).unwrap());
+        assert!(temp_dir.path().join("session-work.json").exists());
+        assert_eq!(sessions_in(temp_dir.path()), vec!["work"]);
+
+        // A session open in another instance is never written
+        app_state.session_read_only = true;
+        app_state.new_tab();
+        assert!(!saver.save_if_changed(&app_state).unwrap());
+    }
+
+    #[test]
+    fn test_session_lock_is_refused_while_its_process_lives() {
+        let temp_dir = TempDir::new("session_lock").unwrap();
+        let session = temp_dir.path().join("session.json");
+        let lock_path = temp_dir.path().join("session.json.lock");
+
+        // PID 1 is always alive
+        std::fs::write(&lock_path, "1").unwrap();
+        assert!(matches!(SessionLock::acquire(&session), Err(LockError::Held(1))));
+
+        // A dead process's lock is taken over and removed on drop
+        std::fs::write(&lock_path, i32::MAX.to_string()).unwrap();
+        let lock = SessionLock::acquire(&session).unwrap();
+        assert_eq!(std::fs::read_to_string(&lock_path).unwrap(), std::process::id().to_string());
+        drop(lock);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn test_session_names_are_safe_file_name_parts() {
+        assert!(is_valid_session_name("work-2_rust"));
+        assert!(!is_valid_session_name(""));
+        assert!(!is_valid_session_name("../evil"));
+        assert!(!is_valid_session_name("a b"));
+    }
 
     #[test]
     fn test_save_and_load_session() {
         // Создаем временную директорию для теста
         let temp_dir = TempDir::new("session_test").unwrap();
-        let old_config_dir = env::var("XDG_CONFIG_HOME").unwrap_or_default();
-        
-        // Устанавливаем временную директорию как директорию конфигурации
-        env::set_var("XDG_CONFIG_HOME", temp_dir.path());
         
         // Создаем тестовое состояние приложения
         let mut app_state = AppState::new();
+        app_state.show_tabs = true;
         
         // Добавляем несколько вкладок
         app_state.new_tab();
         app_state.new_tab();
         
         // Сохраняем сессию
-        assert!(save_session(&app_state).is_ok());
+        assert!(save_session_in(temp_dir.path(), &app_state, None).is_ok());
         
         // Проверяем, что файл сессии создан
-        let session_file_path = get_session_file_path();
-        assert!(session_file_path.exists());
+        assert!(temp_dir.path().join("session.json").exists());
         
         // Загружаем сессию
-        let loaded_session = load_session().unwrap();
+        let loaded_session = load_session_in(temp_dir.path(), None).unwrap();
         assert!(loaded_session.is_some());
         
         let session_state = loaded_session.unwrap();
@@ -151,12 +491,5 @@ mod tests {
         assert_eq!(session_state.tabs.len(), 3); // 3 вкладки
         assert_eq!(session_state.active_tab_index, 2); // Активная вкладка - последняя созданная
         assert_eq!(session_state.show_tabs, true); // Вкладки должны отображаться
-        
-        // Восстанавливаем оригинальную директорию конфигурации
-        if old_config_dir.is_empty() {
-            env::remove_var("XDG_CONFIG_HOME");
-        } else {
-            env::set_var("XDG_CONFIG_HOME", old_config_dir);
-        }
     }
 }
\ No newline at end of file
@@ -1,180 +1,480 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use utils::icons::FileCategory;
+use crate::app_state::DirEntry;
+use config::{Bookmark, Config};
+use io::fs_ops::MAX_COMPRESSION_LEVEL;
+use crate::task_manager::ArchiveFormat;
 
-/// Цветовые схемы
+/// Цвет в формате RGB
+pub type Rgb = (u8, u8, u8);
+
+/// Сколько цветов умеет показывать терминал
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    /// Палитра xterm из 256 цветов
+    Indexed256,
+    /// Только 16 стандартных цветов ANSI
+    Basic16,
+}
+
+/// Стандартные цвета ANSI в том виде, как их обычно показывает xterm
+const ANSI_16: [(Rgb, ratatui::style::Color); 16] = {
+    use ratatui::style::Color::*;
+    [
+        ((0, 0, 0), Black),
+        ((205, 0, 0), Red),
+        ((0, 205, 0), Green),
+        ((205, 205, 0), Yellow),
+        ((0, 0, 238), Blue),
+        ((205, 0, 205), Magenta),
+        ((0, 205, 205), Cyan),
+        ((229, 229, 229), Gray),
+        ((127, 127, 127), DarkGray),
+        ((255, 0, 0), LightRed),
+        ((0, 255, 0), LightGreen),
+        ((255, 255, 0), LightYellow),
+        ((92, 92, 255), LightBlue),
+        ((255, 0, 255), LightMagenta),
+        ((0, 255, 255), LightCyan),
+        ((255, 255, 255), White),
+    ]
+};
+
+/// Уровни каждого канала в цветовом кубе 6×6×6 палитры xterm
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+static COLOR_DEPTH: OnceLock<ColorDepth> = OnceLock::new();
+
+impl ColorDepth {
+    /// Определить по переменным окружения `COLORTERM` и `TERM`
+    pub fn detect() -> Self {
+        Self::from_env(
+            std::env::var("COLORTERM").ok().as_deref(),
+            std::env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        let colorterm = colorterm.unwrap_or_default().to_ascii_lowercase();
+        let term = term.unwrap_or_default().to_ascii_lowercase();
+        if matches!(colorterm.as_str(), "truecolor" | "24bit") || term.contains("direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Indexed256
+        } else {
+            Self::Basic16
+        }
+    }
+
+    /// Возможности терминала, определённые при первом обращении
+    pub fn current() -> Self {
+        *COLOR_DEPTH.get_or_init(Self::detect)
+    }
+
+    /// Ближайший к `rgb` цвет, который терминал умеет показать
+    pub fn color(self, rgb: Rgb) -> ratatui::style::Color {
+        match self {
+            Self::TrueColor => ratatui::style::Color::Rgb(rgb.0, rgb.1, rgb.2),
+            Self::Indexed256 => ratatui::style::Color::Indexed(nearest_indexed(rgb)),
+            Self::Basic16 => {
+                let nearest = ANSI_16.iter().min_by_key(|(ansi, _)| distance(rgb, *ansi));
+                nearest.map_or(ratatui::style::Color::Reset, |&(_, color)| color)
+            }
+        }
+    }
+}
+
+/// Цвет `rgb` с учётом возможностей терминала
+pub fn rgb_color(rgb: Rgb) -> ratatui::style::Color {
+    ColorDepth::current().color(rgb)
+}
+
+/// Квадрат расстояния между цветами
+fn distance(a: Rgb, b: Rgb) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Номер ближайшего цвета из куба (16–231) или серой шкалы (232–255)
+/// палитры xterm. Первые 16 цветов не используются: терминалы их
+/// перекрашивают.
+fn nearest_indexed(rgb: Rgb) -> u8 {
+    let level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * step;
+
+    if distance(rgb, (gray, gray, gray)) < distance(rgb, cube) {
+        232 + step
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+/// Цветовая схема: встроенная или загруженная из каталога `themes/`
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
-pub enum ColorScheme {
+pub struct ColorScheme {
+    pub name: String,
+    /// Светлая тема: от этого зависят цвета имён файлов
+    pub light: bool,
+    pub background: Rgb,
+    pub text: Rgb,
+    pub highlight_bg: Rgb,
+    pub selection_bg: Rgb,
+    /// Рамка активной панели
+    pub accent: Rgb,
+    /// Сообщения об ошибках
+    pub error: Rgb,
+    /// Имена каталогов
+    pub dir: Rgb,
+    /// Без цветов: всё рисуется цветами терминала по умолчанию, а RGB-поля
+    /// не используются
+    #[serde(default)]
+    pub monochrome: bool,
+}
+
+/// Встроенные схемы: имя, светлая ли, фон, текст, выделенный элемент,
+/// множественный выбор, акцент, ошибки, каталоги
+#[allow(clippy::type_complexity)]
+const BUILTIN_SCHEMES: &[(&str, bool, [Rgb; 7])] = &[
     // Светлые темы
-    GithubLight,
-    SolarizedLight,
-    AtomLight,
-    VisualStudioLight,
+    ("GitHub Light", true, [(255, 255, 255), (36, 41, 46), (225, 228, 232), (200, 220, 240), (3, 102, 214), (203, 36, 49), (3, 102, 214)]),
+    ("Solarized Light", true, [(253, 246, 227), (101, 123, 131), (238, 232, 213), (220, 225, 200), (38, 139, 210), (220, 50, 47), (3, 102, 214)]),
+    ("Atom Light", true, [(250, 250, 250), (33, 37, 43), (225, 228, 232), (210, 215, 220), (64, 120, 242), (228, 86, 73), (3, 102, 214)]),
+    ("Visual Studio Light", true, [(245, 245, 245), (30, 30, 30), (225, 230, 240), (210, 215, 225), (0, 122, 204), (205, 49, 49), (3, 102, 214)]),
     // Тёмные темы
-    Dracula,
-    Monokai,
-    OneDark,
-    Nord,
-    GruvboxDark,
-    TokyoNight,
-    MaterialDark,
-    Cyberpunk,
+    ("Dracula", false, [(40, 42, 54), (248, 248, 242), (68, 71, 90), (56, 60, 74), (189, 147, 249), (255, 85, 85), (98, 174, 255)]),
+    ("Monokai", false, [(39, 40, 34), (248, 248, 242), (60, 60, 60), (50, 50, 50), (166, 226, 46), (249, 38, 114), (98, 174, 255)]),
+    ("One Dark", false, [(40, 44, 52), (171, 178, 191), (60, 65, 75), (50, 55, 65), (97, 175, 239), (224, 108, 117), (98, 174, 255)]),
+    ("Nord", false, [(46, 52, 64), (216, 222, 233), (67, 76, 94), (59, 66, 82), (136, 192, 208), (191, 97, 106), (98, 174, 255)]),
+    ("Gruvbox Dark", false, [(40, 40, 40), (235, 219, 178), (60, 56, 54), (50, 48, 47), (250, 189, 47), (251, 73, 52), (98, 174, 255)]),
+    ("Tokyo Night", false, [(25, 26, 35), (195, 200, 210), (45, 47, 63), (35, 37, 50), (122, 162, 247), (247, 118, 142), (98, 174, 255)]),
+    ("Material Dark", false, [(33, 33, 33), (220, 220, 220), (50, 50, 50), (40, 40, 40), (130, 170, 255), (255, 83, 112), (98, 174, 255)]),
+    ("Cyberpunk", false, [(10, 0, 20), (255, 0, 255), (70, 0, 100), (50, 0, 80), (0, 255, 255), (255, 0, 60), (98, 174, 255)]),
+];
+
+/// Встроенные схемы и пользовательские темы, регистрируются при запуске
+static SCHEME_REGISTRY: OnceLock<Vec<ColorScheme>> = OnceLock::new();
+
+fn builtin_schemes() -> Vec<ColorScheme> {
+    BUILTIN_SCHEMES
+        .iter()
+        .map(|&(name, light, [background, text, highlight_bg, selection_bg, accent, error, dir])| ColorScheme {
+            name: name.to_string(),
+            light,
+            background,
+            text,
+            highlight_bg,
+            selection_bg,
+            accent,
+            error,
+            dir,
+            monochrome: false,
+        })
+        .chain(std::iter::once(ColorScheme::monochrome()))
+        .collect()
+}
+
+fn scheme_registry() -> &'static [ColorScheme] {
+    SCHEME_REGISTRY.get_or_init(builtin_schemes)
+}
+
+/// Добавить пользовательские темы к встроенным. Тема с именем встроенной
+/// заменяет её. Действует только до первого обращения к списку схем.
+pub fn register_user_schemes(user_schemes: Vec<ColorScheme>) {
+    let mut schemes = builtin_schemes();
+    for scheme in user_schemes {
+        match schemes.iter_mut().find(|existing| existing.name == scheme.name) {
+            Some(existing) => *existing = scheme,
+            None => schemes.push(scheme),
+        }
+    }
+    if SCHEME_REGISTRY.set(schemes).is_err() {
+        log::warn!("Color schemes are already registered, user themes ignored");
+    }
+}
+
+/// Файл темы. Отсутствующие цвета берутся из Dracula.
+#[derive(Debug, Deserialize)]
+struct ThemeFile {
+    name: Option<String>,
+    light: Option<bool>,
+    background: Option<String>,
+    text: Option<String>,
+    highlight_bg: Option<String>,
+    selection_bg: Option<String>,
+    accent: Option<String>,
+    error: Option<String>,
+    dir: Option<String>,
+}
+
+/// Загрузить пользовательские темы из `*.toml` в каталоге `dir`.
+/// Ошибочные файлы пропускаются с записью в лог.
+pub fn load_user_schemes(dir: &Path) -> Vec<ColorScheme> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| match ColorScheme::from_theme_file(&path) {
+            Ok(scheme) => Some(scheme),
+            Err(e) => {
+                log::error!("Skipping theme {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
 }
 
 impl ColorScheme {
-    pub fn name(&self) -> &'static str {
-        match self {
-            ColorScheme::GithubLight => "GitHub Light",
-            ColorScheme::SolarizedLight => "Solarized Light",
-            ColorScheme::AtomLight => "Atom Light",
-            ColorScheme::VisualStudioLight => "Visual Studio Light",
-            ColorScheme::Dracula => "Dracula",
-            ColorScheme::Monokai => "Monokai",
-            ColorScheme::OneDark => "One Dark",
-            ColorScheme::Nord => "Nord",
-            ColorScheme::GruvboxDark => "Gruvbox Dark",
-            ColorScheme::TokyoNight => "Tokyo Night",
-            ColorScheme::MaterialDark => "Material Dark",
-            ColorScheme::Cyberpunk => "Cyberpunk",
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Схема по умолчанию
+    pub fn dracula() -> ColorScheme {
+        Self::from_name("Dracula").expect("Dracula is a built-in scheme")
+    }
+
+    /// Схема без цветов, для `NO_COLOR` и ограниченных терминалов
+    pub fn monochrome() -> ColorScheme {
+        ColorScheme {
+            name: "Monochrome".to_string(),
+            light: false,
+            background: (0, 0, 0),
+            text: (255, 255, 255),
+            highlight_bg: (255, 255, 255),
+            selection_bg: (255, 255, 255),
+            accent: (255, 255, 255),
+            error: (255, 255, 255),
+            dir: (255, 255, 255),
+            monochrome: true,
         }
     }
-    
-    /// Создать ColorScheme из имени
+
+    /// Найти схему по имени среди встроенных и пользовательских
     pub fn from_name(name: &str) -> Option<ColorScheme> {
-        match name {
-            "GitHub Light" => Some(ColorScheme::GithubLight),
-            "Solarized Light" => Some(ColorScheme::SolarizedLight),
-            "Atom Light" => Some(ColorScheme::AtomLight),
-            "Visual Studio Light" => Some(ColorScheme::VisualStudioLight),
-            "Dracula" => Some(ColorScheme::Dracula),
-            "Monokai" => Some(ColorScheme::Monokai),
-            "One Dark" => Some(ColorScheme::OneDark),
-            "Nord" => Some(ColorScheme::Nord),
-            "Gruvbox Dark" => Some(ColorScheme::GruvboxDark),
-            "Tokyo Night" => Some(ColorScheme::TokyoNight),
-            "Material Dark" => Some(ColorScheme::MaterialDark),
-            "Cyberpunk" => Some(ColorScheme::Cyberpunk),
-            _ => None,
-        }
+        scheme_registry().iter().find(|scheme| scheme.name == name).cloned()
     }
-    
+
+    /// Прочитать тему из TOML-файла. Имя по умолчанию — имя файла без расширения.
+    pub fn from_theme_file(path: &Path) -> Result<ColorScheme, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let file: ThemeFile = toml::from_str(&content)?;
+        let name = file.name.clone().unwrap_or_else(|| {
+            path.file_stem().unwrap_or_default().to_string_lossy().to_string()
+        });
+        let fallback = builtin_schemes().into_iter().find(|scheme| scheme.name == "Dracula").unwrap();
+        let color = |key: &str, value: &Option<String>, default: Rgb| -> Result<Rgb, String> {
+            match value {
+                Some(hex) => parse_hex_color(hex).ok_or_else(|| format!("invalid color for `{}`: {:?}", key, hex)),
+                None => Ok(default),
+            }
+        };
+        let background = color("background", &file.background, fallback.background)?;
+        Ok(ColorScheme {
+            name,
+            // Без явного `light` тема считается светлой по яркости фона
+            light: file.light.unwrap_or_else(|| luminance(background) > 0.5),
+            background,
+            text: color("text", &file.text, fallback.text)?,
+            highlight_bg: color("highlight_bg", &file.highlight_bg, fallback.highlight_bg)?,
+            selection_bg: color("selection_bg", &file.selection_bg, fallback.selection_bg)?,
+            accent: color("accent", &file.accent, fallback.accent)?,
+            error: color("error", &file.error, fallback.error)?,
+            dir: color("dir", &file.dir, fallback.dir)?,
+            monochrome: false,
+        })
+    }
+
     /// Получить цвет фона интерфейса (в формате RGB)
-    pub fn background_rgb(&self) -> (u8, u8, u8) {
-        match self {
-            // Светлые темы - светлый фон
-            ColorScheme::GithubLight => (255, 255, 255),
-            ColorScheme::SolarizedLight => (253, 246, 227),
-            ColorScheme::AtomLight => (250, 250, 250),
-            ColorScheme::VisualStudioLight => (245, 245, 245),
-            // Темные темы - темный фон
-            ColorScheme::Dracula => (40, 42, 54),
-            ColorScheme::Monokai => (39, 40, 34),
-            ColorScheme::OneDark => (40, 44, 52),
-            ColorScheme::Nord => (46, 52, 64),
-            ColorScheme::GruvboxDark => (40, 40, 40),
-            ColorScheme::TokyoNight => (25, 26, 35),
-            ColorScheme::MaterialDark => (33, 33, 33),
-            ColorScheme::Cyberpunk => (10, 0, 20),
-        }
+    pub fn background_rgb(&self) -> Rgb {
+        self.background
     }
-    
+
     /// Получить цвет для фона выделенного элемента (в формате RGB)
-    pub fn highlight_bg_rgb(&self) -> (u8, u8, u8) {
-        match self {
-            // Светлые темы - темный выделенный фон
-            ColorScheme::GithubLight => (225, 228, 232),
-            ColorScheme::SolarizedLight => (238, 232, 213),
-            ColorScheme::AtomLight => (225, 228, 232),
-            ColorScheme::VisualStudioLight => (225, 230, 240),
-            // Темные темы - светлый выделенный фон
-            ColorScheme::Dracula => (68, 71, 90),
-            ColorScheme::Monokai => (60, 60, 60),
-            ColorScheme::OneDark => (60, 65, 75),
-            ColorScheme::Nord => (67, 76, 94),
-            ColorScheme::GruvboxDark => (60, 56, 54),
-            ColorScheme::TokyoNight => (45, 47, 63),
-            ColorScheme::MaterialDark => (50, 50, 50),
-            ColorScheme::Cyberpunk => (70, 0, 100),
-        }
+    pub fn highlight_bg_rgb(&self) -> Rgb {
+        self.highlight_bg
     }
-    
+
     /// Получить цвет для текста (в формате RGB)
-    pub fn text_color_rgb(&self) -> (u8, u8, u8) {
-        match self {
-            // Светлые темы - темный текст
-            ColorScheme::GithubLight => (36, 41, 46),
-            ColorScheme::SolarizedLight => (101, 123, 131),
-            ColorScheme::AtomLight => (33, 37, 43),
-            ColorScheme::VisualStudioLight => (30, 30, 30),
-            // Темные темы - светлый текст
-            ColorScheme::Dracula => (248, 248, 242),
-            ColorScheme::Monokai => (248, 248, 242),
-            ColorScheme::OneDark => (171, 178, 191),
-            ColorScheme::Nord => (216, 222, 233),
-            ColorScheme::GruvboxDark => (235, 219, 178),
-            ColorScheme::TokyoNight => (195, 200, 210),
-            ColorScheme::MaterialDark => (220, 220, 220),
-            ColorScheme::Cyberpunk => (255, 0, 255),
-        }
+    pub fn text_color_rgb(&self) -> Rgb {
+        self.text
     }
-    
-    /// Преобразовать RGB цвет в формат ratatui::style::Color
-    pub fn rgb_to_ratatui_color(&self, rgb: (u8, u8, u8)) -> ratatui::style::Color {
-        ratatui::style::Color::Rgb(rgb.0, rgb.1, rgb.2)
+
+    /// Преобразовать RGB цвет в формат ratatui::style::Color.
+    /// Без поддержки truecolor берётся ближайший цвет из палитры
+    /// терминала, в монохромной схеме — всегда цвет по умолчанию.
+    pub fn rgb_to_ratatui_color(&self, rgb: Rgb) -> ratatui::style::Color {
+        if self.monochrome {
+            return ratatui::style::Color::Reset;
+        }
+        rgb_color(rgb)
     }
-    
+
+    /// Фон, которым монохромная схема отмечает курсор и выделение.
+    /// При отрисовке он заменяется инверсией цветов терминала.
+    pub const MONOCHROME_MARK: ratatui::style::Color = ratatui::style::Color::Gray;
+
     /// Получить цвет фона интерфейса в формате ratatui
     pub fn background(&self) -> ratatui::style::Color {
-        self.rgb_to_ratatui_color(self.background_rgb())
+        self.rgb_to_ratatui_color(self.background)
     }
-    
+
     /// Получить цвет для фона выделенного элемента в формате ratatui
     pub fn highlight_bg(&self) -> ratatui::style::Color {
-        self.rgb_to_ratatui_color(self.highlight_bg_rgb())
+        if self.monochrome {
+            return Self::MONOCHROME_MARK;
+        }
+        self.rgb_to_ratatui_color(self.highlight_bg)
     }
 
     /// Получить цвет для фона выделенных элементов (для множественного выбора)
     pub fn selection_bg(&self) -> ratatui::style::Color {
-        let rgb = match self {
-            // Светлые темы
-            ColorScheme::GithubLight => (200, 220, 240),
-            ColorScheme::SolarizedLight => (220, 225, 200),
-            ColorScheme::AtomLight => (210, 215, 220),
-            ColorScheme::VisualStudioLight => (210, 215, 225),
-            // Темные темы
-            ColorScheme::Dracula => (56, 60, 74),
-            ColorScheme::Monokai => (50, 50, 50),
-            ColorScheme::OneDark => (50, 55, 65),
-            ColorScheme::Nord => (59, 66, 82),
-            ColorScheme::GruvboxDark => (50, 48, 47),
-            ColorScheme::TokyoNight => (35, 37, 50),
-            ColorScheme::MaterialDark => (40, 40, 40),
-            ColorScheme::Cyberpunk => (50, 0, 80),
-        };
-        self.rgb_to_ratatui_color(rgb)
+        if self.monochrome {
+            return Self::MONOCHROME_MARK;
+        }
+        self.rgb_to_ratatui_color(self.selection_bg)
     }
-    
+
     /// Получить цвет для текста в формате ratatui
     pub fn text_color(&self) -> ratatui::style::Color {
-        self.rgb_to_ratatui_color(self.text_color_rgb())
+        self.rgb_to_ratatui_color(self.text)
     }
-    
+
+    /// Акцентный цвет, например для рамки активной панели
+    pub fn accent(&self) -> ratatui::style::Color {
+        self.rgb_to_ratatui_color(self.accent)
+    }
+
+    /// Цвет сообщений об ошибках
+    pub fn error_color(&self) -> ratatui::style::Color {
+        self.rgb_to_ratatui_color(self.error)
+    }
+
+    /// Светлая ли тема
+    pub fn is_light(&self) -> bool {
+        self.light
+    }
+
+    /// Получить цвет имени файла для категории (в формате RGB)
+    pub fn file_type_rgb(&self, category: FileCategory) -> Option<Rgb> {
+        let rgb = if self.monochrome {
+            return None;
+        } else if self.is_light() {
+            match category {
+                FileCategory::Directory => self.dir,
+                FileCategory::Symlink => (0, 120, 150),
+                FileCategory::Executable => (34, 134, 58),
+                FileCategory::Archive => (190, 40, 40),
+                FileCategory::Image => (160, 40, 140),
+                FileCategory::Audio => (150, 120, 0),
+                FileCategory::Video => (180, 90, 0),
+                FileCategory::Document => (100, 60, 170),
+                FileCategory::Other => return None,
+            }
+        } else {
+            match category {
+                FileCategory::Directory => self.dir,
+                FileCategory::Symlink => (139, 233, 253),
+                FileCategory::Executable => (80, 250, 123),
+                FileCategory::Archive => (255, 85, 85),
+                FileCategory::Image => (255, 121, 198),
+                FileCategory::Audio => (241, 250, 140),
+                FileCategory::Video => (255, 184, 108),
+                FileCategory::Document => (189, 147, 249),
+                FileCategory::Other => return None,
+            }
+        };
+        Some(rgb)
+    }
+
+    /// Встроенные схемы, затем пользовательские
     pub fn all() -> Vec<ColorScheme> {
-        vec![
-            ColorScheme::GithubLight,
-            ColorScheme::SolarizedLight,
-            ColorScheme::AtomLight,
-            ColorScheme::VisualStudioLight,
-            ColorScheme::Dracula,
-            ColorScheme::Monokai,
-            ColorScheme::OneDark,
-            ColorScheme::Nord,
-            ColorScheme::GruvboxDark,
-            ColorScheme::TokyoNight,
-            ColorScheme::MaterialDark,
-            ColorScheme::Cyberpunk,
-        ]
+        scheme_registry().to_vec()
+    }
+}
+
+/// Относительная яркость цвета от 0 до 1
+fn luminance((r, g, b): Rgb) -> f32 {
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+}
+
+/// Цвета имён файлов по категориям с учётом переопределений из конфигурации
+#[derive(Debug, Clone)]
+pub struct FileTypePalette {
+    colors: HashMap<FileCategory, (u8, u8, u8)>,
+    background: (u8, u8, u8),
+}
+
+impl FileTypePalette {
+    /// Собрать палитру из цветовой схемы и переопределений `theme.file_colors`
+    pub fn new(color_scheme: &ColorScheme, overrides: &HashMap<String, String>) -> Self {
+        let mut colors = HashMap::new();
+        // Монохромная схема не раскрашивает имена и по переопределениям
+        let categories = if color_scheme.monochrome { Vec::new() } else { FileCategory::all().to_vec() };
+        for category in categories {
+            let rgb = overrides
+                .get(category.key())
+                .and_then(|hex| parse_hex_color(hex))
+                .or_else(|| color_scheme.file_type_rgb(category));
+            if let Some(rgb) = rgb {
+                colors.insert(category, rgb);
+            }
+        }
+        Self {
+            colors,
+            background: color_scheme.background_rgb(),
+        }
+    }
+    
+    /// Цвет иконки для категории
+    pub fn icon_color(&self, category: FileCategory) -> Option<ratatui::style::Color> {
+        self.colors
+            .get(&category)
+            .map(|&rgb| rgb_color(rgb))
+    }
+    
+    /// Цвет имени файла: цвет категории, приглушённый к фону относительно иконки
+    pub fn name_color(&self, category: FileCategory) -> Option<ratatui::style::Color> {
+        self.colors.get(&category).map(|&rgb| {
+            rgb_color(blend(rgb, self.background, 0.25))
+        })
+    }
+}
+
+/// Разобрать цвет вида `#rrggbb`
+pub fn parse_hex_color(hex: &str) -> Option<(u8, u8, u8)> {
+    let hex = hex.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
     }
+    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+    Some((r, g, b))
+}
+
+fn blend(color: (u8, u8, u8), towards: (u8, u8, u8), amount: f32) -> (u8, u8, u8) {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    (mix(color.0, towards.0), mix(color.1, towards.1), mix(color.2, towards.2))
 }
 
 /// Принцип сортировки файлов
@@ -184,6 +484,153 @@ pub enum SortOrder {
     Descending,
 }
 
+impl SortOrder {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "ascending",
+            SortOrder::Descending => "descending",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SortOrder> {
+        match name {
+            "ascending" => Some(SortOrder::Ascending),
+            "descending" => Some(SortOrder::Descending),
+            _ => None,
+        }
+    }
+
+    pub fn reversed(&self) -> SortOrder {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        }
+    }
+}
+
+/// Язык интерфейса
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+}
+
+impl Language {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Russian => "ru",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Language> {
+        match name {
+            "en" => Some(Language::English),
+            "ru" => Some(Language::Russian),
+            _ => None,
+        }
+    }
+
+    /// Язык системной локали из `LC_ALL`, `LC_MESSAGES` или `LANG`
+    /// (первая непустая переменная, как в POSIX). Русский для `ru_*`,
+    /// иначе английский.
+    pub fn from_locale() -> Language {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        Self::from_locale_name(&locale)
+    }
+
+    fn from_locale_name(locale: &str) -> Language {
+        if locale.starts_with("ru") {
+            Language::Russian
+        } else {
+            Language::English
+        }
+    }
+
+    pub fn next(&self) -> Language {
+        match self {
+            Language::English => Language::Russian,
+            Language::Russian => Language::English,
+        }
+    }
+
+    /// Название языка на нём самом
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Russian => "Русский",
+        }
+    }
+
+    /// Выбирает вариант строки для этого языка
+    pub fn pick<T>(self, english: T, russian: T) -> T {
+        match self {
+            Language::English => english,
+            Language::Russian => russian,
+        }
+    }
+}
+
+/// Единицы, в которых показываются размеры
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeUnits {
+    /// Степени 1024: KiB, MiB
+    #[default]
+    Binary,
+    /// Степени 1000: kB, MB
+    Decimal,
+}
+
+impl SizeUnits {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SizeUnits::Binary => "binary",
+            SizeUnits::Decimal => "decimal",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SizeUnits> {
+        match name {
+            "binary" => Some(SizeUnits::Binary),
+            "decimal" => Some(SizeUnits::Decimal),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> SizeUnits {
+        match self {
+            SizeUnits::Binary => SizeUnits::Decimal,
+            SizeUnits::Decimal => SizeUnits::Binary,
+        }
+    }
+
+    /// Во сколько раз каждая единица больше предыдущей
+    pub fn base(&self) -> u64 {
+        match self {
+            SizeUnits::Binary => 1024,
+            SizeUnits::Decimal => 1000,
+        }
+    }
+
+    pub fn format_options(&self) -> humansize::FormatSizeOptions {
+        match self {
+            SizeUnits::Binary => humansize::BINARY,
+            SizeUnits::Decimal => humansize::DECIMAL,
+        }
+    }
+}
+
+/// Размер в единицах из настроек: `1.50 MiB` или `1.57 MB`.
+/// Все размеры в интерфейсе форматируются через эту функцию.
+pub fn format_size_with_config(size: u64, units: SizeUnits) -> String {
+    humansize::format_size(size, units.format_options())
+}
+
 /// Поле сортировки файлов
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub enum SortField {
@@ -192,6 +639,41 @@ pub enum SortField {
     Modified,
 }
 
+impl SortField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortField::Name => "name",
+            SortField::Size => "size",
+            SortField::Modified => "date",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SortField> {
+        match name {
+            "name" => Some(SortField::Name),
+            "size" => Some(SortField::Size),
+            "date" => Some(SortField::Modified),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> SortField {
+        match self {
+            SortField::Name => SortField::Size,
+            SortField::Size => SortField::Modified,
+            SortField::Modified => SortField::Name,
+        }
+    }
+}
+
+/// Ширина предпросмотра по умолчанию: список и предпросмотр поровну
+pub const DEFAULT_PREVIEW_WIDTH: u16 = 50;
+/// Пределы ширины предпросмотра, чтобы ни одна из панелей не исчезла
+pub const MIN_PREVIEW_WIDTH: u16 = 20;
+pub const MAX_PREVIEW_WIDTH: u16 = 80;
+/// Шаг изменения ширины предпросмотра по Ctrl+Left и Ctrl+Right
+pub const PREVIEW_WIDTH_STEP: u16 = 5;
+
 /// Настройки отображения интерфейса
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DisplaySettings {
@@ -203,6 +685,16 @@ pub struct DisplaySettings {
     pub show_footer: bool,
     /// Показывать скрытые файлы
     pub show_hidden_files: bool,
+    /// Показывать колонки размера и даты
+    pub show_file_details: bool,
+    /// Строка подсказок с клавишами
+    pub show_key_hints: bool,
+    /// Ширина предпросмотра в процентах от места справа от левой панели
+    pub preview_width: u16,
+    /// Язык интерфейса
+    pub language: Language,
+    /// Двоичные или десятичные единицы размеров
+    pub size_units: SizeUnits,
     /// Цветовая схема
     pub color_scheme: ColorScheme,
 }
@@ -214,13 +706,19 @@ impl Default for DisplaySettings {
             show_tabs: true,
             show_footer: true,
             show_hidden_files: false,
-            color_scheme: ColorScheme::Dracula,
+            show_file_details: true,
+            show_key_hints: true,
+            preview_width: DEFAULT_PREVIEW_WIDTH,
+            language: Language::default(),
+            size_units: SizeUnits::default(),
+            color_scheme: ColorScheme::dracula(),
         }
     }
 }
 
 /// Настройки сортировки файлов
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct SortSettings {
     /// Поле сортировки
     pub field: SortField,
@@ -228,6 +726,8 @@ pub struct SortSettings {
     pub order: SortOrder,
     /// Разделять файлы и каталоги
     pub separate_dirs: bool,
+    /// Естественная сортировка имён: `file2` перед `file10`, без учёта регистра
+    pub natural: bool,
 }
 
 impl Default for SortSettings {
@@ -236,10 +736,77 @@ impl Default for SortSettings {
             field: SortField::Name,
             order: SortOrder::Ascending,
             separate_dirs: true,
+            natural: true,
+        }
+    }
+}
+
+impl SortSettings {
+    /// Сравнивает записи каталога по полю и порядку сортировки.
+    /// При `separate_dirs` каталоги всегда идут перед файлами.
+    pub fn compare(&self, a: &DirEntry, b: &DirEntry) -> Ordering {
+        let by_name = || if self.natural { natural_cmp(&a.name, &b.name) } else { a.name.cmp(&b.name) };
+        let by_field = match self.field {
+            SortField::Name => by_name(),
+            SortField::Size => a.size.cmp(&b.size).then_with(by_name),
+            SortField::Modified => a.modified.cmp(&b.modified).then_with(by_name),
+        };
+        let by_field = match self.order {
+            SortOrder::Ascending => by_field,
+            SortOrder::Descending => by_field.reverse(),
+        };
+        if self.separate_dirs {
+            b.is_dir.cmp(&a.is_dir).then(by_field)
+        } else {
+            by_field
+        }
+    }
+}
+
+/// Сравнивает имена «по-человечески»: последовательности цифр сравниваются
+/// как числа, буквы — без учёта регистра. При равенстве решает обычное сравнение,
+/// чтобы порядок оставался строгим.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut left = a.chars().peekable();
+    let mut right = b.chars().peekable();
+    loop {
+        match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x_digits = take_digits(&mut left);
+                let y_digits = take_digits(&mut right);
+                let x_trimmed = x_digits.trim_start_matches('0');
+                let y_trimmed = y_digits.trim_start_matches('0');
+                let ordering = x_trimmed
+                    .len()
+                    .cmp(&y_trimmed.len())
+                    .then_with(|| x_trimmed.cmp(y_trimmed));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                left.next();
+                right.next();
+            }
         }
     }
 }
 
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        digits.push(c);
+    }
+    digits
+}
+
 /// Настройки предпросмотра
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PreviewSettings {
@@ -247,6 +814,12 @@ pub struct PreviewSettings {
     pub enabled: bool,
     /// Максимальный размер файла для предпросмотра (в байтах)
     pub max_preview_size: u64,
+    /// Сколько первых строк текстового файла читать для предпросмотра
+    pub max_lines: usize,
+    /// Считать в фоне общий размер каталога под курсором
+    pub directory_sizes: bool,
+    /// Показывать у файлов `.log` последние строки вместо первых
+    pub tail_logs: bool,
     /// Разрешение для предпросмотра изображений
     pub image_preview_resolution: (u32, u32),
 }
@@ -256,6 +829,9 @@ impl Default for PreviewSettings {
         Self {
             enabled: true,
             max_preview_size: 1024 * 1024, // 1MB
+            max_lines: io::fs_ops::PREVIEW_LINE_COUNT,
+            directory_sizes: true,
+            tail_logs: true,
             image_preview_resolution: (800, 600),
         }
     }
@@ -282,6 +858,21 @@ impl Default for BehaviorSettings {
     }
 }
 
+/// Значения, выбранные в диалоге архивации по умолчанию
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ArchiveSettings {
+    /// Формат архива
+    pub default_format: ArchiveFormat,
+    /// Степень сжатия от 0 (без сжатия) до 9
+    pub compression_level: u32,
+}
+
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        Self { default_format: ArchiveFormat::Zip, compression_level: 6 }
+    }
+}
+
 /// Основная структура настроек приложения
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Settings {
@@ -293,8 +884,10 @@ pub struct Settings {
     pub preview: PreviewSettings,
     /// Настройки поведения
     pub behavior: BehaviorSettings,
+    /// Настройки архивации
+    pub archive: ArchiveSettings,
     /// Закладки
-    pub bookmarks: HashMap<String, PathBuf>,
+    pub bookmarks: Vec<Bookmark>,
 }
 
 impl Default for Settings {
@@ -304,7 +897,8 @@ impl Default for Settings {
             sort: SortSettings::default(),
             preview: PreviewSettings::default(),
             behavior: BehaviorSettings::default(),
-            bookmarks: HashMap::new(),
+            archive: ArchiveSettings::default(),
+            bookmarks: Vec::new(),
         }
     }
 }
@@ -315,11 +909,265 @@ impl Settings {
         Self::default()
     }
     
+    /// Прочитать настройки из разделов `display`, `sort`, `preview`,
+    /// `behavior`, `archive` и `theme` конфигурации. Неизвестные значения
+    /// заменяются значениями по умолчанию.
+    pub fn from_config(config: &Config) -> Self {
+        let defaults = Self::default();
+        Self {
+            display: DisplaySettings {
+                show_tabs: config.display.show_tabs,
+                show_hidden_files: config.display.show_hidden_files,
+                show_file_details: config.display.show_file_details,
+                show_key_hints: config.display.show_key_hints,
+                show_left_pane: config.display.show_left_pane,
+                show_footer: config.display.show_footer,
+                preview_width: config.display.preview_width.clamp(MIN_PREVIEW_WIDTH, MAX_PREVIEW_WIDTH),
+                language: config
+                    .display
+                    .language
+                    .as_deref()
+                    .and_then(Language::from_name)
+                    .unwrap_or_else(Language::from_locale),
+                size_units: SizeUnits::from_name(&config.display.size_units).unwrap_or_default(),
+                color_scheme: config
+                    .theme
+                    .color_scheme
+                    .as_deref()
+                    .and_then(ColorScheme::from_name)
+                    .unwrap_or(defaults.display.color_scheme.clone()),
+            },
+            sort: SortSettings {
+                field: SortField::from_name(&config.sort.field).unwrap_or(defaults.sort.field),
+                order: SortOrder::from_name(&config.sort.order).unwrap_or(defaults.sort.order),
+                separate_dirs: config.sort.separate_dirs,
+                natural: config.sort.natural,
+            },
+            preview: PreviewSettings {
+                enabled: config.preview.enabled,
+                max_preview_size: config.preview.max_size,
+                max_lines: config.preview.max_lines.max(1),
+                directory_sizes: config.preview.directory_sizes,
+                tail_logs: config.preview.tail_logs,
+                image_preview_resolution: (config.preview.resolution.width, config.preview.resolution.height),
+            },
+            behavior: BehaviorSettings {
+                confirm_delete: config.behavior.confirm_delete,
+                confirm_overwrite: config.behavior.confirm_overwrite,
+                ..defaults.behavior
+            },
+            archive: ArchiveSettings {
+                default_format: ArchiveFormat::from_name(&config.archive.default_format)
+                    .unwrap_or(defaults.archive.default_format),
+                compression_level: config.archive.compression_level.min(MAX_COMPRESSION_LEVEL),
+            },
+            bookmarks: config.bookmarks.clone(),
+        }
+    }
+
+    /// Собрать настройки из текущего состояния приложения
+    pub fn from_app_state(app_state: &crate::app_state::AppState) -> Self {
+        Self {
+            display: DisplaySettings {
+                show_tabs: app_state.show_tabs,
+                show_hidden_files: app_state.show_hidden_files,
+                show_file_details: app_state.show_file_details,
+                show_key_hints: app_state.show_key_hints,
+                show_left_pane: app_state.show_left_pane,
+                show_footer: app_state.show_footer,
+                preview_width: app_state.preview_width,
+                language: app_state.language,
+                size_units: app_state.size_units,
+                color_scheme: app_state.configured_color_scheme(),
+            },
+            sort: app_state.sort_settings.clone(),
+            preview: app_state.preview_settings.clone(),
+            behavior: app_state.behavior_settings.clone(),
+            archive: app_state.archive_settings.clone(),
+            bookmarks: app_state.bookmarks.clone(),
+        }
+    }
+
+    /// Записать настройки в соответствующие разделы конфигурации.
+    /// Закладки сохраняются отдельно и здесь не трогаются.
+    pub fn store_in(&self, config: &mut Config) {
+        config.display.show_tabs = self.display.show_tabs;
+        config.display.show_hidden_files = self.display.show_hidden_files;
+        config.display.show_file_details = self.display.show_file_details;
+        config.display.show_key_hints = self.display.show_key_hints;
+        config.display.show_left_pane = self.display.show_left_pane;
+        config.display.show_footer = self.display.show_footer;
+        config.display.preview_width = self.display.preview_width;
+        // Язык, взятый из локали, в файл не пишется, чтобы и дальше следовать ей
+        if config.display.language.is_some() || self.display.language != Language::from_locale() {
+            config.display.language = Some(self.display.language.name().to_string());
+        }
+        config.display.size_units = self.display.size_units.name().to_string();
+        config.theme.color_scheme = Some(self.display.color_scheme.name().to_string());
+        config.sort.field = self.sort.field.name().to_string();
+        config.sort.order = self.sort.order.name().to_string();
+        config.sort.separate_dirs = self.sort.separate_dirs;
+        config.sort.natural = self.sort.natural;
+        config.preview.enabled = self.preview.enabled;
+        config.preview.max_size = self.preview.max_preview_size;
+        config.preview.max_lines = self.preview.max_lines;
+        config.preview.directory_sizes = self.preview.directory_sizes;
+        config.preview.tail_logs = self.preview.tail_logs;
+        config.preview.resolution.width = self.preview.image_preview_resolution.0;
+        config.preview.resolution.height = self.preview.image_preview_resolution.1;
+        config.behavior.confirm_delete = self.behavior.confirm_delete;
+        config.behavior.confirm_overwrite = self.behavior.confirm_overwrite;
+        config.archive.default_format = self.archive.default_format.name().to_string();
+        config.archive.compression_level = self.archive.compression_level;
+    }
+
     /// Применить настройки к состоянию приложения
     pub fn apply_to_app_state(&self, app_state: &mut crate::app_state::AppState) {
+        app_state.show_tabs = self.display.show_tabs;
         app_state.show_hidden_files = self.display.show_hidden_files;
-        
-        // Применяем другие настройки по мере необходимости
-        // ...
+        app_state.show_file_details = self.display.show_file_details;
+        app_state.show_key_hints = self.display.show_key_hints;
+        app_state.show_left_pane = self.display.show_left_pane;
+        app_state.show_footer = self.display.show_footer;
+        app_state.preview_width = self.display.preview_width;
+        app_state.language = self.display.language;
+        app_state.size_units = self.display.size_units;
+        app_state.sort_settings = self.sort.clone();
+        app_state.preview_settings = self.preview.clone();
+        app_state.behavior_settings = self.behavior.clone();
+        app_state.archive_settings = self.archive.clone();
+        app_state.archive_format = self.archive.default_format;
+        app_state.archive_compression_level = self.archive.compression_level;
+        app_state.apply_sort_settings();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monochrome_scheme_uses_terminal_colors() {
+        use ratatui::style::Color;
+        let scheme = ColorScheme::from_name("Monochrome").unwrap();
+        assert!(scheme.monochrome);
+        assert_eq!(scheme.background(), Color::Reset);
+        assert_eq!(scheme.text_color(), Color::Reset);
+        assert_eq!(scheme.accent(), Color::Reset);
+        assert_eq!(scheme.highlight_bg(), ColorScheme::MONOCHROME_MARK);
+
+        let overrides = HashMap::from([("image".to_string(), "#ff0000".to_string())]);
+        let palette = FileTypePalette::new(&scheme, &overrides);
+        assert_eq!(palette.icon_color(FileCategory::Image), None);
+        assert_eq!(palette.name_color(FileCategory::Directory), None);
+    }
+
+    #[test]
+    fn test_colors_degrade_without_truecolor() {
+        use ratatui::style::Color;
+        assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm-256color")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm-256color")), ColorDepth::Indexed256);
+        assert_eq!(ColorDepth::from_env(None, Some("linux")), ColorDepth::Basic16);
+
+        assert_eq!(ColorDepth::TrueColor.color((1, 2, 3)), Color::Rgb(1, 2, 3));
+        assert_eq!(ColorDepth::Indexed256.color((255, 0, 0)), Color::Indexed(196));
+        assert_eq!(ColorDepth::Indexed256.color((0x87, 0xaf, 0xff)), Color::Indexed(111));
+        assert_eq!(ColorDepth::Indexed256.color((40, 40, 40)), Color::Indexed(235));
+        assert_eq!(ColorDepth::Basic16.color((30, 30, 46)), Color::Black);
+        assert_eq!(ColorDepth::Basic16.color((250, 80, 80)), Color::LightRed);
+    }
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn test_natural_cmp_orders_numbers_numerically() {
+        assert_eq!(sorted(&["file10.txt", "file2.txt", "file1.txt"]), vec!["file1.txt", "file2.txt", "file10.txt"]);
+        assert_eq!(sorted(&["v1.10", "v1.9", "v1.2"]), vec!["v1.2", "v1.9", "v1.10"]);
+    }
+
+    #[test]
+    fn test_natural_cmp_ignores_case_and_stays_total() {
+        assert_eq!(sorted(&["banana", "Apple", "cherry"]), vec!["Apple", "banana", "cherry"]);
+        assert_eq!(natural_cmp("img01", "img1"), "img01".cmp("img1"));
+        assert_ne!(natural_cmp("README", "readme"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_user_themes_fall_back_to_dracula_and_skip_invalid_files() {
+        let dir = tempdir::TempDir::new("themes").unwrap();
+        std::fs::write(
+            dir.path().join("paper.toml"),
+            "background = \"#fafafa\"\ntext = \"#202020\"\naccent = \"#0066cc\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("broken.toml"), "background = \"not a color\"\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let schemes = load_user_schemes(dir.path());
+        assert_eq!(schemes.len(), 1);
+        let paper = &schemes[0];
+        let dracula = ColorScheme::dracula();
+        assert_eq!(paper.name(), "paper");
+        assert!(paper.is_light());
+        assert_eq!(paper.accent, (0, 102, 204));
+        assert_eq!(paper.highlight_bg, dracula.highlight_bg);
+        assert_eq!(paper.error, dracula.error);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_language_follows_locale_unless_configured() {
+        assert_eq!(Language::from_locale_name("ru_RU.UTF-8"), Language::Russian);
+        assert_eq!(Language::from_locale_name("en_GB.UTF-8"), Language::English);
+        assert_eq!(Language::from_locale_name("C"), Language::English);
+
+        let mut config = Config::default();
+        config.display.language = Some("ru".to_string());
+        assert_eq!(Settings::from_config(&config).display.language, Language::Russian);
+        config.display.language = Some("en".to_string());
+        assert_eq!(Settings::from_config(&config).display.language, Language::English);
+    }
+
+    #[test]
+    fn test_settings_round_trip_through_config() {
+        let mut settings = Settings::default();
+        settings.display.show_hidden_files = true;
+        settings.display.color_scheme = ColorScheme::from_name("Nord").unwrap();
+        settings.display.size_units = SizeUnits::Decimal;
+        settings.display.show_left_pane = false;
+        settings.display.preview_width = 35;
+        settings.sort.field = SortField::Modified;
+        settings.sort.order = SortOrder::Descending;
+        settings.preview.enabled = false;
+        settings.preview.max_preview_size = 4 * 1024 * 1024;
+        settings.preview.max_lines = 1000;
+        settings.behavior.confirm_delete = false;
+        settings.archive.default_format = ArchiveFormat::TarGz;
+        settings.archive.compression_level = 0;
+
+        let mut config = Config::default();
+        settings.store_in(&mut config);
+        let toml = toml::to_string_pretty(&config).unwrap();
+        let config: Config = toml::from_str(&toml).unwrap();
+        let loaded = Settings::from_config(&config);
+
+        assert!(loaded.display.show_hidden_files);
+        assert_eq!(loaded.display.color_scheme.name(), "Nord");
+        assert_eq!(loaded.display.size_units, SizeUnits::Decimal);
+        assert!(!loaded.display.show_left_pane);
+        assert!(loaded.display.show_footer);
+        assert_eq!(loaded.display.preview_width, 35);
+        assert_eq!(format_size_with_config(1_500_000, loaded.display.size_units), "1.50 MB");
+        assert_eq!(format_size_with_config(1_500_000, SizeUnits::Binary), "1.43 MiB");
+        assert_eq!(loaded.sort, settings.sort);
+        assert!(!loaded.preview.enabled);
+        assert_eq!(loaded.preview.max_preview_size, 4 * 1024 * 1024);
+        assert_eq!(loaded.preview.max_lines, 1000);
+        assert!(!loaded.behavior.confirm_delete);
+        assert!(loaded.behavior.confirm_overwrite);
+        assert_eq!(loaded.archive, settings.archive);
+    }
+}
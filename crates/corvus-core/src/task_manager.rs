@@ -1,27 +1,45 @@
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
-use std::path::PathBuf;
-use tokio::sync::mpsc;
+use std::path::{Path, PathBuf};
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
 use io::fs_ops;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum ArchiveFormat {
-    Zip,
-    Tar,
-    TarGz,
-}
+pub use io::fs_ops::ArchiveFormat;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TaskKind {
     Copy { src: PathBuf, dest: PathBuf },
     Move { src: PathBuf, dest: PathBuf },
     Delete { path: PathBuf },
+    /// Перемещение в корзину `trash` вместо удаления
+    Trash { path: PathBuf, trash: PathBuf },
     CreateFile { path: PathBuf },
     CreateDirectory { path: PathBuf },
     Chmod { path: PathBuf, mode: u32 },
     Chown { path: PathBuf, owner: String },
     Unmount { path: PathBuf },
-    Archive { paths: Vec<PathBuf>, dest: PathBuf, format: String },
+    /// Отмонтирование всех разделов съёмного диска `device` и его отключение.
+    /// `partitions` — устройства разделов, `mountpoints` — их точки монтирования.
+    Eject {
+        device: PathBuf,
+        #[serde(default)]
+        partitions: Vec<PathBuf>,
+        mountpoints: Vec<PathBuf>,
+    },
+    /// Монтирование удалённого каталога `url` через sshfs или davfs
+    MountRemote { url: String, mountpoint: PathBuf },
+    /// Отмонтирование удалённого каталога и удаление точки монтирования
+    UnmountRemote { url: String, mountpoint: PathBuf },
+    /// `level` — степень сжатия от 0 (без сжатия) до 9
+    Archive { paths: Vec<PathBuf>, dest: PathBuf, format: ArchiveFormat, level: u32 },
+    Extract { archive: PathBuf, dest: PathBuf },
+    /// Пользовательская команда из `[[commands]]`, выполняемая через `sh -c`
+    RunCommand { name: String, command: String, dir: PathBuf },
+    /// Клонирование плагина из git в каталог плагинов
+    InstallPlugin { url: String, dest: PathBuf },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -38,6 +56,201 @@ pub struct Task {
     pub kind: TaskKind,
     pub status: TaskStatus,
     pub description: String,
+    /// Когда задача завершилась успешно или с ошибкой
+    pub finished_at: Option<Instant>,
+    /// Задача, после успешного завершения которой запускается эта
+    pub parent: Option<Uuid>,
+    /// Общий id задач, поставленных одной операцией над многими файлами.
+    /// В списке задач группа показывается одной строкой.
+    pub group: Option<Uuid>,
+    /// Стандартный вывод команды
+    pub output: String,
+}
+
+/// Незавершённая задача в виде, пригодном для сохранения между запусками
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTask {
+    pub kind: TaskKind,
+    pub description: String,
+    /// Индекс родительской задачи в том же списке
+    #[serde(default)]
+    pub parent: Option<usize>,
+    /// Индекс первой задачи той же группы в этом списке
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<usize>,
+}
+
+/// Событие выполнения задачи, которое получает главный цикл
+#[derive(Debug, Clone)]
+pub enum TaskEvent {
+    /// Задача продвинулась, но ещё не завершилась
+    Progress { id: Uuid, progress: f32 },
+    Completed { id: Uuid, kind: TaskKind, summary: String },
+    Failed { id: Uuid, kind: TaskKind, description: String, error: String },
+    /// Завершилась задача из группы. `report` есть, если вместе с ней
+    /// завершилась и вся группа
+    GroupItemFinished { id: Uuid, kind: TaskKind, report: Option<GroupReport> },
+}
+
+/// Итог группы задач: сводка и ошибки всех неудачных задач
+#[derive(Debug, Clone)]
+pub struct GroupReport {
+    pub summary: String,
+    pub errors: Vec<String>,
+}
+
+/// Имя файла для сообщений, а если его нет — весь путь
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn parent_dir(path: &Path) -> Option<PathBuf> {
+    path.parent().map(|parent| parent.to_path_buf())
+}
+
+impl TaskKind {
+    /// Краткое описание выполненной задачи для уведомления
+    pub fn summary(&self) -> String {
+        match self {
+            TaskKind::Copy { src, .. } => format!("Copied {}", display_name(src)),
+            TaskKind::Move { src, dest } => format!("Moved {} to {}", display_name(src), display_name(dest)),
+            TaskKind::Delete { path } => format!("Deleted {}", display_name(path)),
+            TaskKind::Trash { path, .. } => format!("Moved {} to the trash", display_name(path)),
+            TaskKind::CreateFile { path } => format!("Created file {}", display_name(path)),
+            TaskKind::CreateDirectory { path } => format!("Created folder {}", display_name(path)),
+            TaskKind::Chmod { path, mode } => format!("Changed permissions of {} to {:o}", display_name(path), mode),
+            TaskKind::Chown { path, owner } => format!("Changed owner of {} to {}", display_name(path), owner),
+            TaskKind::Unmount { path } => format!("Unmounted {}", path.display()),
+            TaskKind::Eject { device, .. } => format!("Ejected {}, it can be unplugged", display_name(device)),
+            TaskKind::MountRemote { url, .. } => format!("Connected {}", url),
+            TaskKind::UnmountRemote { url, .. } => format!("Disconnected {}", url),
+            TaskKind::Archive { dest, .. } => format!("Archive {} created successfully", display_name(dest)),
+            TaskKind::Extract { archive, dest } => format!("Extracted {} to {}", display_name(archive), display_name(dest)),
+            TaskKind::RunCommand { name, .. } => format!("{} finished", name),
+            TaskKind::InstallPlugin { url, .. } => format!("Cloned {}", url),
+        }
+    }
+
+    /// Пути, которые должны существовать к началу выполнения задачи
+    pub fn source_paths(&self) -> Vec<&Path> {
+        match self {
+            TaskKind::Copy { src: path, .. }
+            | TaskKind::Move { src: path, .. }
+            | TaskKind::Delete { path }
+            | TaskKind::Trash { path, .. }
+            | TaskKind::Chmod { path, .. }
+            | TaskKind::Chown { path, .. }
+            | TaskKind::Unmount { path }
+            | TaskKind::UnmountRemote { mountpoint: path, .. }
+            | TaskKind::Extract { archive: path, .. } => vec![path.as_path()],
+            TaskKind::Archive { paths, .. } | TaskKind::Eject { mountpoints: paths, .. } => {
+                paths.iter().map(|path| path.as_path()).collect()
+            }
+            TaskKind::CreateFile { .. }
+            | TaskKind::CreateDirectory { .. }
+            | TaskKind::MountRemote { .. }
+            | TaskKind::RunCommand { .. }
+            | TaskKind::InstallPlugin { .. } => Vec::new(),
+        }
+    }
+
+    /// Каталоги, содержимое которых меняет задача
+    pub fn affected_dirs(&self) -> Vec<PathBuf> {
+        match self {
+            TaskKind::Move { src, dest } => parent_dir(src).into_iter().chain(parent_dir(dest)).collect(),
+            TaskKind::Copy { dest: path, .. }
+            | TaskKind::Delete { path }
+            | TaskKind::Trash { path, .. }
+            | TaskKind::CreateFile { path }
+            | TaskKind::CreateDirectory { path }
+            | TaskKind::Chmod { path, .. }
+            | TaskKind::Chown { path, .. }
+            | TaskKind::Archive { dest: path, .. } => parent_dir(path).into_iter().collect(),
+            // Распаковка идёт либо в новый подкаталог, либо прямо в каталог архива
+            TaskKind::Unmount { path }
+            | TaskKind::MountRemote { mountpoint: path, .. }
+            | TaskKind::UnmountRemote { mountpoint: path, .. }
+            | TaskKind::Extract { dest: path, .. } => {
+                std::iter::once(path.clone()).chain(parent_dir(path)).collect()
+            }
+            TaskKind::Eject { mountpoints, .. } => {
+                mountpoints.iter().flat_map(|path| std::iter::once(path.clone()).chain(parent_dir(path))).collect()
+            }
+            TaskKind::RunCommand { dir, .. } => vec![dir.clone()],
+            TaskKind::InstallPlugin { dest, .. } => parent_dir(dest).into_iter().collect(),
+        }
+    }
+
+    /// Вид задачи в snake case — для уведомлений плагинам
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskKind::Copy { .. } => "copy",
+            TaskKind::Move { .. } => "move",
+            TaskKind::Delete { .. } => "delete",
+            TaskKind::Trash { .. } => "trash",
+            TaskKind::CreateFile { .. } => "create_file",
+            TaskKind::CreateDirectory { .. } => "create_directory",
+            TaskKind::Chmod { .. } => "chmod",
+            TaskKind::Chown { .. } => "chown",
+            TaskKind::Unmount { .. } => "unmount",
+            TaskKind::Eject { .. } => "eject",
+            TaskKind::MountRemote { .. } => "mount_remote",
+            TaskKind::UnmountRemote { .. } => "unmount_remote",
+            TaskKind::Archive { .. } => "archive",
+            TaskKind::Extract { .. } => "extract",
+            TaskKind::RunCommand { .. } => "run_command",
+            TaskKind::InstallPlugin { .. } => "install_plugin",
+        }
+    }
+
+    /// Все пути задачи: источники, затем созданный или изменённый путь
+    pub fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            TaskKind::Copy { src, dest } | TaskKind::Move { src, dest } => vec![src.clone(), dest.clone()],
+            TaskKind::Extract { archive, dest } => vec![archive.clone(), dest.clone()],
+            TaskKind::Archive { paths, dest, .. } => paths.iter().chain(std::iter::once(dest)).cloned().collect(),
+            TaskKind::Eject { device, mountpoints, .. } => mountpoints.iter().chain(std::iter::once(device)).cloned().collect(),
+            TaskKind::Delete { path }
+            | TaskKind::Trash { path, .. }
+            | TaskKind::CreateFile { path }
+            | TaskKind::CreateDirectory { path }
+            | TaskKind::Chmod { path, .. }
+            | TaskKind::Chown { path, .. }
+            | TaskKind::Unmount { path }
+            | TaskKind::MountRemote { mountpoint: path, .. }
+            | TaskKind::UnmountRemote { mountpoint: path, .. } => vec![path.clone()],
+            TaskKind::RunCommand { dir, .. } => vec![dir.clone()],
+            TaskKind::InstallPlugin { dest, .. } => vec![dest.clone()],
+        }
+    }
+
+    /// Глагол для строки группы из таких задач и для её итога
+    fn group_verbs(&self) -> (&'static str, &'static str) {
+        match self {
+            TaskKind::Copy { .. } => ("Copying", "Copied"),
+            TaskKind::Move { .. } => ("Moving", "Moved"),
+            TaskKind::Delete { .. } => ("Deleting", "Deleted"),
+            TaskKind::Trash { .. } => ("Moving to the trash", "Moved to the trash"),
+            _ => ("Processing", "Processed"),
+        }
+    }
+
+    /// Копирование, перемещение и работа с архивами нагружают диск и получают
+    /// отдельный лимит, чтобы не задерживать быстрые операции с метаданными
+    pub fn is_io_heavy(&self) -> bool {
+        matches!(
+            self,
+            TaskKind::Copy { .. } | TaskKind::Move { .. } | TaskKind::Archive { .. } | TaskKind::Extract { .. }
+        )
+    }
+}
+
+impl TaskStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Failed(_))
+    }
 }
 
 impl Task {
@@ -47,6 +260,10 @@ impl Task {
             kind,
             status: TaskStatus::Pending,
             description,
+            finished_at: None,
+            parent: None,
+            group: None,
+            output: String::new(),
         }
     }
 }
@@ -57,6 +274,13 @@ pub struct TaskManager {
     tasks: Arc<Mutex<Vec<Task>>>,
     progress_rx: mpsc::Receiver<(Uuid, fs_ops::ProgressEvent)>,
     progress_tx: mpsc::Sender<(Uuid, fs_ops::ProgressEvent)>,
+    /// Слоты для тяжёлых задач (копирование, перемещение, архивация)
+    io_slots: Arc<Semaphore>,
+    /// Слоты для быстрых задач
+    light_slots: Arc<Semaphore>,
+    /// Занятые слоты выполняющихся задач. Освобождаются при получении
+    /// итогового события, чтобы следующая задача стартовала в том же цикле
+    running: Mutex<HashMap<Uuid, OwnedSemaphorePermit>>,
 }
 
 impl fmt::Debug for TaskManager {
@@ -67,29 +291,190 @@ impl fmt::Debug for TaskManager {
     }
 }
 
+const DEFAULT_MAX_IO_TASKS: usize = 4;
+const DEFAULT_MAX_LIGHT_TASKS: usize = 8;
+
 impl TaskManager {
     pub fn new() -> Self {
+        Self::with_limits(DEFAULT_MAX_IO_TASKS, DEFAULT_MAX_LIGHT_TASKS)
+    }
+
+    /// Создаёт менеджер, выполняющий одновременно не больше `max_io_tasks`
+    /// тяжёлых и `max_light_tasks` быстрых задач. Остальные ждут в очереди.
+    pub fn with_limits(max_io_tasks: usize, max_light_tasks: usize) -> Self {
         let (tx, rx) = mpsc::channel(100);
         Self {
             tasks: Arc::new(Mutex::new(Vec::new())),
             progress_rx: rx,
             progress_tx: tx,
+            io_slots: Arc::new(Semaphore::new(max_io_tasks.max(1))),
+            light_slots: Arc::new(Semaphore::new(max_light_tasks.max(1))),
+            running: Mutex::new(HashMap::new()),
         }
     }
 
-    pub fn add_task(&self, kind: TaskKind, description: String) {
+    pub fn add_task(&self, kind: TaskKind, description: String) -> Uuid {
         let task = Task::new(kind, description);
+        let id = task.id;
+        self.tasks.lock().unwrap().push(task);
+        id
+    }
+
+    /// Добавляет задачу, которая ждёт успешного завершения `parent_id`.
+    /// Если родительская задача завершится с ошибкой, эта будет отменена.
+    pub fn add_task_after(&self, parent_id: Uuid, kind: TaskKind, description: String) -> Uuid {
+        let mut task = Task::new(kind, description);
+        task.parent = Some(parent_id);
+        let id = task.id;
         self.tasks.lock().unwrap().push(task);
+        id
+    }
+
+    /// Ставит задачи одной группой и возвращает её id
+    pub fn add_group(&self, tasks: Vec<(TaskKind, String)>) -> Uuid {
+        let group = Uuid::new_v4();
+        let mut all = self.tasks.lock().unwrap();
+        for (kind, description) in tasks {
+            let mut task = Task::new(kind, description);
+            task.group = Some(group);
+            all.push(task);
+        }
+        group
     }
 
     pub fn get_tasks(&self) -> Vec<Task> {
         self.tasks.lock().unwrap().clone()
     }
 
+    /// Как `get_tasks`, но каждая группа свёрнута в одну задачу с id группы
+    /// на месте первой из них. Прогресс общий по группе, ошибки собраны вместе.
+    pub fn get_tasks_grouped(&self) -> Vec<Task> {
+        let tasks = self.tasks.lock().unwrap();
+        let mut rows: Vec<Task> = Vec::new();
+        for task in tasks.iter() {
+            match task.group {
+                None => rows.push(task.clone()),
+                Some(group) if !rows.iter().any(|row| row.id == group) => {
+                    let members: Vec<&Task> = tasks.iter().filter(|t| t.group == Some(group)).collect();
+                    rows.push(summarize_group(group, &members));
+                }
+                Some(_) => {}
+            }
+        }
+        rows
+    }
+
+    /// Число задач, ожидающих в очереди или выполняющихся
+    pub fn active_count(&self) -> usize {
+        self.tasks.lock().unwrap().iter().filter(|task| !task.status.is_finished()).count()
+    }
+
+    /// Убирает все завершённые задачи, в том числе неудачные.
+    /// Группа убирается целиком, когда завершатся все её задачи.
+    pub fn clear_finished(&self) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let running = running_groups(&tasks);
+        tasks.retain(|task| !task.status.is_finished() || task.group.is_some_and(|group| running.contains(&group)));
+    }
+
+    /// Убирает успешно завершённые задачи старше `ttl`.
+    /// Неудачные остаются, пока их не уберут явно, и вместе с ними вся их группа.
+    pub fn expire_completed(&self, ttl: Duration) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let expired = |task: &Task| {
+            task.status == TaskStatus::Completed && task.finished_at.is_some_and(|finished| finished.elapsed() >= ttl)
+        };
+        let kept_groups: HashSet<Uuid> = tasks
+            .iter()
+            .filter(|task| !expired(task))
+            .filter_map(|task| task.group)
+            .collect();
+        tasks.retain(|task| !expired(task) || task.group.is_some_and(|group| kept_groups.contains(&group)));
+    }
+
+    /// Ожидающие и выполняющиеся задачи для сохранения при выходе.
+    /// Цепочки сохраняются, пока родительская задача тоже не завершена.
+    pub fn unfinished_tasks(&self) -> Vec<SavedTask> {
+        let tasks = self.tasks.lock().unwrap();
+        let unfinished: Vec<&Task> = tasks.iter().filter(|task| !task.status.is_finished()).collect();
+        unfinished
+            .iter()
+            .map(|task| SavedTask {
+                kind: task.kind.clone(),
+                description: task.description.clone(),
+                parent: task.parent.and_then(|parent| unfinished.iter().position(|t| t.id == parent)),
+                group: task.group.and_then(|group| unfinished.iter().position(|t| t.group == Some(group))),
+            })
+            .collect()
+    }
+
+    /// Ставит сохранённые задачи в очередь заново, с самого начала.
+    /// Задачи с пропавшими исходными путями пропускаются вместе с зависящими от них.
+    /// Возвращает число восстановленных задач.
+    pub fn restore(&self, saved: Vec<SavedTask>) -> usize {
+        let mut ids: Vec<Option<Uuid>> = Vec::with_capacity(saved.len());
+        let mut groups: HashMap<usize, Uuid> = HashMap::new();
+        for task in saved {
+            let parent = match task.parent {
+                Some(index) => match ids.get(index).copied().flatten() {
+                    Some(id) => Some(id),
+                    None => {
+                        log::warn!("Dropping resumed task {:?}: the task it depends on was dropped", task.description);
+                        ids.push(None);
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            if let Some(missing) = task.kind.source_paths().into_iter().find(|path| path.symlink_metadata().is_err()) {
+                log::warn!("Dropping resumed task {:?}: {} no longer exists", task.description, missing.display());
+                ids.push(None);
+                continue;
+            }
+            let id = match parent {
+                Some(parent) => self.add_task_after(parent, task.kind, task.description),
+                None => self.add_task(task.kind, task.description),
+            };
+            if let Some(index) = task.group {
+                let group = *groups.entry(index).or_insert_with(Uuid::new_v4);
+                if let Some(restored) = self.tasks.lock().unwrap().iter_mut().find(|t| t.id == id) {
+                    restored.group = Some(group);
+                }
+            }
+            ids.push(Some(id));
+        }
+        ids.iter().flatten().count()
+    }
+
     pub fn process_pending_tasks(&self) {
         let mut tasks = self.tasks.lock().unwrap();
+        let mut running = self.running.lock().unwrap();
+        // Дочерние задачи добавляются после родителя, поэтому отмена
+        // по цепочке успевает пройти за один проход
+        let mut statuses: HashMap<Uuid, TaskStatus> = tasks.iter().map(|t| (t.id, t.status.clone())).collect();
         for task in tasks.iter_mut() {
             if task.status == TaskStatus::Pending {
+                // Убранный из списка родитель уже завершился успешно
+                match task.parent.and_then(|parent| statuses.get(&parent)) {
+                    Some(TaskStatus::Failed(_)) => {
+                        task.status = TaskStatus::Failed("Cancelled: the previous task failed".to_string());
+                        task.finished_at = Some(Instant::now());
+                        statuses.insert(task.id, task.status.clone());
+                        continue;
+                    }
+                    Some(TaskStatus::Pending | TaskStatus::InProgress(_)) => continue,
+                    Some(TaskStatus::Completed) | None => {}
+                }
+                // Команды пользователя могут работать часами (плеер, редактор),
+                // поэтому не занимают слотов
+                if !matches!(task.kind, TaskKind::RunCommand { .. }) {
+                    let slots = if task.kind.is_io_heavy() { &self.io_slots } else { &self.light_slots };
+                    // Нет свободного слота — задача остаётся в очереди
+                    let Ok(permit) = slots.clone().try_acquire_owned() else {
+                        continue;
+                    };
+                    running.insert(task.id, permit);
+                }
                 task.status = TaskStatus::InProgress(0.0);
 
                 let task_id = task.id;
@@ -107,6 +492,9 @@ impl TaskManager {
                         TaskKind::Delete { path } => {
                             fs_ops::delete_item_task(task_id, path, progress_tx).await;
                         }
+                        TaskKind::Trash { path, trash } => {
+                            fs_ops::trash_item_task(task_id, path, trash, progress_tx).await;
+                        }
                         TaskKind::CreateFile { path } => {
                             fs_ops::create_file_task(task_id, path, progress_tx).await;
                         }
@@ -122,8 +510,34 @@ impl TaskManager {
                         TaskKind::Unmount { path } => {
                             fs_ops::unmount_task(task_id, path, progress_tx).await;
                         }
-                        TaskKind::Archive { paths, dest, format } => {
-                            fs_ops::archive_task(task_id, paths, dest, format, progress_tx).await;
+                        TaskKind::Eject { device, partitions, .. } => {
+                            fs_ops::eject_task(task_id, device, partitions, progress_tx).await;
+                        }
+                        TaskKind::MountRemote { url, mountpoint } => match crate::remote::mount_command(&url, &mountpoint) {
+                            Some((program, args)) => fs_ops::mount_remote_task(task_id, program, args, mountpoint, progress_tx).await,
+                            None => {
+                                let error = format!("{} is not an SFTP or WebDAV address", url);
+                                let _ = progress_tx.send((task_id, fs_ops::ProgressEvent::Error(error))).await;
+                            }
+                        },
+                        TaskKind::UnmountRemote { url, mountpoint } => match crate::remote::unmount_command(&url, &mountpoint) {
+                            Some((program, args)) => fs_ops::unmount_remote_task(task_id, program, args, mountpoint, progress_tx).await,
+                            None => {
+                                let error = format!("{} is not an SFTP or WebDAV address", url);
+                                let _ = progress_tx.send((task_id, fs_ops::ProgressEvent::Error(error))).await;
+                            }
+                        },
+                        TaskKind::Archive { paths, dest, format, level } => {
+                            fs_ops::archive_task(task_id, paths, dest, format, level, progress_tx).await;
+                        }
+                        TaskKind::Extract { archive, dest } => {
+                            fs_ops::extract_task(task_id, archive, dest, progress_tx).await;
+                        }
+                        TaskKind::RunCommand { command, dir, .. } => {
+                            fs_ops::run_command_task(task_id, command, dir, progress_tx).await;
+                        }
+                        TaskKind::InstallPlugin { url, dest } => {
+                            fs_ops::git_clone_task(task_id, url, dest, progress_tx).await;
                         }
                     }
                 });
@@ -131,28 +545,116 @@ impl TaskManager {
         }
     }
 
-    pub async fn wait_for_event(&mut self) -> bool {
-        if let Some((task_id, event)) = self.progress_rx.recv().await {
+    /// Ждёт следующего события от выполняющихся задач и обновляет их статус.
+    /// Возвращает `None`, только если канал событий закрыт.
+    pub async fn wait_for_event(&mut self) -> Option<TaskEvent> {
+        loop {
+            let (task_id, event) = self.progress_rx.recv().await?;
             let mut tasks = self.tasks.lock().unwrap();
-            if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-                match event {
-                    fs_ops::ProgressEvent::Completed => {
-                        task.status = TaskStatus::Completed;
-                        return true;
-                    }
-                    fs_ops::ProgressEvent::Error(e) => {
-                        task.status = TaskStatus::Failed(e);
-                    }
-                    fs_ops::ProgressEvent::Update(p) => {
-                        task.status = TaskStatus::InProgress(p)
-                    }
+            let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) else {
+                // Задачу уже убрали из списка
+                continue;
+            };
+            let event = match event {
+                fs_ops::ProgressEvent::Completed => {
+                    task.status = TaskStatus::Completed;
+                    task.finished_at = Some(Instant::now());
+                    self.running.lock().unwrap().remove(&task_id);
+                    // Последняя строка вывода команды — её итог
+                    let summary = match task.output.lines().rev().find(|line| !line.trim().is_empty()) {
+                        Some(line) => format!("{}: {}", task.kind.summary(), line.trim()),
+                        None => task.kind.summary(),
+                    };
+                    TaskEvent::Completed { id: task_id, kind: task.kind.clone(), summary }
                 }
-            }
+                fs_ops::ProgressEvent::Error(e) => {
+                    task.status = TaskStatus::Failed(e.clone());
+                    task.finished_at = Some(Instant::now());
+                    self.running.lock().unwrap().remove(&task_id);
+                    TaskEvent::Failed { id: task_id, kind: task.kind.clone(), description: task.description.clone(), error: e }
+                }
+                fs_ops::ProgressEvent::Output(output) => {
+                    task.output.push_str(&output);
+                    continue;
+                }
+                fs_ops::ProgressEvent::Update(p) => {
+                    task.status = TaskStatus::InProgress(p);
+                    return Some(TaskEvent::Progress { id: task_id, progress: p });
+                }
+            };
+            let Some(group) = task.group else {
+                return Some(event);
+            };
+            // Задачи группы сообщают об итоге один раз, когда завершится последняя
+            let kind = task.kind.clone();
+            let members: Vec<&Task> = tasks.iter().filter(|t| t.group == Some(group)).collect();
+            let report = members.iter().all(|t| t.status.is_finished()).then(|| group_report(&members));
+            return Some(TaskEvent::GroupItemFinished { id: task_id, kind, report });
+        }
+    }
+}
+
+/// Группы, в которых ещё есть незавершённые задачи
+fn running_groups(tasks: &[Task]) -> HashSet<Uuid> {
+    tasks.iter().filter(|task| !task.status.is_finished()).filter_map(|task| task.group).collect()
+}
+
+/// Одна задача, описывающая всю группу `members`
+fn summarize_group(group: Uuid, members: &[&Task]) -> Task {
+    let total = members.len();
+    let finished = members.iter().filter(|task| task.status.is_finished()).count();
+    let (verb, _) = members[0].kind.group_verbs();
+    let status = if finished == total {
+        match group_errors(members) {
+            errors if errors.is_empty() => TaskStatus::Completed,
+            errors => TaskStatus::Failed(errors.join("; ")),
         }
-        false
+    } else if members.iter().all(|task| task.status == TaskStatus::Pending) {
+        TaskStatus::Pending
+    } else {
+        let done: f32 = members
+            .iter()
+            .map(|task| match task.status {
+                TaskStatus::InProgress(progress) => progress,
+                TaskStatus::Pending => 0.0,
+                _ => 1.0,
+            })
+            .sum();
+        TaskStatus::InProgress(done / total as f32)
+    };
+    Task {
+        id: group,
+        kind: members[0].kind.clone(),
+        status,
+        description: format!("{} {} files ({}/{})", verb, total, finished, total),
+        finished_at: members.iter().filter_map(|task| task.finished_at).max(),
+        parent: None,
+        group: Some(group),
+        output: String::new(),
     }
 }
 
+fn group_errors(members: &[&Task]) -> Vec<String> {
+    members
+        .iter()
+        .filter_map(|task| match &task.status {
+            TaskStatus::Failed(error) => Some(format!("{}: {}", task.description, error)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn group_report(members: &[&Task]) -> GroupReport {
+    let (verb, done) = members[0].kind.group_verbs();
+    let errors = group_errors(members);
+    let summary = if errors.is_empty() {
+        format!("{} {} files", done, members.len())
+    } else {
+        format!("{} {} files: {} of them failed", verb, members.len(), errors.len())
+    };
+    GroupReport { summary, errors }
+}
+
 impl Default for TaskManager {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use utils::fs::walk_directory_size;
+
+/// A batch is sent once it holds this many entries...
+const BATCH_ENTRIES: usize = 1024;
+/// ...or this long after the previous one, so a slow disk still shows progress
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A file or directory in a scanned tree. `size` of a directory is the sum
+/// of all files below it.
+#[derive(Debug, Clone, Default)]
+pub struct UsageNode {
+    pub size: u64,
+    pub is_dir: bool,
+    pub children: HashMap<OsString, UsageNode>,
+}
+
+impl UsageNode {
+    /// An empty directory, the root of a new scan
+    pub fn directory() -> Self {
+        Self { is_dir: true, ..Self::default() }
+    }
+
+    /// Adds the entry at `relative`, creating missing directories on the
+    /// way and adding `size` to each of them
+    pub fn insert(&mut self, relative: &Path, is_dir: bool, size: u64) {
+        let mut node = self;
+        for component in relative.components() {
+            node.size += size;
+            node = node.children.entry(component.as_os_str().to_os_string()).or_insert_with(UsageNode::directory);
+        }
+        node.is_dir = is_dir;
+        node.size += size;
+    }
+
+    /// The node at `relative`, `self` for an empty path
+    pub fn get(&self, relative: &Path) -> Option<&UsageNode> {
+        relative
+            .components()
+            .try_fold(self, |node, component| node.children.get(component.as_os_str()))
+    }
+
+    /// Takes the node at `relative` out of the tree and subtracts its size
+    /// from every directory above it
+    pub fn remove(&mut self, relative: &Path) -> Option<UsageNode> {
+        let mut components = relative.components();
+        let first = components.next()?.as_os_str();
+        let rest = components.as_path();
+        let removed = if rest.as_os_str().is_empty() {
+            self.children.remove(first)?
+        } else {
+            self.children.get_mut(first)?.remove(rest)?
+        };
+        self.size -= removed.size;
+        Some(removed)
+    }
+}
+
+/// An entry found by the scan, relative to the scanned directory
+#[derive(Debug, Clone)]
+pub struct ScannedEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// Entries found since the previous batch of the scan of `root`
+#[derive(Debug)]
+pub struct UsageBatch {
+    pub root: PathBuf,
+    pub entries: Vec<ScannedEntry>,
+    /// Bytes counted so far in the whole scan
+    pub total: u64,
+    /// The walk is over, this is the last batch
+    pub done: bool,
+}
+
+/// Walks a directory on a worker thread and streams what it finds in
+/// batches, so a large tree shows its biggest entries long before the walk
+/// ends. Like `DirSizer`, only one scan runs: a new request or `cancel`
+/// bumps the generation, which stops the old walk and drops its batches.
+#[derive(Debug)]
+pub struct UsageScanner {
+    generation: Arc<AtomicU64>,
+    scanning: Option<PathBuf>,
+    tx: mpsc::UnboundedSender<(u64, UsageBatch)>,
+    rx: mpsc::UnboundedReceiver<(u64, UsageBatch)>,
+}
+
+impl UsageScanner {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            scanning: None,
+            tx,
+            rx,
+        }
+    }
+
+    /// The directory being scanned
+    pub fn scanning(&self) -> Option<&Path> {
+        self.scanning.as_deref()
+    }
+
+    /// Starts scanning `root`, stopping any scan under way
+    pub fn request(&mut self, root: PathBuf) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.scanning = Some(root.clone());
+        let current = self.generation.clone();
+        let tx = self.tx.clone();
+
+        std::thread::spawn(move || {
+            let cancelled = || current.load(Ordering::SeqCst) != generation;
+            let mut entries = Vec::new();
+            let mut last_sent = Instant::now();
+            let total = walk_directory_size(&root, cancelled, |entry, total| {
+                let path = entry.path.strip_prefix(&root).unwrap_or(entry.path).to_path_buf();
+                entries.push(ScannedEntry { path, is_dir: entry.is_dir, size: entry.size });
+                if entries.len() >= BATCH_ENTRIES || last_sent.elapsed() >= BATCH_INTERVAL {
+                    let entries = std::mem::take(&mut entries);
+                    let _ = tx.send((generation, UsageBatch { root: root.clone(), entries, total, done: false }));
+                    last_sent = Instant::now();
+                }
+            });
+            if let Some(total) = total {
+                let _ = tx.send((generation, UsageBatch { root, entries, total, done: true }));
+            }
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        if self.scanning.take().is_some() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Waits for the next batch of the current scan, skipping stale ones
+    pub async fn wait_for_update(&mut self) -> Option<UsageBatch> {
+        loop {
+            let (generation, batch) = self.rx.recv().await?;
+            if generation == self.generation.load(Ordering::SeqCst) {
+                if batch.done {
+                    self.scanning = None;
+                }
+                return Some(batch);
+            }
+        }
+    }
+}
+
+impl Default for UsageScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[tokio::test]
+    async fn test_scan_builds_cumulative_sizes() {
+        let dir = TempDir::new("usage_scan").unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a/b/big"), vec![0; 1000]).unwrap();
+        std::fs::write(dir.path().join("a/small"), vec![0; 24]).unwrap();
+        std::fs::write(dir.path().join("top"), vec![0; 7]).unwrap();
+
+        let mut scanner = UsageScanner::new();
+        scanner.request(dir.path().to_path_buf());
+        let mut tree = UsageNode::directory();
+        loop {
+            let batch = scanner.wait_for_update().await.unwrap();
+            for entry in &batch.entries {
+                tree.insert(&entry.path, entry.is_dir, entry.size);
+            }
+            if batch.done {
+                assert_eq!(batch.total, 1031);
+                break;
+            }
+        }
+        assert!(scanner.scanning().is_none());
+        assert_eq!(tree.size, 1031);
+        assert_eq!(tree.get(Path::new("a")).unwrap().size, 1024);
+        assert!(tree.get(Path::new("empty")).unwrap().is_dir);
+        assert!(!tree.get(Path::new("top")).unwrap().is_dir);
+
+        let removed = tree.remove(Path::new("a/b")).unwrap();
+        assert_eq!(removed.size, 1000);
+        assert_eq!(tree.get(Path::new("a")).unwrap().size, 24);
+        assert_eq!(tree.size, 31);
+        assert!(tree.remove(Path::new("a/b")).is_none());
+    }
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::settings::{SortField, SortOrder, SortSettings};
+use globset::{Glob, GlobMatcher};
+
+/// Настройки вида каталога
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewPrefs {
+    /// Сортировка
+    #[serde(default)]
+    pub sort: SortSettings,
+    /// Показывать скрытые файлы
+    #[serde(default)]
+    pub show_hidden: bool,
+    /// Показывать колонки размера и даты
+    #[serde(default)]
+    pub show_file_details: bool,
+}
+
+/// Запомненные настройки вида по каталогам.
+/// Клоны ссылаются на одну и ту же таблицу, поэтому её можно раздать вкладкам.
+#[derive(Debug, Clone, Default)]
+pub struct DirPrefs {
+    dirs: Arc<Mutex<HashMap<PathBuf, ViewPrefs>>>,
+}
+
+impl DirPrefs {
+    pub fn get(&self, dir: &Path) -> Option<ViewPrefs> {
+        self.dirs.lock().unwrap().get(dir).cloned()
+    }
+
+    pub fn set(&self, dir: PathBuf, prefs: ViewPrefs) {
+        self.dirs.lock().unwrap().insert(dir, prefs);
+    }
+
+    pub fn remove(&self, dir: &Path) {
+        self.dirs.lock().unwrap().remove(dir);
+    }
+
+    pub fn len(&self) -> usize {
+        self.dirs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dirs.lock().unwrap().is_empty()
+    }
+
+    /// Загружает таблицу из файла. Отсутствующий файл даёт пустую таблицу.
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(path)?;
+        let dirs: HashMap<PathBuf, ViewPrefs> = serde_json::from_str(&json)?;
+        Ok(Self { dirs: Arc::new(Mutex::new(dirs)) })
+    }
+
+    /// Сохраняет таблицу в файл, предварительно забывая удалённые каталоги
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut dirs = self.dirs.lock().unwrap();
+        dirs.retain(|dir, _| dir.is_dir());
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&*dirs)?)?;
+        Ok(())
+    }
+}
+
+/// Закреплённые каталоги, которые стоят в списке родителя выше остальных.
+/// Хранятся по абсолютным путям; клоны ссылаются на один и тот же набор.
+#[derive(Debug, Clone, Default)]
+pub struct PinnedDirs {
+    paths: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl PinnedDirs {
+    pub fn new(paths: &[PathBuf]) -> Self {
+        Self { paths: Arc::new(Mutex::new(paths.iter().cloned().collect())) }
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.lock().unwrap().contains(path)
+    }
+
+    /// Закрепляет или открепляет `path`. Возвращает `true`, если каталог теперь закреплён.
+    pub fn toggle(&self, path: PathBuf) -> bool {
+        let mut paths = self.paths.lock().unwrap();
+        if paths.remove(&path) {
+            false
+        } else {
+            paths.insert(path);
+            true
+        }
+    }
+
+    /// Заменяет набор целиком, не отвязывая его от вкладок
+    pub fn replace(&self, paths: &[PathBuf]) {
+        *self.paths.lock().unwrap() = paths.iter().cloned().collect();
+    }
+
+    /// Переносит закрепления каталога `from` и вложенных в него на новое
+    /// место `to`. Возвращает `true`, если что-то изменилось.
+    pub fn move_paths(&self, from: &Path, to: &Path) -> bool {
+        let mut paths = self.paths.lock().unwrap();
+        let moved: Vec<PathBuf> = paths.iter().filter(|path| path.starts_with(from)).cloned().collect();
+        for path in &moved {
+            paths.remove(path);
+            let rest = path.strip_prefix(from).unwrap_or(Path::new(""));
+            paths.insert(to.join(rest));
+        }
+        !moved.is_empty()
+    }
+
+    /// Пути по алфавиту, для записи в config.toml
+    pub fn to_vec(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.paths.lock().unwrap().iter().cloned().collect();
+        paths.sort();
+        paths
+    }
+}
+
+/// Имя файла с настройками вида внутри самого каталога
+pub const DIR_OVERRIDES_FILE: &str = ".corvus.toml";
+
+/// Настройки вида из `.corvus.toml` в каталоге. Заданные поля перекрывают
+/// и общие, и запомненные настройки, но только в этом каталоге.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirOverrides {
+    /// `name`, `size` или `date`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    /// `ascending` или `descending`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separate_dirs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub natural: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_hidden: Option<bool>,
+    /// Glob по именам файлов, например `*.{jpg,png}`. Каталоги видны всегда.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+impl DirOverrides {
+    /// Читает `.corvus.toml` из `dir`. Отсутствующий файл даёт `None`,
+    /// ошибки разбора пишутся в лог и тоже дают `None`.
+    pub fn load(dir: &Path) -> Option<Self> {
+        let path = dir.join(DIR_OVERRIDES_FILE);
+        let content = std::fs::read_to_string(&path).ok()?;
+        match toml::from_str(&content) {
+            Ok(overrides) => Some(overrides),
+            Err(e) => {
+                log::error!("Ignoring {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Записывает настройки в `.corvus.toml` в `dir`
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let path = dir.join(DIR_OVERRIDES_FILE);
+        std::fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Полный набор настроек из вида `view` и фильтра `filter`
+    pub fn from_view(view: &ViewPrefs, filter: Option<String>) -> Self {
+        Self {
+            sort: Some(view.sort.field.name().to_string()),
+            order: Some(view.sort.order.name().to_string()),
+            separate_dirs: Some(view.sort.separate_dirs),
+            natural: Some(view.sort.natural),
+            show_hidden: Some(view.show_hidden),
+            filter,
+        }
+    }
+
+    /// Накладывает заданные поля на `view`. Неизвестные значения пропускаются.
+    pub fn apply(&self, view: &mut ViewPrefs) {
+        if let Some(name) = &self.sort {
+            match SortField::from_name(name) {
+                Some(field) => view.sort.field = field,
+                None => log::warn!("Unknown sort field {:?} in {}", name, DIR_OVERRIDES_FILE),
+            }
+        }
+        if let Some(name) = &self.order {
+            match SortOrder::from_name(name) {
+                Some(order) => view.sort.order = order,
+                None => log::warn!("Unknown sort order {:?} in {}", name, DIR_OVERRIDES_FILE),
+            }
+        }
+        if let Some(separate_dirs) = self.separate_dirs {
+            view.sort.separate_dirs = separate_dirs;
+        }
+        if let Some(natural) = self.natural {
+            view.sort.natural = natural;
+        }
+        if let Some(show_hidden) = self.show_hidden {
+            view.show_hidden = show_hidden;
+        }
+    }
+
+    /// Скомпилированный фильтр имён, если он задан и корректен
+    pub fn filter_matcher(&self) -> Option<GlobMatcher> {
+        let pattern = self.filter.as_deref()?;
+        match Glob::new(pattern) {
+            Ok(glob) => Some(glob.compile_matcher()),
+            Err(e) => {
+                log::warn!("Invalid filter {:?} in {}: {}", pattern, DIR_OVERRIDES_FILE, e);
+                None
+            }
+        }
+    }
+}
+
+pub fn load_view_prefs() -> Result<DirPrefs, Box<dyn std::error::Error>> {
+    DirPrefs::load_from(&crate::paths::view_prefs_file())
+}
+
+pub fn save_view_prefs(prefs: &DirPrefs) -> Result<(), Box<dyn std::error::Error>> {
+    prefs.save_to(&crate::paths::view_prefs_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_save_prunes_missing_directories() {
+        let temp_dir = TempDir::new("view_prefs").unwrap();
+        let kept = temp_dir.path().join("downloads");
+        std::fs::create_dir(&kept).unwrap();
+
+        let mut by_date = ViewPrefs { sort: SortSettings::default(), show_hidden: true, show_file_details: true };
+        by_date.sort.field = SortField::Modified;
+
+        let prefs = DirPrefs::default();
+        prefs.set(kept.clone(), by_date.clone());
+        prefs.set(temp_dir.path().join("gone"), by_date.clone());
+
+        let file = temp_dir.path().join("view_prefs.json");
+        prefs.save_to(&file).unwrap();
+
+        let loaded = DirPrefs::load_from(&file).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get(&kept), Some(by_date));
+    }
+
+    #[test]
+    fn test_pins_follow_moved_directories() {
+        let pinned = PinnedDirs::new(&[PathBuf::from("/home/me/dev/corvus"), PathBuf::from("/home/me/music")]);
+        assert!(pinned.move_paths(Path::new("/home/me/dev"), Path::new("/home/me/src")));
+        assert!(!pinned.move_paths(Path::new("/home/me/mus"), Path::new("/home/me/audio")));
+        assert_eq!(pinned.to_vec(), [PathBuf::from("/home/me/music"), PathBuf::from("/home/me/src/corvus")]);
+
+        assert!(!pinned.toggle(PathBuf::from("/home/me/music")));
+        assert!(!pinned.contains(Path::new("/home/me/music")));
+    }
+
+    #[test]
+    fn test_dir_overrides_round_trip_and_partial_apply() {
+        let temp_dir = TempDir::new("dir_overrides").unwrap();
+        std::fs::write(
+            temp_dir.path().join(DIR_OVERRIDES_FILE),
+            "sort = \"date\"\norder = \"descending\"\nfilter = \"*.jpg\"\n",
+        )
+        .unwrap();
+
+        let overrides = DirOverrides::load(temp_dir.path()).unwrap();
+        let mut view = ViewPrefs { sort: SortSettings::default(), show_hidden: true, show_file_details: true };
+        overrides.apply(&mut view);
+        assert_eq!(view.sort.field, SortField::Modified);
+        assert_eq!(view.sort.order, SortOrder::Descending);
+        // Fields missing from the file keep their value
+        assert!(view.show_hidden);
+        assert!(overrides.filter_matcher().unwrap().is_match("cat.jpg"));
+
+        DirOverrides::from_view(&view, overrides.filter.clone()).save(temp_dir.path()).unwrap();
+        let saved = DirOverrides::load(temp_dir.path()).unwrap();
+        assert_eq!(saved.show_hidden, Some(true));
+        assert_eq!(saved.filter.as_deref(), Some("*.jpg"));
+
+        std::fs::write(temp_dir.path().join(DIR_OVERRIDES_FILE), "sort = [").unwrap();
+        assert!(DirOverrides::load(temp_dir.path()).is_none());
+    }
+}
@@ -1,9 +1,44 @@
-use corvus_core::app_state::AppState;
+use corvus_core::app_state::{describe_plan, AppState, DirEntry, FocusBlock, InputMode, LayoutMode, PanelSide, PreviewContent, TabState, TerminalLifecycle, TerminalState, PREVIEW_DIR_LIMIT};
 use corvus_core::clipboard::ClipboardMode;
-use corvus_core::task_manager::TaskKind;
+use corvus_core::settings::Language;
+use corvus_core::task_manager::{ArchiveFormat, TaskEvent, TaskKind, TaskManager, TaskStatus};
+use config::Bookmark;
+use std::ffi::OsString;
 use std::fs;
+use std::sync::{Mutex, MutexGuard};
 use tempdir::TempDir;
 
+/// Serializes the tests that point `CORVUS_CONFIG_DIR` at a temporary
+/// directory, so that none of them reads or writes the user's config
+static CONFIG_DIR_LOCK: Mutex<()> = Mutex::new(());
+
+/// Config, data and cache directory of one test. The previous value of
+/// `CORVUS_CONFIG_DIR` comes back when it is dropped.
+struct TempConfigDir {
+    previous: Option<OsString>,
+    dir: TempDir,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for TempConfigDir {
+    fn drop(&mut self) {
+        match self.previous.take() {
+            Some(previous) => std::env::set_var(config::CONFIG_DIR_ENV, previous),
+            None => std::env::remove_var(config::CONFIG_DIR_ENV),
+        }
+    }
+}
+
+/// Call before `AppState::new` in every test that saves settings, plugins
+/// or remembered views
+fn temp_config_dir() -> TempConfigDir {
+    let lock = CONFIG_DIR_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let dir = TempDir::new("config").unwrap();
+    let previous = std::env::var_os(config::CONFIG_DIR_ENV);
+    std::env::set_var(config::CONFIG_DIR_ENV, dir.path());
+    TempConfigDir { previous, dir, _lock: lock }
+}
+
 #[test]
 fn test_new_app_state() {
     let app_state = AppState::new();
@@ -55,6 +90,105 @@ fn test_new_tab() {
     assert_eq!(app_state.active_tab_index, 1);
 }
 
+#[test]
+fn test_duplicate_tab_keeps_directory_and_cursor() {
+    let dir = TempDir::new("duplicate_tab").unwrap();
+    std::fs::create_dir(dir.path().join("deep")).unwrap();
+    std::fs::write(dir.path().join("deep/a.txt"), "").unwrap();
+    std::fs::write(dir.path().join("deep/b.txt"), "").unwrap();
+
+    let mut app_state = AppState::new();
+    let show_hidden = app_state.show_hidden_files;
+    app_state.get_active_tab_mut().set_current_dir(dir.path().join("deep"), show_hidden);
+    app_state.get_active_tab_mut().cursor = 1;
+    let tab_count = app_state.tabs.len();
+
+    app_state.duplicate_tab();
+    assert_eq!(app_state.tabs.len(), tab_count + 1);
+    assert_eq!(app_state.active_tab_index, tab_count);
+    let duplicate = app_state.get_active_tab();
+    assert_eq!(duplicate.current_dir, dir.path().join("deep"));
+    assert_eq!(duplicate.cursor, 1);
+    assert_eq!(duplicate.entries.len(), 2);
+}
+
+#[test]
+fn test_history_back_and_forward() {
+    let dir = TempDir::new("history").unwrap();
+    let a = dir.path().join("a");
+    let b = dir.path().join("b");
+    fs::create_dir_all(a.join("inner")).unwrap();
+    fs::create_dir(&b).unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.set_current_dir(a.clone(), false);
+    tab.enter_directory(false);
+    assert_eq!(tab.current_dir, a.join("inner"));
+    tab.leave_directory(false);
+    tab.set_current_dir(b.clone(), false);
+
+    assert!(tab.go_back(false));
+    assert_eq!(tab.current_dir, a);
+    assert!(tab.go_back(false));
+    assert_eq!(tab.current_dir, a.join("inner"));
+    assert!(tab.go_forward(false));
+    assert_eq!(tab.current_dir, a);
+
+    // A new visit drops the forward entries
+    tab.set_current_dir(dir.path().to_path_buf(), false);
+    assert!(!tab.go_forward(false));
+    assert!(tab.go_back(false));
+    assert_eq!(tab.current_dir, a);
+}
+
+#[test]
+fn test_type_ahead_jumps_to_matching_prefix() {
+    let dir = TempDir::new("type_ahead").unwrap();
+    for name in ["Apple.txt", "apricot.txt", "banana.txt", "cherry.txt"] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(dir.path().to_path_buf(), false);
+    let selected = |app_state: &AppState| app_state.get_active_tab().get_selected_entry_path().unwrap();
+
+    app_state.start_type_ahead();
+    assert!(app_state.is_typing_ahead());
+    app_state.type_ahead_char('C');
+    assert_eq!(selected(&app_state), dir.path().join("cherry.txt"));
+
+    // Matching starts over from the cursor and wraps around
+    app_state.start_type_ahead();
+    app_state.type_ahead_char('a');
+    app_state.type_ahead_char('p');
+    app_state.type_ahead_char('r');
+    assert_eq!(selected(&app_state), dir.path().join("apricot.txt"));
+    // A letter that matches nothing is ignored
+    app_state.type_ahead_char('z');
+    assert_eq!(app_state.type_ahead, "apr");
+
+    app_state.cancel_type_ahead();
+    assert!(!app_state.is_typing_ahead());
+}
+
+#[test]
+fn test_move_tab_renumbers_and_follows() {
+    let mut app_state = AppState::new();
+    app_state.new_tab();
+    app_state.new_tab();
+    let last = app_state.tabs.len() - 1;
+    app_state.tabs[last].current_dir = std::path::PathBuf::from("/scratch");
+
+    app_state.move_tab(false);
+    assert_eq!(app_state.active_tab_index, last - 1);
+    assert_eq!(app_state.tabs[last - 1].current_dir, std::path::PathBuf::from("/scratch"));
+    assert!(app_state.tabs.iter().enumerate().all(|(index, tab)| tab.id == index));
+
+    // The last tab cannot move further right
+    app_state.move_tab(true);
+    app_state.move_tab(true);
+    assert_eq!(app_state.active_tab_index, last);
+}
+
 #[test]
 fn test_close_tab() {
     let mut app_state = AppState::new();
@@ -63,3 +197,1714 @@ fn test_close_tab() {
     assert_eq!(app_state.tabs.len(), 1);
     assert_eq!(app_state.active_tab_index, 0);
 }
+
+#[test]
+fn test_update_entries_detects_symlinks() {
+    let tmp_dir = TempDir::new("symlinks").unwrap();
+    let target = tmp_dir.path().join("target.txt");
+    fs::write(&target, "hello").unwrap();
+    std::os::unix::fs::symlink(&target, tmp_dir.path().join("link")).unwrap();
+    std::os::unix::fs::symlink(tmp_dir.path().join("missing"), tmp_dir.path().join("broken")).unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+
+    let link = tab.entries.iter().find(|e| e.name == "link").unwrap();
+    assert!(link.is_symlink);
+    assert!(!link.is_broken_link);
+    assert_eq!(link.target.as_ref(), Some(&target));
+
+    let broken = tab.entries.iter().find(|e| e.name == "broken").unwrap();
+    assert!(broken.is_symlink);
+    assert!(broken.is_broken_link);
+
+    let regular = tab.entries.iter().find(|e| e.name == "target.txt").unwrap();
+    assert!(!regular.is_symlink);
+    assert!(regular.target.is_none());
+}
+
+#[test]
+fn test_pinned_directories_come_first() {
+    let tmp_dir = TempDir::new("pinned").unwrap();
+    for name in ["alpha", "beta", "zeta"] {
+        fs::create_dir(tmp_dir.path().join(name)).unwrap();
+    }
+    fs::write(tmp_dir.path().join("a.txt"), "").unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.pinned.toggle(tmp_dir.path().join("zeta"));
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    let names: Vec<&str> = tab.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["zeta", "alpha", "beta", "a.txt"]);
+
+    tab.pinned.toggle(tmp_dir.path().join("zeta"));
+    tab.update_entries(false);
+    assert_eq!(tab.entries[0].name, "alpha");
+}
+
+#[test]
+fn test_enter_symlinked_directory_keeps_logical_path() {
+    let tmp_dir = TempDir::new("symlink_dirs").unwrap();
+    let real_dir = tmp_dir.path().join("real");
+    fs::create_dir(&real_dir).unwrap();
+    fs::write(real_dir.join("inside.txt"), "hello").unwrap();
+    let link_dir = tmp_dir.path().join("zlink");
+    std::os::unix::fs::symlink(&real_dir, &link_dir).unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    tab.cursor = tab.filtered_entries.iter().position(|e| e.path == link_dir).unwrap();
+
+    tab.enter_directory(false);
+    assert_eq!(tab.current_dir, link_dir);
+    assert_eq!(tab.entries.len(), 1);
+    assert_eq!(tab.entries[0].path, link_dir.join("inside.txt"));
+
+    tab.leave_directory(false);
+    assert_eq!(tab.current_dir, tmp_dir.path());
+    assert_eq!(tab.filtered_entries[tab.cursor].path, link_dir);
+}
+
+#[test]
+fn test_search_results_listing_survives_refresh() {
+    let tmp_dir = TempDir::new("search_listing").unwrap();
+    let sub_dir = tmp_dir.path().join("sub");
+    fs::create_dir(&sub_dir).unwrap();
+    fs::write(tmp_dir.path().join("a.log"), "").unwrap();
+    fs::write(sub_dir.join("b.log"), "").unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    let results = vec![
+        DirEntry::from_path(tmp_dir.path().join("a.log")),
+        DirEntry::from_path(sub_dir.join("b.log")),
+    ];
+    tab.show_search_results(results);
+    assert!(tab.is_showing_search_results());
+    assert_eq!(tab.entries[1].name, "sub/b.log");
+
+    // A refresh after a task must not replace the results with the directory listing
+    fs::remove_file(tmp_dir.path().join("a.log")).unwrap();
+    tab.update_entries(false);
+    assert_eq!(tab.entries.len(), 1);
+    assert_eq!(tab.get_selected_entry_path(), Some(sub_dir.join("b.log")));
+
+    tab.leave_directory(false);
+    assert!(!tab.is_showing_search_results());
+    assert_eq!(tab.current_dir, tmp_dir.path());
+}
+
+#[test]
+fn test_export_search_results() {
+    let tmp_dir = TempDir::new("search_export").unwrap();
+    let dir = fs::canonicalize(tmp_dir.path()).unwrap();
+    fs::write(dir.join("a.log"), "").unwrap();
+    fs::write(dir.join("b.log"), "").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(dir.clone(), false);
+    app_state.search_results = vec![
+        DirEntry::from_path(dir.join("a.log")),
+        DirEntry::from_path(dir.join("b.log")),
+    ];
+
+    app_state.export_search_results();
+    let exported = fs::read_to_string(dir.join("search-results.txt")).unwrap();
+    let expected = format!("{}\n{}\n", dir.join("a.log").display(), dir.join("b.log").display());
+    assert_eq!(exported, expected);
+
+    // An existing export is never overwritten
+    app_state.export_search_results();
+    assert!(dir.join("search-results-1.txt").exists());
+    assert_eq!(app_state.notification.as_deref(), Some("Exported 2 paths to search-results-1.txt"));
+}
+
+#[test]
+fn test_marked_search_results_become_the_selection() {
+    let tmp_dir = TempDir::new("search_marks").unwrap();
+    let dir = tmp_dir.path().to_path_buf();
+    let mut app_state = AppState::new();
+    app_state.show_search_dialog = true;
+    app_state.search_results = ["a.log", "b.log", "c.log"].iter().map(|name| DirEntry::from_path(dir.join(name))).collect();
+
+    app_state.toggle_search_result();
+    app_state.toggle_search_result();
+    app_state.move_search_cursor_up();
+    app_state.toggle_search_result();
+    assert_eq!(app_state.search_cursor, 2);
+    assert_eq!(app_state.search_selection.len(), 1);
+
+    app_state.select_marked_search_results();
+    assert!(!app_state.show_search_dialog);
+    assert!(app_state.search_selection.is_empty());
+    assert_eq!(app_state.get_active_tab().selected_entries, [dir.join("a.log")].into_iter().collect());
+}
+
+#[test]
+fn test_recursive_search_results_survive_visiting_them() {
+    let tmp_dir = TempDir::new("search_visit").unwrap();
+    let root = fs::canonicalize(tmp_dir.path()).unwrap();
+    fs::create_dir_all(root.join("one")).unwrap();
+    fs::create_dir_all(root.join("two")).unwrap();
+    fs::write(root.join("one/x.log"), "").unwrap();
+    fs::write(root.join("two/a.txt"), "").unwrap();
+    fs::write(root.join("two/y.log"), "").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(root.clone(), false);
+    app_state.show_search_dialog = true;
+    app_state.search_recursive = true;
+    app_state.search_root = Some(root.clone());
+    app_state.search_results = vec![DirEntry::from_path(root.join("one/x.log")), DirEntry::from_path(root.join("two/y.log"))];
+
+    app_state.move_search_cursor_down();
+    app_state.select_search_result();
+    let tab = app_state.get_active_tab();
+    assert_eq!(tab.current_dir, root.join("two"));
+    assert_eq!(tab.filtered_entries[tab.cursor].path, root.join("two/y.log"));
+    assert_eq!(app_state.search_results.len(), 2);
+
+    app_state.navigate_history(false);
+    assert_eq!(app_state.get_active_tab().current_dir, root);
+    assert!(app_state.show_search_dialog);
+    assert_eq!(app_state.search_root, Some(root));
+}
+
+#[test]
+fn test_update_entries_captures_size_and_mtime() {
+    let tmp_dir = TempDir::new("details").unwrap();
+    fs::write(tmp_dir.path().join("file.txt"), "hello").unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    assert_eq!(tab.entries[0].size, 5);
+    assert!(tab.entries[0].modified.is_some());
+}
+
+#[test]
+fn test_separate_dirs_can_be_disabled() {
+    let tmp_dir = TempDir::new("sort").unwrap();
+    fs::create_dir(tmp_dir.path().join("b_dir")).unwrap();
+    fs::write(tmp_dir.path().join("a.txt"), "").unwrap();
+    fs::write(tmp_dir.path().join("c.txt"), "").unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    let names: Vec<&str> = tab.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["b_dir", "a.txt", "c.txt"]);
+
+    tab.sort.separate_dirs = false;
+    tab.update_entries(false);
+    let names: Vec<&str> = tab.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["a.txt", "b_dir", "c.txt"]);
+}
+
+#[tokio::test]
+async fn test_task_manager_queues_tasks_beyond_limit() {
+    let tmp_dir = TempDir::new("queue").unwrap();
+    fs::write(tmp_dir.path().join("a.txt"), "a").unwrap();
+    fs::write(tmp_dir.path().join("b.txt"), "b").unwrap();
+
+    let mut task_manager = TaskManager::with_limits(1, 1);
+    for name in ["a.txt", "b.txt"] {
+        task_manager.add_task(
+            TaskKind::Copy { src: tmp_dir.path().join(name), dest: tmp_dir.path().join(format!("copy_{}", name)) },
+            format!("Copy {}", name),
+        );
+    }
+    task_manager.add_task(TaskKind::CreateDirectory { path: tmp_dir.path().join("dir") }, "Create dir".to_string());
+
+    task_manager.process_pending_tasks();
+    let statuses: Vec<TaskStatus> = task_manager.get_tasks().into_iter().map(|t| t.status).collect();
+    // The light task has its own slot and is not stuck behind the copy
+    assert!(matches!(statuses[0], TaskStatus::InProgress(_)));
+    assert_eq!(statuses[1], TaskStatus::Pending);
+    assert!(matches!(statuses[2], TaskStatus::InProgress(_)));
+
+    let mut completed = 0;
+    while completed < 3 {
+        if let Some(TaskEvent::Completed { .. }) = task_manager.wait_for_event().await {
+            completed += 1;
+        }
+        task_manager.process_pending_tasks();
+    }
+    assert!(task_manager.get_tasks().iter().all(|t| t.status == TaskStatus::Completed));
+}
+
+#[test]
+fn test_remembered_directory_view_overrides_global() {
+    let tmp_dir = TempDir::new("view").unwrap();
+    let downloads = tmp_dir.path().join("downloads");
+    fs::create_dir(&downloads).unwrap();
+    fs::write(downloads.join(".hidden"), "").unwrap();
+    fs::write(tmp_dir.path().join(".hidden"), "").unwrap();
+
+    let _config_dir = temp_config_dir();
+    let mut app_state = AppState::new();
+    app_state.dir_prefs = Default::default();
+    app_state.show_hidden_files = false;
+    app_state.apply_sort_settings();
+    app_state.get_active_tab_mut().set_current_dir(downloads.clone(), false);
+
+    app_state.toggle_remember_view();
+    app_state.toggle_view_hidden_files();
+    assert!(!app_state.show_hidden_files, "global setting must stay untouched");
+    assert_eq!(app_state.get_active_tab().entries.len(), 1);
+
+    // Other directories keep the global view
+    app_state.get_active_tab_mut().leave_directory(false);
+    assert!(app_state.get_active_tab().view.is_none());
+    assert!(app_state.get_active_tab().entries.iter().all(|e| !e.name.starts_with('.')));
+
+    app_state.get_active_tab_mut().set_current_dir(downloads.clone(), false);
+    assert!(app_state.current_view().show_hidden);
+    assert_eq!(app_state.get_active_tab().entries.len(), 1);
+}
+
+/// Runs queued tasks until none is pending or in progress
+async fn run_tasks(task_manager: &mut TaskManager) {
+    loop {
+        task_manager.process_pending_tasks();
+        let busy = task_manager
+            .get_tasks()
+            .iter()
+            .any(|t| matches!(t.status, TaskStatus::Pending | TaskStatus::InProgress(_)));
+        if !busy {
+            break;
+        }
+        task_manager.wait_for_event().await;
+    }
+}
+
+#[tokio::test]
+async fn test_extract_into_unique_subfolder() {
+    let tmp_dir = TempDir::new("extract").unwrap();
+    let src = tmp_dir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("a.txt"), "a").unwrap();
+    fs::write(src.join("b.txt"), "b").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(src.clone(), false);
+    app_state.input_buffer = "bundle".to_string();
+    app_state.archive_format = ArchiveFormat::Zip;
+    app_state.get_active_tab_mut().selected_entries.extend(&[src.join("a.txt"), src.join("b.txt")].map(DirEntry::from_path));
+    app_state.archive_item();
+    run_tasks(&mut app_state.task_manager).await;
+
+    // The folder named after the archive is taken, so a suffix is added
+    fs::create_dir(src.join("bundle")).unwrap();
+    app_state.get_active_tab_mut().update_entries(false);
+    app_state.get_active_tab_mut().select_path(&src.join("bundle.zip"));
+    app_state.extract_selection();
+    assert!(app_state.confirmation_message.contains("bundle-1"));
+    app_state.confirm();
+    run_tasks(&mut app_state.task_manager).await;
+
+    assert_eq!(fs::read_to_string(src.join("bundle-1/a.txt")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(src.join("bundle-1/b.txt")).unwrap(), "b");
+}
+
+#[tokio::test]
+async fn test_delete_moves_files_to_the_trash() {
+    let config_dir = temp_config_dir();
+    let tmp_dir = TempDir::new("trash").unwrap();
+    let src = tmp_dir.path().join("my files");
+    let other = tmp_dir.path().join("other");
+    fs::create_dir(&src).unwrap();
+    fs::create_dir(&other).unwrap();
+    fs::write(src.join("a.txt"), "first").unwrap();
+    fs::write(other.join("a.txt"), "second").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.config.behavior.use_trash = true;
+    for dir in [&src, &other] {
+        app_state.get_active_tab_mut().set_current_dir(dir.clone(), false);
+        app_state.get_active_tab_mut().select_path(&dir.join("a.txt"));
+        app_state.delete_selection();
+        assert!(app_state.confirmation_message.contains("to the trash"));
+        app_state.confirm();
+        run_tasks(&mut app_state.task_manager).await;
+    }
+
+    // Второй файл с тем же именем получает в корзине суффикс
+    let trash = config_dir.dir.path().join("Trash");
+    assert!(!src.join("a.txt").exists());
+    assert_eq!(fs::read_to_string(trash.join("files/a.txt")).unwrap(), "first");
+    assert_eq!(fs::read_to_string(trash.join("files/a.txt.2")).unwrap(), "second");
+    let info = fs::read_to_string(trash.join("info/a.txt.trashinfo")).unwrap();
+    assert!(info.starts_with("[Trash Info]\n"));
+    assert!(info.contains(&format!("Path={}/my%20files/a.txt\n", tmp_dir.path().display())));
+    assert!(info.contains("DeletionDate="));
+}
+
+#[tokio::test]
+async fn test_finished_tasks_housekeeping() {
+    let tmp_dir = TempDir::new("housekeeping").unwrap();
+    let mut task_manager = TaskManager::new();
+    task_manager.add_task(TaskKind::CreateDirectory { path: tmp_dir.path().join("ok") }, "ok".to_string());
+    task_manager.add_task(
+        TaskKind::CreateDirectory { path: tmp_dir.path().join("missing/parent/dir") },
+        "fails".to_string(),
+    );
+    run_tasks(&mut task_manager).await;
+
+    // Completed tasks expire, failed ones stay until cleared
+    task_manager.expire_completed(std::time::Duration::ZERO);
+    let tasks = task_manager.get_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert!(matches!(tasks[0].status, TaskStatus::Failed(_)));
+
+    task_manager.clear_finished();
+    assert!(task_manager.get_tasks().is_empty());
+}
+
+#[tokio::test]
+async fn test_compressed_tar_round_trip() {
+    for format in [ArchiveFormat::TarGz, ArchiveFormat::TarBz2, ArchiveFormat::TarXz] {
+        let tmp_dir = TempDir::new("tar_formats").unwrap();
+        let src = tmp_dir.path().to_path_buf();
+        fs::write(src.join("note.txt"), "hello").unwrap();
+
+        let mut app_state = AppState::new();
+        app_state.get_active_tab_mut().set_current_dir(src.clone(), false);
+        app_state.get_active_tab_mut().select_path(&src.join("note.txt"));
+        app_state.input_buffer = "packed".to_string();
+        app_state.archive_format = format;
+        app_state.archive_item();
+        run_tasks(&mut app_state.task_manager).await;
+
+        let archive = src.join(format!("packed.{}", format));
+        assert!(archive.exists(), "{} archive was not created", format);
+        app_state.get_active_tab_mut().update_entries(false);
+        app_state.get_active_tab_mut().select_path(&archive);
+        app_state.extract_selection();
+        app_state.confirm();
+        run_tasks(&mut app_state.task_manager).await;
+
+        assert_eq!(fs::read_to_string(src.join("packed/note.txt")).unwrap(), "hello", "{}", format);
+    }
+}
+
+#[tokio::test]
+async fn test_task_event_refreshes_only_affected_tabs() {
+    let tmp_dir = TempDir::new("task_event").unwrap();
+    let first = tmp_dir.path().join("first");
+    let second = tmp_dir.path().join("second");
+    fs::create_dir(&first).unwrap();
+    fs::create_dir(&second).unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(first.clone(), false);
+    app_state.new_tab();
+    app_state.get_active_tab_mut().set_current_dir(second.clone(), false);
+
+    fs::write(first.join("new.txt"), "").unwrap();
+    fs::write(second.join("other.txt"), "").unwrap();
+    let kind = TaskKind::CreateFile { path: first.join("new.txt") };
+    app_state.handle_task_event(TaskEvent::Completed { id: uuid::Uuid::new_v4(), summary: kind.summary(), kind });
+
+    assert_eq!(app_state.notification.as_deref(), Some("Created file new.txt"));
+    assert_eq!(app_state.tabs[0].entries.len(), 1);
+    assert!(app_state.tabs[1].entries.is_empty(), "unrelated tab must not be re-read");
+}
+
+#[tokio::test]
+async fn test_archive_compression_level() {
+    let tmp_dir = TempDir::new("compression").unwrap();
+    let src = tmp_dir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("data.txt"), "corvus ".repeat(10_000)).unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(src.clone(), false);
+    app_state.archive_format = ArchiveFormat::Zip;
+    for (name, level) in [("stored", 0), ("best", 9)] {
+        app_state.archive_compression_level = level;
+        app_state.input_buffer = name.to_string();
+        app_state.get_active_tab_mut().select_path(&src.join("data.txt"));
+        app_state.archive_item();
+    }
+    run_tasks(&mut app_state.task_manager).await;
+
+    let stored = fs::metadata(src.join("stored.zip")).unwrap().len();
+    let best = fs::metadata(src.join("best.zip")).unwrap().len();
+    assert!(stored > 70_000, "level 0 must store files uncompressed");
+    assert!(best < stored / 10);
+
+    app_state.adjust_compression_level(5);
+    assert_eq!(app_state.archive_compression_level, 9);
+    app_state.archive_compression_level = 0;
+    app_state.adjust_compression_level(-1);
+    assert_eq!(app_state.archive_compression_level, 0);
+
+    // Opening the dialog starts from the configured defaults
+    app_state.archive_settings.default_format = ArchiveFormat::TarXz;
+    app_state.archive_settings.compression_level = 3;
+    app_state.archive_selection();
+    assert_eq!(app_state.archive_format, ArchiveFormat::TarXz);
+    assert_eq!(app_state.archive_compression_level, 3);
+}
+
+#[tokio::test]
+async fn test_archive_keeps_relative_layout() {
+    let tmp_dir = TempDir::new("layout").unwrap();
+    let root = tmp_dir.path().join("root");
+    for dir in ["one", "two", "docs/sub"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    fs::write(root.join("one/notes.txt"), "one").unwrap();
+    fs::write(root.join("two/notes.txt"), "two").unwrap();
+    fs::write(root.join("docs/sub/readme.md"), "docs").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(root.clone(), false);
+    for format in [ArchiveFormat::Zip, ArchiveFormat::TarGz] {
+        // Files with the same name from different folders, e.g. picked from search results
+        app_state.get_active_tab_mut().selected_entries.extend(&[
+            root.join("one/notes.txt"),
+            root.join("two/notes.txt"),
+            root.join("docs"),
+        ].map(DirEntry::from_path));
+        app_state.archive_format = format;
+        app_state.input_buffer = "bundle".to_string();
+        app_state.archive_item();
+        run_tasks(&mut app_state.task_manager).await;
+
+        let archive = root.join(format!("bundle.{}", format));
+        let out = tmp_dir.path().join(format!("out-{}", format));
+        app_state.task_manager.add_task(TaskKind::Extract { archive: archive.clone(), dest: out.clone() }, "Extract".to_string());
+        run_tasks(&mut app_state.task_manager).await;
+        fs::remove_file(archive).unwrap();
+
+        assert_eq!(fs::read_to_string(out.join("one/notes.txt")).unwrap(), "one");
+        assert_eq!(fs::read_to_string(out.join("two/notes.txt")).unwrap(), "two");
+        assert_eq!(fs::read_to_string(out.join("docs/sub/readme.md")).unwrap(), "docs");
+    }
+}
+
+#[tokio::test]
+async fn test_chained_task_waits_for_parent() {
+    let tmp_dir = TempDir::new("chain").unwrap();
+    let mut task_manager = TaskManager::new();
+
+    // The parent fails because the source does not exist, so the child is cancelled
+    let failing = task_manager.add_task(
+        TaskKind::Copy { src: tmp_dir.path().join("missing"), dest: tmp_dir.path().join("copy") },
+        "Copy missing".to_string(),
+    );
+    let cancelled = task_manager.add_task_after(failing, TaskKind::CreateDirectory { path: tmp_dir.path().join("a") }, "a".to_string());
+    let grandchild = task_manager.add_task_after(cancelled, TaskKind::CreateDirectory { path: tmp_dir.path().join("b") }, "b".to_string());
+
+    task_manager.process_pending_tasks();
+    let status_of = |task_manager: &TaskManager, id| task_manager.get_tasks().into_iter().find(|t| t.id == id).unwrap().status;
+    assert_eq!(status_of(&task_manager, cancelled), TaskStatus::Pending);
+    run_tasks(&mut task_manager).await;
+    assert!(matches!(status_of(&task_manager, cancelled), TaskStatus::Failed(_)));
+    assert!(matches!(status_of(&task_manager, grandchild), TaskStatus::Failed(_)));
+    assert!(!tmp_dir.path().join("a").exists());
+
+    let parent = task_manager.add_task(TaskKind::CreateDirectory { path: tmp_dir.path().join("c") }, "c".to_string());
+    task_manager.add_task_after(parent, TaskKind::CreateFile { path: tmp_dir.path().join("c/file") }, "file".to_string());
+    run_tasks(&mut task_manager).await;
+    assert!(tmp_dir.path().join("c/file").exists());
+}
+
+#[tokio::test]
+async fn test_archive_and_remove_sources() {
+    let tmp_dir = TempDir::new("archive_remove").unwrap();
+    fs::write(tmp_dir.path().join("a.txt"), "a").unwrap();
+    fs::write(tmp_dir.path().join("b.txt"), "b").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.get_active_tab_mut().selected_entries.extend(&[tmp_dir.path().join("a.txt"), tmp_dir.path().join("b.txt")].map(DirEntry::from_path));
+    app_state.archive_selection();
+    app_state.toggle_archive_remove_sources();
+    app_state.archive_format = ArchiveFormat::Tar;
+    app_state.input_buffer = "both".to_string();
+    app_state.archive_item();
+    run_tasks(&mut app_state.task_manager).await;
+
+    assert!(tmp_dir.path().join("both.tar").exists());
+    assert!(!tmp_dir.path().join("a.txt").exists());
+    assert!(!tmp_dir.path().join("b.txt").exists());
+}
+
+#[tokio::test]
+async fn test_archive_reports_progress() {
+    let tmp_dir = TempDir::new("archive_progress").unwrap();
+    let src = tmp_dir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    for i in 0..4 {
+        fs::write(src.join(format!("{}.bin", i)), vec![i as u8; 4096]).unwrap();
+    }
+
+    let mut task_manager = TaskManager::new();
+    task_manager.add_task(
+        TaskKind::Archive { paths: vec![src], dest: tmp_dir.path().join("src.tar.gz"), format: ArchiveFormat::TarGz, level: 6 },
+        "Archive".to_string(),
+    );
+    task_manager.process_pending_tasks();
+    let mut progress = Vec::new();
+    loop {
+        match task_manager.wait_for_event().await.unwrap() {
+            TaskEvent::Progress { progress: p, .. } => progress.push(p),
+            TaskEvent::Completed { .. } => break,
+            TaskEvent::Failed { error, .. } => panic!("archiving failed: {}", error),
+            TaskEvent::GroupItemFinished { .. } => unreachable!("the archive task is not grouped"),
+        }
+    }
+    assert_eq!(progress, vec![0.25, 0.5, 0.75, 1.0]);
+}
+
+#[test]
+fn test_plan_preview_lists_operations_without_queueing() {
+    let tmp_dir = TempDir::new("plan").unwrap();
+    let src = tmp_dir.path().join("src");
+    let dest = tmp_dir.path().join("dest");
+    fs::create_dir_all(src.join("folder/nested")).unwrap();
+    fs::create_dir(&dest).unwrap();
+    fs::write(src.join("a.txt"), "a").unwrap();
+    fs::write(src.join("folder/nested/b.txt"), "b").unwrap();
+    fs::write(dest.join("a.txt"), "old").unwrap();
+
+    let mut clipboard = corvus_core::clipboard::Clipboard::new();
+    clipboard.yank(vec![src.join("a.txt"), src.join("folder")]);
+    let plan = AppState::paste_plan(&clipboard, &dest);
+    let lines = describe_plan(&plan);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("copy ") && lines[0].ends_with("[overwrites existing]"));
+    assert!(!lines[1].contains('['));
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(src.clone(), false);
+    app_state.get_active_tab_mut().selected_entries.extend(&[src.join("a.txt"), src.join("folder")].map(DirEntry::from_path));
+    app_state.delete_selection();
+    app_state.toggle_plan_preview();
+    let preview = app_state.plan_preview.clone().unwrap();
+    assert_eq!(preview.len(), 2);
+    assert!(preview.iter().any(|line| line.ends_with("(folder, 2 items inside)")));
+    assert!(app_state.task_manager.get_tasks().is_empty());
+
+    app_state.cancel();
+    assert!(app_state.plan_preview.is_none());
+    assert!(src.join("a.txt").exists());
+
+    // В пробном режиме список открывается сразу, даже без конфликта имён
+    app_state.toggle_dry_run();
+    app_state.clipboard.cut(vec![src.join("folder")]);
+    app_state.get_active_tab_mut().set_current_dir(dest.clone(), false);
+    app_state.paste();
+    let preview = app_state.plan_preview.clone().unwrap();
+    assert_eq!(preview.len(), 1);
+    assert!(preview[0].starts_with("move "));
+    assert!(app_state.confirmation_message.contains("v: hide operations"));
+    app_state.cancel();
+    assert!(app_state.task_manager.get_tasks().is_empty());
+    assert!(src.join("folder").exists());
+
+    // A name conflict opens on the list too, and says so
+    app_state.clipboard.yank(vec![src.join("a.txt")]);
+    app_state.paste();
+    assert!(app_state.confirmation_message.contains("Overwrite? (y/n, v: hide operations)"));
+    let preview = app_state.plan_preview.clone().unwrap();
+    assert!(preview[0].ends_with("[overwrites existing]"));
+    app_state.cancel();
+    assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "old");
+}
+
+#[test]
+fn test_setup_saves_opener_and_trash() {
+    let _config = temp_config_dir();
+    let mut app_state = AppState::new();
+    app_state.setup_cursor = 3;
+    app_state.change_setup_item();
+    app_state.move_setup_cursor(1);
+    app_state.change_setup_item();
+    app_state.finish_setup();
+
+    let (saved, _) = config::load_config().unwrap();
+    assert!(saved.behavior.opener.unwrap().ends_with(" {file}"));
+    assert!(saved.behavior.opener_in_terminal);
+    assert!(saved.behavior.use_trash);
+}
+
+#[test]
+fn test_notifications_follow_the_language() {
+    let _config = temp_config_dir();
+    let mut app_state = AppState::new();
+    app_state.language = Language::Russian;
+    app_state.toggle_dry_run();
+    assert_eq!(app_state.notification.as_deref(), Some("Пробный режим включён: удаление и вставка сначала показывают список операций"));
+
+    app_state.language = Language::English;
+    app_state.toggle_dry_run();
+    assert_eq!(app_state.notification.as_deref(), Some("Dry run off"));
+}
+
+#[test]
+fn test_resume_unfinished_tasks() {
+    let tmp_dir = TempDir::new("resume").unwrap();
+    fs::write(tmp_dir.path().join("kept.txt"), "").unwrap();
+    fs::write(tmp_dir.path().join("gone.txt"), "").unwrap();
+
+    let previous = TaskManager::new();
+    previous.add_task(
+        TaskKind::Copy { src: tmp_dir.path().join("kept.txt"), dest: tmp_dir.path().join("copy.txt") },
+        "Copy kept".to_string(),
+    );
+    let archive = previous.add_task(
+        TaskKind::Archive { paths: vec![tmp_dir.path().join("gone.txt")], dest: tmp_dir.path().join("a.zip"), format: ArchiveFormat::Zip, level: 6 },
+        "Archive gone".to_string(),
+    );
+    previous.add_task_after(archive, TaskKind::Delete { path: tmp_dir.path().join("kept.txt") }, "Delete after archive".to_string());
+
+    let saved = previous.unfinished_tasks();
+    assert_eq!(saved.len(), 3);
+    assert_eq!(saved[2].parent, Some(1));
+    let json = serde_json::to_string(&saved).unwrap();
+    fs::remove_file(tmp_dir.path().join("gone.txt")).unwrap();
+
+    // The archive source is gone, so the archive and the delete chained to it are dropped
+    let mut app_state = AppState::new();
+    app_state.offer_task_resume(serde_json::from_str(&json).unwrap());
+    assert!(app_state.confirmation_message.contains("Resume 3 unfinished tasks"));
+    app_state.confirm();
+    let tasks = app_state.task_manager.get_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].description, "Copy kept");
+    assert_eq!(tasks[0].status, TaskStatus::Pending);
+}
+
+#[tokio::test]
+async fn test_dual_layout_copies_to_other_panel() {
+    let tmp_dir = TempDir::new("dual").unwrap();
+    let left = tmp_dir.path().join("left");
+    let right = tmp_dir.path().join("right");
+    fs::create_dir(&left).unwrap();
+    fs::create_dir(&right).unwrap();
+    fs::write(left.join("a.txt"), "a").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(left.clone(), false);
+    app_state.toggle_layout_mode();
+    assert_eq!(app_state.layout_mode, LayoutMode::Dual);
+
+    // Tab moves to the right panel, which navigates independently
+    app_state.focus = FocusBlock::Middle;
+    app_state.cycle_focus();
+    assert_eq!(app_state.active_panel, PanelSide::Right);
+    app_state.get_active_tab_mut().set_current_dir(right.clone(), false);
+    assert_eq!(app_state.tabs[0].current_dir, left);
+
+    app_state.cycle_focus();
+    app_state.cycle_focus();
+    app_state.cycle_focus();
+    app_state.cycle_focus();
+    app_state.cycle_focus();
+    assert_eq!((app_state.focus, app_state.active_panel), (FocusBlock::Middle, PanelSide::Left));
+
+    app_state.get_active_tab_mut().select_path(&left.join("a.txt"));
+    app_state.transfer_to_other_panel(ClipboardMode::Copy);
+    run_tasks(&mut app_state.task_manager).await;
+    assert_eq!(fs::read_to_string(right.join("a.txt")).unwrap(), "a");
+    assert!(app_state.clipboard.paths.is_empty(), "the clipboard is not used");
+}
+
+#[test]
+fn test_quit_asks_while_tasks_are_running() {
+    let tmp_dir = TempDir::new("quit").unwrap();
+    let mut app_state = AppState::new();
+    assert!(app_state.request_quit());
+
+    app_state.quit_requested = false;
+    app_state.task_manager.add_task(TaskKind::CreateDirectory { path: tmp_dir.path().join("dir") }, "Create dir".to_string());
+    assert!(!app_state.request_quit());
+    assert_eq!(app_state.confirmation_message, "1 operation in progress, quit anyway? (y/n)");
+    app_state.cancel();
+    assert!(!app_state.quit_requested);
+
+    app_state.request_quit();
+    app_state.confirm();
+    assert!(app_state.quit_requested);
+}
+
+#[test]
+fn test_refuse_pasting_directory_into_itself() {
+    let tmp_dir = TempDir::new("into_itself").unwrap();
+    let folder = tmp_dir.path().join("folder");
+    fs::create_dir_all(folder.join("sub")).unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.get_active_tab_mut().select_path(&folder);
+    app_state.yank_selection();
+    app_state.get_active_tab_mut().set_current_dir(folder.join("sub"), false);
+    app_state.paste();
+    assert!(app_state.task_manager.get_tasks().is_empty());
+    assert_eq!(app_state.notification.as_deref(), Some("Cannot paste \"folder\" into itself"));
+
+    // A name with a slash would move the folder into its own subfolder
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.get_active_tab_mut().select_path(&folder);
+    app_state.rename_selection();
+    app_state.input_buffer = "folder/sub/folder".to_string();
+    app_state.rename_item();
+    assert!(app_state.input_dialog_error.is_some());
+    assert!(app_state.task_manager.get_tasks().is_empty());
+}
+
+#[tokio::test]
+async fn test_custom_command_runs_with_selection() {
+    let tmp_dir = TempDir::new("custom_command").unwrap();
+    fs::write(tmp_dir.path().join("a b.txt"), "a").unwrap();
+    fs::write(tmp_dir.path().join("c'd.txt"), "c").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.config.commands = vec![
+        config::CustomCommand { key: 'j', name: "Join".to_string(), cmd: "cat {files} > joined.txt".to_string(), run_in_terminal: false },
+        config::CustomCommand { key: 'f', name: "Fail".to_string(), cmd: "echo broken >&2; exit 3".to_string(), run_in_terminal: false },
+    ];
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.get_active_tab_mut().selected_entries.extend(&[tmp_dir.path().join("a b.txt"), tmp_dir.path().join("c'd.txt")].map(DirEntry::from_path));
+    assert!(app_state.run_custom_command_by_key('j'));
+    assert!(!app_state.run_custom_command_by_key('z'));
+    run_tasks(&mut app_state.task_manager).await;
+    assert_eq!(fs::read_to_string(tmp_dir.path().join("joined.txt")).unwrap(), "ac");
+
+    app_state.run_custom_command_by_key('f');
+    run_tasks(&mut app_state.task_manager).await;
+    let failed = app_state.task_manager.get_tasks().into_iter().find(|t| t.description == "Fail").unwrap();
+    assert_eq!(failed.status, TaskStatus::Failed("exit status: 3: broken".to_string()));
+}
+
+#[tokio::test]
+async fn test_shell_command_runs_for_each_selected_file() {
+    let tmp_dir = TempDir::new("shell_command").unwrap();
+    fs::write(tmp_dir.path().join("a b.txt"), "a").unwrap();
+    fs::write(tmp_dir.path().join("c'd.txt"), "c").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.get_active_tab_mut().selected_entries.extend(&[tmp_dir.path().join("a b.txt"), tmp_dir.path().join("c'd.txt")].map(DirEntry::from_path));
+    app_state.start_shell_command();
+    app_state.input_buffer = "cat {} >> joined.txt".to_string();
+    app_state.run_shell_command();
+    assert_eq!(app_state.input_mode, InputMode::Normal);
+    run_tasks(&mut app_state.task_manager).await;
+    let joined = fs::read_to_string(tmp_dir.path().join("joined.txt")).unwrap();
+    assert!(joined == "ac" || joined == "ca");
+
+    app_state.start_shell_command();
+    app_state.input_buffer = "echo first; echo done".to_string();
+    app_state.run_shell_command();
+    let event = loop {
+        app_state.task_manager.process_pending_tasks();
+        match app_state.task_manager.wait_for_event().await.unwrap() {
+            TaskEvent::Progress { .. } => continue,
+            event => break event,
+        }
+    };
+    assert!(matches!(event, TaskEvent::Completed { summary, .. } if summary == "echo first; echo done finished: done"));
+
+    // Long output is not collected, only its last line is reported
+    app_state.start_shell_command();
+    app_state.input_buffer = "yes | head -c 20000000; echo last".to_string();
+    app_state.run_shell_command();
+    let event = loop {
+        app_state.task_manager.process_pending_tasks();
+        match app_state.task_manager.wait_for_event().await.unwrap() {
+            TaskEvent::Progress { .. } => continue,
+            event => break event,
+        }
+    };
+    assert!(matches!(event, TaskEvent::Completed { summary, .. } if summary.ends_with("finished: last")));
+    assert_eq!(app_state.task_manager.get_tasks().last().unwrap().output, "last");
+}
+
+#[test]
+fn test_glob_filter_anchors_the_pattern_and_reports_errors() {
+    let tmp_dir = TempDir::new("glob_filter").unwrap();
+    for name in ["main.rs", "main.rs.bak", "test_a", "test_ab", "Lib.RS"] {
+        fs::write(tmp_dir.path().join(name), "").unwrap();
+    }
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    let names = |app_state: &AppState| {
+        let mut names: Vec<String> = app_state.get_active_tab().filtered_entries.iter().map(|entry| entry.name.clone()).collect();
+        names.sort();
+        names
+    };
+
+    app_state.start_glob_filter();
+    app_state.input_buffer = "*.rs".to_string();
+    app_state.apply_glob_filter();
+    assert_eq!(names(&app_state), ["main.rs"]);
+
+    app_state.start_glob_filter();
+    assert_eq!(app_state.input_buffer, "*.rs");
+    app_state.input_buffer = "test_?".to_string();
+    app_state.apply_glob_filter();
+    assert_eq!(names(&app_state), ["test_a"]);
+
+    // The listing is re-read with the filter still applied
+    fs::write(tmp_dir.path().join("test_b"), "").unwrap();
+    app_state.get_active_tab_mut().update_entries(false);
+    assert_eq!(names(&app_state), ["test_a", "test_b"]);
+
+    app_state.start_glob_filter();
+    app_state.input_buffer = "[a-".to_string();
+    app_state.apply_glob_filter();
+    assert!(app_state.input_dialog_error.as_deref().is_some_and(|error| error.starts_with("Invalid glob")));
+    assert_eq!(app_state.input_mode, InputMode::GlobFilter);
+    assert_eq!(names(&app_state), ["test_a", "test_b"]);
+
+    app_state.input_mode = InputMode::Normal;
+    app_state.escape();
+    assert!(app_state.get_active_tab().glob_filter.is_none());
+    assert_eq!(names(&app_state).len(), 6);
+}
+
+// The config lock is only contended by other threads, the runtime of this test is its own
+#[allow(clippy::await_holding_lock)]
+#[tokio::test]
+async fn test_plugins_are_installed_from_a_directory_or_git_and_removed() {
+    let tmp_dir = TempDir::new("plugin_install").unwrap();
+    let plugins_dir = tmp_dir.path().join("plugins");
+    let src = tmp_dir.path().join("hello");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("plugin.toml"), "name = \"Hello\"\nauthor = \"\"\nversion = \"1.0\"\ndescription = \"\"\napi_version = \"0.1\"\n").unwrap();
+
+    let _config_dir = temp_config_dir();
+    let mut app_state = AppState::new();
+    app_state.plugins_dir = Some(plugins_dir.clone());
+    app_state.plugins.clear();
+    app_state.input_mode = InputMode::Settings;
+    app_state.start_plugin_install();
+    app_state.input_buffer = src.display().to_string();
+    app_state.install_plugin();
+    assert_eq!(app_state.input_mode, InputMode::Settings);
+    assert_eq!(app_state.plugin_message.as_deref(), Some("Installed Hello 1.0"));
+    assert!(plugins_dir.join("hello/plugin.toml").exists());
+    assert_eq!(app_state.plugins.len(), 1);
+
+    // The same plugin twice, and one written for a newer API, are refused
+    app_state.start_plugin_install();
+    app_state.input_buffer = src.display().to_string();
+    app_state.install_plugin();
+    assert!(app_state.plugin_message.as_deref().unwrap().contains("already exists"));
+    let newer = tmp_dir.path().join("newer");
+    fs::create_dir(&newer).unwrap();
+    fs::write(newer.join("plugin.toml"), "name = \"Newer\"\nauthor = \"\"\nversion = \"1.0\"\ndescription = \"\"\napi_version = \"9.0\"\n").unwrap();
+    app_state.start_plugin_install();
+    app_state.input_buffer = newer.display().to_string();
+    app_state.install_plugin();
+    assert_eq!(app_state.plugin_message.as_deref(), Some("Not installed: Newer needs API 9.0, supported are 0.1 to 0.1"));
+    assert!(!plugins_dir.join("newer").exists());
+
+    // A git repository is cloned by a task and checked once it is there
+    let git = |args: &[&str]| {
+        std::process::Command::new("git").args(args).current_dir(&newer).output().map(|output| output.status.success())
+    };
+    if git(&["init", "-q"]).unwrap_or(false) {
+        fs::write(newer.join("plugin.toml"), "name = \"Newer\"\nauthor = \"\"\nversion = \"2.0\"\ndescription = \"\"\napi_version = \"0.1\"\n").unwrap();
+        assert!(git(&["add", "."]).unwrap());
+        assert!(git(&["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-qm", "init"]).unwrap());
+        app_state.start_plugin_install();
+        app_state.input_buffer = format!("file://{}", newer.display());
+        app_state.install_plugin();
+        let event = loop {
+            app_state.task_manager.process_pending_tasks();
+            match app_state.task_manager.wait_for_event().await.unwrap() {
+                TaskEvent::Progress { .. } => continue,
+                event => break event,
+            }
+        };
+        app_state.handle_task_event(event);
+        assert_eq!(app_state.plugin_message.as_deref(), Some("Installed Newer 2.0"));
+        assert_eq!(app_state.plugins.len(), 2);
+    }
+
+    app_state.remove_plugin(0);
+    assert!(app_state.show_confirmation);
+    app_state.confirm();
+    assert_eq!(app_state.plugin_message.as_deref(), Some("Removed Hello"));
+    assert!(!plugins_dir.join("hello").exists());
+    assert!(app_state.plugins.iter().all(|plugin| plugin.manifest.name != "Hello"));
+}
+
+#[test]
+fn test_plugin_stderr_is_reported_unless_its_log_is_open() {
+    use std::os::unix::fs::PermissionsExt;
+    let tmp_dir = TempDir::new("plugin_stderr").unwrap();
+    let src = tmp_dir.path().join("noisy");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("plugin.toml"), "name = \"Noisy\"\nauthor = \"\"\nversion = \"1\"\ndescription = \"\"\napi_version = \"0.1\"\ncommand = \"run.sh\"\n").unwrap();
+    // Complains about `init` right away and once more a little later
+    fs::write(src.join("run.sh"), "#!/bin/sh\necho starting >&2; read line; echo \"got $line\" >&2; echo twice >&2\nsleep 0.3; echo later >&2; sleep 5\n").unwrap();
+    fs::set_permissions(src.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+
+    let _config_dir = temp_config_dir();
+    let mut app_state = AppState::new();
+    app_state.plugins_dir = Some(tmp_dir.path().join("plugins"));
+    app_state.plugins.clear();
+    app_state.input_buffer = src.display().to_string();
+    app_state.install_plugin();
+    let wait_for = |app_state: &mut AppState, count: usize| {
+        let started = std::time::Instant::now();
+        while app_state.plugin_host.log_lines(&app_state.plugins[0].path).len() < count {
+            assert!(started.elapsed() < std::time::Duration::from_secs(5));
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+    };
+    wait_for(&mut app_state, 3);
+    app_state.poll_plugins();
+    assert_eq!(app_state.notification.as_deref(), Some("plugin Noisy wrote 3 errors"));
+
+    app_state.notification = None;
+    app_state.open_plugin_log(0);
+    wait_for(&mut app_state, 4);
+    app_state.poll_plugins();
+    assert!(app_state.notification.is_none());
+    app_state.close_plugin_log();
+}
+
+#[test]
+fn test_plugin_starts_only_after_its_permissions_are_allowed() {
+    use corvus_core::plugin::PluginState;
+    use std::os::unix::fs::PermissionsExt;
+    let tmp_dir = TempDir::new("plugin_consent").unwrap();
+    let src = tmp_dir.path().join("reader");
+    fs::create_dir(&src).unwrap();
+    let manifest = "name = \"Reader\"\nauthor = \"\"\nversion = \"1\"\ndescription = \"\"\napi_version = \"0.1\"\ncommand = \"run.sh\"\n\n[permissions]\nread_paths = [\"~/Documents\"]\n";
+    fs::write(src.join("plugin.toml"), manifest).unwrap();
+    fs::write(src.join("run.sh"), "#!/bin/sh\nsleep 5\n").unwrap();
+    fs::set_permissions(src.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+
+    let config_dir = temp_config_dir();
+    let mut app_state = AppState::new();
+    app_state.plugins_dir = Some(tmp_dir.path().join("plugins"));
+    app_state.plugins.clear();
+    assert!(app_state.config.plugins.permissions.is_empty());
+    app_state.input_buffer = src.display().to_string();
+    app_state.install_plugin();
+    assert_eq!(app_state.plugins[0].state, PluginState::NeedsConsent);
+    assert!(app_state.show_confirmation);
+    assert!(app_state.confirmation_message.contains("  read ~/Documents\n"));
+
+    // Declining leaves the plugin waiting; Enter on it asks again
+    app_state.cancel();
+    assert_eq!(app_state.plugins[0].state, PluginState::NeedsConsent);
+    app_state.toggle_plugin(0);
+    assert!(app_state.show_confirmation);
+    app_state.confirm();
+    assert_eq!(app_state.plugins[0].state, PluginState::Running);
+    assert_eq!(app_state.config.plugins.permissions["Reader"].read_paths, vec!["~/Documents"]);
+
+    // An update asking for more needs a new answer, with the addition marked
+    app_state.plugins[0].manifest.permissions.network = true;
+    app_state.toggle_plugin(0);
+    app_state.toggle_plugin(0);
+    assert_eq!(app_state.plugins[0].state, PluginState::NeedsConsent);
+    assert!(app_state.confirmation_message.contains("  read ~/Documents\n  network access (new)\n"));
+    app_state.confirm();
+    assert_eq!(app_state.plugins[0].state, PluginState::Running);
+    app_state.toggle_plugin(0);
+
+    // The answers and the disabled plugin are kept in the test's own config
+    let saved = fs::read_to_string(config_dir.dir.path().join("config.toml")).unwrap();
+    assert!(saved.contains("Reader"));
+}
+
+#[test]
+fn test_apply_config_keeps_tabs_and_updates_changed_sections() {
+    let mut app_state = AppState::new();
+    app_state.new_tab();
+    let tab_count = app_state.tabs.len();
+    let sort_before = app_state.sort_settings.clone();
+
+    let mut config = config::Config::default();
+    config.bookmarks = app_state.config.bookmarks.clone();
+    config.bookmarks.push(Bookmark { name: "reloaded".to_string(), path: std::path::PathBuf::from("/tmp"), key: None });
+    config.behavior.confirm_delete = !app_state.behavior_settings.confirm_delete;
+    config.sort = app_state.config.sort.clone();
+    let confirm_delete = config.behavior.confirm_delete;
+
+    app_state.apply_config(config);
+    assert_eq!(app_state.tabs.len(), tab_count);
+    assert!(app_state.bookmarks.iter().any(|bookmark| bookmark.name == "reloaded"));
+    assert_eq!(app_state.behavior_settings.confirm_delete, confirm_delete);
+    assert_eq!(app_state.sort_settings, sort_before);
+}
+
+#[test]
+fn test_missing_bookmark_is_flagged_and_not_opened() {
+    let dir = TempDir::new("missing_bookmark").unwrap();
+    let gone = dir.path().join("gone");
+    let mut app_state = AppState::new();
+    let current_dir = app_state.get_active_tab().current_dir.clone();
+    app_state.bookmarks = vec![Bookmark { name: "gone".to_string(), path: gone.clone(), key: None }];
+    app_state.refresh_missing_places();
+    assert!(app_state.missing_places.contains(&gone));
+
+    app_state.focus = FocusBlock::Bookmarks;
+    app_state.bookmarks_cursor = 0;
+    app_state.update_middle_pane_from_left_pane_selection();
+    assert_eq!(app_state.get_active_tab().current_dir, current_dir);
+    assert!(app_state.notification.as_deref().unwrap().contains("no longer exists"));
+
+    // The bookmark works again once its directory is back
+    fs::create_dir(&gone).unwrap();
+    app_state.update_middle_pane_from_left_pane_selection();
+    assert_eq!(app_state.get_active_tab().current_dir, gone);
+    assert!(!app_state.missing_places.contains(&gone));
+}
+
+#[tokio::test]
+async fn test_directory_preview_counts_and_sizes_children() {
+    let tmp_dir = TempDir::new("dir_preview").unwrap();
+    let big = tmp_dir.path().join("big");
+    fs::create_dir_all(big.join("sub")).unwrap();
+    fs::write(big.join("sub/a"), vec![0; 100]).unwrap();
+    fs::write(big.join("sub/b"), vec![0; 28]).unwrap();
+    for i in 0..PREVIEW_DIR_LIMIT {
+        fs::write(big.join(format!("file{}", i)), "").unwrap();
+    }
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().current_dir = tmp_dir.path().to_path_buf();
+    app_state.get_active_tab_mut().update_entries(false);
+    let Some(PreviewContent::Directory { entries, total, size, .. }) = app_state.get_active_tab().preview_content.clone() else {
+        panic!("expected a directory preview");
+    };
+    assert_eq!(total, PREVIEW_DIR_LIMIT + 1);
+    assert_eq!(entries.len(), PREVIEW_DIR_LIMIT);
+    assert_eq!((entries[0].name.as_str(), entries[0].child_count), ("sub", Some(2)));
+    assert!(size.is_none());
+
+    app_state.request_dir_sizes();
+    let update = app_state.dir_sizer.wait_for_update().await.unwrap();
+    app_state.apply_dir_size(update);
+    assert!(matches!(
+        app_state.get_active_tab().preview_content,
+        Some(PreviewContent::Directory { size: Some(128), .. })
+    ));
+}
+
+#[test]
+fn test_text_preview_reads_only_the_configured_lines() {
+    let tmp_dir = TempDir::new("text_preview").unwrap();
+    let lines: Vec<String> = (1..=5).map(|i| format!("line {}", i)).collect();
+    fs::write(tmp_dir.path().join("a.txt"), lines.join("\n")).unwrap();
+    fs::write(tmp_dir.path().join("b.bin"), [b'o', b'k', b'\n', 0xff, 0xfe]).unwrap();
+
+    let mut app_state = AppState::new();
+    let tab = app_state.get_active_tab_mut();
+    tab.preview.max_lines = 3;
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    tab.select_path(&tmp_dir.path().join("a.txt"));
+    tab.update_preview();
+    let Some(PreviewContent::File { text, truncated, tail: false }) = tab.preview_content.clone() else {
+        panic!("expected a text preview");
+    };
+    assert_eq!(text, "line 1\nline 2\nline 3\n");
+    assert!(truncated);
+
+    tab.preview.max_lines = 5;
+    tab.update_preview();
+    assert!(matches!(&tab.preview_content, Some(PreviewContent::File { truncated: false, .. })));
+
+    tab.select_path(&tmp_dir.path().join("b.bin"));
+    tab.update_preview();
+    assert!(matches!(tab.preview_content, Some(PreviewContent::Binary)));
+}
+
+#[test]
+fn test_log_preview_shows_the_last_lines() {
+    let tmp_dir = TempDir::new("tail_preview").unwrap();
+    let lines: Vec<String> = (1..=20_000).map(|i| format!("line {}", i)).collect();
+    fs::write(tmp_dir.path().join("big.log"), lines.join("\n") + "\n").unwrap();
+    fs::write(tmp_dir.path().join("short.log"), "only\none").unwrap();
+
+    let mut app_state = AppState::new();
+    let tab = app_state.get_active_tab_mut();
+    tab.preview.max_lines = 3;
+    // Only the end is read, so a log over the size limit is still shown
+    tab.preview.max_preview_size = 1024;
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    tab.select_path(&tmp_dir.path().join("big.log"));
+    let Some(PreviewContent::File { text, truncated, tail }) = tab.preview_content.clone() else {
+        panic!("expected a text preview");
+    };
+    assert_eq!(text, "line 19998\nline 19999\nline 20000\n");
+    assert!(truncated && tail);
+
+    tab.select_path(&tmp_dir.path().join("short.log"));
+    let Some(PreviewContent::File { text, truncated, .. }) = tab.preview_content.clone() else {
+        panic!("expected a text preview");
+    };
+    assert_eq!(text, "only\none");
+    assert!(!truncated);
+
+    tab.preview.tail_logs = false;
+    tab.select_path(&tmp_dir.path().join("big.log"));
+    assert!(matches!(tab.preview_content, Some(PreviewContent::TooLarge { .. })));
+}
+
+#[test]
+fn test_log_tail_reads_no_more_than_the_size_limit() {
+    let tmp_dir = TempDir::new("tail_limit").unwrap();
+    // 8 MiB without a single newline; the limit falls inside a two-byte character
+    let mut line = "é".repeat(4 * 1024 * 1024);
+    line.push_str("end");
+    fs::write(tmp_dir.path().join("one_line.log"), &line).unwrap();
+
+    let mut app_state = AppState::new();
+    let tab = app_state.get_active_tab_mut();
+    tab.preview.max_preview_size = 1024;
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    tab.select_path(&tmp_dir.path().join("one_line.log"));
+    let Some(PreviewContent::File { text, truncated, tail }) = tab.preview_content.clone() else {
+        panic!("expected a text preview");
+    };
+    assert!(truncated && tail);
+    assert!(text.len() <= 1024 && text.len() >= 1020);
+    assert!(text.ends_with("éend"));
+}
+
+#[tokio::test]
+async fn test_pasting_many_files_reports_one_group() {
+    let tmp_dir = TempDir::new("paste_group").unwrap();
+    let dest = tmp_dir.path().join("dest");
+    fs::create_dir(&dest).unwrap();
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        fs::write(tmp_dir.path().join(name), name).unwrap();
+    }
+
+    let mut app_state = AppState::new();
+    app_state.clipboard.yank(["a.txt", "b.txt", "c.txt"].iter().map(|name| tmp_dir.path().join(name)).collect());
+    app_state.get_active_tab_mut().current_dir = dest.clone();
+    app_state.paste();
+    let rows = app_state.task_manager.get_tasks_grouped();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].description, "Copying 3 files (0/3)");
+
+    // One source disappears before its copy starts
+    fs::remove_file(tmp_dir.path().join("b.txt")).unwrap();
+    app_state.task_manager.process_pending_tasks();
+    let report = loop {
+        match app_state.task_manager.wait_for_event().await.unwrap() {
+            TaskEvent::GroupItemFinished { report: Some(report), .. } => break report,
+            TaskEvent::GroupItemFinished { .. } | TaskEvent::Progress { .. } => {}
+            other => panic!("unexpected event {:?}", other),
+        }
+    };
+    assert_eq!(report.summary, "Copying 3 files: 1 of them failed");
+    assert_eq!(report.errors.len(), 1);
+    assert!(report.errors[0].contains("b.txt"));
+    assert!(dest.join("a.txt").exists() && dest.join("c.txt").exists());
+    assert!(matches!(&app_state.task_manager.get_tasks_grouped()[0].status, TaskStatus::Failed(_)));
+}
+
+#[test]
+fn test_escape_closes_info_panel_before_clearing_selection() {
+    let tmp_dir = TempDir::new("escape").unwrap();
+    fs::write(tmp_dir.path().join("a.txt"), "a").unwrap();
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().current_dir = tmp_dir.path().to_path_buf();
+    app_state.get_active_tab_mut().update_entries(false);
+    app_state.get_active_tab_mut().selected_entries.insert(&DirEntry::from_path(tmp_dir.path().join("a.txt")));
+    app_state.show_info_panel();
+    let info = app_state.file_info.clone().unwrap();
+    let metadata = fs::metadata(tmp_dir.path().join("a.txt")).unwrap();
+    assert_eq!((info.uid, info.gid), (std::os::unix::fs::MetadataExt::uid(&metadata), std::os::unix::fs::MetadataExt::gid(&metadata)));
+    assert!(!info.group.is_empty());
+
+    app_state.escape();
+    assert!(!app_state.show_info_panel);
+    assert_eq!(app_state.get_active_tab().selected_entries.len(), 1);
+    app_state.escape();
+    assert!(app_state.get_active_tab().selected_entries.is_empty());
+}
+
+#[test]
+fn test_info_panel_describes_a_symlink_itself() {
+    let tmp_dir = TempDir::new("info_links").unwrap();
+    let file = tmp_dir.path().join("a.txt");
+    fs::write(&file, "abc").unwrap();
+    fs::hard_link(&file, tmp_dir.path().join("b.txt")).unwrap();
+    std::os::unix::fs::symlink("a.txt", tmp_dir.path().join("link")).unwrap();
+    std::os::unix::fs::symlink("gone", tmp_dir.path().join("broken")).unwrap();
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().current_dir = tmp_dir.path().to_path_buf();
+    app_state.get_active_tab_mut().update_entries(false);
+    let mut info_for = |name: &str| {
+        app_state.get_active_tab_mut().select_path(&tmp_dir.path().join(name));
+        app_state.show_info_panel();
+        app_state.file_info.clone().unwrap()
+    };
+
+    let info = info_for("a.txt");
+    assert_eq!(info.nlink, 2);
+    assert_eq!(info.target, None);
+    let link = info_for("link");
+    assert_eq!(link.target, Some(std::path::PathBuf::from("a.txt")));
+    assert_ne!(link.inode, info.inode);
+    assert!(!link.broken_link);
+    assert!(info_for("broken").broken_link);
+}
+
+#[test]
+fn test_clicks_move_the_cursor_and_double_click_enters() {
+    let tmp_dir = TempDir::new("clicks").unwrap();
+    fs::create_dir(tmp_dir.path().join("a")).unwrap();
+    fs::create_dir(tmp_dir.path().join("b")).unwrap();
+    fs::write(tmp_dir.path().join("b/inner.txt"), "").unwrap();
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().current_dir = tmp_dir.path().to_path_buf();
+    app_state.get_active_tab_mut().update_entries(false);
+
+    app_state.scroll_entries(PanelSide::Left, 5);
+    assert_eq!(app_state.get_active_tab().cursor, 1);
+    app_state.click_entry(PanelSide::Left, 0);
+    app_state.click_entry(PanelSide::Left, 1);
+    assert_eq!(app_state.get_active_tab().cursor, 1);
+    assert_eq!(app_state.get_active_tab().current_dir, tmp_dir.path());
+    app_state.click_entry(PanelSide::Left, 1);
+    assert_eq!(app_state.get_active_tab().current_dir, tmp_dir.path().join("b"));
+
+    // A place opens on the first click, a row past the list does nothing
+    let home = app_state.xdg_dirs.len() - 1;
+    let (_, home_path) = app_state.xdg_dirs[home].clone();
+    app_state.click_place(FocusBlock::Xdg, home + 1);
+    assert_eq!(app_state.focus, FocusBlock::Middle);
+    app_state.click_place(FocusBlock::Xdg, home);
+    assert_eq!(app_state.focus, FocusBlock::Xdg);
+    assert_eq!(app_state.get_active_tab().current_dir, home_path);
+
+    app_state.new_tab();
+    app_state.click_tab(0);
+    assert_eq!(app_state.active_tab_index, 0);
+    app_state.click_tab(5);
+    assert_eq!(app_state.active_tab_index, 0);
+}
+
+#[test]
+fn test_copy_mode_selects_into_scrollback() {
+    let mut terminal = TerminalState::new((3, 10), 10);
+    terminal.process(b"one\r\ntwo\r\nthree\r\nfour");
+
+    terminal.enter_copy_mode();
+    terminal.toggle_selection();
+    terminal.move_copy_cursor(-5, -10);
+    assert_eq!(terminal.scroll_offset(), 1);
+    assert_eq!(terminal.copy_selection().as_deref(), Some("one\ntwo\nthree\nfour"));
+    assert!(terminal.copy_mode.is_none());
+    assert_eq!(terminal.scroll_offset(), 0);
+}
+
+#[tokio::test]
+async fn test_each_tab_restarts_its_own_shell() {
+    let mut app_state = AppState::new();
+    app_state.show_terminal();
+    app_state.new_tab();
+    app_state.show_terminal();
+    assert_eq!(app_state.tabs[0].terminal.lifecycle, TerminalLifecycle::Running);
+    assert_eq!(app_state.tabs[1].terminal.lifecycle, TerminalLifecycle::Running);
+
+    app_state.send_to_terminal(b"exit\r");
+    while app_state.tabs[1].terminal.lifecycle == TerminalLifecycle::Running {
+        let output = tokio::time::timeout(std::time::Duration::from_secs(10), app_state.terminal_channel.wait_for_output())
+            .await
+            .expect("shell did not exit")
+            .unwrap();
+        app_state.handle_terminal_output(output);
+    }
+    assert_eq!(app_state.tabs[1].terminal.lifecycle, TerminalLifecycle::Exited);
+    assert_eq!(app_state.tabs[0].terminal.lifecycle, TerminalLifecycle::Running);
+
+    app_state.start_terminal();
+    assert_eq!(app_state.tabs[1].terminal.lifecycle, TerminalLifecycle::Running);
+    app_state.close_tab();
+    assert_eq!(app_state.tabs.len(), 1);
+    assert_eq!(app_state.tabs[0].terminal.lifecycle, TerminalLifecycle::Running);
+}
+
+/// Passes shell output to the tabs until `done` holds
+async fn wait_for_terminal(app_state: &mut AppState, done: impl Fn(&AppState) -> bool) {
+    while !done(app_state) {
+        let output = tokio::time::timeout(std::time::Duration::from_secs(10), app_state.terminal_channel.wait_for_output())
+            .await
+            .expect("no output from the shell")
+            .unwrap();
+        app_state.handle_terminal_output(output);
+    }
+}
+
+fn terminal_screen(app_state: &AppState) -> String {
+    app_state.get_active_tab().terminal.parser.screen().contents()
+}
+
+#[tokio::test]
+async fn test_shell_keeps_running_between_sends() {
+    let mut app_state = AppState::new();
+    app_state.show_terminal();
+    app_state.send_to_terminal(b"echo first-$((1+1))\r");
+    app_state.send_to_terminal(b"echo second-$((2+2))\r");
+    wait_for_terminal(&mut app_state, |app_state| {
+        let screen = terminal_screen(app_state);
+        screen.contains("first-2") && screen.contains("second-4")
+    })
+    .await;
+    assert_eq!(app_state.get_active_tab().terminal.lifecycle, TerminalLifecycle::Running);
+    assert!(app_state.notification.is_none());
+}
+
+#[tokio::test]
+async fn test_selection_is_typed_into_the_shell_without_running() {
+    let tmp_dir = TempDir::new("ctrl_s").unwrap();
+    fs::write(tmp_dir.path().join("a b.txt"), "").unwrap();
+    fs::write(tmp_dir.path().join("c.txt"), "").unwrap();
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().selected_entries.extend(&[tmp_dir.path().join("a b.txt"), tmp_dir.path().join("c.txt")].map(DirEntry::from_path));
+
+    app_state.send_selection_to_terminal();
+    wait_for_terminal(&mut app_state, |app_state| terminal_screen(app_state).contains("c.txt'")).await;
+    let screen = terminal_screen(&app_state);
+    let input_line = screen.lines().rev().find(|line| !line.trim().is_empty()).unwrap();
+    assert!(input_line.contains("a b.txt' '") && input_line.trim_end().ends_with("c.txt'"), "{}", screen);
+
+    // The line was not run: the shell is alive and takes the next command
+    app_state.send_to_terminal(b"\x15echo alive-$((3+3))\r");
+    wait_for_terminal(&mut app_state, |app_state| terminal_screen(app_state).contains("alive-6")).await;
+    assert_eq!(app_state.get_active_tab().terminal.lifecycle, TerminalLifecycle::Running);
+    assert!(!terminal_screen(&app_state).contains("not found"));
+}
+
+#[test]
+fn test_rename_applies_to_the_active_panel() {
+    let mut app_state = AppState::new();
+    app_state.toggle_layout_mode();
+    app_state.cycle_focus();
+    assert_eq!(app_state.active_panel, PanelSide::Right);
+
+    app_state.start_tab_rename();
+    app_state.input_buffer = "right".to_string();
+    app_state.set_tab_name();
+    assert_eq!(app_state.get_active_tab().name.as_deref(), Some("right"));
+    assert_eq!(app_state.tabs[0].name, None);
+}
+
+#[test]
+fn test_bookmark_names_and_keys_stay_unique() {
+    let dir = TempDir::new("bookmark_keys").unwrap();
+    let mut app_state = AppState::new();
+    app_state.bookmarks = vec![Bookmark { name: "src".to_string(), path: dir.path().to_path_buf(), key: Some('s') }];
+
+    app_state.add_bookmark();
+    app_state.input_buffer = "src".to_string();
+    app_state.save_bookmark();
+    assert!(app_state.input_dialog_error.as_deref().unwrap().contains("already exists"));
+    app_state.input_buffer = "other src #s".to_string();
+    app_state.save_bookmark();
+    assert!(app_state.input_dialog_error.as_deref().unwrap().contains("'src'"));
+    assert_eq!(app_state.bookmarks.len(), 1);
+
+    app_state.jump_to_bookmark('s');
+    assert_eq!(app_state.get_active_tab().current_dir, dir.path());
+    app_state.jump_to_bookmark('q');
+    assert_eq!(app_state.notification.as_deref(), Some("No bookmark on key q"));
+}
+
+#[test]
+fn test_corvus_toml_applies_only_inside_its_directory() {
+    let dir = TempDir::new("dir_overrides").unwrap();
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(photos.join("albums")).unwrap();
+    fs::write(photos.join("cat.jpg"), "").unwrap();
+    fs::write(photos.join("notes.txt"), "").unwrap();
+    fs::write(photos.join(".corvus.toml"), "filter = \"*.jpg\"\nshow_hidden = false\n").unwrap();
+    fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+    let mut tab = TabState::new(0);
+    tab.set_current_dir(photos.clone(), true);
+    let names: Vec<&str> = tab.entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names.len(), 2);
+    assert!(names.contains(&"albums") && names.contains(&"cat.jpg"));
+
+    tab.leave_directory(true);
+    assert!(tab.dir_overrides.is_none());
+    assert!(tab.entries.iter().any(|entry| entry.name == "notes.txt"));
+}
+
+#[test]
+fn test_disks_list_block_devices_and_hide_pseudo_file_systems() {
+    use corvus_core::app_state::{mount_kind, MountKind};
+    let disks = config::DisksConfig::default();
+    let kind = |source: &str, dest: &str, fstype: &str, disks: &config::DisksConfig| {
+        mount_kind(std::path::Path::new(source), std::path::Path::new(dest), fstype, disks)
+    };
+    assert_eq!(kind("/dev/sda2", "/home", "ext4", &disks), Some(MountKind::System));
+    assert_eq!(kind("/dev/sdb1", "/run/media/me/USB", "vfat", &disks), Some(MountKind::Removable));
+    assert_eq!(kind("me@host:", "/mnt/remote", "fuse.sshfs", &disks), Some(MountKind::Network));
+    assert_eq!(kind("tmpfs", "/tmp", "tmpfs", &disks), None);
+    // Not a block device even though its type is not in the hidden list
+    assert_eq!(kind("portal", "/run/user/1000/doc", "fuse.portal", &disks), None);
+
+    let disks = config::DisksConfig { show_system: false, hide_fs_types: vec!["vfat".to_string()] };
+    assert_eq!(kind("/dev/sda2", "/home", "ext4", &disks), None);
+    assert_eq!(kind("/dev/sdb1", "/run/media/me/USB", "vfat", &disks), None);
+    assert_eq!(kind("/dev/sdc1", "/media/backup", "ext4", &disks), Some(MountKind::Removable));
+}
+
+#[test]
+fn test_quick_connect_lists_the_remote_until_it_fails() {
+    let mut app_state = AppState::new();
+    app_state.start_remote_connect();
+    app_state.input_buffer = "/not/a/remote".to_string();
+    app_state.connect_remote_from_input();
+    assert!(app_state.input_dialog_error.is_some());
+    assert_eq!(app_state.input_mode, InputMode::RemoteConnect);
+
+    app_state.input_buffer = "me@nas.invalid:/srv".to_string();
+    app_state.connect_remote_from_input();
+    assert_eq!(app_state.input_mode, InputMode::Normal);
+    let remote = app_state.remotes.iter().find(|remote| remote.url == "me@nas.invalid:/srv").unwrap();
+    assert_eq!(remote.name, "nas.invalid");
+    assert!(!remote.saved && !remote.connected);
+    let mountpoint = remote.mountpoint();
+    let task = app_state.task_manager.get_tasks().into_iter().find(|task| matches!(&task.kind, TaskKind::MountRemote { .. })).unwrap();
+    assert!(matches!(&task.kind, TaskKind::MountRemote { mountpoint: path, .. } if *path == mountpoint));
+
+    app_state.handle_task_event(TaskEvent::Failed {
+        id: task.id,
+        kind: task.kind.clone(),
+        description: task.description.clone(),
+        error: "Permission denied (publickey).".to_string(),
+    });
+    assert!(app_state.notification.as_deref().unwrap().contains("Permission denied"));
+    assert!(app_state.remotes.iter().all(|remote| remote.url != "me@nas.invalid:/srv"));
+}
+
+#[tokio::test]
+async fn test_disk_usage_drills_into_the_scanned_tree() {
+    let tmp_dir = TempDir::new("disk_usage").unwrap();
+    let big = tmp_dir.path().join("big");
+    fs::create_dir_all(big.join("nested")).unwrap();
+    fs::write(big.join("nested/a"), vec![0; 600]).unwrap();
+    fs::write(big.join("b"), vec![0; 400]).unwrap();
+    fs::write(tmp_dir.path().join(".small"), vec![0; 10]).unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.start_disk_usage();
+    while app_state.get_active_tab().disk_usage().unwrap().scanning {
+        let batch = app_state.usage_scanner.wait_for_update().await.unwrap();
+        app_state.apply_usage_batch(batch);
+    }
+    let sizes = |app_state: &AppState| -> Vec<(String, u64)> {
+        app_state.get_active_tab().entries.iter().map(|e| (e.name.clone(), e.size)).collect()
+    };
+    assert_eq!(sizes(&app_state), [("big".to_string(), 1000), (".small".to_string(), 10)]);
+    assert_eq!(app_state.get_active_tab().disk_usage().unwrap().total, 1010);
+
+    // Entering a directory takes it from the tree, deleting updates the totals
+    app_state.get_active_tab_mut().enter_directory(false);
+    assert_eq!(sizes(&app_state), [("nested".to_string(), 600), ("b".to_string(), 400)]);
+    fs::remove_dir_all(big.join("nested")).unwrap();
+    let kind = TaskKind::Delete { path: big.join("nested") };
+    app_state.handle_task_event(TaskEvent::Completed { id: uuid::Uuid::new_v4(), summary: kind.summary(), kind });
+    assert_eq!(sizes(&app_state), [("b".to_string(), 400)]);
+    assert_eq!(app_state.get_active_tab().usage_node().unwrap().size, 400);
+
+    app_state.get_active_tab_mut().leave_directory(false);
+    assert_eq!(sizes(&app_state), [("big".to_string(), 400), (".small".to_string(), 10)]);
+    app_state.escape();
+    assert!(app_state.get_active_tab().disk_usage().is_none());
+    assert_eq!(app_state.get_active_tab().entries.len(), 1, "hidden files are back to hidden");
+}
+
+#[tokio::test]
+async fn test_first_escape_only_stops_the_disk_usage_scan() {
+    let tmp_dir = TempDir::new("disk_usage_stop").unwrap();
+    fs::write(tmp_dir.path().join("a"), vec![0; 100]).unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.start_disk_usage();
+    app_state.escape();
+    let view = app_state.get_active_tab().disk_usage().expect("the view stays open");
+    assert!(!view.scanning && view.stopped);
+    assert!(app_state.usage_scanner.scanning().is_none());
+
+    app_state.escape();
+    assert!(app_state.get_active_tab().disk_usage().is_none());
+}
+
+#[tokio::test]
+async fn test_slow_preview_arrives_through_the_loader() {
+    let tmp_dir = TempDir::new("dir_load").unwrap();
+    let fifo = tmp_dir.path().join("fifo");
+    nix::unistd::mkfifo(&fifo, nix::sys::stat::Mode::S_IRWXU).unwrap();
+    fs::write(tmp_dir.path().join("note.txt"), "note").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.enable_background_loading();
+    // A local directory is read within the wait, so nothing shows as loading
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    assert!(app_state.get_active_tab().loading_entries.is_none());
+    assert_eq!(app_state.get_active_tab().entries.len(), 2);
+
+    // Reading a fifo blocks until something writes to it
+    app_state.get_active_tab_mut().select_path(&fifo);
+    assert!(matches!(app_state.get_active_tab().preview_content, Some(PreviewContent::Loading)));
+    fs::write(&fifo, "from the writer").unwrap();
+    let loaded = app_state.dir_loader.wait_for_update().await.unwrap();
+    app_state.apply_loaded(loaded);
+    match &app_state.get_active_tab().preview_content {
+        Some(PreviewContent::File { text, .. }) => assert_eq!(text, "from the writer"),
+        other => panic!("unexpected preview {:?}", other),
+    }
+}
+
+#[test]
+fn test_focus_skips_the_hidden_left_pane() {
+    let mut app_state = AppState::new();
+    app_state.show_left_pane = false;
+    app_state.cycle_focus();
+    assert_eq!(app_state.focus, FocusBlock::Middle);
+
+    app_state.toggle_layout_mode();
+    app_state.cycle_focus();
+    assert_eq!((app_state.focus, app_state.active_panel), (FocusBlock::Middle, PanelSide::Right));
+    app_state.cycle_focus();
+    assert_eq!((app_state.focus, app_state.active_panel), (FocusBlock::Middle, PanelSide::Left));
+}
+
+#[test]
+fn test_external_terminal_starts_in_the_current_directory() {
+    let tmp_dir = TempDir::new("external_terminal").unwrap();
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.config.behavior.terminal = Some("pwd > opened-in && ls {dir} > /dev/null".to_string());
+    app_state.open_external_terminal();
+    assert!(app_state.notification.as_ref().unwrap().starts_with("Started"));
+
+    let output = tmp_dir.path().join("opened-in");
+    let started = std::time::Instant::now();
+    while fs::read_to_string(&output).map_or(true, |text| text.is_empty()) {
+        assert!(started.elapsed() < std::time::Duration::from_secs(5), "the command never ran");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let dir = fs::read_to_string(&output).unwrap();
+    assert_eq!(fs::canonicalize(dir.trim()).unwrap(), fs::canonicalize(tmp_dir.path()).unwrap());
+}
+
+#[test]
+fn test_status_counts_follow_the_listing_and_the_selection() {
+    let tmp_dir = TempDir::new("status").unwrap();
+    fs::write(tmp_dir.path().join("a.txt"), "abc").unwrap();
+    fs::write(tmp_dir.path().join("b.txt"), "hello").unwrap();
+    fs::write(tmp_dir.path().join(".hidden"), "").unwrap();
+    fs::create_dir(tmp_dir.path().join("sub")).unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.show_hidden_files = false;
+    let tab = app_state.get_active_tab_mut();
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    assert_eq!(tab.hidden_count, 1);
+    assert!(tab.free_space.is_some());
+
+    tab.selected_entries.extend(&[tmp_dir.path().join("a.txt"), tmp_dir.path().join("b.txt"), tmp_dir.path().join("sub")].map(DirEntry::from_path));
+    assert_eq!((tab.selected_entries.bytes(), tab.selected_entries.dirs()), (8, 1));
+    tab.selected_entries.remove(&tmp_dir.path().join("b.txt"));
+    assert_eq!((tab.selected_entries.bytes(), tab.selected_entries.dirs()), (3, 1));
+    tab.selected_entries.clear();
+    assert_eq!((tab.selected_entries.bytes(), tab.selected_entries.dirs()), (0, 0));
+}
+
+#[test]
+fn test_selection_size_matches_the_listing() {
+    let tmp_dir = TempDir::new("selection_size").unwrap();
+    fs::write(tmp_dir.path().join("target.txt"), "hello").unwrap();
+    std::os::unix::fs::symlink(tmp_dir.path().join("target.txt"), tmp_dir.path().join("link")).unwrap();
+
+    let mut app_state = AppState::new();
+    let tab = app_state.get_active_tab_mut();
+    tab.set_current_dir(tmp_dir.path().to_path_buf(), false);
+    // Выделение берёт размер из списка, а не читает файл заново
+    fs::write(tmp_dir.path().join("target.txt"), "hello, world").unwrap();
+    tab.select_path(&tmp_dir.path().join("link"));
+    tab.toggle_selection();
+    assert_eq!(tab.selected_entries.bytes(), 5);
+
+    // Выделение из сессии читается с диска, для ссылки — размер цели
+    let restored: corvus_core::app_state::Selection = [tmp_dir.path().join("link")].into_iter().collect::<std::collections::HashSet<_>>().into();
+    assert_eq!(restored.bytes(), 12);
+}
+
+#[test]
+fn test_jump_to_ancestor_selects_the_directory_on_the_way() {
+    let tmp_dir = TempDir::new("ancestors").unwrap();
+    let deep = tmp_dir.path().join("a/b/c");
+    fs::create_dir_all(&deep).unwrap();
+    fs::create_dir(tmp_dir.path().join("0_first")).unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(deep.clone(), false);
+    app_state.open_ancestor_picker();
+    assert_eq!(app_state.input_mode, InputMode::AncestorPicker);
+    assert_eq!(app_state.ancestor_dirs()[0], tmp_dir.path().join("a/b"));
+
+    // Три уровня вверх за одно действие
+    app_state.jump_to_ancestor(2);
+    assert_eq!(app_state.input_mode, InputMode::Normal);
+    let tab = app_state.get_active_tab();
+    assert_eq!(tab.current_dir, tmp_dir.path());
+    assert_eq!(tab.get_selected_entry_path(), Some(tmp_dir.path().join("a")));
+
+    app_state.click_breadcrumb(PanelSide::Left, std::path::PathBuf::from("/"));
+    assert_eq!(app_state.get_active_tab().current_dir, std::path::PathBuf::from("/"));
+}
+
+#[test]
+fn test_invalid_regex_keeps_the_previous_results() {
+    let tmp_dir = TempDir::new("regex_typo").unwrap();
+    fs::write(tmp_dir.path().join("test_a.py"), "").unwrap();
+    fs::write(tmp_dir.path().join("other.rs"), "").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.search_syntax = corvus_core::search::SearchSyntax::Regex;
+    app_state.search_query = "^test_".to_string();
+    app_state.update_search();
+    assert_eq!(app_state.search_results.len(), 1);
+
+    app_state.search_query = "^test_(".to_string();
+    app_state.update_search();
+    assert!(app_state.search_error.is_some());
+    assert_eq!(app_state.search_results.len(), 1);
+}
+
+#[test]
+fn test_cursor_stays_on_its_entry_when_the_sort_changes() {
+    let tmp_dir = TempDir::new("resort").unwrap();
+    fs::write(tmp_dir.path().join("a.txt"), "").unwrap();
+    fs::write(tmp_dir.path().join("b.txt"), "").unwrap();
+    fs::write(tmp_dir.path().join("c.txt"), "").unwrap();
+
+    let mut app_state = AppState::new();
+    app_state.dir_prefs = Default::default();
+    app_state.sort_settings.field = corvus_core::settings::SortField::Name;
+    app_state.apply_sort_settings();
+    app_state.get_active_tab_mut().set_current_dir(tmp_dir.path().to_path_buf(), false);
+    app_state.get_active_tab_mut().select_path(&tmp_dir.path().join("a.txt"));
+    let cursor = app_state.get_active_tab().cursor;
+
+    app_state.sort_settings.order = app_state.sort_settings.order.reversed();
+    app_state.apply_sort_settings();
+    let tab = app_state.get_active_tab();
+    assert_ne!(tab.cursor, cursor);
+    assert_eq!(tab.get_selected_entry_path(), Some(tmp_dir.path().join("a.txt")));
+}
@@ -1,13 +1,11 @@
 use anyhow::Result;
 use crossterm::event::{Event, EventStream, KeyEventKind};
 use futures::StreamExt;
-use corvus_core::app_state::{AppState, TerminalState};
+use corvus_core::app_state::AppState;
 use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::time::interval;
 use ui::tui::{self, Tui};
-use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
-use std::io::{Read};
-use tokio::sync::mpsc;
 fn setup_logger() -> Result<(), fern::InitError> {
     fern::Dispatch::new()
         .format(|out, message, record| {
@@ -25,61 +23,44 @@ fn setup_logger() -> Result<(), fern::InitError> {
     Ok(())
 }
 
+/// Имя сессии из `--session <name>` или `--session=<name>`
+fn session_arg(mut args: impl Iterator<Item = String>) -> Result<Option<String>, String> {
+    let mut session = None;
+    while let Some(arg) = args.next() {
+        if arg == "--session" {
+            session = Some(args.next().ok_or("--session needs a name")?);
+        } else if let Some(name) = arg.strip_prefix("--session=") {
+            session = Some(name.to_string());
+        }
+    }
+    match session {
+        Some(name) if !corvus_core::session::is_valid_session_name(&name) => {
+            Err(format!("Invalid session name {:?}: use letters, digits, - and _", name))
+        }
+        session => Ok(session),
+    }
+}
+
 struct App {
     app_state: AppState,
     tui: Tui,
-    terminal_rx: mpsc::Receiver<String>,
+    session_saver: corvus_core::session::SessionSaver,
 }
 
 impl App {
-    fn new() -> Result<Self> {
-        let mut app_state = AppState::new();
+    fn new(session: Option<String>) -> Result<Self> {
+        corvus_core::paths::migrate_legacy_files();
+        let mut app_state = AppState::with_session(session);
+        app_state.lock_session();
         let tui = Tui::new()?;
-
-        let (terminal_tx, terminal_rx) = mpsc::channel(100);
-
-        let pty_system = NativePtySystem::default();
-        let pair = pty_system.openpty(PtySize {
-            rows: 9,
-            cols: 80,
-            ..Default::default()
-        })?;
-
-        let shell = std::env::var("SHELL").unwrap_or_else(|_| "bash".to_string());
-        let cmd = CommandBuilder::new(shell);
-        let _child = pair.slave.spawn_command(cmd)?;
-
-        let mut pty_reader = pair.master.try_clone_reader()?;
-
-        tokio::spawn(async move {
-            let mut buf = [0u8; 8192];
-            loop {
-                match pty_reader.read(&mut buf) {
-                    Ok(count) => {
-                        if count > 0 {
-                            let s = String::from_utf8_lossy(&buf[..count]).to_string();
-                            if terminal_tx.send(s).await.is_err() {
-                                break;
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
-
-        let terminal_state = TerminalState {
-            pty_writer: pair.master,
-            lines: Vec::new(),
-        };
-        app_state.terminal = Some(terminal_state);
+        // Только что загруженную сессию переписывать незачем
+        let mut session_saver = corvus_core::session::SessionSaver::new(corvus_core::paths::sessions_dir());
+        session_saver.mark_saved(&app_state);
 
         Ok(Self {
             app_state,
             tui,
-            terminal_rx,
+            session_saver,
         })
     }
 
@@ -87,9 +68,16 @@ impl App {
         self.tui.enter()?;
         let mut event_stream = EventStream::new();
         let mut mount_update_interval = interval(Duration::from_secs(5));
+        // SIGTERM и SIGINT завершают цикл как обычный выход, с сохранением сессии
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        self.app_state.file_index.rebuild();
+        self.app_state.start_plugins();
+        self.app_state.enable_background_loading();
 
         'main: loop {
             self.app_state.task_manager.process_pending_tasks();
+            self.app_state.expire_finished_tasks();
 
             if let Some(timer) = self.app_state.notification_timer {
                 if timer.elapsed().as_secs() > 3 {
@@ -98,54 +86,96 @@ impl App {
                 }
             }
 
+            self.app_state.request_dir_sizes();
+            self.app_state.request_command_preview();
+            self.app_state.poll_plugins();
+            self.app_state.request_plugin_preview();
+            self.app_state.notify_plugins();
             self.tui.terminal.draw(|frame| {
                 ui::layout::render_main_layout(frame, &mut self.app_state);
             })?;
+            // Вкладки, вид правой панели и размер окна меняют область терминала
+            self.app_state.get_active_tab_mut().terminal.sync_size();
+            self.tui.set_mouse_capture(self.app_state.mouse_capture_wanted())?;
 
             tokio::select! {
                 biased;
                 _ = mount_update_interval.tick() => {
                     self.app_state.update_mounts();
+                    self.app_state.refresh_missing_places();
+                    // Пока пользователь не решил судьбу старых задач, файл не трогаем
+                    if self.app_state.pending_resume.is_empty() {
+                        if let Err(e) = corvus_core::session::save_unfinished_tasks(&self.app_state.task_manager) {
+                            log::error!("Failed to save unfinished tasks: {:?}", e);
+                        }
+                    }
+                    if let Err(e) = self.session_saver.save_if_changed(&self.app_state) {
+                        log::error!("Failed to save session: {:?}", e);
+                    }
+                }
+                _ = terminate.recv() => {
+                    log::info!("Received SIGTERM, exiting");
+                    break 'main;
+                }
+                _ = interrupt.recv() => {
+                    log::info!("Received SIGINT, exiting");
+                    break 'main;
                 }
                 maybe_event = event_stream.next() => {
                     if let Some(Ok(event)) = maybe_event {
-                        if let Event::Key(key) = event {
-                            if key.kind == KeyEventKind::Press {
-                                if !tui::handle_key_press(key, &mut self.app_state) {
+                        match event {
+                            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                                let keep_running = tui::handle_key_press(key, &mut self.app_state);
+                                if !keep_running {
                                     break 'main;
                                 }
                             }
+                            Event::Mouse(mouse) => tui::handle_mouse(mouse, &mut self.app_state),
+                            Event::Paste(text) if !tui::handle_paste(&text, &mut self.app_state) => break 'main,
+                            // Следующая отрисовка займёт новый размер окна и
+                            // подгонит под него PTY
+                            Event::Resize(..) => {
+                                self.tui.terminal.autoresize()?;
+                            }
+                            _ => {}
                         }
                     } else {
                         break 'main;
                     }
                 }
-                task_completed = self.app_state.task_manager.wait_for_event() => {
-                    if task_completed {
-                        // Проверяем, была ли завершена задача архивирования
-                        let tasks = self.app_state.task_manager.get_tasks();
-                        if let Some(last_task) = tasks.last() {
-                            if matches!(last_task.kind, corvus_core::task_manager::TaskKind::Archive { .. }) {
-                                if last_task.status == corvus_core::task_manager::TaskStatus::Completed {
-                                    // Получаем имя архива из описания задачи
-                                    let archive_name = last_task.description.split(" to ").nth(1)
-                                        .map(|s| s.trim_matches('"'))
-                                        .unwrap_or("archive");
-                                    self.app_state.notification = Some(format!("Archive {} created successfully", archive_name));
-                                    self.app_state.notification_timer = Some(std::time::Instant::now());
-                                }
-                            }
-                        }
-                        
-                        let show_hidden = self.app_state.show_hidden_files;
-                        self.app_state.get_active_tab_mut().update_entries(show_hidden);
-                        self.app_state.update_mounts(); // Also update mounts after a task completes
-                    }
+                Some(event) = self.app_state.task_manager.wait_for_event() => {
+                    self.app_state.handle_task_event(event);
                 }
-                Some(s) = self.terminal_rx.recv() => {
-                    if let Some(terminal) = &mut self.app_state.terminal {
-                        terminal.lines.extend(s.lines().map(String::from));
-                    }
+                Some(update) = self.app_state.background_search.wait_for_update() => {
+                    self.app_state.apply_search_update(update);
+                }
+                Some(output) = self.app_state.command_previewer.wait_for_output() => {
+                    self.app_state.apply_command_output(output);
+                }
+                Some(preview) = self.app_state.plugin_host.wait_for_preview() => {
+                    self.app_state.apply_plugin_preview(preview);
+                }
+                Some(size) = self.app_state.dir_sizer.wait_for_update() => {
+                    self.app_state.apply_dir_size(size);
+                }
+                Some(batch) = self.app_state.usage_scanner.wait_for_update() => {
+                    self.app_state.apply_usage_batch(batch);
+                }
+                Some(loaded) = self.app_state.dir_loader.wait_for_update() => {
+                    self.app_state.apply_loaded(loaded);
+                }
+                Some((path, result)) = self.app_state.mount_prober.wait_for_result() => {
+                    self.app_state.apply_mount_probe(path, result);
+                }
+                Some(()) = self.app_state.disk_usage.wait_for_update() => {}
+                Some(()) = self.app_state.file_index.wait_for_update() => {
+                    self.app_state.apply_index_update();
+                }
+                Some(()) = self.app_state.config_watcher.wait_for_change() => {
+                    self.app_state.reload_config();
+                }
+                Some(output) = self.app_state.terminal_channel.wait_for_output() => {
+                    self.app_state.handle_terminal_output(output);
                 }
             }
         }
@@ -156,10 +186,18 @@ impl App {
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let session = match session_arg(std::env::args().skip(1)) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
     setup_logger().expect("Failed to set up logger");
     log::info!("Application starting up");
+    log::info!("Terminal colors: {:?}", corvus_core::settings::ColorDepth::current());
 
-    match App::new() {
+    match App::new(session) {
         Ok(mut app) => {
             if let Err(e) = app.run().await {
                 eprintln!("Error: {:?}", e);
@@ -170,11 +208,19 @@ async fn main() -> Result<()> {
                 std::process::exit(1);
             }
             
-            // Сохраняем сессию при выходе
-            if let Err(e) = corvus_core::session::save_session(&app.app_state) {
-                log::error!("Failed to save session: {:?}", e);
-            } else {
-                log::info!("Session saved successfully");
+            // Сохраняем сессию при выходе, если она менялась с последнего сохранения
+            match app.session_saver.save_if_changed(&app.app_state) {
+                Ok(true) => log::info!("Session saved successfully"),
+                Ok(false) => log::info!("Session unchanged since the last save"),
+                Err(e) => log::error!("Failed to save session: {:?}", e),
+            }
+            if let Err(e) = corvus_core::view_prefs::save_view_prefs(&app.app_state.dir_prefs) {
+                log::error!("Failed to save view preferences: {:?}", e);
+            }
+            if app.app_state.pending_resume.is_empty() {
+                if let Err(e) = corvus_core::session::save_unfinished_tasks(&app.app_state.task_manager) {
+                    log::error!("Failed to save unfinished tasks: {:?}", e);
+                }
             }
         }
         Err(e) => {
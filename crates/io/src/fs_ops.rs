@@ -1,5 +1,5 @@
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::sync::mpsc;
 use uuid::Uuid;
@@ -7,16 +7,46 @@ use std::fs::File;
 use std::io::Write;
 use zip::{ZipWriter, write::FileOptions};
 use tar::Builder;
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
+use bzip2::read::BzDecoder;
+use bzip2::write::BzEncoder;
+use xz2::read::XzDecoder;
+use xz2::write::XzEncoder;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub enum ProgressEvent {
     Update(f32),
+    /// The last line of a command's standard output, sent before it finishes
+    Output(String),
     Completed,
     Error(String),
 }
 
+/// Whether `dest` is `src` itself or lies inside it. Symlinks are resolved
+/// for the part of each path that exists, so a link into `src` counts too.
+pub fn is_within(dest: &Path, src: &Path) -> bool {
+    fn resolve(path: &Path) -> PathBuf {
+        let mut missing = Vec::new();
+        let mut existing = path;
+        loop {
+            if let Ok(resolved) = existing.canonicalize() {
+                return missing.iter().rev().fold(resolved, |acc, part| acc.join(part));
+            }
+            match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name);
+                    existing = parent;
+                }
+                _ => return path.to_path_buf(),
+            }
+        }
+    }
+    resolve(dest).starts_with(resolve(src))
+}
+
 pub async fn copy_file_task(
     task_id: Uuid,
     src: PathBuf,
@@ -62,6 +92,177 @@ pub async fn chown_task(
     }
 }
 
+/// Runs `command` with `sh -c` in `dir`, detached from the terminal, and
+/// reports a non-zero exit status along with the last line of stderr.
+/// Only the last line of each output is kept, however much it prints.
+pub async fn run_command_task(
+    task_id: Uuid,
+    command: String,
+    dir: PathBuf,
+    progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+) {
+    let spawned = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .current_dir(&dir)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        // A new process group keeps terminal signals away from the command
+        .process_group(0)
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = progress_tx.send((task_id, ProgressEvent::Error(e.to_string()))).await;
+            return;
+        }
+    };
+
+    let (stdout, stderr) = tokio::join!(last_line(child.stdout.take()), last_line(child.stderr.take()));
+    if !stdout.is_empty() {
+        let _ = progress_tx.send((task_id, ProgressEvent::Output(stdout))).await;
+    }
+    let event = match child.wait().await {
+        Ok(status) if status.success() => ProgressEvent::Completed,
+        Ok(status) if stderr.is_empty() => ProgressEvent::Error(status.to_string()),
+        Ok(status) => ProgressEvent::Error(format!("{}: {}", status, stderr)),
+        Err(e) => ProgressEvent::Error(e.to_string()),
+    };
+    let _ = progress_tx.send((task_id, event)).await;
+}
+
+/// Longest part of a line `last_line` keeps
+const LAST_LINE_LIMIT: usize = 4096;
+
+/// Reads `stream` to the end and gives its last non-blank line, trimmed.
+/// Of a longer line only the first `LAST_LINE_LIMIT` bytes are kept.
+async fn last_line(stream: Option<impl tokio::io::AsyncRead + Unpin>) -> String {
+    use tokio::io::AsyncReadExt;
+    let Some(mut stream) = stream else {
+        return String::new();
+    };
+    let is_blank = |line: &[u8]| line.iter().all(u8::is_ascii_whitespace);
+    let mut buf = [0u8; 8192];
+    let mut line = Vec::new();
+    let mut last = Vec::new();
+    while let Ok(count @ 1..) = stream.read(&mut buf).await {
+        for &byte in &buf[..count] {
+            if byte == b'\n' {
+                if !is_blank(&line) {
+                    last = std::mem::take(&mut line);
+                }
+                line.clear();
+            } else if line.len() < LAST_LINE_LIMIT {
+                line.push(byte);
+            }
+        }
+    }
+    if !is_blank(&line) {
+        last = line;
+    }
+    String::from_utf8_lossy(&last).trim().to_string()
+}
+
+/// Starts `command` with `sh -c` in `dir` and leaves it running on its
+/// own, e.g. a terminal emulator. Its exit status is only collected so
+/// that it does not linger as a zombie.
+pub fn spawn_detached(command: &str, dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::process::CommandExt;
+    let mut child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .process_group(0)
+        .spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Clones the git repository at `url` into `dest` with the `git` binary.
+/// Progress comes from the `Receiving objects` lines git prints to stderr;
+/// a failed clone leaves no directory behind and reports what git printed.
+/// git never asks for credentials or host keys: a prompt would either hang
+/// the task or draw over the interface.
+pub async fn git_clone_task(
+    task_id: Uuid,
+    url: String,
+    dest: PathBuf,
+    progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+) {
+    let ssh_command = match std::env::var("GIT_SSH_COMMAND") {
+        Ok(command) if !command.trim().is_empty() => format!("{} -o BatchMode=yes", command),
+        _ => "ssh -o BatchMode=yes".to_string(),
+    };
+    let spawned = tokio::process::Command::new("git")
+        .args(["clone", "--progress", "--depth", "1", "--"])
+        .arg(&url)
+        .arg(&dest)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_SSH_COMMAND", ssh_command)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .process_group(0)
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = progress_tx.send((task_id, ProgressEvent::Error(format!("git: {}", e)))).await;
+            return;
+        }
+    };
+
+    // git redraws its progress with `\r`, so lines are split on both.
+    // Everything else it prints is kept for the error, the latest lines last.
+    let mut messages: Vec<String> = Vec::new();
+    if let Some(stderr) = child.stderr.take() {
+        let mut reader = BufReader::new(stderr);
+        let mut chunk = Vec::new();
+        while let Ok(1..) = reader.read_until(b'\r', &mut chunk).await {
+            for line in String::from_utf8_lossy(&chunk).split(['\r', '\n']).filter(|line| !line.trim().is_empty()) {
+                if let Some(progress) = git_progress(line) {
+                    let _ = progress_tx.send((task_id, ProgressEvent::Update(progress))).await;
+                } else if !is_git_status(line) {
+                    if messages.len() == GIT_ERROR_LINES {
+                        messages.remove(0);
+                    }
+                    messages.push(line.trim().to_string());
+                }
+            }
+            chunk.clear();
+        }
+    }
+    let event = match child.wait().await {
+        Ok(status) if status.success() => ProgressEvent::Completed,
+        Ok(status) => {
+            let _ = fs::remove_dir_all(&dest).await;
+            ProgressEvent::Error(if messages.is_empty() { status.to_string() } else { messages.join(" ") })
+        }
+        Err(e) => ProgressEvent::Error(e.to_string()),
+    };
+    let _ = progress_tx.send((task_id, event)).await;
+}
+
+/// Fraction done from a line like `Receiving objects:  45% (9/20)`
+/// Lines of git's stderr kept for the error of a failed clone
+const GIT_ERROR_LINES: usize = 10;
+
+/// Lines git prints while a clone goes well, which say nothing about a failure
+fn is_git_status(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("Cloning into") || line.contains('%') || line.starts_with("remote: Enumerating") || line.starts_with("remote: Total")
+}
+
+fn git_progress(line: &str) -> Option<f32> {
+    let rest = line.trim().strip_prefix("Receiving objects:")?;
+    let percent: f32 = rest.trim_start().split('%').next()?.parse().ok()?;
+    Some(percent / 100.0)
+}
+
 pub async fn chmod_task(
     task_id: Uuid,
     path: PathBuf,
@@ -132,6 +333,85 @@ pub async fn delete_item_task(
     }
 }
 
+/// Moves `path` into the freedesktop trash at `trash`: the item goes to
+/// `files/` under a free name, and `info/<name>.trashinfo` records where it
+/// came from so a file manager can restore it.
+pub async fn trash_item_task(
+    task_id: Uuid,
+    path: PathBuf,
+    trash: PathBuf,
+    progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+) {
+    let result = tokio::task::spawn_blocking(move || move_to_trash(&path, &trash))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    let event = match result {
+        Ok(_) => ProgressEvent::Completed,
+        Err(error) => ProgressEvent::Error(error),
+    };
+    let _ = progress_tx.send((task_id, event)).await;
+}
+
+/// Moves `path` into the trash and returns where it ended up. The info file
+/// is created first with `create_new`, which reserves the name against
+/// another corvus trashing a file of the same name at the same time.
+pub fn move_to_trash(path: &Path, trash: &Path) -> Result<PathBuf, String> {
+    let path = std::path::absolute(path).map_err(|e| e.to_string())?;
+    let name = path.file_name().ok_or_else(|| format!("{} cannot be trashed", path.display()))?;
+    let files = trash.join("files");
+    let info = trash.join("info");
+    std::fs::create_dir_all(&files).and_then(|_| std::fs::create_dir_all(&info)).map_err(|e| e.to_string())?;
+
+    let mut number = 1;
+    let (dest, info_path) = loop {
+        let mut candidate = name.to_os_string();
+        if number > 1 {
+            candidate.push(format!(".{}", number));
+        }
+        number += 1;
+        let dest = files.join(&candidate);
+        if dest.symlink_metadata().is_ok() {
+            continue;
+        }
+        candidate.push(".trashinfo");
+        let info_path = info.join(candidate);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(mut file) => {
+                if let Err(e) = write_trash_info(&mut file, &path) {
+                    let _ = std::fs::remove_file(&info_path);
+                    return Err(e.to_string());
+                }
+                break (dest, info_path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+
+    if let Err(e) = std::fs::rename(&path, &dest) {
+        let _ = std::fs::remove_file(&info_path);
+        if e.kind() == std::io::ErrorKind::CrossesDevices {
+            return Err(format!("{} is on another filesystem than the trash", path.display()));
+        }
+        return Err(e.to_string());
+    }
+    Ok(dest)
+}
+
+fn write_trash_info(file: &mut File, path: &Path) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    let date = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S");
+    write!(file, "[Trash Info]\nPath={}\nDeletionDate={}\n", encoded, date)
+}
+
 pub async fn move_item_task(
     task_id: Uuid,
     src: PathBuf,
@@ -151,26 +431,75 @@ pub async fn move_item_task(
 
 use tokio::io::{AsyncBufReadExt, BufReader};
 
-const PREVIEW_LINE_COUNT: usize = 100;
+/// Lines shown in a text preview unless configured otherwise
+pub const PREVIEW_LINE_COUNT: usize = 100;
 
-pub async fn load_text_preview(path: PathBuf) -> Result<String, String> {
-    let file = tokio::fs::File::open(path).await.map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
+/// The first or the last lines of a text file
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextPreview {
+    pub text: String,
+    /// The file has more lines than were read
+    pub truncated: bool,
+}
 
-    let mut content = String::new();
-    for _ in 0..PREVIEW_LINE_COUNT {
-        match lines.next_line().await {
-            Ok(Some(line)) => {
-                content.push_str(&line);
-                content.push('\n');
-            }
-            Ok(None) => break, // End of file
-            Err(e) => return Err(e.to_string()),
+/// Reads at most `max_lines` lines of `path`, never the rest of the file.
+/// Gives `None` when those lines are not valid UTF-8.
+pub fn load_text_preview(path: &Path, max_lines: usize) -> std::io::Result<Option<TextPreview>> {
+    use std::io::BufRead;
+    let mut reader = std::io::BufReader::new(File::open(path)?);
+    let mut text = String::new();
+    for _ in 0..max_lines {
+        match reader.read_line(&mut text) {
+            Ok(0) => return Ok(Some(TextPreview { text, truncated: false })),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => return Ok(None),
+            Err(e) => return Err(e),
         }
     }
+    let truncated = !reader.fill_buf()?.is_empty();
+    Ok(Some(TextPreview { text, truncated }))
+}
 
-    Ok(content)
+/// Reads the last `max_lines` lines of `path` in chunks from the end, so a
+/// large log costs no more than its tail. At most `max_bytes` are read: of a
+/// longer last line only its end is shown. Gives `None` when those lines are
+/// not valid UTF-8.
+pub fn load_text_tail(path: &Path, max_lines: usize, max_bytes: u64) -> std::io::Result<Option<TextPreview>> {
+    use std::io::{Read, Seek, SeekFrom};
+    const CHUNK: u64 = 8192;
+    let mut file = File::open(path)?;
+    let len = file.seek(SeekFrom::End(0))?;
+    let mut start = len;
+    let mut tail: Vec<u8> = Vec::new();
+    let mut newlines = 0;
+    // The newline that ends the file closes the last line, it starts no new one
+    while start > 0 && newlines <= max_lines && (tail.len() as u64) < max_bytes {
+        let size = CHUNK.min(start).min(max_bytes - tail.len() as u64);
+        start -= size;
+        file.seek(SeekFrom::Start(start))?;
+        let mut chunk = vec![0; size as usize];
+        file.read_exact(&mut chunk)?;
+        let counted = if start + size == len { chunk.strip_suffix(b"\n").unwrap_or(&chunk) } else { &chunk };
+        newlines += counted.iter().filter(|&&byte| byte == b'\n').count();
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+    }
+    let body = tail.strip_suffix(b"\n").unwrap_or(&tail);
+    // Lines start after the `max_lines`-th newline from the end
+    let cut = body
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, &byte)| byte == b'\n')
+        .nth(max_lines.saturating_sub(1))
+        .map(|(index, _)| index + 1);
+    let truncated = cut.is_some() || start > 0;
+    // The byte limit may cut a character in two, its remainder is dropped
+    let cut = cut.unwrap_or_else(|| tail.iter().take_while(|&&byte| start > 0 && byte & 0xC0 == 0x80).count());
+    match String::from_utf8(tail[cut..].to_vec()) {
+        Ok(text) => Ok(Some(TextPreview { text, truncated })),
+        Err(_) => Ok(None),
+    }
 }
 
 pub async fn unmount_task(
@@ -198,19 +527,187 @@ pub async fn unmount_task(
     }
 }
 
+/// Unmounts every partition of a removable drive, then powers the drive
+/// off with `udisksctl power-off`. Stops at the first step that fails, so
+/// a busy partition leaves the drive on.
+pub async fn eject_task(
+    task_id: Uuid,
+    device: PathBuf,
+    partitions: Vec<PathBuf>,
+    progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+) {
+    let steps = partitions.len() + 1;
+    for (index, partition) in partitions.iter().enumerate() {
+        let result = tokio::process::Command::new("udisksctl")
+            .args(["unmount", "--no-user-interaction", "-b"])
+            .arg(partition)
+            .output()
+            .await;
+        if let Err(error) = command_result("udisksctl", result) {
+            let message = format!("{}: {}", partition.display(), error);
+            let _ = progress_tx.send((task_id, ProgressEvent::Error(message))).await;
+            return;
+        }
+        let _ = progress_tx.send((task_id, ProgressEvent::Update((index + 1) as f32 / steps as f32))).await;
+    }
+    let result = tokio::process::Command::new("udisksctl")
+        .args(["power-off", "--no-user-interaction", "-b"])
+        .arg(&device)
+        .output()
+        .await;
+    let event = match command_result("udisksctl", result) {
+        Ok(()) => ProgressEvent::Completed,
+        Err(error) => ProgressEvent::Error(error),
+    };
+    let _ = progress_tx.send((task_id, event)).await;
+}
+
+/// Mounts a remote location by running `program` with `args`, creating
+/// `mountpoint` first. On failure the empty mount point is removed again
+/// and the command's stderr, e.g. an authentication error, is reported.
+pub async fn mount_remote_task(
+    task_id: Uuid,
+    program: &str,
+    args: Vec<String>,
+    mountpoint: PathBuf,
+    progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+) {
+    if let Err(e) = tokio::fs::create_dir_all(&mountpoint).await {
+        let message = format!("{}: {}", mountpoint.display(), e);
+        let _ = progress_tx.send((task_id, ProgressEvent::Error(message))).await;
+        return;
+    }
+    let result = tokio::process::Command::new(program)
+        .args(&args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await;
+    let event = match command_result(program, result) {
+        Ok(()) => ProgressEvent::Completed,
+        Err(error) => {
+            let _ = tokio::fs::remove_dir(&mountpoint).await;
+            ProgressEvent::Error(error)
+        }
+    };
+    let _ = progress_tx.send((task_id, event)).await;
+}
+
+/// Unmounts a remote location with `program` and removes its mount point
+pub async fn unmount_remote_task(
+    task_id: Uuid,
+    program: &str,
+    args: Vec<String>,
+    mountpoint: PathBuf,
+    progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+) {
+    let result = tokio::process::Command::new(program)
+        .args(&args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await;
+    let event = match command_result(program, result) {
+        Ok(()) => match tokio::fs::remove_dir(&mountpoint).await {
+            Ok(()) => ProgressEvent::Completed,
+            Err(e) => ProgressEvent::Error(format!("unmounted, but {} was not removed: {}", mountpoint.display(), e)),
+        },
+        Err(error) => ProgressEvent::Error(error),
+    };
+    let _ = progress_tx.send((task_id, event)).await;
+}
+
+/// The error of a finished command: its stderr, or why it did not start
+fn command_result(program: &str, result: std::io::Result<std::process::Output>) -> Result<(), String> {
+    match result {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            Err(if stderr.is_empty() { format!("{} failed with {}", program, output.status) } else { stderr })
+        }
+        Err(e) => Err(format!("{}: {}", program, e)),
+    }
+}
+
+/// Archive formats that `archive_task` can create and `extract_task` can read.
+/// Serialized by name, e.g. `"tar.gz"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar")]
+    Tar,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    #[serde(rename = "tar.bz2")]
+    TarBz2,
+    #[serde(rename = "tar.xz")]
+    TarXz,
+}
+
+impl ArchiveFormat {
+    /// Every format, in the order the archive dialog lists them
+    pub const ALL: [ArchiveFormat; 5] =
+        [ArchiveFormat::Zip, ArchiveFormat::Tar, ArchiveFormat::TarGz, ArchiveFormat::TarBz2, ArchiveFormat::TarXz];
+
+    /// Name used in the config and the UI, e.g. `tar.gz`
+    pub fn name(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarBz2 => "tar.bz2",
+            ArchiveFormat::TarXz => "tar.xz",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+
+    /// File extension of new archives, including the leading dot
+    pub fn extension(self) -> String {
+        format!(".{}", self.name())
+    }
+}
+
+impl std::fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Highest compression level accepted by `archive_task`. 0 stores files uncompressed.
+pub const MAX_COMPRESSION_LEVEL: u32 = 9;
+
+/// Zip entry options for a compression level from 0 (stored) to 9 (best)
+fn zip_options(level: u32) -> FileOptions {
+    if level == 0 {
+        FileOptions::default().compression_method(zip::CompressionMethod::Stored)
+    } else {
+        FileOptions::default().compression_level(Some(level as i32))
+    }
+}
+
 pub async fn archive_task(
     task_id: Uuid,
     paths: Vec<PathBuf>,
     dest: PathBuf,
-    format: String,
+    format: ArchiveFormat,
+    level: u32,
     progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
 ) {
-    let result = match format.as_str() {
-        "zip" => create_zip_archive(&paths, &dest).await,
-        "tar" => create_tar_archive(&paths, &dest, false).await,
-        "tar.gz" => create_tar_archive(&paths, &dest, true).await,
-        _ => Err(format!("Unsupported archive format: {}", format)),
-    };
+    let level = level.min(MAX_COMPRESSION_LEVEL);
+    let tx = progress_tx.clone();
+    // Compression is CPU-bound, so it runs off the async workers and reports
+    // progress from there
+    let result = tokio::task::spawn_blocking(move || {
+        let mut reporter = ProgressReporter::new(task_id, tx);
+        match format {
+            ArchiveFormat::Zip => create_zip_archive(&paths, &dest, level, &mut reporter),
+            _ => create_tar_archive(&paths, &dest, format, level, &mut reporter),
+        }
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
 
     match result {
         Ok(_) => {
@@ -222,86 +719,259 @@ pub async fn archive_task(
     }
 }
 
-async fn create_zip_archive(paths: &[PathBuf], dest: &PathBuf) -> Result<(), String> {
+/// Deepest directory containing all `paths`, used as the root of archive
+/// entry names. `None` when the paths share no ancestor, e.g. they are on
+/// different drives or mix absolute and relative paths.
+pub fn common_ancestor(paths: &[PathBuf]) -> Option<PathBuf> {
+    let mut ancestor = paths.first()?.parent()?.to_path_buf();
+    for path in &paths[1..] {
+        // Compare parents so that a selected directory never becomes the root itself
+        while !path.parent().is_some_and(|parent| parent.starts_with(&ancestor)) {
+            ancestor = ancestor.parent()?.to_path_buf();
+        }
+    }
+    Some(ancestor)
+}
+
+/// A file or directory to be stored in an archive
+struct ArchiveEntry {
+    path: PathBuf,
+    /// Name inside the archive, relative to the selection's common ancestor
+    name: PathBuf,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Lists everything under `paths`, directories before their contents
+fn archive_entries(paths: &[PathBuf]) -> Result<Vec<ArchiveEntry>, String> {
+    fn visit(path: &Path, name: PathBuf, entries: &mut Vec<ArchiveEntry>) -> std::io::Result<()> {
+        let metadata = std::fs::metadata(path)?;
+        if metadata.is_dir() {
+            entries.push(ArchiveEntry { path: path.to_path_buf(), name: name.clone(), is_dir: true, size: 0 });
+            for entry in std::fs::read_dir(path)? {
+                let entry = entry?;
+                visit(&entry.path(), name.join(entry.file_name()), entries)?;
+            }
+        } else {
+            entries.push(ArchiveEntry { path: path.to_path_buf(), name, is_dir: false, size: metadata.len() });
+        }
+        Ok(())
+    }
+
+    let root = common_ancestor(paths).ok_or("Selected items have no common parent directory")?;
+    let mut entries = Vec::new();
+    for path in paths {
+        let name = path.strip_prefix(&root).unwrap_or(path).to_path_buf();
+        visit(path, name, &mut entries).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(entries)
+}
+
+fn create_zip_archive(paths: &[PathBuf], dest: &PathBuf, level: u32, reporter: &mut ProgressReporter) -> Result<(), String> {
     use std::io::BufWriter;
-    
+
+    let options = zip_options(level);
+    let entries = archive_entries(paths)?;
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
     let file = File::create(dest).map_err(|e| e.to_string())?;
     let mut zip = ZipWriter::new(BufWriter::new(file));
-    
-    for path in paths {
-        if path.is_dir() {
-            add_dir_to_zip(&mut zip, path, path).map_err(|e| e.to_string())?;
+
+    let mut written = 0;
+    for entry in entries {
+        let name = entry.name.to_str().ok_or_else(|| format!("Invalid path: {}", entry.path.display()))?;
+        if entry.is_dir {
+            zip.add_directory(name, options).map_err(|e| e.to_string())?;
         } else {
-            let file_name = path.file_name().unwrap().to_str().unwrap();
-            zip.start_file(file_name, FileOptions::default())
-                .map_err(|e| e.to_string())?;
-            let content = std::fs::read(path).map_err(|e| e.to_string())?;
-            zip.write_all(&content).map_err(|e| e.to_string())?;
+            zip.start_file(name, options).map_err(|e| e.to_string())?;
+            let mut file = File::open(&entry.path).map_err(|e| e.to_string())?;
+            std::io::copy(&mut file, &mut zip).map_err(|e| e.to_string())?;
+            written += entry.size;
+            reporter.report(written as usize, total as usize);
         }
     }
-    
+
     zip.finish().map_err(|e| e.to_string())?;
     Ok(())
 }
 
-fn add_dir_to_zip<W: Write + std::io::Seek>(
-    zip: &mut ZipWriter<W>,
-    base_path: &PathBuf,
-    dir_path: &PathBuf,
-) -> zip::result::ZipResult<()> {
-    use zip::write::FileOptions;
-    
-    for entry in std::fs::read_dir(dir_path).map_err(|_| zip::result::ZipError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Failed to read directory")))? {
-        let entry = entry.map_err(|_| zip::result::ZipError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Failed to read directory entry")))?;
-        let path = entry.path();
-        
-        let relative_path = path.strip_prefix(base_path).map_err(|_| zip::result::ZipError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Failed to create relative path")))?;
-        let path_str = relative_path.to_str().ok_or(zip::result::ZipError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Invalid path")))?;
-        
-        if path.is_dir() {
-            zip.add_directory(path_str, FileOptions::default())?;
-            add_dir_to_zip(zip, base_path, &path)?;
+fn create_tar_archive(
+    paths: &[PathBuf],
+    dest: &PathBuf,
+    format: ArchiveFormat,
+    level: u32,
+    reporter: &mut ProgressReporter,
+) -> Result<(), String> {
+    let entries = archive_entries(paths)?;
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    let file = File::create(dest).map_err(|e| e.to_string())?;
+    let writer: Box<dyn Write> = match format {
+        ArchiveFormat::TarGz => Box::new(GzEncoder::new(file, Compression::new(level))),
+        // bzip2 has no uncompressed mode, its lowest level is 1
+        ArchiveFormat::TarBz2 => Box::new(BzEncoder::new(file, bzip2::Compression::new(level.max(1)))),
+        ArchiveFormat::TarXz => Box::new(XzEncoder::new(file, level)),
+        _ => Box::new(file),
+    };
+    let mut builder = Builder::new(writer);
+
+    let mut written = 0;
+    for entry in entries {
+        if entry.is_dir {
+            builder.append_dir(&entry.name, &entry.path).map_err(|e| e.to_string())?;
         } else {
-            zip.start_file(path_str, FileOptions::default())?;
-            let content = std::fs::read(&path).map_err(|_| zip::result::ZipError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Failed to read file")))?;
-            zip.write_all(&content).map_err(|_| zip::result::ZipError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Failed to write file")))?;
+            builder.append_path_with_name(&entry.path, &entry.name).map_err(|e| e.to_string())?;
+            written += entry.size;
+            reporter.report(written as usize, total as usize);
         }
     }
+
+    // Dropping the encoder writes the compressed stream trailer
+    let mut writer = builder.into_inner().map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())?;
     Ok(())
 }
 
-async fn create_tar_archive(paths: &[PathBuf], dest: &PathBuf, compressed: bool) -> Result<(), String> {
-    let file = File::create(dest).map_err(|e| e.to_string())?;
-    
-    if compressed {
-        let enc = GzEncoder::new(file, Compression::default());
-        let mut builder = Builder::new(enc);
-        
-        for path in paths {
-            if path.is_dir() {
-                builder.append_dir_all(path.file_name().unwrap(), path).map_err(|e| e.to_string())?;
-            } else {
-                builder.append_path(path).map_err(|e| e.to_string())?;
-            }
+/// Archive extensions recognised for extraction, longest first
+const ARCHIVE_EXTENSIONS: &[(&str, ArchiveFormat)] = &[
+    (".tar.gz", ArchiveFormat::TarGz),
+    (".tar.bz2", ArchiveFormat::TarBz2),
+    (".tar.xz", ArchiveFormat::TarXz),
+    (".tgz", ArchiveFormat::TarGz),
+    (".tbz2", ArchiveFormat::TarBz2),
+    (".tbz", ArchiveFormat::TarBz2),
+    (".txz", ArchiveFormat::TarXz),
+    (".tar", ArchiveFormat::Tar),
+    (".zip", ArchiveFormat::Zip),
+];
+
+/// Returns the archive format of `path` judging by its extension
+pub fn archive_format_of(path: &Path) -> Option<ArchiveFormat> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    ARCHIVE_EXTENSIONS
+        .iter()
+        .find(|(extension, _)| name.ends_with(extension))
+        .map(|(_, format)| *format)
+}
+
+/// Returns the archive file name without its archive extension, e.g. `photos` for `photos.tar.gz`
+pub fn archive_stem(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let lower = name.to_lowercase();
+    ARCHIVE_EXTENSIONS
+        .iter()
+        .find(|(extension, _)| lower.ends_with(extension) && lower.len() > extension.len())
+        .map(|(extension, _)| name[..name.len() - extension.len()].to_string())
+}
+
+/// Extracts `archive` into `dest`, creating `dest` if needed.
+/// Progress is reported as the share of archive entries processed.
+pub async fn extract_task(
+    task_id: Uuid,
+    archive: PathBuf,
+    dest: PathBuf,
+    progress_tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+) {
+    let tx = progress_tx.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let format = archive_format_of(&archive)
+            .ok_or_else(|| format!("Unsupported archive: {}", archive.display()))?;
+        std::fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
+        let mut reporter = ProgressReporter::new(task_id, tx);
+        match format {
+            ArchiveFormat::Zip => extract_zip(&archive, &dest, &mut reporter),
+            _ => extract_tar(&archive, format, &dest, &mut reporter),
         }
-        
-        builder.into_inner()
-            .map_err(|e| e.to_string())?
-            .finish()
-            .map_err(|e| e.to_string())?;
-    } else {
-        let mut builder = Builder::new(file);
-        
-        for path in paths {
-            if path.is_dir() {
-                builder.append_dir_all(path.file_name().unwrap(), path).map_err(|e| e.to_string())?;
-            } else {
-                builder.append_path(path).map_err(|e| e.to_string())?;
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
+
+    match result {
+        Ok(_) => {
+            let _ = progress_tx.send((task_id, ProgressEvent::Completed)).await;
+        }
+        Err(e) => {
+            let _ = progress_tx.send((task_id, ProgressEvent::Error(e))).await;
+        }
+    }
+}
+
+/// Sends `Update` events from blocking code, at most once per percent
+struct ProgressReporter {
+    task_id: Uuid,
+    tx: mpsc::Sender<(Uuid, ProgressEvent)>,
+    last_percent: u32,
+}
+
+impl ProgressReporter {
+    fn new(task_id: Uuid, tx: mpsc::Sender<(Uuid, ProgressEvent)>) -> Self {
+        Self { task_id, tx, last_percent: 0 }
+    }
+
+    fn report(&mut self, done: usize, total: usize) {
+        if total == 0 {
+            return;
+        }
+        let progress = done as f32 / total as f32;
+        let percent = (progress * 100.0) as u32;
+        if percent > self.last_percent {
+            self.last_percent = percent;
+            let _ = self.tx.blocking_send((self.task_id, ProgressEvent::Update(progress)));
+        }
+    }
+}
+
+fn extract_zip(archive: &Path, dest: &Path, reporter: &mut ProgressReporter) -> Result<(), String> {
+    let file = File::open(archive).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let total = zip.len();
+
+    for i in 0..total {
+        let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+        // `enclosed_name` rejects absolute paths and `..` components
+        let Some(relative) = entry.enclosed_name().map(|p| p.to_path_buf()) else {
+            continue;
+        };
+        let out_path = dest.join(relative);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&out_path).map_err(|e| e.to_string())?;
+        } else {
+            if let Some(parent) = out_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut out = File::create(&out_path).map_err(|e| e.to_string())?;
+            std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+            if let Some(mode) = entry.unix_mode() {
+                let _ = std::fs::set_permissions(&out_path, std::fs::Permissions::from_mode(mode));
             }
         }
-        
-        builder.into_inner().map_err(|e| e.to_string())?;
+        reporter.report(i + 1, total);
+    }
+    Ok(())
+}
+
+fn open_tar(archive: &Path, format: ArchiveFormat) -> Result<tar::Archive<Box<dyn std::io::Read>>, String> {
+    let file = File::open(archive).map_err(|e| e.to_string())?;
+    let reader: Box<dyn std::io::Read> = match format {
+        ArchiveFormat::TarGz => Box::new(GzDecoder::new(file)),
+        ArchiveFormat::TarBz2 => Box::new(BzDecoder::new(file)),
+        ArchiveFormat::TarXz => Box::new(XzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+fn extract_tar(archive: &Path, format: ArchiveFormat, dest: &Path, reporter: &mut ProgressReporter) -> Result<(), String> {
+    // Tar has no index, so the entries are counted in a separate pass
+    let total = open_tar(archive, format)?
+        .entries()
+        .map_err(|e| e.to_string())?
+        .count();
+
+    let mut tar = open_tar(archive, format)?;
+    for (i, entry) in tar.entries().map_err(|e| e.to_string())?.enumerate() {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        // `unpack_in` refuses to write outside of `dest`
+        entry.unpack_in(dest).map_err(|e| e.to_string())?;
+        reporter.report(i + 1, total);
     }
-    
     Ok(())
-}
\ No newline at end of file
+}
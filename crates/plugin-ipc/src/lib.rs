@@ -17,12 +17,50 @@ pub struct Response {
     pub error: Option<RpcError>,
 }
 
+/// A message from the host that expects no response
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Notification {
+    pub method: String,
+    pub params: serde_json::Value,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RpcError {
     pub code: i64,
     pub message: String,
 }
 
+/// Version of this protocol, sent to plugins in `init`
+pub const API_VERSION: &str = "0.1";
+
+/// Oldest protocol version the host still speaks. A plugin's manifest
+/// names the version it was written for, which must lie between this and
+/// `API_VERSION`.
+pub const MIN_API_VERSION: &str = "0.1";
+
+/// Whether a plugin written for `version` (`major.minor`, an optional
+/// patch part is ignored) can talk to this host
+pub fn is_compatible(version: &str) -> bool {
+    fn parse(version: &str) -> Option<(u32, u32)> {
+        let mut parts = version.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+        Some((major, minor))
+    }
+    match (parse(version), parse(MIN_API_VERSION), parse(API_VERSION)) {
+        (Some(version), Some(min), Some(max)) => min <= version && version <= max,
+        _ => false,
+    }
+}
+
+/// Capability a plugin lists in its `init` result to receive event
+/// notifications
+pub const EVENTS_CAPABILITY: &str = "events";
+
+/// Longest line, in bytes, the host accepts from a plugin. Longer messages
+/// are dropped unread.
+pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
 // --- Method-specific params and results ---
 
 // Method: "init"
@@ -38,6 +76,9 @@ pub struct InitResult {
     pub capabilities: Vec<String>,
 }
 
+// Method: "shutdown" (no params). The plugin should exit; it is killed
+// if it is still running shortly after.
+
 // Method: "on_select"
 #[derive(Serialize, Deserialize, Debug)]
 pub struct OnSelectParams {
@@ -45,9 +86,77 @@ pub struct OnSelectParams {
     pub mime_type: Option<String>,
 }
 
-// A preview can be simple text for now
-#[derive(Serialize, Deserialize, Debug)]
+// Result of "on_select": what to show in the preview pane. `null` leaves
+// the built-in preview in place. Variants are externally tagged, e.g.
+// `{"Table": [["Codec", "FLAC"]]}`; new ones are only ever appended.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum PreviewResult {
     Text(String),
     Error(String),
+    /// Text with SGR escape sequences for colors and attributes
+    AnsiText(String),
+    /// Key/value rows, shown as two aligned columns
+    Table(Vec<(String, String)>),
+    /// An image file to show instead of text
+    ImagePath(PathBuf),
+}
+
+// --- Event notifications, sent only to plugins with `EVENTS_CAPABILITY` ---
+//
+// Events are fire-and-forget: a plugin that does not keep up with its
+// input misses them.
+
+// Notification: "on_dir_changed"
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DirChangedParams {
+    pub path: PathBuf,
+}
+
+// Notification: "on_task_completed". `kind` is the task kind in snake case
+// (`copy`, `move`, `delete`, ...), `paths` its sources and destinations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaskCompletedParams {
+    pub kind: String,
+    pub paths: Vec<PathBuf>,
+}
+
+// Notification: "on_selection_changed"
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SelectionChangedParams {
+    pub paths: Vec<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_api_version_compatibility() {
+        assert!(is_compatible(API_VERSION));
+        assert!(is_compatible("0.1.5"));
+        assert!(!is_compatible("0.2"));
+        assert!(!is_compatible("1"));
+        assert!(!is_compatible("0.0"));
+        assert!(!is_compatible("latest"));
+    }
+
+    #[test]
+    fn test_preview_results_round_trip() {
+        let cases = [
+            (PreviewResult::Text("plain".to_string()), json!({"Text": "plain"})),
+            (PreviewResult::Error("oops".to_string()), json!({"Error": "oops"})),
+            (PreviewResult::AnsiText("\u{1b}[1mbold\u{1b}[0m".to_string()), json!({"AnsiText": "\u{1b}[1mbold\u{1b}[0m"})),
+            (
+                PreviewResult::Table(vec![("Codec".to_string(), "FLAC".to_string()), ("Rate".to_string(), "44.1 kHz".to_string())]),
+                json!({"Table": [["Codec", "FLAC"], ["Rate", "44.1 kHz"]]}),
+            ),
+            (PreviewResult::ImagePath(PathBuf::from("/tmp/cover.png")), json!({"ImagePath": "/tmp/cover.png"})),
+        ];
+        for (result, expected) in cases {
+            let value = serde_json::to_value(&result).unwrap();
+            assert_eq!(value, expected);
+            assert_eq!(serde_json::from_value::<PreviewResult>(value).unwrap(), result);
+        }
+    }
 }
@@ -0,0 +1,133 @@
+use corvus_core::settings::rgb_color;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// Цвета SGR 30–37 и 40–47
+const BASIC: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+];
+
+/// Яркие цвета SGR 90–97 и 100–107
+const BRIGHT: [Color; 8] = [
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Разбирает текст с SGR-последовательностями (`ESC [ ... m`) в строки
+/// ratatui. Остальные управляющие последовательности отбрасываются.
+pub fn ansi_lines(text: &str) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut style = Style::default();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut command = None;
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            command = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    if command == Some('m') {
+                        flush(&mut current, &mut spans, style);
+                        style = apply_sgr(style, &params);
+                    }
+                }
+                // OSC (заголовок окна, ссылки) заканчивается BEL или ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\n' => {
+                flush(&mut current, &mut spans, style);
+                lines.push(Line::from(std::mem::take(&mut spans)));
+            }
+            '\t' => current.push_str("    "),
+            c if c.is_control() => {}
+            c => current.push(c),
+        }
+    }
+    flush(&mut current, &mut spans, style);
+    if !spans.is_empty() {
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+fn flush(current: &mut String, spans: &mut Vec<Span<'static>>, style: Style) {
+    if !current.is_empty() {
+        spans.push(Span::styled(std::mem::take(current), style));
+    }
+}
+
+/// Применяет параметры одной последовательности `ESC [ ... m`
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params.split([';', ':']).map(|code| code.parse().unwrap_or(0)).collect();
+    let mut codes = codes.into_iter();
+    while let Some(code) = codes.next() {
+        match code {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg = Some(BASIC[usize::from(code - 30)]),
+            38 => style.fg = extended_color(&mut codes).or(style.fg),
+            // Цвет по умолчанию — цвет схемы
+            39 => style.fg = None,
+            40..=47 => style.bg = Some(BASIC[usize::from(code - 40)]),
+            48 => style.bg = extended_color(&mut codes).or(style.bg),
+            49 => style.bg = None,
+            90..=97 => style.fg = Some(BRIGHT[usize::from(code - 90)]),
+            100..=107 => style.bg = Some(BRIGHT[usize::from(code - 100)]),
+            _ => {}
+        }
+    }
+    style
+}
+
+/// Цвет после 38 или 48: `5;n` из палитры или `2;r;g;b`
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let byte = |code: Option<u16>| code.and_then(|code| u8::try_from(code).ok());
+    match codes.next()? {
+        5 => byte(codes.next()).map(Color::Indexed),
+        2 => {
+            let (r, g, b) = (byte(codes.next())?, byte(codes.next())?, byte(codes.next())?);
+            Some(rgb_color((r, g, b)))
+        }
+        _ => None,
+    }
+}
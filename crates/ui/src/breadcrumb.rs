@@ -0,0 +1,76 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::text_width::display_width;
+
+/// Часть пути в заголовке списка. У разделителей и многоточия нет
+/// каталога, по ним некуда перейти.
+pub struct Segment {
+    pub label: String,
+    pub target: Option<PathBuf>,
+}
+
+const SEPARATOR: &str = "/";
+const ELLIPSIS: &str = "…";
+
+/// Части пути `dir`, уложенные в `width` столбцов. Если весь путь не
+/// помещается, средние каталоги заменяются на «…»: первый и два последних
+/// видны всегда, остальные добавляются с конца, пока хватает места.
+pub fn segments(dir: &Path, width: usize) -> Vec<Segment> {
+    let mut root = None;
+    let mut names = Vec::new();
+    let mut path = PathBuf::new();
+    for component in dir.components() {
+        path.push(component);
+        match component {
+            Component::RootDir => root = Some(path.clone()),
+            Component::Normal(name) => names.push((name.to_string_lossy().into_owned(), path.clone())),
+            _ => {}
+        }
+    }
+
+    let root_width = if root.is_some() { SEPARATOR.len() } else { 0 };
+    let name_width = |(name, _): &(String, PathBuf)| display_width(name);
+    let full: usize = root_width + names.iter().map(name_width).sum::<usize>() + names.len().saturating_sub(1);
+    // Первый каталог остаётся виден, хвост начинается с `tail`
+    let mut tail = 1;
+    if full > width && names.len() > 3 {
+        tail = names.len() - 2;
+        let mut used = root_width + name_width(&names[0]) + 1 + display_width(ELLIPSIS) + names[tail..].iter().map(|name| 1 + name_width(name)).sum::<usize>();
+        while tail > 2 && used + 1 + name_width(&names[tail - 1]) <= width {
+            tail -= 1;
+            used += 1 + name_width(&names[tail]);
+        }
+    }
+
+    let mut segments = Vec::new();
+    if let Some(root) = root {
+        segments.push(Segment { label: SEPARATOR.to_string(), target: Some(root) });
+    }
+    for (index, (name, path)) in names.into_iter().enumerate() {
+        if index > 0 && index < tail {
+            if index == 1 {
+                segments.push(Segment { label: SEPARATOR.to_string(), target: None });
+                segments.push(Segment { label: ELLIPSIS.to_string(), target: None });
+            }
+            continue;
+        }
+        if index > 0 {
+            segments.push(Segment { label: SEPARATOR.to_string(), target: None });
+        }
+        segments.push(Segment { label: name, target: Some(path) });
+    }
+    segments
+}
+
+/// Каталог под столбцом `column` пути, нарисованного с левого края `area`
+pub fn target_at_column(dir: &Path, area_x: u16, width: u16, column: u16) -> Option<PathBuf> {
+    let mut column = column.checked_sub(area_x)? as usize;
+    for segment in segments(dir, width as usize) {
+        let segment_width = display_width(&segment.label);
+        if column < segment_width {
+            return segment.target;
+        }
+        column -= segment_width;
+    }
+    None
+}
@@ -0,0 +1,270 @@
+use corvus_core::app_state::{AppState, FocusBlock, InputMode, RightPaneView};
+use crate::i18n::Language;
+
+/// Где показывается подсказка о клавише
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintContext {
+    /// Список файлов
+    Files,
+    /// Стандартные каталоги в левой панели
+    Places,
+    Bookmarks,
+    Disks,
+    /// Удалённые каталоги
+    Remotes,
+    /// Встроенный терминал
+    Terminal,
+    /// Режим копирования терминала
+    CopyMode,
+    /// Диалог поиска
+    Search,
+}
+
+impl HintContext {
+    /// Заголовок раздела справки с клавишами этого контекста
+    pub fn title(self, language: Language) -> &'static str {
+        let [english, russian] = match self {
+            Files => ["File list", "Список файлов"],
+            Places => ["Places pane", "Стандартные каталоги"],
+            Bookmarks => ["Bookmarks pane", "Закладки"],
+            Disks => ["Disks pane", "Диски"],
+            Remotes => ["Remotes pane", "Удалённые каталоги"],
+            Terminal => ["Terminal", "Терминал"],
+            CopyMode => ["Terminal copy mode", "Режим копирования терминала"],
+            Search => ["Search dialog", "Диалог поиска"],
+        };
+        language.pick(english, russian)
+    }
+}
+
+use HintContext::*;
+
+/// Горячая клавиша для списка в настройках и, если задана подпись,
+/// для строки подсказок. Тексты — на английском и русском.
+pub struct Hotkey {
+    pub keys: &'static str,
+    pub description: [&'static str; 2],
+    /// Короткая подпись и контексты, в которых она видна
+    pub hint: Option<([&'static str; 2], &'static [HintContext])>,
+}
+
+impl Hotkey {
+    /// Клавиша в строке подсказок — первый из вариантов `keys` через ` / `
+    pub fn hint_key(&self) -> &'static str {
+        self.keys.split(" / ").next().unwrap_or(self.keys).trim()
+    }
+
+    pub fn description(&self, language: Language) -> &'static str {
+        language.pick(self.description[0], self.description[1])
+    }
+
+    /// Подходит ли клавиша под фильтр справки: по клавишам или описанию,
+    /// без учёта регистра
+    fn matches(&self, filter: &str, language: Language) -> bool {
+        filter.is_empty()
+            || self.keys.to_lowercase().contains(filter)
+            || self.description(language).to_lowercase().contains(filter)
+    }
+}
+
+/// Группа клавиш с заголовком в справке
+pub struct HotkeyGroup {
+    pub title: [&'static str; 2],
+    pub hotkeys: &'static [Hotkey],
+}
+
+const fn key(keys: &'static str, description: [&'static str; 2]) -> Hotkey {
+    Hotkey { keys, description, hint: None }
+}
+
+const fn hinted(
+    keys: &'static str,
+    description: [&'static str; 2],
+    label: [&'static str; 2],
+    contexts: &'static [HintContext],
+) -> Hotkey {
+    Hotkey { keys, description, hint: Some((label, contexts)) }
+}
+
+/// Все горячие клавиши по группам в порядке списка в настройках
+pub const HOTKEY_GROUPS: &[HotkeyGroup] = &[
+    HotkeyGroup { title: ["Navigation", "Навигация"], hotkeys: &[
+        key("j / ↓", ["Move the cursor down", "Переместить курсор вниз"]),
+        key("k / ↑", ["Move the cursor up", "Переместить курсор вверх"]),
+        key("h / ← / Backspace", ["Go to the parent directory", "Перейти в родительский каталог"]),
+        key("H", ["Jump several levels up: pick a directory above by its number", "Подняться на несколько уровней: выбрать каталог выше по номеру"]),
+        hinted("l / → / Enter", ["Enter the directory", "Войти в каталог"], ["open", "открыть"], &[Places, Bookmarks, Disks]),
+        key("J (Shift+j)", ["Move the cursor down and select the file", "Переместить курсор вниз и выделить файл"]),
+        key("K (Shift+k)", ["Move the cursor up and select the file", "Переместить курсор вверх и выделить файл"]),
+    ] },
+    HotkeyGroup { title: ["Tabs", "Вкладки"], hotkeys: &[
+        key("Ctrl+n", ["New tab", "Создать новую вкладку"]),
+        key("Alt+n", ["Copy of the current tab in the same directory", "Копия текущей вкладки в том же каталоге"]),
+        key("Ctrl+w", ["Close the current tab", "Закрыть текущую вкладку"]),
+        key("Ctrl+Tab", ["Next tab", "Перейти к следующей вкладке"]),
+        key("Ctrl+Shift+Tab", ["Previous tab", "Перейти к предыдущей вкладке"]),
+        key("Ctrl+Shift+←/→", ["Move the tab left/right", "Переместить вкладку влево/вправо"]),
+        key("Alt+1-9", ["Go to a tab by number", "Перейти к вкладке по номеру"]),
+        key("Alt+t", ["Show/hide the tab bar", "Показать/скрыть панель вкладок"]),
+    ] },
+    HotkeyGroup { title: ["Panes", "Панели"], hotkeys: &[
+        key("F9", ["Show/hide the left pane", "Показать/скрыть левую панель"]),
+        key("F8", ["Show/hide the tasks and info panels", "Показать/скрыть панели задач и информации"]),
+        key("Ctrl+←/→", ["Move the border between the file list and the preview", "Сдвинуть границу между списком файлов и предпросмотром"]),
+    ] },
+    HotkeyGroup { title: ["File operations", "Файловые операции"], hotkeys: &[
+        hinted("y", ["Copy file(s)", "Копировать файл(ы)"], ["yank", "копия"], &[Files]),
+        hinted("x", ["Cut file(s)", "Вырезать файл(ы)"], ["cut", "вырезать"], &[Files]),
+        hinted("d", ["Delete file(s)", "Удалить файл(ы)"], ["del", "удалить"], &[Files]),
+        hinted("p", ["Paste file(s)", "Вставить файл(ы)"], ["paste", "вставить"], &[Files]),
+        key("m", ["Bookmark the current directory", "Добавить текущий каталог в закладки"]),
+        hinted("`", ["Jump to a bookmark by its key", "Перейти к закладке по её клавише"], ["jump", "переход"], &[Bookmarks]),
+        hinted("r", ["Rename the file", "Переименовать файл"], ["rename", "имя"], &[Files]),
+        hinted("n", ["Open the create menu (file/directory)", "Открыть меню создания (файл/каталог)"], ["new", "создать"], &[Files]),
+        key("  n+f", ["Create a new file", "Создать новый файл"]),
+        key("  n+d", ["Create a new directory", "Создать новый каталог"]),
+        hinted("i", ["Show file information", "Показать информацию о файле"], ["info", "сведения"], &[Files]),
+        hinted("u", ["Unmount the disk (in the disks pane)", "Отмонтировать диск (в панели дисков)"], ["unmount", "отмонтировать"], &[Disks]),
+        hinted("e", ["Eject the drive: unmount all its partitions and power it off", "Извлечь диск: отмонтировать все разделы и отключить питание"], ["eject", "извлечь"], &[Disks]),
+        hinted("u", ["Remove the bookmark (in the bookmarks pane)", "Удалить закладку (в панели закладок)"], ["remove", "удалить"], &[Bookmarks]),
+        hinted("Enter", ["Connect to the remote location or open it (in the remotes pane)", "Подключить удалённый каталог или открыть его (в панели удалённых)"], ["connect", "подключить"], &[Remotes]),
+        hinted("u", ["Disconnect the remote location (in the remotes pane)", "Отключить удалённый каталог (в панели удалённых)"], ["disconnect", "отключить"], &[Remotes]),
+        key("c+m", ["Change permissions (chmod)", "Изменить права доступа (chmod)"]),
+        key("c+o", ["Change owner (chown)", "Изменить владельца (chown)"]),
+        key("c+a", ["Pack into an archive", "Упаковать в архив"]),
+        key("c+e", ["Extract the archive (into a new or the current directory)", "Распаковать архив (в новый каталог или в текущий)"]),
+        key("c+p", ["Preview the output of a command run on the file", "Показать в предпросмотре вывод команды для файла"]),
+        key("c+!", ["Run a shell command, {} is each selected path", "Выполнить команду оболочки, {} — путь каждого выделенного файла"]),
+        key("c+v", ["Save the directory view to .corvus.toml", "Сохранить вид каталога в .corvus.toml"]),
+        key("c+s", ["Save the session under a name", "Сохранить сессию под именем"]),
+        key("c+w", ["Switch to another saved session", "Перейти к другой сохранённой сессии"]),
+        key("c+T", ["Open $TERMINAL (or behavior.terminal) in the current directory", "Открыть $TERMINAL (или behavior.terminal) в текущем каталоге"]),
+        key("c+d", ["Dry run: deletes and pastes always ask and list their operations first", "Пробный режим: удаление и вставка всегда спрашивают и сначала показывают операции"]),
+        key("c+y", ["Copy the path of the current directory", "Скопировать путь текущего каталога"]),
+        hinted("c+r", ["Connect to user@host:/path over SFTP or to a WebDAV https:// address", "Подключиться к user@host:/path по SFTP или к WebDAV-адресу https://"], ["new", "новый"], &[Remotes]),
+    ] },
+    HotkeyGroup { title: ["Selection", "Выделение"], hotkeys: &[
+        key("Shift+Space", ["Deselect the current file", "Снять выделение с текущего файла"]),
+        key("Esc", ["Clear the selection / leave search results", "Отменить все выделения / выйти из результатов поиска"]),
+    ] },
+    HotkeyGroup { title: ["Search", "Поиск"], hotkeys: &[
+        key("F", ["Filter the list by a glob such as *.rs; Esc clears it", "Фильтр списка по glob, например *.rs; Esc снимает его"]),
+        hinted("/", ["Open the search dialog", "Открыть диалог поиска"], ["search", "поиск"], &[Files]),
+        hinted("  Tab", ["Query syntax: substring, glob, regex", "Синтаксис запроса: подстрока, glob, regex"], ["syntax", "синтаксис"], &[Search]),
+        hinted("  Shift+Tab", ["Search mode: name, contents, metadata, index", "Режим поиска: имя, содержимое, метаданные, индекс"], ["mode", "режим"], &[Search]),
+        hinted("  Ctrl+Space", ["Mark a result; Enter selects the marked ones in the tab", "Отметить результат; Enter выделит отмеченные во вкладке"], ["mark", "отметить"], &[Search]),
+        key("  Ctrl+a", ["Select all results", "Выделить все найденные элементы"]),
+        hinted("  Ctrl+l", ["Show the results in the middle pane", "Показать результаты поиска в средней панели"], ["list", "список"], &[Search]),
+        hinted("  Ctrl+r", ["Search subdirectories too; Enter then opens a result's directory and keeps the results", "Рекурсивный поиск по подкаталогам; Enter откроет каталог результата, не закрывая список"], ["recursive", "подкаталоги"], &[Search]),
+        key("  Ctrl+e", ["Export the results to a file", "Экспортировать результаты в файл"]),
+    ] },
+    HotkeyGroup { title: ["Settings", "Настройки"], hotkeys: &[
+        hinted("F2", ["Open settings", "Открыть настройки"], ["settings", "настройки"], &[Files]),
+        hinted("?", ["Show all keys", "Показать все клавиши"], ["help", "справка"], &[Files, Places, Bookmarks, Disks, Remotes]),
+    ] },
+    HotkeyGroup { title: ["Terminal", "Терминал"], hotkeys: &[
+        hinted("Ctrl+t", ["Show/hide the embedded terminal; the only key the terminal keeps for itself", "Показать/скрыть встроенный терминал; единственная клавиша, которую терминал не передаёт оболочке"], ["back to files", "к файлам"], &[Terminal]),
+        key("Ctrl+s", ["Type the selected paths into the terminal", "Вставить пути выделенных файлов в терминал"]),
+        hinted("  Shift+PageUp", ["Scroll through terminal output", "Листать вывод терминала"], ["scroll", "листать"], &[Terminal]),
+        hinted("  Ctrl+b [", ["Terminal copy mode", "Режим копирования терминала"], ["copy mode", "копирование"], &[Terminal]),
+        hinted("    v", ["Start or clear the selection", "Начать или снять выделение"], ["select", "выделить"], &[CopyMode]),
+        hinted("    y", ["Copy the selection or the line", "Скопировать выделение или строку"], ["copy", "копировать"], &[CopyMode]),
+        hinted("    Esc", ["Leave copy mode", "Выйти из режима копирования"], ["exit", "выход"], &[CopyMode]),
+    ] },
+    HotkeyGroup { title: ["View", "Вид"], hotkeys: &[
+        key(".", ["Show/hide hidden files", "Показать/скрыть скрытые файлы"]),
+        key("D", ["Show/hide size and modification date", "Показать/скрыть размер и дату изменения"]),
+        key("s", ["Change the sort field: name, size, date", "Сменить поле сортировки: имя, размер, дата"]),
+        key("S", ["Reverse the sort order", "Обратный порядок сортировки"]),
+        key("P", ["Pin/unpin the directory at the top of its parent", "Закрепить/открепить каталог вверху списка родителя"]),
+        key("Z", ["Remember/forget the view for this directory", "Запомнить/забыть вид для текущего каталога"]),
+        key("z+u", ["Disk usage of the current directory, largest first; Esc stops the scan or leaves", "Занятое место в текущем каталоге, от больших к меньшим; Esc прерывает сканирование или выходит"]),
+        key("C", ["Clear finished tasks, including failures", "Убрать завершённые задачи, включая ошибки"]),
+    ] },
+    HotkeyGroup { title: ["Quit", "Выход"], hotkeys: &[
+        hinted("q", ["Quit", "Выход из приложения"], ["quit", "выход"], &[Files]),
+    ] },
+];
+
+/// Все горячие клавиши подряд, без групп
+pub fn hotkeys() -> impl Iterator<Item = &'static Hotkey> {
+    HOTKEY_GROUPS.iter().flat_map(|group| group.hotkeys)
+}
+
+/// Контекст подсказок для текущего фокуса и режима. В диалогах, кроме
+/// поиска, подсказок нет — у них свои строки с клавишами.
+pub fn hint_context(app_state: &AppState) -> Option<HintContext> {
+    if app_state.show_search_dialog {
+        return Some(Search);
+    }
+    if app_state.input_mode != InputMode::Normal || app_state.show_input_dialog || app_state.show_confirmation {
+        return None;
+    }
+    Some(focus_context(app_state))
+}
+
+/// Контекст текущего фокуса без учёта режима ввода. Пока открыта справка,
+/// фокус не меняется, так что это фокус на момент нажатия `?`.
+fn focus_context(app_state: &AppState) -> HintContext {
+    if app_state.get_active_tab().right_pane_view == RightPaneView::Terminal {
+        return if app_state.get_active_tab().terminal.copy_mode.is_some() { CopyMode } else { Terminal };
+    }
+    match app_state.focus {
+        FocusBlock::Middle => Files,
+        FocusBlock::Xdg => Places,
+        FocusBlock::Bookmarks => Bookmarks,
+        FocusBlock::Disks => Disks,
+        FocusBlock::Remotes => Remotes,
+        FocusBlock::Terminal => Terminal,
+    }
+}
+
+/// Клавиши и подписи для строки подсказок в порядке списка
+pub fn hints_for(context: HintContext, language: Language) -> Vec<(&'static str, &'static str)> {
+    hotkeys()
+        .filter_map(|hotkey| {
+            let ([english, russian], contexts) = hotkey.hint?;
+            contexts.contains(&context).then_some((hotkey.hint_key(), language.pick(english, russian)))
+        })
+        .collect()
+}
+
+/// Строка справки: заголовок группы или клавиша
+pub enum HelpRow {
+    Title(&'static str),
+    Key(&'static Hotkey),
+}
+
+/// Строки справки `?` с учётом фильтра. Сначала отдельной группой идут
+/// клавиши контекста, в котором открыли справку, затем остальные по группам.
+/// Группы, где ничего не подошло под фильтр, не показываются.
+pub fn help_rows(app_state: &AppState) -> Vec<HelpRow> {
+    let language = app_state.language;
+    let filter = app_state.help_filter.to_lowercase();
+    let context = focus_context(app_state);
+    let in_context =
+        |hotkey: &Hotkey| hotkey.hint.is_some_and(|(_, contexts)| contexts.contains(&context));
+
+    let mut rows = Vec::new();
+    let mut push_group = |title, hotkeys: Vec<&'static Hotkey>| {
+        if !hotkeys.is_empty() {
+            rows.push(HelpRow::Title(title));
+            rows.extend(hotkeys.into_iter().map(HelpRow::Key));
+        }
+    };
+    push_group(
+        context.title(language),
+        hotkeys().filter(|hotkey| in_context(hotkey) && hotkey.matches(&filter, language)).collect(),
+    );
+    for group in HOTKEY_GROUPS {
+        push_group(
+            language.pick(group.title[0], group.title[1]),
+            group
+                .hotkeys
+                .iter()
+                .filter(|hotkey| !in_context(hotkey) && hotkey.matches(&filter, language))
+                .collect(),
+        );
+    }
+    rows
+}
@@ -1,13 +1,16 @@
-use crate::{left_pane, middle_pane, top_bar, right_pane, settings};
+use crate::{breadcrumb, hotkeys, left_pane, middle_pane, status_bar, top_bar, right_pane, settings, setup};
+use crate::i18n::Msg;
 use ratatui::{
-    prelude::{Constraint, Direction, Layout, Rect, Style},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    prelude::{Buffer, Color, Constraint, Direction, Layout, Line, Modifier, Rect, Span, Style},
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
-use corvus_core::app_state::{AppState, CreateFileType, InputMode};
+use corvus_core::app_state::{AppState, CreateFileType, FocusBlock, InputMode, LayoutMode, PaneAreas, PanelSide, RightPaneView, TabState};
 use corvus_core::clipboard::ClipboardMode;
-use corvus_core::settings::ColorScheme;
-use humansize;
+use corvus_core::settings::{format_size_with_config, ColorScheme};
+use corvus_core::task_manager::{ArchiveFormat, Task, TaskStatus};
+use crate::text_width::{display_width, wrap_to_width};
+use std::collections::HashSet;
 use chrono;
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
@@ -36,16 +39,39 @@ pub fn render_main_layout(frame: &mut Frame, app_state: &mut AppState) {
     let color_scheme = app_state.get_current_color_scheme();
     
     match app_state.input_mode {
-        InputMode::Settings => {
-            // Отображаем экран настроек
+        InputMode::Settings | InputMode::PluginInstall => {
+            // Отображаем экран настроек и открытые поверх него диалоги плагинов
             settings::render_settings_screen(frame, frame.size(), app_state);
-            return;
+            if app_state.show_confirmation {
+                render_confirmation_dialog(frame, app_state, &color_scheme);
+            }
+            if app_state.show_input_dialog {
+                render_input_dialog(frame, app_state, &color_scheme);
+            }
+        }
+        InputMode::Setup => {
+            setup::render_setup_screen(frame, frame.size(), app_state);
         }
         _ => {
             // Отображаем обычный интерфейс
             render_normal_layout(frame, app_state, &color_scheme);
         }
     }
+    if color_scheme.monochrome {
+        strip_colors(frame.buffer_mut());
+    }
+}
+
+/// Убирает из кадра все цвета, включая цвета файлов и вывода терминала.
+/// Отмеченный схемой фон курсора и выделения становится инверсией.
+fn strip_colors(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        if cell.bg == ColorScheme::MONOCHROME_MARK {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
 }
 
 fn render_normal_layout(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
@@ -55,13 +81,17 @@ fn render_normal_layout(frame: &mut Frame, app_state: &AppState, color_scheme: &
         .constraints([
             Constraint::Length(top_bar_height), // Top bar
             Constraint::Min(0),    // Main content
-            Constraint::Length(9), // Footer
+            Constraint::Length(1), // Status bar
+            Constraint::Length(if app_state.show_footer { 9 } else { 0 }), // Footer
+            Constraint::Length(if app_state.show_key_hints { 1 } else { 0 }), // Key hints
         ])
         .split(frame.size());
 
     let top_bar_area = main_chunks[0];
     let main_area = main_chunks[1];
-    let footer_area = main_chunks[2];
+    let status_area = main_chunks[2];
+    let footer_area = main_chunks[3];
+    let hints_area = main_chunks[4];
 
     // --- Top Bar (Tabs) ---
     top_bar::render_top_bar(frame, top_bar_area, app_state, color_scheme);
@@ -70,54 +100,83 @@ fn render_normal_layout(frame: &mut Frame, app_state: &AppState, color_scheme: &
     let main_horizontal_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(20), // Left
-            Constraint::Percentage(40), // Middle
-            Constraint::Percentage(40), // Right
+            Constraint::Percentage(if app_state.show_left_pane { 20 } else { 0 }), // Left
+            Constraint::Min(0), // Middle and right
         ])
         .split(main_area);
+    let list_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(100 - app_state.preview_width), // Middle
+            Constraint::Percentage(app_state.preview_width), // Right
+        ])
+        .split(main_horizontal_chunks[1]);
 
     let left_pane_area = main_horizontal_chunks[0];
-    let middle_pane_area = main_horizontal_chunks[1];
-    let right_pane_area = main_horizontal_chunks[2];
+    let middle_pane_area = list_chunks[0];
+    let right_pane_area = list_chunks[1];
 
     // --- Render Panes with Borders ---
-    let active_tab = app_state.get_active_tab();
-
     // Left Pane
-    let left_pane_block = Block::default()
-        .borders(Borders::ALL)
-        .style(Style::default()
-            .fg(color_scheme.text_color())
-            .bg(color_scheme.background()));
-    let left_pane_inner_area = left_pane_block.inner(left_pane_area);
-    frame.render_widget(left_pane_block, left_pane_area);
-    left_pane::render_left_pane(frame, left_pane_inner_area, app_state, color_scheme);
+    let mut places = None;
+    if app_state.show_left_pane {
+        let left_pane_block = Block::default()
+            .borders(Borders::ALL)
+            .style(Style::default()
+                .fg(color_scheme.text_color())
+                .bg(color_scheme.background()));
+        let left_pane_inner_area = left_pane_block.inner(left_pane_area);
+        frame.render_widget(left_pane_block, left_pane_area);
+        places = Some(left_pane::render_left_pane(frame, left_pane_inner_area, app_state, color_scheme));
+    }
 
-    // Middle Pane
-    let middle_pane_block = Block::default()
-        .title(format!("Current: {}", active_tab.current_dir.display()))
-        .borders(Borders::ALL)
-        .style(Style::default()
-            .fg(color_scheme.text_color())
-            .bg(color_scheme.background()));
-    let middle_pane_inner_area = middle_pane_block.inner(middle_pane_area);
-    frame.render_widget(middle_pane_block, middle_pane_area);
-    middle_pane::render_middle_pane(frame, middle_pane_inner_area, active_tab, color_scheme);
+    // Запоминаем, где что нарисовано, чтобы найти запись под мышью
+    let pane_areas = match (app_state.layout_mode, &app_state.second_panel) {
+        // Два списка вместо списка и предпросмотра
+        (LayoutMode::Dual, Some(second_panel)) => {
+            let left_active = app_state.active_panel == PanelSide::Left;
+            let (left_list, left_path) = render_file_panel(frame, middle_pane_area, app_state.get_selected_tab(), left_active, app_state, color_scheme);
+            let (right_list, right_path) = render_file_panel(frame, right_pane_area, second_panel, !left_active, app_state, color_scheme);
+            PaneAreas {
+                left_list: Some(left_list),
+                right_list: Some(right_list),
+                preview: None,
+                left_path,
+                right_path,
+                ..PaneAreas::default()
+            }
+        }
+        _ => {
+            let (left_list, left_path) = render_file_panel(frame, middle_pane_area, app_state.get_active_tab(), true, app_state, color_scheme);
+            right_pane::render_right_pane(frame, right_pane_area, app_state, color_scheme);
+            let preview = (app_state.get_active_tab().right_pane_view == RightPaneView::Preview).then_some(right_pane_area);
+            PaneAreas { left_list: Some(left_list), preview, left_path, ..PaneAreas::default() }
+        }
+    };
+    app_state.pane_areas.set(PaneAreas {
+        places,
+        tabs: app_state.show_tabs.then_some(top_bar_area),
+        ..pane_areas
+    });
 
-    // Right Pane
-    right_pane::render_right_pane(frame, right_pane_area, app_state, color_scheme);
+    status_bar::render_status_bar(frame, status_area, app_state, color_scheme);
 
     // --- Footer (Tasks, Info) ---
-    let footer_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(50), // Tasks
-            Constraint::Percentage(50), // Info
-        ])
-        .split(footer_area);
-
-    render_tasks_footer(frame, footer_chunks[0], app_state, color_scheme);
-    render_info_panel(frame, footer_chunks[1], app_state, color_scheme);
+    if app_state.show_footer {
+        let footer_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(50), // Tasks
+                Constraint::Percentage(50), // Info
+            ])
+            .split(footer_area);
+
+        render_tasks_footer(frame, footer_chunks[0], app_state, color_scheme);
+        render_info_panel(frame, footer_chunks[1], app_state, color_scheme);
+    } else {
+        render_notification_line(frame, main_area, app_state, color_scheme);
+    }
+    render_key_hints(frame, hints_area, app_state, color_scheme);
 
     if app_state.show_confirmation {
         render_confirmation_dialog(frame, app_state, color_scheme);
@@ -128,23 +187,256 @@ fn render_normal_layout(frame: &mut Frame, app_state: &AppState, color_scheme: &
     if app_state.show_search_dialog {
         render_search_dialog(frame, app_state, color_scheme);
     }
+    if app_state.input_mode == InputMode::CommandPalette {
+        render_command_palette(frame, app_state, color_scheme);
+    }
+    if app_state.input_mode == InputMode::SessionPicker {
+        render_session_picker(frame, app_state, color_scheme);
+    }
+    if app_state.input_mode == InputMode::AncestorPicker {
+        render_ancestor_picker(frame, app_state, color_scheme);
+    }
+    if app_state.input_mode == InputMode::Help {
+        render_help(frame, app_state, color_scheme);
+    }
+}
+
+/// Справка `?` на весь экран: клавиши по группам, сначала для контекста, в
+/// котором её открыли. В заголовке — набранный фильтр.
+fn render_help(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
+    let language = app_state.language;
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let title = if app_state.help_filter.is_empty() {
+        Msg::HelpTitle.text(language).to_string()
+    } else {
+        Msg::HelpFiltered.fill(language, &[&app_state.help_filter])
+    };
+    let rows = hotkeys::help_rows(app_state);
+    let items: Vec<ListItem> = if rows.is_empty() {
+        vec![ListItem::new(Msg::HelpNoMatches.text(language)).style(style)]
+    } else {
+        rows.iter()
+            .skip(app_state.help_scroll)
+            .map(|row| match row {
+                hotkeys::HelpRow::Title(title) => {
+                    ListItem::new(*title).style(style.add_modifier(Modifier::BOLD))
+                }
+                hotkeys::HelpRow::Key(hotkey) => {
+                    ListItem::new(format!("  {:<18} {}", hotkey.keys, hotkey.description(language))).style(style)
+                }
+            })
+            .collect()
+    };
+    let list = List::new(items)
+        .block(Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(style));
+
+    let area = frame.size();
+    frame.render_widget(Clear, area);
+    frame.render_widget(list, area);
+}
+
+/// Список пользовательских команд с их клавишами
+fn render_command_palette(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
+    let language = app_state.language;
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let items: Vec<ListItem> = app_state
+        .config
+        .commands
+        .iter()
+        .map(|command| {
+            let target = if command.run_in_terminal { Msg::CommandInTerminal.text(language) } else { "" };
+            ListItem::new(format!(" {}  {}{}", command.key, command.name, target)).style(style)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default()
+            .title(Msg::CommandsTitle.text(language))
+            .borders(Borders::ALL)
+            .style(style))
+        .highlight_style(Style::default()
+            .bg(color_scheme.highlight_bg())
+            .fg(color_scheme.text_color()));
+    let mut list_state = ListState::default();
+    list_state.select(Some(app_state.command_palette_cursor));
+
+    let area = centered_rect(50, 40, frame.size());
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Список сессий; текущая отмечена точкой
+fn render_session_picker(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
+    let language = app_state.language;
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let items: Vec<ListItem> = app_state
+        .session_choices
+        .iter()
+        .map(|name| {
+            let marker = if *name == app_state.session_name { "•" } else { " " };
+            let label = name.as_deref().unwrap_or(Msg::SessionDefault.text(language));
+            ListItem::new(format!(" {} {}", marker, label)).style(style)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default()
+            .title(Msg::SessionsTitle.text(language))
+            .borders(Borders::ALL)
+            .style(style))
+        .highlight_style(Style::default()
+            .bg(color_scheme.highlight_bg())
+            .fg(color_scheme.text_color()));
+    let mut list_state = ListState::default();
+    list_state.select(Some(app_state.session_cursor));
+
+    let area = centered_rect(50, 40, frame.size());
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Каталоги выше текущего с номером — на сколько уровней подняться
+fn render_ancestor_picker(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let items: Vec<ListItem> = app_state
+        .ancestor_dirs()
+        .iter()
+        .enumerate()
+        .map(|(index, dir)| {
+            let number = if index < 9 { (index + 1).to_string() } else { " ".to_string() };
+            ListItem::new(format!(" {} {}", number, dir.display())).style(style)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default()
+            .title(Msg::AncestorsTitle.text(app_state.language))
+            .borders(Borders::ALL)
+            .style(style))
+        .highlight_style(Style::default()
+            .bg(color_scheme.highlight_bg())
+            .fg(color_scheme.text_color()));
+    let mut list_state = ListState::default();
+    list_state.select(Some(app_state.ancestor_cursor));
+
+    let area = centered_rect(60, 40, frame.size());
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Список файлов в рамке. Рамка активного списка выделяется, пока фокус на списках.
+/// В заголовке обычного каталога — путь по частям; кроме строк списка
+/// возвращается место, где он нарисован.
+fn render_file_panel(
+    frame: &mut Frame,
+    area: Rect,
+    tab: &TabState,
+    is_active: bool,
+    app_state: &AppState,
+    color_scheme: &ColorScheme,
+) -> ((Rect, usize), Option<Rect>) {
+    let language = app_state.language;
+    let text = if tab.is_showing_search_results() {
+        Some(Msg::SearchResultsIn.fill(language, &[&tab.current_dir.display(), &tab.entries.len()]))
+    } else if let Some(view) = tab.disk_usage() {
+        let message = match (view.scanning, view.stopped) {
+            (true, _) => Msg::DiskUsageScanning,
+            (false, true) => Msg::DiskUsageStopped,
+            (false, false) => Msg::DiskUsageIn,
+        };
+        let size = tab.usage_node().map_or(0, |node| node.size);
+        Some(message.fill(language, &[&tab.current_dir.display(), &format_size_with_config(size, app_state.size_units)]))
+    } else {
+        None
+    };
+    let suffix = tab.glob_filter.as_ref().map_or_else(String::new, |filter| {
+        format!(" [{}]", Msg::GlobFilterActive.fill(language, &[&filter.pattern]))
+    });
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let mut border_style = style;
+    let mut prefix = "";
+    if app_state.layout_mode == LayoutMode::Dual && is_active {
+        prefix = "▶ ";
+        if app_state.focus == FocusBlock::Middle {
+            border_style = border_style.fg(color_scheme.accent());
+        }
+    }
+    let mut path_area = None;
+    let title = match text {
+        Some(text) => Line::from(format!("{}{}{}", prefix, text, suffix)),
+        None => {
+            let taken = 2 + display_width(prefix) + display_width(&suffix);
+            let width = (area.width as usize).saturating_sub(taken);
+            let segments = breadcrumb::segments(&tab.current_dir, width);
+            let last = segments.len().saturating_sub(1);
+            let mut spans = vec![Span::raw(prefix)];
+            for (index, segment) in segments.into_iter().enumerate() {
+                let segment_style = if segment.target.is_none() {
+                    style.fg(Color::DarkGray)
+                } else if index == last {
+                    style.fg(color_scheme.accent()).add_modifier(Modifier::BOLD)
+                } else {
+                    style
+                };
+                spans.push(Span::styled(segment.label, segment_style));
+            }
+            spans.push(Span::raw(suffix));
+            path_area = Some(Rect::new(area.x + 1 + display_width(prefix) as u16, area.y, width as u16, 1));
+            Line::from(spans)
+        }
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(border_style)
+        .style(style);
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+    // Каталог ещё читается в фоне, например на медленном сетевом диске
+    if tab.loading_entries.is_some() {
+        frame.render_widget(Paragraph::new(Msg::Loading.text(language)).style(style.fg(Color::DarkGray)), inner_area);
+        return ((inner_area, 0), path_area);
+    }
+    let palette = app_state.get_file_type_palette();
+    let show_details = app_state.view_of(tab).show_file_details;
+    let offset = middle_pane::render_middle_pane(frame, inner_area, tab, color_scheme, &palette, show_details, app_state.size_units);
+    ((inner_area, offset), path_area)
 }
 
 fn render_input_dialog(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
+    if app_state.input_mode == InputMode::Archive {
+        render_archive_dialog(frame, app_state, color_scheme);
+        return;
+    }
+    let language = app_state.language;
     let title = match app_state.input_mode {
-        InputMode::Create => {
-            let file_type = match app_state.create_file_type {
-                Some(CreateFileType::File) => "file",
-                Some(CreateFileType::Directory) => "directory",
-                None => "",
-            };
-            format!("Create new {}", file_type)
-        }
-        InputMode::Rename => "Rename".to_string(),
-        InputMode::Chmod => "Chmod (e.g. 755)".to_string(),
-        InputMode::Chown => "Chown (e.g. user:group)".to_string(),
-        InputMode::Archive => format!("Archive (Format: {})", app_state.archive_format),
-        _ => "Input".to_string(),
+        InputMode::Create => match app_state.create_file_type {
+            Some(CreateFileType::Directory) => Msg::CreateDirectory.text(language).to_string(),
+            _ => Msg::CreateFile.text(language).to_string(),
+        },
+        InputMode::Rename => Msg::Rename.text(language).to_string(),
+        InputMode::Chmod => Msg::Chmod.text(language).to_string(),
+        InputMode::Chown => Msg::Chown.text(language).to_string(),
+        InputMode::PreviewCommand => Msg::PreviewCommand.text(language).to_string(),
+        InputMode::ShellCommand => Msg::ShellCommand.text(language).to_string(),
+        InputMode::GlobFilter => Msg::GlobFilter.text(language).to_string(),
+        InputMode::SessionName => Msg::SessionName.text(language).to_string(),
+        InputMode::RemoteConnect => Msg::RemoteConnect.text(language).to_string(),
+        InputMode::PluginInstall => Msg::PluginInstall.text(language).to_string(),
+        InputMode::TabName => Msg::TabName.text(language).to_string(),
+        InputMode::Bookmark => Msg::BookmarkName.text(language).to_string(),
+        InputMode::Archive => Msg::ArchiveTitle.fill(language, &[&app_state.archive_format]),
+        _ => Msg::Input.text(language).to_string(),
     };
 
     let mut text = app_state.input_buffer.clone();
@@ -169,25 +461,106 @@ fn render_input_dialog(frame: &mut Frame, app_state: &AppState, color_scheme: &C
     frame.render_widget(paragraph, area);
 }
 
+/// Диалог архивации: имя архива и список форматов
+fn render_archive_dialog(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
+    let language = app_state.language;
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let block = Block::default()
+        .title(Msg::ArchiveDialogTitle.fill(language, &[&app_state.archive_format]))
+        .borders(Borders::ALL)
+        .style(style);
+
+    let area = centered_rect(50, 40, frame.size());
+    frame.render_widget(Clear, area);
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(1)])
+        .split(inner_area);
+
+    // Привычные названия крайних уровней
+    let level = app_state.archive_compression_level;
+    let preset = match level {
+        0 => Msg::CompressionStore.text(language),
+        1 => Msg::CompressionFast.text(language),
+        9 => Msg::CompressionBest.text(language),
+        _ => "",
+    };
+    let mut lines = vec![
+        Line::from(Msg::ArchiveName.fill(language, &[&app_state.input_buffer])),
+        Line::from(Msg::CompressionValue.fill(language, &[&level, &preset])),
+        Line::from(Msg::RemoveSources.fill(
+            language,
+            &[&if app_state.archive_remove_sources { "x" } else { " " }],
+        )),
+    ];
+    if let Some(error) = &app_state.input_dialog_error {
+        lines.push(Line::styled(error.clone(), Style::default().fg(color_scheme.error_color())));
+    }
+    frame.render_widget(Paragraph::new(lines).style(style), chunks[0]);
+
+    let items: Vec<ListItem> = ArchiveFormat::ALL
+        .iter()
+        .map(|format| ListItem::new(format!(" {}", format)).style(style))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().title(Msg::ArchiveFormat.text(language)).borders(Borders::TOP).style(style))
+        .highlight_style(Style::default()
+            .bg(color_scheme.highlight_bg())
+            .fg(color_scheme.text_color()));
+    let mut list_state = ListState::default();
+    list_state.select(ArchiveFormat::ALL.iter().position(|format| *format == app_state.archive_format));
+    frame.render_stateful_widget(list, chunks[1], &mut list_state);
+}
+
 fn render_search_dialog(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
-    let title = "Search (Press Esc to cancel, Enter to select)".to_string();
+    let language = app_state.language;
+    let running = if app_state.background_search.running { Msg::Searching.text(language) } else { "" };
+    let scope = if app_state.search_recursive { Msg::SearchRecursive.text(language) } else { "" };
+    let title = Msg::SearchTitle.fill(
+        language,
+        &[&app_state.search_mode.name(), &app_state.search_syntax.name(), &scope, &running],
+    );
     
     // Create the text content
-    let mut text = format!("Search query: {}\n\n", app_state.search_query);
+    let marked = if app_state.search_selection.is_empty() {
+        String::new()
+    } else {
+        Msg::SearchMarked.fill(language, &[&app_state.search_selection.len()])
+    };
+    let mut lines = vec![
+        Line::from(Msg::SearchQuery.fill(language, &[&app_state.search_query])),
+        Line::from(marked),
+    ];
     
     // Add search results
-    if app_state.search_results.is_empty() {
-        text.push_str("No results found");
+    if let Some(error) = &app_state.search_error {
+        lines.push(Line::styled(
+            Msg::InvalidPattern.fill(language, &[error]),
+            Style::default().fg(color_scheme.error_color()),
+        ));
+    } else if app_state.search_results.is_empty() {
+        lines.push(Line::from(Msg::NoResults.text(language)));
     } else {
-        text.push_str("Search results:\n");
+        lines.push(Line::from(Msg::SearchResults.text(language)));
         for (i, result) in app_state.search_results.iter().enumerate() {
-            let prefix = if i == app_state.search_cursor { "> " } else { "  " };
+            let prefix = if i == app_state.search_cursor { ">" } else { " " };
+            let mark = if app_state.search_selection.contains(&result.path) { "+" } else { " " };
             let icon = if result.is_dir { "📁" } else { "📄" };
-            text.push_str(&format!("{}{} {}\n", prefix, icon, result.name));
+            let line = Line::from(format!("{}{}{} {}", prefix, mark, icon, result.name));
+            if mark == "+" {
+                lines.push(line.style(Style::default().bg(color_scheme.selection_bg())));
+            } else {
+                lines.push(line);
+            }
         }
     }
 
-    let paragraph = Paragraph::new(text)
+    let paragraph = Paragraph::new(lines)
         .block(Block::default()
             .title(title)
             .borders(Borders::ALL)
@@ -208,7 +581,7 @@ fn render_confirmation_dialog(frame: &mut Frame, app_state: &AppState, color_sch
     let message = &app_state.confirmation_message;
     let text = Paragraph::new(message.as_str())
         .block(Block::default()
-            .title("Confirmation")
+            .title(Msg::Confirmation.text(app_state.language))
             .borders(Borders::ALL)
             .style(Style::default()
                 .fg(color_scheme.text_color())
@@ -217,40 +590,247 @@ fn render_confirmation_dialog(frame: &mut Frame, app_state: &AppState, color_sch
             .fg(color_scheme.text_color())
             .bg(color_scheme.background()));
 
+    if let Some(plan) = &app_state.plan_preview {
+        render_plan_preview(frame, app_state, plan, color_scheme);
+        return;
+    }
+    if app_state.plugin_consent.is_some() {
+        render_consent_dialog(frame, app_state, color_scheme);
+        return;
+    }
+
     // Center the dialog
     let area = centered_rect(50, 20, frame.size());
     frame.render_widget(Clear, area); //this clears the background
     frame.render_widget(text, area);
 }
 
+/// Запрос прав плагина. Окно подстраивается под текст, чтобы все права
+/// и вопрос были видны до ответа.
+fn render_consent_dialog(frame: &mut Frame, app_state: &AppState, color_scheme: &ColorScheme) {
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let screen = frame.size();
+    let message = &app_state.confirmation_message;
+    let text_width = message.lines().map(display_width).max().unwrap_or(0);
+    let width = (text_width as u16).saturating_add(4).max(30).min(screen.width);
+    let lines: Vec<Line> = message
+        .lines()
+        .flat_map(|line| wrap_to_width(line, width.saturating_sub(4) as usize))
+        .map(Line::from)
+        .collect();
+    let height = (lines.len() as u16).saturating_add(2).min(screen.height);
+    let area = Rect {
+        x: screen.x + (screen.width - width) / 2,
+        y: screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .title(Msg::Confirmation.text(app_state.language))
+        .borders(Borders::ALL)
+        .style(style);
+    frame.render_widget(Clear, area);
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+    let text_area = Rect { x: inner_area.x + 1, width: inner_area.width.saturating_sub(2), ..inner_area };
+    frame.render_widget(Paragraph::new(lines).style(style), text_area);
+}
+
+/// Подтверждение вместе со списком операций, которые будут выполнены
+fn render_plan_preview(frame: &mut Frame, app_state: &AppState, plan: &[String], color_scheme: &ColorScheme) {
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let block = Block::default()
+        .title(Msg::ConfirmationPlan.fill(app_state.language, &[&plan.len()]))
+        .borders(Borders::ALL)
+        .style(style);
+
+    let area = centered_rect(80, 70, frame.size());
+    frame.render_widget(Clear, area);
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner_area);
+    frame.render_widget(
+        Paragraph::new(app_state.confirmation_message.as_str()).style(style).wrap(Wrap { trim: true }),
+        chunks[0],
+    );
+
+    let lines: Vec<Line> = plan
+        .iter()
+        .skip(app_state.plan_scroll)
+        .map(|line| {
+            // Конфликты выделяются цветом
+            let line_style = if line.ends_with(']') { style.fg(Color::Yellow) } else { style };
+            Line::styled(line.clone(), line_style)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines).style(style), chunks[1]);
+}
+
+/// Строка с клавишами, полезными в панели или диалоге в фокусе
+fn render_key_hints(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+    if area.height == 0 {
+        return;
+    }
+    let Some(context) = hotkeys::hint_context(app_state) else {
+        return;
+    };
+    let text_style = Style::default().fg(color_scheme.text_color()).bg(color_scheme.background());
+    let key_style = text_style.fg(color_scheme.accent()).add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    for (key, label) in hotkeys::hints_for(context, app_state.language) {
+        spans.push(Span::styled(format!(" {}", key), key_style));
+        spans.push(Span::styled(format!(":{} ", label), text_style));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)).style(text_style), area);
+}
+
 fn render_tasks_footer(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Tasks")
+        .title(Msg::Tasks.text(app_state.language))
         .style(Style::default()
             .fg(color_scheme.text_color())
             .bg(color_scheme.background()));
     let inner_area = block.inner(area);
     frame.render_widget(block, area);
 
-    let tasks = app_state.task_manager.get_tasks();
-    let task_items: Vec<ListItem> = tasks
+    // Сначала активные задачи, затем остальные; внутри групп новые выше
+    let mut tasks = app_state.task_manager.get_tasks_grouped();
+    tasks.reverse();
+    tasks.sort_by_key(|task| !matches!(task.status, TaskStatus::InProgress(_)));
+    let rows_of_tasks = nest_chained_tasks(tasks);
+    let tasks: Vec<&Task> = rows_of_tasks.iter().map(|(_, task)| task).collect();
+
+    let width = inner_area.width as usize;
+    let rows = inner_area.height as usize;
+    // Задачи цепочки показываются с отступом под родительской
+    let label_of = |depth: usize, task: &Task| match depth {
+        0 => task.description.clone(),
+        _ => format!("{}↳ {}", "  ".repeat(depth - 1), task.description),
+    };
+    // Ошибки переносятся на несколько строк, остальные задачи занимают одну
+    let lines_of = |depth: usize, task: &Task| match &task.status {
+        TaskStatus::Failed(error) => wrap_to_width(&format!("{}: {}", label_of(depth, task), error), width),
+        _ => vec![String::new()],
+    };
+    let total_height: usize = rows_of_tasks.iter().map(|(depth, task)| lines_of(*depth, task).len()).sum();
+    let budget = if total_height > rows { rows.saturating_sub(1) } else { rows };
+
+    let mut y = inner_area.y;
+    let mut used = 0;
+    let mut shown = 0;
+    for (depth, task) in &rows_of_tasks {
+        let label = label_of(*depth, task);
+        let lines = lines_of(*depth, task);
+        if used + lines.len() > budget {
+            break;
+        }
+        let area = Rect { y, height: lines.len() as u16, ..inner_area };
+        let text_style = Style::default().bg(color_scheme.background());
+        match &task.status {
+            TaskStatus::InProgress(progress) => {
+                let ratio = f64::from(progress.clamp(0.0, 1.0));
+                let gauge = Gauge::default()
+                    .gauge_style(Style::default()
+                        .fg(color_scheme.highlight_bg())
+                        .bg(color_scheme.background()))
+                    .ratio(ratio)
+                    .label(format!("{} {:.0}%", label, ratio * 100.0));
+                frame.render_widget(gauge, area);
+            }
+            TaskStatus::Pending => {
+                let line = Paragraph::new(Msg::TaskQueued.fill(app_state.language, &[&label]))
+                    .style(text_style.fg(Color::Yellow));
+                frame.render_widget(line, area);
+            }
+            TaskStatus::Completed => {
+                let line = Paragraph::new(Msg::TaskDone.fill(app_state.language, &[&label]))
+                    .style(text_style.fg(Color::Green));
+                frame.render_widget(line, area);
+            }
+            TaskStatus::Failed(_) => {
+                let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+                frame.render_widget(Paragraph::new(text).style(text_style.fg(color_scheme.error_color())), area);
+            }
+        }
+        y += area.height;
+        used += area.height as usize;
+        shown += 1;
+    }
+
+    if shown < tasks.len() {
+        let count = |f: fn(&TaskStatus) -> bool| tasks.iter().filter(|task| f(&task.status)).count();
+        let summary: Vec<String> = [
+            (count(|s| matches!(s, TaskStatus::InProgress(_))), Msg::TasksRunning),
+            (count(|s| matches!(s, TaskStatus::Pending)), Msg::TasksQueued),
+            (count(|s| matches!(s, TaskStatus::Failed(_))), Msg::TasksFailed),
+            (count(|s| matches!(s, TaskStatus::Completed)), Msg::TasksDone),
+        ]
         .iter()
-        .map(|task| ListItem::new(task.description.clone())
-            .style(Style::default()
-                .fg(color_scheme.text_color())
-                .bg(color_scheme.background())))
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, label)| format!("{} {}", n, label.text(app_state.language)))
         .collect();
+        let row = Rect { y, height: 1, ..inner_area };
+        let summary = Paragraph::new(Msg::TasksClearHint.fill(app_state.language, &[&summary.join(", ")]))
+            .style(Style::default()
+                .fg(Color::DarkGray)
+                .bg(color_scheme.background()));
+        frame.render_widget(summary, row);
+    }
+}
 
-    let task_list = List::new(task_items);
+/// Ставит задачи цепочки сразу под родительскую и возвращает их вместе с
+/// глубиной вложенности. Порядок остальных задач не меняется.
+fn nest_chained_tasks(tasks: Vec<Task>) -> Vec<(usize, Task)> {
+    fn push_with_children(task: Task, depth: usize, children: &mut Vec<Task>, rows: &mut Vec<(usize, Task)>) {
+        let id = task.id;
+        rows.push((depth, task));
+        while let Some(index) = children.iter().position(|child| child.parent == Some(id)) {
+            let child = children.remove(index);
+            push_with_children(child, depth + 1, children, rows);
+        }
+    }
+
+    let ids: HashSet<_> = tasks.iter().map(|task| task.id).collect();
+    let (roots, mut children): (Vec<Task>, Vec<Task>) = tasks
+        .into_iter()
+        .partition(|task| task.parent.is_none_or(|parent| !ids.contains(&parent)));
+    let mut rows = Vec::new();
+    for task in roots {
+        push_with_children(task, 0, &mut children, &mut rows);
+    }
+    rows
+}
 
-    frame.render_widget(task_list, inner_area);
+/// Уведомление поверх нижней строки `area`, пока панель информации скрыта
+fn render_notification_line(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+    let Some(notification) = &app_state.notification else {
+        return;
+    };
+    if area.height == 0 {
+        return;
+    }
+    let line_area = Rect { y: area.y + area.height - 1, height: 1, ..area };
+    let paragraph = Paragraph::new(notification.lines().next().unwrap_or_default())
+        .style(Style::default()
+            .fg(color_scheme.background())
+            .bg(color_scheme.accent()));
+    frame.render_widget(Clear, line_area);
+    frame.render_widget(paragraph, line_area);
 }
 
 fn render_info_panel(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Info")
+        .title(Msg::Info.text(app_state.language))
         .style(Style::default()
             .fg(color_scheme.text_color())
             .bg(color_scheme.background()));
@@ -259,18 +839,28 @@ fn render_info_panel(frame: &mut Frame, area: Rect, app_state: &AppState, color_
     let mut info_text = String::new();
 
     // Always display clipboard info
+    let language = app_state.language;
     let clipboard = &app_state.clipboard;
     let clipboard_info = if !clipboard.paths.is_empty() {
         let mode = match clipboard.mode {
-            Some(ClipboardMode::Copy) => "Copy",
-            Some(ClipboardMode::Move) => "Move",
-            None => "None",
+            Some(ClipboardMode::Copy) => Msg::BufferCopy,
+            Some(ClipboardMode::Move) => Msg::BufferMove,
+            None => Msg::BufferNone,
         };
-        format!("Buffer: {} files ({})", clipboard.paths.len(), mode)
+        Msg::BufferFiles.fill(language, &[&clipboard.paths.len(), &mode.text(language)])
     } else {
-        "Buffer: Empty".to_string()
+        Msg::BufferEmpty.text(language).to_string()
     };
     info_text.push_str(&clipboard_info);
+    if app_state.session_read_only {
+        info_text.push_str("\n\n");
+        info_text.push_str(Msg::SessionReadOnly.text(language));
+    }
+
+    if app_state.is_typing_ahead() {
+        info_text.push_str("\n\n");
+        info_text.push_str(&Msg::JumpTo.fill(language, &[&app_state.type_ahead]));
+    }
 
     // Display notification if there is one
     if let Some(notification) = &app_state.notification {
@@ -281,18 +871,28 @@ fn render_info_panel(frame: &mut Frame, area: Rect, app_state: &AppState, color_
     if app_state.show_info_panel {
         if let Some(info) = &app_state.file_info {
             info_text.push_str("\n\n");
-            info_text.push_str(&format!("Path: {}\n", info.path.display()));
-            info_text.push_str(&format!("Size: {}\n", humansize::format_size(info.size, humansize::BINARY)));
-            info_text.push_str(&format!("Owner: {}\n", info.owner));
-            info_text.push_str(&format!("Permissions: {}\n", info.permissions));
+            let size = info.size.map_or(Msg::Calculating.text(language).to_string(), |size| format_size_with_config(size, app_state.size_units));
+            let mut lines = vec![
+                Msg::InfoPath.fill(language, &[&info.path.display()]),
+                Msg::InfoSize.fill(language, &[&size]),
+                Msg::InfoOwner.fill(language, &[&info.owner, &info.uid]),
+                Msg::InfoGroup.fill(language, &[&info.group, &info.gid]),
+                Msg::InfoPermissions.fill(language, &[&info.permissions]),
+                Msg::InfoInode.fill(language, &[&info.inode, &info.nlink]),
+            ];
+            if let Some(target) = &info.target {
+                let msg = if info.broken_link { Msg::InfoTargetMissing } else { Msg::InfoTarget };
+                lines.insert(1, msg.fill(language, &[&target.display()]));
+            }
             if let Some(created) = info.created {
                 let datetime: chrono::DateTime<chrono::Local> = created.into();
-                info_text.push_str(&format!("Created: {}\n", datetime.format("%Y-%m-%d %H:%M:%S")));
+                lines.push(Msg::InfoCreated.fill(language, &[&datetime.format("%Y-%m-%d %H:%M:%S")]));
             }
             if let Some(modified) = info.modified {
                 let datetime: chrono::DateTime<chrono::Local> = modified.into();
-                info_text.push_str(&format!("Modified: {}", datetime.format("%Y-%m-%d %H:%M:%S")));
+                lines.push(Msg::InfoModified.fill(language, &[&datetime.format("%Y-%m-%d %H:%M:%S")]));
             }
+            info_text.push_str(&lines.join("\n"));
         }
     }
 
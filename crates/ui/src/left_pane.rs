@@ -1,18 +1,36 @@
 use ratatui::{
     prelude::{Rect, Frame, Style, Span, Constraint, Direction, Layout},
+    style::Modifier,
     widgets::{block::Title, Block, Borders, List, ListItem, ListState},
 };
 use corvus_core::app_state::{AppState, FocusBlock};
 use corvus_core::settings::ColorScheme;
+#[cfg(feature = "mounts")]
+use corvus_core::disk_usage::{short_size, DiskUsage};
+#[cfg(feature = "mounts")]
+use corvus_core::settings::SizeUnits;
+use ratatui::text::Line;
+#[cfg(feature = "mounts")]
+use corvus_core::app_state::{mount_kind, MountKind};
+use crate::i18n::Msg;
+use std::path::Path;
+
+/// Builds a list item for a place, dimmed in the error color when its path is missing
+fn place_item<'a>(name: &str, path: &Path, app_state: &AppState, color_scheme: &ColorScheme) -> ListItem<'a> {
+    let style = Style::default().bg(color_scheme.background());
+    let style = if app_state.missing_places.contains(path) {
+        style.fg(color_scheme.error_color()).add_modifier(Modifier::DIM)
+    } else {
+        style.fg(color_scheme.text_color())
+    };
+    ListItem::new(name.to_string()).style(style)
+}
 
-pub fn render_xdg_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+pub fn render_xdg_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) -> (Rect, usize) {
     let items: Vec<ListItem> = app_state
         .xdg_dirs
         .iter()
-        .map(|(name, _path)| ListItem::new(name.clone())
-            .style(Style::default()
-                .fg(color_scheme.text_color())
-                .bg(color_scheme.background())))
+        .map(|(name, path)| place_item(name, path, app_state, color_scheme))
         .collect();
 
     let is_focused = app_state.focus == FocusBlock::Xdg;
@@ -38,7 +56,7 @@ pub fn render_xdg_block(frame: &mut Frame, area: Rect, app_state: &AppState, col
     let list = List::new(items)
         .block(
             Block::default()
-                .title(Title::from(Span::styled("XDG Dirs", title_style)))
+                .title(Title::from(Span::styled(Msg::XdgDirs.text(app_state.language), title_style)))
                 .borders(Borders::BOTTOM)
                 .style(Style::default()
                     .fg(color_scheme.text_color())
@@ -50,16 +68,20 @@ pub fn render_xdg_block(frame: &mut Frame, area: Rect, app_state: &AppState, col
     list_state.select(Some(app_state.xdg_cursor));
 
     frame.render_stateful_widget(list, area, &mut list_state);
+    (list_rows(area), list_state.offset())
 }
 
-pub fn render_bookmarks_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+pub fn render_bookmarks_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) -> (Rect, usize) {
     let items: Vec<ListItem> = app_state
         .bookmarks
         .iter()
-        .map(|(name, _path)| ListItem::new(name.clone())
-            .style(Style::default()
-                .fg(color_scheme.text_color())
-                .bg(color_scheme.background())))
+        .map(|bookmark| {
+            let label = match bookmark.key {
+                Some(key) => format!("{} [{}]", bookmark.name, key),
+                None => bookmark.name.clone(),
+            };
+            place_item(&label, &bookmark.path, app_state, color_scheme)
+        })
         .collect();
 
     let is_focused = app_state.focus == FocusBlock::Bookmarks;
@@ -85,7 +107,7 @@ pub fn render_bookmarks_block(frame: &mut Frame, area: Rect, app_state: &AppStat
     let list = List::new(items)
         .block(
             Block::default()
-                .title(Title::from(Span::styled("Bookmarks", title_style)))
+                .title(Title::from(Span::styled(Msg::Bookmarks.text(app_state.language), title_style)))
                 .borders(Borders::BOTTOM)
                 .style(Style::default()
                     .fg(color_scheme.text_color())
@@ -97,14 +119,51 @@ pub fn render_bookmarks_block(frame: &mut Frame, area: Rect, app_state: &AppStat
     list_state.select(Some(app_state.bookmarks_cursor));
 
     frame.render_stateful_widget(list, area, &mut list_state);
+    (list_rows(area), list_state.offset())
+}
+
+/// Width of the usage bar under a mount, without the brackets
+#[cfg(feature = "mounts")]
+const USAGE_BAR_WIDTH: usize = 10;
+
+/// Compact usage line such as `[████░░░░░░] 12.3G / 50G`, the bar in the
+/// error color when the disk is more than 90% full
+#[cfg(feature = "mounts")]
+fn usage_line<'a>(usage: &DiskUsage, size_units: SizeUnits, color_scheme: &ColorScheme) -> Line<'a> {
+    let ratio = usage.used_ratio();
+    let filled = ((ratio * USAGE_BAR_WIDTH as f64).round() as usize).min(USAGE_BAR_WIDTH);
+    let bar_color = if ratio > 0.9 { color_scheme.error_color() } else { color_scheme.text_color() };
+    Line::from(vec![
+        Span::raw("   ["),
+        Span::styled(format!("{}{}", "█".repeat(filled), "░".repeat(USAGE_BAR_WIDTH - filled)), Style::default().fg(bar_color)),
+        Span::raw(format!("] {} / {}", short_size(usage.used, size_units), short_size(usage.total, size_units))),
+    ])
 }
 
 #[cfg(feature = "mounts")]
-pub fn render_mounts_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+pub fn render_mounts_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) -> (Rect, usize) {
     let mount_items: Vec<ListItem> = app_state.mounts.iter().map(|mount| {
-        // Extract the last component of the path for display
-        let display_name = mount.dest.file_name().unwrap_or_default().to_string_lossy();
-        ListItem::new(format!("💾 {}", display_name))
+        let kind = mount_kind(&mount.source, &mount.dest, &mount.fstype, &app_state.config.disks);
+        let icon = match kind {
+            Some(MountKind::Removable) => "⏏",
+            Some(MountKind::Network) => "🌐",
+            _ => "💾",
+        };
+        // Device basename for block devices, the whole source for network mounts
+        let device = match kind {
+            Some(MountKind::Network) => mount.source.to_string_lossy(),
+            _ => mount.source.file_name().unwrap_or(mount.source.as_os_str()).to_string_lossy(),
+        };
+        let detail = Style::default().fg(color_scheme.text_color()).add_modifier(Modifier::DIM);
+        let mut lines = vec![Line::from(vec![
+            Span::raw(format!("{} {}", icon, mount.dest.display())),
+            Span::styled(format!("  {} {}", device, mount.fstype), detail),
+        ])];
+        // Usage is missing until the first statvfs returns or while it hangs
+        if let Some(usage) = app_state.disk_usage.get(&mount.dest) {
+            lines.push(usage_line(usage, app_state.size_units, color_scheme));
+        }
+        ListItem::new(lines)
             .style(Style::default()
                 .fg(color_scheme.text_color())
                 .bg(color_scheme.background()))
@@ -133,7 +192,7 @@ pub fn render_mounts_block(frame: &mut Frame, area: Rect, app_state: &AppState,
     let mounts_list = List::new(mount_items)
         .block(
             Block::default()
-                .title(Title::from(Span::styled("Disks", title_style)))
+                .title(Title::from(Span::styled(Msg::Disks.text(app_state.language), title_style)))
                 .borders(Borders::BOTTOM)
                 .style(Style::default()
                     .fg(color_scheme.text_color())
@@ -144,32 +203,113 @@ pub fn render_mounts_block(frame: &mut Frame, area: Rect, app_state: &AppState,
     let mut mounts_state = ListState::default();
     mounts_state.select(Some(app_state.disks_cursor));
     frame.render_stateful_widget(mounts_list, area, &mut mounts_state);
+    (list_rows(area), mounts_state.offset())
 }
 
 #[cfg(not(feature = "mounts"))]
-pub fn render_mounts_block(frame: &mut Frame, area: Rect, _app_state: &AppState, color_scheme: &ColorScheme) {
+pub fn render_mounts_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) -> (Rect, usize) {
     let block = Block::new()
         .borders(Borders::ALL)
-        .title("Mounts (unsupported)")
+        .title(Msg::MountsUnsupported.text(app_state.language))
         .style(Style::default()
             .fg(color_scheme.text_color())
             .bg(color_scheme.background()));
     frame.render_widget(block, area);
+    (Rect::default(), 0)
+}
+
+/// Remote locations: `●` when mounted, `○` otherwise, with the address dimmed
+pub fn render_remotes_block(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) -> (Rect, usize) {
+    let detail = Style::default().fg(color_scheme.text_color()).add_modifier(Modifier::DIM);
+    let mut items: Vec<ListItem> = app_state.remotes.iter().map(|remote| {
+        let marker = if remote.connected { "●" } else { "○" };
+        ListItem::new(Line::from(vec![
+            Span::raw(format!("{} {}", marker, remote.name)),
+            Span::styled(format!("  {}", remote.url), detail),
+        ]))
+        .style(Style::default()
+            .fg(color_scheme.text_color())
+            .bg(color_scheme.background()))
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(Span::styled(Msg::RemotesEmpty.text(app_state.language), detail)));
+    }
+
+    let is_focused = app_state.focus == FocusBlock::Remotes;
+    let highlight_style = if is_focused && !app_state.remotes.is_empty() {
+        Style::default()
+            .bg(color_scheme.highlight_bg())
+            .fg(color_scheme.text_color())
+    } else {
+        Style::default()
+            .bg(color_scheme.background())
+            .fg(color_scheme.text_color())
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(Title::from(Span::styled(
+                    Msg::Remotes.text(app_state.language),
+                    Style::default().fg(color_scheme.text_color()).bg(color_scheme.background()),
+                )))
+                .borders(Borders::BOTTOM)
+                .style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background()))
+        )
+        .highlight_style(highlight_style);
+
+    let mut list_state = ListState::default();
+    list_state.select(Some(app_state.remotes_cursor));
+    frame.render_stateful_widget(list, area, &mut list_state);
+    (list_rows(area), list_state.offset())
+}
+
+/// Blocks of the left pane from top to bottom
+pub const PLACE_BLOCKS: [FocusBlock; 4] = [FocusBlock::Xdg, FocusBlock::Bookmarks, FocusBlock::Disks, FocusBlock::Remotes];
+
+/// Rows of a block with its title on top and a border at the bottom
+fn list_rows(area: Rect) -> Rect {
+    Rect { y: area.y + 1, height: area.height.saturating_sub(2), ..area }
+}
+
+/// Index of the entry of `block` shown on `row` of its list scrolled to
+/// `offset`. A disk takes a second row for its usage bar.
+#[cfg_attr(not(feature = "mounts"), allow(unused_variables))]
+pub fn place_at_row(app_state: &AppState, block: FocusBlock, offset: usize, row: usize) -> Option<usize> {
+    #[cfg(feature = "mounts")]
+    if block == FocusBlock::Disks {
+        let mut top = 0;
+        for (index, mount) in app_state.mounts.iter().enumerate().skip(offset) {
+            top += 1 + usize::from(app_state.disk_usage.get(&mount.dest).is_some());
+            if row < top {
+                return Some(index);
+            }
+        }
+        return None;
+    }
+    Some(offset + row)
 }
 
-/// Render the entire left pane with all its blocks
-pub fn render_left_pane(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
-    // Split the area into three parts for XDG, Bookmarks, and Mounts
+/// Render the entire left pane with all its blocks, returning the rows and
+/// scroll offset of each block's list in `PLACE_BLOCKS` order
+pub fn render_left_pane(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) -> [(Rect, usize); 4] {
+    // Split the area into four parts for XDG, Bookmarks, Mounts and Remotes
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(33), // XDG
-            Constraint::Percentage(33), // Bookmarks
-            Constraint::Percentage(34), // Mounts
+            Constraint::Percentage(25), // XDG
+            Constraint::Percentage(25), // Bookmarks
+            Constraint::Percentage(30), // Mounts
+            Constraint::Percentage(20), // Remotes
         ])
         .split(area);
     
-    render_xdg_block(frame, chunks[0], app_state, color_scheme);
-    render_bookmarks_block(frame, chunks[1], app_state, color_scheme);
-    render_mounts_block(frame, chunks[2], app_state, color_scheme);
+    [
+        render_xdg_block(frame, chunks[0], app_state, color_scheme),
+        render_bookmarks_block(frame, chunks[1], app_state, color_scheme),
+        render_mounts_block(frame, chunks[2], app_state, color_scheme),
+        render_remotes_block(frame, chunks[3], app_state, color_scheme),
+    ]
 }
\ No newline at end of file
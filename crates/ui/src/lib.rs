@@ -1,7 +1,14 @@
 pub mod tui;
+pub mod ansi;
 pub mod layout;
+pub mod hotkeys;
+pub use corvus_core::i18n;
 pub mod left_pane;
 pub mod top_bar;
+pub mod breadcrumb;
+pub mod status_bar;
 pub mod middle_pane;
 pub mod right_pane;
 pub mod settings;
+pub mod setup;
+pub mod text_width;
@@ -1,17 +1,29 @@
 use ratatui::{
-    prelude::{Rect, Style, Line},
+    prelude::{Color, Rect, Style, Line},
     text::Span,
     widgets::{List, ListItem, ListState},
     Frame,
 };
-use corvus_core::app_state::TabState;
-use corvus_core::settings::ColorScheme;
-use utils::icons::{get_color_for_file, get_icon_for_file, IconColor};
+use chrono::{DateTime, Datelike, Local};
+use corvus_core::app_state::{DirEntry, TabState};
+use corvus_core::settings::{format_size_with_config, rgb_color, ColorScheme, FileTypePalette, SizeUnits};
+use std::time::SystemTime;
+use crate::text_width::{display_width, pad_to_width, truncate_to_width};
+use utils::icons::{get_color_for_file, get_file_category, get_icon_for_file, IconColor, PIN_ICON, SYMLINK_ICON};
+
+/// Ширина колонки размера, например `1023.5 KiB`
+const SIZE_COLUMN_WIDTH: usize = 10;
+/// Ширина колонки даты, например `Oct 16 14:03`
+const DATE_COLUMN_WIDTH: usize = 12;
+/// Минимум места под имя, при котором колонки ещё показываются
+const MIN_NAME_WIDTH: usize = 16;
+/// Ширина полосы доли каталога в анализе занятого места
+const USAGE_BAR_WIDTH: usize = 10;
 
 fn to_ratatui_color(icon_color: IconColor) -> ratatui::prelude::Color {
     match icon_color {
         IconColor::Blue => ratatui::prelude::Color::Blue,
-        IconColor::Rgb(r, g, b) => ratatui::prelude::Color::Rgb(r, g, b),
+        IconColor::Rgb(r, g, b) => rgb_color((r, g, b)),
         IconColor::Magenta => ratatui::prelude::Color::Magenta,
         IconColor::Yellow => ratatui::prelude::Color::Yellow,
         IconColor::Cyan => ratatui::prelude::Color::Cyan,
@@ -21,7 +33,53 @@ fn to_ratatui_color(icon_color: IconColor) -> ratatui::prelude::Color {
     }
 }
 
-pub fn render_middle_pane(frame: &mut Frame, area: Rect, tab_state: &TabState, color_scheme: &ColorScheme) {
+fn format_size_column(entry: &DirEntry, size_units: SizeUnits) -> String {
+    if entry.is_dir || entry.is_broken_link {
+        "-".to_string()
+    } else {
+        format_size_with_config(entry.size, size_units)
+    }
+}
+
+/// Дата в духе `ls -l`: время для файлов текущего года, иначе год
+fn format_modified(modified: Option<SystemTime>) -> String {
+    let Some(modified) = modified else {
+        return "-".to_string();
+    };
+    let modified: DateTime<Local> = modified.into();
+    if modified.year() == Local::now().year() {
+        modified.format("%b %d %H:%M").to_string()
+    } else {
+        modified.format("%b %d  %Y").to_string()
+    }
+}
+
+/// Полоса вида `[####      ]`, доля `size` от `total`
+fn usage_bar(size: u64, total: u64) -> String {
+    let filled = if total == 0 {
+        0
+    } else {
+        ((size as f64 / total as f64) * USAGE_BAR_WIDTH as f64).round() as usize
+    };
+    let filled = filled.min(USAGE_BAR_WIDTH);
+    format!("[{}{}]", "#".repeat(filled), " ".repeat(USAGE_BAR_WIDTH - filled))
+}
+
+/// Рисует список записей и возвращает индекс первой видимой из них
+pub fn render_middle_pane(
+    frame: &mut Frame,
+    area: Rect,
+    tab_state: &TabState,
+    color_scheme: &ColorScheme,
+    palette: &FileTypePalette,
+    show_details: bool,
+    size_units: SizeUnits,
+) -> usize {
+    // Колонки показываются, только если рядом остаётся место для имени
+    let details_width = SIZE_COLUMN_WIDTH + DATE_COLUMN_WIDTH + 2;
+    let show_details = show_details && area.width as usize >= details_width + MIN_NAME_WIDTH;
+    // В анализе занятого места перед именем стоят размер и доля от каталога
+    let usage_total = tab_state.usage_node().map(|node| node.size);
     let items: Vec<ListItem> = tab_state
         .filtered_entries
         .iter()
@@ -43,8 +101,17 @@ pub fn render_middle_pane(frame: &mut Frame, area: Rect, tab_state: &TabState, c
                 style = style.bg(color_scheme.background());
             }
 
-            let icon = get_icon_for_file(&entry.name, entry.is_dir);
-            let color = to_ratatui_color(get_color_for_file(&entry.name, entry.is_dir));
+            let category = get_file_category(&entry.name, entry.is_dir, entry.is_executable, entry.is_symlink);
+            let (icon, color) = if entry.is_broken_link {
+                (SYMLINK_ICON, Color::Red)
+            } else if entry.is_symlink {
+                (SYMLINK_ICON, palette.icon_color(category).unwrap_or(Color::Cyan))
+            } else {
+                (
+                    get_icon_for_file(&entry.name, entry.is_dir),
+                    to_ratatui_color(get_color_for_file(&entry.name, entry.is_dir)),
+                )
+            };
             let icon_span = Span::styled(icon, Style::default().fg(color));
 
             let mut name = entry.name.clone();
@@ -53,9 +120,57 @@ pub fn render_middle_pane(frame: &mut Frame, area: Rect, tab_state: &TabState, c
             }
             
             let selection_indicator = if is_selected { "* " } else { "  " };
-            let name_span = Span::raw(format!("{}{}", selection_indicator, name));
+            // Dotfiles keep the dimmed style of the whole row
+            let name_style = if entry.is_broken_link {
+                Style::default().fg(Color::Red)
+            } else {
+                match palette.name_color(category) {
+                    Some(name_color) if !entry.name.starts_with('.') => Style::default().fg(name_color),
+                    _ => Style::default(),
+                }
+            };
+            let mut label = format!("{}{}", selection_indicator, name);
+            if entry.is_dir && tab_state.pinned.contains(&entry.path) {
+                label.push(' ');
+                label.push_str(PIN_ICON);
+            }
+            if let Some(target) = &entry.target {
+                label.push_str(&format!(" -> {}", target.display()));
+            }
 
-            let line = Line::from(vec![icon_span, name_span]);
+            let mut spans = Vec::new();
+            if let Some(total) = usage_total {
+                spans.push(Span::styled(
+                    format!(
+                        "{:>size_w$} {} ",
+                        format_size_with_config(entry.size, size_units),
+                        usage_bar(entry.size, total),
+                        size_w = SIZE_COLUMN_WIDTH
+                    ),
+                    Style::default().fg(color_scheme.accent()),
+                ));
+            }
+            spans.push(icon_span);
+            // Ширина считается в колонках терминала, а не в байтах или символах
+            let prefix_width: usize = spans.iter().map(|span| display_width(&span.content)).sum();
+            if show_details && usage_total.is_none() {
+                let name_width = (area.width as usize).saturating_sub(details_width + prefix_width);
+                spans.push(Span::styled(pad_to_width(&truncate_to_width(&label, name_width), name_width), name_style));
+                spans.push(Span::styled(
+                    format!(
+                        " {:>size_w$} {:>date_w$}",
+                        format_size_column(entry, size_units),
+                        format_modified(entry.modified),
+                        size_w = SIZE_COLUMN_WIDTH,
+                        date_w = DATE_COLUMN_WIDTH
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            } else {
+                let name_width = (area.width as usize).saturating_sub(prefix_width);
+                spans.push(Span::styled(truncate_to_width(&label, name_width), name_style));
+            }
+            let line = Line::from(spans);
             ListItem::new(line).style(style)
         })
         .collect();
@@ -70,4 +185,5 @@ pub fn render_middle_pane(frame: &mut Frame, area: Rect, tab_state: &TabState, c
     list_state.select(Some(tab_state.cursor));
 
     frame.render_stateful_widget(list, area, &mut list_state);
+    list_state.offset()
 }
\ No newline at end of file
@@ -1,17 +1,23 @@
 use ratatui::{
-    prelude::{Line, Rect, Style},
-    text::Span,
+    layout::{Constraint, Layout},
+    prelude::{Color, Line, Modifier, Rect, Style},
+    text::{Span, Text},
     widgets::{Block, Borders, List, ListItem, Paragraph},
     Frame,
 };
-use corvus_core::app_state::{AppState, PreviewContent, RightPaneView};
-use corvus_core::settings::ColorScheme;
-use utils::icons::{get_color_for_file, get_icon_for_file, IconColor};
+use corvus_core::app_state::{AppState, PreviewContent, RightPaneView, TerminalLifecycle, TerminalState};
+use corvus_core::settings::{format_size_with_config, rgb_color, ColorScheme, SizeUnits};
+use corvus_core::command_preview::{CommandStatus, TIMEOUT};
+use corvus_core::plugin::PreviewResult;
+use unicode_width::UnicodeWidthStr;
+use crate::i18n::{Language, Msg};
+use crate::text_width::{display_width, truncate_to_width};
+use utils::icons::{get_color_for_file, get_icon_for_file, IconColor, SYMLINK_ICON};
 
 fn to_ratatui_color(icon_color: IconColor) -> ratatui::prelude::Color {
     match icon_color {
         IconColor::Blue => ratatui::prelude::Color::Blue,
-        IconColor::Rgb(r, g, b) => ratatui::prelude::Color::Rgb(r, g, b),
+        IconColor::Rgb(r, g, b) => rgb_color((r, g, b)),
         IconColor::Magenta => ratatui::prelude::Color::Magenta,
         IconColor::Yellow => ratatui::prelude::Color::Yellow,
         IconColor::Cyan => ratatui::prelude::Color::Cyan,
@@ -26,7 +32,7 @@ pub fn render_right_pane(frame: &mut Frame, area: Rect, app_state: &AppState, co
 
     match active_tab.right_pane_view {
         RightPaneView::Preview => {
-            render_preview_pane(frame, area, active_tab, color_scheme);
+            render_preview_pane(frame, area, active_tab, color_scheme, app_state.language, app_state.size_units);
         }
         RightPaneView::Terminal => {
             render_terminal_pane(frame, area, app_state, color_scheme);
@@ -35,26 +41,131 @@ pub fn render_right_pane(frame: &mut Frame, area: Rect, app_state: &AppState, co
 }
 
 fn render_terminal_pane(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+    // Положение в прокрутке и режим копирования видны в заголовке
+    let terminal_state = &app_state.get_active_tab().terminal;
+    let language = app_state.language;
+    let mut title = Msg::Terminal.text(language).to_string();
+    let offset = terminal_state.scroll_offset();
+    let copy = Msg::TerminalCopy.text(language);
+    match (terminal_state.copy_mode.is_some(), offset) {
+        (true, 0) => title.push_str(&format!(" [{}]", copy)),
+        (true, offset) => title.push_str(&format!(" [{}, ↑{}]", copy, offset)),
+        (false, 0) => {}
+        (false, offset) => title.push_str(&format!(" [↑{}]", offset)),
+    }
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Terminal")
+        .title(title)
         .style(Style::default()
             .fg(color_scheme.text_color())
             .bg(color_scheme.background()));
     let inner_area = block.inner(area);
     frame.render_widget(block.clone(), area);
 
-    if let Some(terminal_state) = &app_state.terminal {
-        let lines: Vec<Line> = terminal_state.lines.iter().map(|s| Line::from(s.as_str())).collect();
-        let paragraph = Paragraph::new(lines);
-        frame.render_widget(paragraph, inner_area);
+    terminal_state.drawn_size.set(Some((inner_area.height, inner_area.width)));
+    render_screen(frame, inner_area, terminal_state, color_scheme);
+
+    // Подсказка о перезапуске занимает нижнюю строку поверх экрана оболочки
+    let hint = match terminal_state.lifecycle {
+        TerminalLifecycle::Running => return,
+        TerminalLifecycle::NotStarted => Msg::ShellNotStarted.text(language),
+        TerminalLifecycle::Exited => Msg::ShellExited.text(language),
+    };
+    if inner_area.height > 0 {
+        let hint_area = Rect { y: inner_area.bottom() - 1, height: 1, ..inner_area };
+        let style = Style::default().fg(color_scheme.accent()).bg(color_scheme.highlight_bg());
+        frame.render_widget(Paragraph::new(hint).style(style), hint_area);
     }
 }
 
-fn render_preview_pane(frame: &mut Frame, area: Rect, tab_state: &corvus_core::app_state::TabState, color_scheme: &ColorScheme) {
+/// Переносит сетку экрана эмулятора в ячейки кадра вместе с цветами и
+/// атрибутами. Курсор показывается инверсией, пока не листается история;
+/// в режиме копирования вместо него видны его курсор и выделение.
+fn render_screen(frame: &mut Frame, area: Rect, terminal_state: &TerminalState, color_scheme: &ColorScheme) {
+    let screen = terminal_state.parser.screen();
+    let (rows, cols) = screen.size();
+    let offset = screen.scrollback() as isize;
+    let (cursor, show_cursor) = match terminal_state.copy_mode {
+        Some(mode) => ((mode.cursor.0 + offset, mode.cursor.1), true),
+        None => {
+            let (row, col) = screen.cursor_position();
+            ((row as isize, col), !screen.hide_cursor() && offset == 0)
+        }
+    };
+    let selection = terminal_state.copy_mode.filter(|mode| mode.anchor.is_some()).map(|mode| mode.selection());
+    let buffer = frame.buffer_mut();
+    for row in 0..rows.min(area.height) {
+        for col in 0..cols.min(area.width) {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+            if cell.is_wide_continuation() {
+                continue;
+            }
+            let mut style = Style::default()
+                .fg(vt_color(cell.fgcolor()).unwrap_or(color_scheme.text_color()))
+                .bg(vt_color(cell.bgcolor()).unwrap_or(color_scheme.background()));
+            if cell.bold() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if cell.italic() {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
+            if cell.underline() {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            if cell.inverse() != (show_cursor && (row as isize, col) == cursor) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            let position = (row as isize - offset, col);
+            if selection.is_some_and(|(start, end)| start <= position && position <= end) {
+                style = style.bg(color_scheme.highlight_bg());
+            }
+            let contents = cell.contents();
+            buffer
+                .get_mut(area.x + col, area.y + row)
+                .set_symbol(if contents.is_empty() { " " } else { &contents })
+                .set_style(style);
+        }
+    }
+}
+
+/// Цвет ячейки терминала; `None` — цвет по умолчанию из схемы
+fn vt_color(color: vt100::Color) -> Option<Color> {
+    match color {
+        vt100::Color::Default => None,
+        vt100::Color::Idx(index) => Some(Color::Indexed(index)),
+        vt100::Color::Rgb(r, g, b) => Some(rgb_color((r, g, b))),
+    }
+}
+
+fn render_preview_pane(
+    frame: &mut Frame,
+    area: Rect,
+    tab_state: &corvus_core::app_state::TabState,
+    color_scheme: &ColorScheme,
+    language: Language,
+    size_units: SizeUnits,
+) {
+    let title = match &tab_state.preview_content {
+        Some(PreviewContent::CommandOutput { command, status, .. }) => {
+            let status = match status {
+                CommandStatus::Running => Msg::CommandRunning.text(language).to_string(),
+                CommandStatus::Exited(code) => Msg::CommandExited.fill(language, &[code]),
+                CommandStatus::Signaled => Msg::CommandSignaled.text(language).to_string(),
+                CommandStatus::TimedOut => Msg::CommandTimedOut.fill(language, &[&TIMEOUT.as_secs()]),
+                CommandStatus::Cancelled => Msg::CommandCancelled.text(language).to_string(),
+                CommandStatus::Failed => Msg::CommandFailed.text(language).to_string(),
+            };
+            Msg::CommandOutputTitle.fill(language, &[command, &status])
+        }
+        Some(PreviewContent::Plugin { plugin, .. }) => Msg::PluginPreviewTitle.fill(language, &[plugin]),
+        Some(PreviewContent::File { tail: true, .. }) => Msg::PreviewTail.text(language).to_string(),
+        _ => Msg::Preview.text(language).to_string(),
+    };
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Preview")
+        .title(title)
         .style(Style::default()
             .fg(color_scheme.text_color())
             .bg(color_scheme.background()));
@@ -63,21 +174,64 @@ fn render_preview_pane(frame: &mut Frame, area: Rect, tab_state: &corvus_core::a
 
     match &tab_state.preview_content {
         Some(content) => match content {
-            PreviewContent::Directory(entries) => {
+            PreviewContent::Directory { entries, total, size, .. } => {
+                let mut header = Msg::DirItems.fill(language, &[total]);
+                if tab_state.preview.directory_sizes {
+                    match size {
+                        Some(size) => header.push_str(&format!(", {}", format_size_with_config(*size, size_units))),
+                        None => header.push_str(Msg::DirSizing.text(language)),
+                    }
+                }
+                if *total > entries.len() {
+                    header.push_str(&Msg::DirFirstShown.fill(language, &[&entries.len()]));
+                }
+                let [header_area, list_area] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner_area);
+                frame.render_widget(
+                    Paragraph::new(header).style(Style::default()
+                        .fg(color_scheme.text_color())
+                        .bg(color_scheme.background())
+                        .add_modifier(Modifier::DIM)),
+                    header_area,
+                );
+
                 let items: Vec<ListItem> = entries
                     .iter()
                     .map(|entry| {
-                        let icon = get_icon_for_file(&entry.name, entry.is_dir);
-                        let color = to_ratatui_color(get_color_for_file(&entry.name, entry.is_dir));
+                        let (icon, color) = if entry.is_broken_link {
+                            (SYMLINK_ICON, Color::Red)
+                        } else if entry.is_symlink {
+                            (SYMLINK_ICON, Color::Cyan)
+                        } else {
+                            (
+                                get_icon_for_file(&entry.name, entry.is_dir),
+                                to_ratatui_color(get_color_for_file(&entry.name, entry.is_dir)),
+                            )
+                        };
                         let icon_span = Span::styled(icon, Style::default().fg(color));
 
                         let mut name = entry.name.clone();
                         if entry.is_dir {
                             name.push('/');
                         }
-                        let name_span = Span::raw(format!(" {}", name));
+                        let name_style = if entry.is_broken_link {
+                            Style::default().fg(Color::Red)
+                        } else {
+                            Style::default()
+                        };
+                        let mut label = format!(" {}", name);
+                        if let Some(target) = &entry.target {
+                            label.push_str(&format!(" -> {}", target.display()));
+                        }
+                        let count = entry.child_count.map(|count| format!(" ({})", count));
+                        // Число записей остаётся видно, обрезается имя
+                        let room = (list_area.width as usize)
+                            .saturating_sub(display_width(icon) + count.as_deref().map_or(0, display_width));
+                        let mut spans = vec![icon_span, Span::styled(truncate_to_width(&label, room), name_style)];
+                        if let Some(count) = count {
+                            spans.push(Span::styled(count, Style::default().add_modifier(Modifier::DIM)));
+                        }
 
-                        let line = Line::from(vec![icon_span, name_span]);
+                        let line = Line::from(spans);
                         ListItem::new(line)
                             .style(Style::default()
                                 .fg(color_scheme.text_color())
@@ -90,18 +244,37 @@ fn render_preview_pane(frame: &mut Frame, area: Rect, tab_state: &corvus_core::a
                         .bg(color_scheme.highlight_bg())
                         .fg(color_scheme.text_color())
                 );
-                frame.render_widget(list, inner_area);
+                frame.render_widget(list, list_area);
             }
-            PreviewContent::File(text) => {
-                let paragraph = Paragraph::new(text.as_str())
+            PreviewContent::File { text, truncated, tail } => {
+                let marker = Line::styled(
+                    Msg::PreviewTruncated.text(language),
+                    Style::default().fg(color_scheme.text_color()).add_modifier(Modifier::DIM),
+                );
+                // У конца файла пропущенные строки — выше показанных
+                let text = match (*truncated, *tail) {
+                    (true, true) => {
+                        let mut lines = vec![marker];
+                        lines.extend(Text::from(text.as_str()).lines);
+                        Text::from(lines)
+                    }
+                    (true, false) => {
+                        let mut text = Text::from(text.as_str());
+                        text.push_line(marker);
+                        text
+                    }
+                    (false, _) => Text::from(text.as_str()),
+                };
+                let paragraph = Paragraph::new(text)
                     .style(Style::default()
                         .fg(color_scheme.text_color())
                         .bg(color_scheme.background()))
                     .scroll(tab_state.preview_scroll);
                 frame.render_widget(paragraph, inner_area);
             }
-            PreviewContent::TooLarge(size) => {
-                let text = format!("File is too large to preview ({} > 1MB)", size);
+            PreviewContent::TooLarge { size, limit } => {
+                let size = format!("{} > {}", format_size_with_config(*size, size_units), format_size_with_config(*limit, size_units));
+                let text = Msg::FileTooLarge.fill(language, &[&size]);
                 let paragraph = Paragraph::new(text)
                     .style(Style::default()
                         .fg(color_scheme.text_color())
@@ -111,24 +284,76 @@ fn render_preview_pane(frame: &mut Frame, area: Rect, tab_state: &corvus_core::a
             PreviewContent::Error(error) => {
                 let paragraph = Paragraph::new(error.as_str())
                     .style(Style::default()
-                        .fg(ratatui::prelude::Color::Red)
+                        .fg(color_scheme.error_color())
                         .bg(color_scheme.background()));
                 frame.render_widget(paragraph, inner_area);
             }
+            PreviewContent::CommandOutput { output, status, .. } => {
+                let color = if *status == CommandStatus::Failed { color_scheme.error_color() } else { color_scheme.text_color() };
+                let paragraph = Paragraph::new(output.as_str())
+                    .style(Style::default()
+                        .fg(color)
+                        .bg(color_scheme.background()))
+                    .scroll(tab_state.preview_scroll);
+                frame.render_widget(paragraph, inner_area);
+            }
+            PreviewContent::Plugin { result, .. } => {
+                let text_style = Style::default().fg(color_scheme.text_color()).bg(color_scheme.background());
+                let (lines, style) = match result {
+                    PreviewResult::Text(text) => (text.lines().map(|line| Line::from(line.to_string())).collect(), text_style),
+                    PreviewResult::AnsiText(text) => (crate::ansi::ansi_lines(text), text_style),
+                    PreviewResult::Table(rows) => (table_lines(rows, color_scheme), text_style),
+                    // Показа изображений пока нет, поэтому виден только путь
+                    PreviewResult::ImagePath(path) => {
+                        (vec![Line::from(Msg::PluginImage.fill(language, &[&path.display()]))], text_style)
+                    }
+                    PreviewResult::Error(error) => {
+                        (vec![Line::from(error.clone())], text_style.fg(color_scheme.error_color()))
+                    }
+                };
+                let paragraph = Paragraph::new(lines).style(style).scroll(tab_state.preview_scroll);
+                frame.render_widget(paragraph, inner_area);
+            }
             PreviewContent::Binary => {
-                let paragraph = Paragraph::new("[Binary File]")
+                let paragraph = Paragraph::new(Msg::BinaryFile.text(language))
                     .style(Style::default()
                         .fg(color_scheme.text_color())
                         .bg(color_scheme.background()));
                 frame.render_widget(paragraph, inner_area);
             }
+            PreviewContent::Loading => {
+                let paragraph = Paragraph::new(Msg::Loading.text(language))
+                    .style(Style::default()
+                        .fg(Color::DarkGray)
+                        .bg(color_scheme.background()));
+                frame.render_widget(paragraph, inner_area);
+            }
         },
         None => {
-            let paragraph = Paragraph::new("No item selected")
+            let paragraph = Paragraph::new(Msg::NoItemSelected.text(language))
                 .style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background()));
             frame.render_widget(paragraph, inner_area);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Строки таблицы «ключ — значение» с выровненными ключами. Значение из
+/// нескольких строк продолжается под первой.
+fn table_lines(rows: &[(String, String)], color_scheme: &ColorScheme) -> Vec<Line<'static>> {
+    const MAX_KEY_WIDTH: usize = 24;
+    let key_width = rows.iter().map(|(key, _)| key.width()).max().unwrap_or(0).min(MAX_KEY_WIDTH);
+    let key_style = Style::default().fg(color_scheme.accent()).add_modifier(Modifier::BOLD);
+    let mut lines = Vec::new();
+    for (key, value) in rows {
+        let padding = " ".repeat(key_width.saturating_sub(key.width()) + 2);
+        let mut values = value.lines();
+        let first = values.next().unwrap_or_default().to_string();
+        lines.push(Line::from(vec![Span::styled(key.clone(), key_style), Span::raw(padding), Span::raw(first)]));
+        for value in values {
+            lines.push(Line::from(format!("{}{}", " ".repeat(key_width + 2), value)));
+        }
+    }
+    lines
+}
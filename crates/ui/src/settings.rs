@@ -4,7 +4,10 @@ use ratatui::{
     Frame,
 };
 use corvus_core::app_state::AppState;
-use corvus_core::settings::ColorScheme;
+use corvus_core::plugin::PluginState;
+use corvus_core::settings::{format_size_with_config, ColorScheme, SizeUnits, SortField, SortOrder};
+use crate::hotkeys;
+use crate::i18n::{Language, Msg};
 
 /// Enum для отслеживания текущего режима навигации в настройках
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -46,17 +49,17 @@ impl SettingsState {
         }
     }
     
-    pub fn categories() -> Vec<&'static str> {
-        vec![
-            "Интерфейс",
-            "Цветовые схемы",
-            "Сортировка",
-            "Предпросмотр",
-            "Поведение",
-            "Закладки",
-            "Поиск",
-            "Горячие клавиши",
-            "Плагины",
+    pub fn categories() -> &'static [Msg] {
+        &[
+            Msg::CategoryInterface,
+            Msg::CategoryColorSchemes,
+            Msg::CategorySorting,
+            Msg::CategoryPreview,
+            Msg::CategoryBehavior,
+            Msg::CategoryBookmarks,
+            Msg::CategorySearch,
+            Msg::CategoryHotkeys,
+            Msg::CategoryPlugins,
         ]
     }
     
@@ -105,13 +108,13 @@ impl SettingsState {
     /// Получить количество элементов в текущей категории
     fn get_current_category_item_count(&self, app_state: &AppState) -> usize {
         match self.selected_category {
-            0 => 5, // Интерфейс
+            0 => 9, // Интерфейс
             1 => ColorScheme::all().len(), // Цветовые схемы
-            2 => 3, // Сортировка
-            3 => 3, // Предпросмотр
-            4 => 3, // Поведение
+            2 => 4, // Сортировка
+            3 => 6, // Предпросмотр
+            4 => 5, // Поведение
             5 => 1, // Закладки
-            6 => 2, // Поиск
+            6 => 4, // Поиск
             7 => 1, // Горячие клавиши
             8 => app_state.plugins.len().max(1), // Плагины
             _ => 0,
@@ -126,17 +129,37 @@ impl SettingsState {
         };
     }
     
-    /// Обработать выбор элемента
+    /// Обработать выбор элемента. Изменённые настройки сразу сохраняются в config.toml
     pub fn select_item(&mut self, app_state: &mut AppState) {
         match self.selected_category {
             0 => {
                 // Интерфейс
                 match self.selected_item {
+                    1 => {
+                        // Отображать вкладки
+                        app_state.toggle_tabs();
+                    }
                     3 => {
                         // Показывать скрытые файлы
                         app_state.toggle_hidden_files();
                     }
-                    _ => {}
+                    4 => {
+                        // Показывать размер и дату
+                        app_state.toggle_file_details();
+                    }
+                    6 => {
+                        // Подсказки клавиш
+                        app_state.toggle_key_hints();
+                    }
+                    7 => {
+                        // Язык
+                        app_state.cycle_language();
+                    }
+                    8 => {
+                        // Единицы размеров
+                        app_state.cycle_size_units();
+                    }
+                    _ => return,
                 }
             }
             1 => {
@@ -144,18 +167,99 @@ impl SettingsState {
                 let all_schemes = ColorScheme::all();
                 if self.selected_item < all_schemes.len() {
                     let selected_scheme = all_schemes[self.selected_item].clone();
-                    // Обновляем цветовую схему в конфигурации приложения
+                    // Обновляем цветовую схему в конфигурации и сохраняем её
                     app_state.set_color_scheme(selected_scheme);
                 }
+                return;
+            }
+            2 => {
+                // Сортировка
+                match self.selected_item {
+                    0 => {
+                        // Поле сортировки
+                        app_state.cycle_sort_field();
+                    }
+                    1 => {
+                        // Порядок
+                        app_state.reverse_sort_order();
+                    }
+                    2 => {
+                        // Разделение файлов и каталогов
+                        app_state.toggle_separate_dirs();
+                    }
+                    3 => {
+                        // Естественная сортировка
+                        app_state.toggle_natural_sort();
+                    }
+                    _ => return,
+                }
+            }
+            3 => {
+                // Предпросмотр
+                match self.selected_item {
+                    0 => {
+                        // Включить предпросмотр
+                        app_state.toggle_preview();
+                    }
+                    1 => {
+                        // Максимальный размер файла
+                        app_state.cycle_preview_size_limit();
+                    }
+                    2 => {
+                        // Размер каталогов
+                        app_state.toggle_directory_sizes();
+                    }
+                    4 => {
+                        // Строк в предпросмотре текста
+                        app_state.cycle_preview_line_count();
+                    }
+                    5 => {
+                        // Конец файлов .log
+                        app_state.toggle_tail_logs();
+                    }
+                    _ => return,
+                }
+            }
+            4 => {
+                // Поведение
+                match self.selected_item {
+                    0 => {
+                        // Подтверждение удаления
+                        app_state.toggle_confirm_delete();
+                    }
+                    1 => {
+                        // Подтверждение перезаписи
+                        app_state.toggle_confirm_overwrite();
+                    }
+                    3 => {
+                        // Формат архива по умолчанию
+                        app_state.cycle_default_archive_format();
+                    }
+                    4 => {
+                        // Степень сжатия по умолчанию
+                        app_state.cycle_default_compression_level();
+                    }
+                    _ => return,
+                }
+            }
+            6 => {
+                // Поиск
+                match self.selected_item {
+                    1 => {
+                        // Учёт регистра
+                        app_state.toggle_search_case_sensitive();
+                    }
+                    _ => return,
+                }
             }
             8 => {
                 // Плагины
-                if let Some(plugin) = app_state.plugins.get_mut(self.selected_item) {
-                    plugin.enabled = !plugin.enabled;
-                }
+                app_state.toggle_plugin(self.selected_item);
+                return;
             }
-            _ => {}
+            _ => return,
         }
+        app_state.save_settings();
     }
 }
 
@@ -174,15 +278,20 @@ pub fn render_settings_screen(frame: &mut Frame, area: Rect, app_state: &mut App
     
     // Если показываем список горячих клавиш, отображаем его
     if settings_state.show_hotkeys_list {
-        render_hotkeys_list(frame, area, &app_state.get_current_color_scheme());
+        render_hotkeys_list(frame, area, &app_state.get_current_color_scheme(), app_state.language, 0);
+        return;
+    }
+    if app_state.plugin_log_view.is_some() {
+        render_plugin_log(frame, area, app_state, &app_state.get_current_color_scheme());
         return;
     }
     
     // Получаем текущую цветовую схему
     let current_scheme = app_state.get_current_color_scheme();
+    let language = app_state.language;
     
     let block = Block::default()
-        .title("Настройки приложения (↑/↓ - навигация, Tab - переключение, Enter - выбор, Esc - выход)")
+        .title(Msg::SettingsTitle.text(language))
         .borders(Borders::ALL)
         .style(Style::default()
             .fg(current_scheme.text_color())
@@ -203,17 +312,45 @@ pub fn render_settings_screen(frame: &mut Frame, area: Rect, app_state: &mut App
     let details_area = chunks[1];
     
     // Рендерим категории
-    render_categories(frame, categories_area, settings_state, &current_scheme);
+    render_categories(frame, categories_area, settings_state, &current_scheme, language);
     
     // Рендерим детали выбранной категории
     render_category_details(frame, details_area, settings_state, app_state, &current_scheme);
 }
 
-fn render_categories(frame: &mut Frame, area: Rect, settings_state: &mut SettingsState, color_scheme: &ColorScheme) {
+fn index_roots_label(app_state: &AppState) -> String {
+    let roots = app_state.file_index.roots();
+    if roots.is_empty() {
+        return Msg::IndexRootsUnset.text(app_state.language).to_string();
+    }
+    roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+fn index_status_label(app_state: &AppState) -> String {
+    let index = &app_state.file_index;
+    let language = app_state.language;
+    if index.roots().is_empty() {
+        return Msg::IndexOff.text(language).to_string();
+    }
+    let refreshed = match index.last_refresh {
+        Some(time) => {
+            let time: chrono::DateTime<chrono::Local> = time.into();
+            Msg::IndexFiles.fill(language, &[&index.len(), &time.format("%H:%M:%S")])
+        }
+        None => Msg::IndexNotBuilt.text(language).to_string(),
+    };
+    if index.building {
+        Msg::IndexRefreshing.fill(language, &[&refreshed])
+    } else {
+        refreshed
+    }
+}
+
+fn render_categories(frame: &mut Frame, area: Rect, settings_state: &mut SettingsState, color_scheme: &ColorScheme, language: Language) {
     let categories = SettingsState::categories();
     let items: Vec<ListItem> = categories
         .iter()
-        .map(|&category| ListItem::new(category)
+        .map(|category| ListItem::new(category.text(language))
             .style(Style::default()
                 .fg(color_scheme.text_color())
                 .bg(color_scheme.background())))
@@ -221,7 +358,7 @@ fn render_categories(frame: &mut Frame, area: Rect, settings_state: &mut Setting
     
     let list = List::new(items)
         .block(Block::default()
-            .title("Категории")
+            .title(Msg::Categories.text(language))
             .borders(Borders::ALL)
             .style(Style::default()
                 .fg(color_scheme.text_color())
@@ -234,40 +371,70 @@ fn render_categories(frame: &mut Frame, area: Rect, settings_state: &mut Setting
 }
 
 fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut SettingsState, app_state: &AppState, color_scheme: &ColorScheme) {
+    let language = app_state.language;
     let items: Vec<ListItem> = match settings_state.selected_category {
         0 => {
             // Интерфейс
             vec![
                 ListItem::new(format!(
-                    "[{}] Отображать левую панель",
-                    " " // Пока не реализовано
+                    "[{}] {}",
+                    " ", // Пока не реализовано
+                    Msg::ShowLeftPane.text(language)
+                )).style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background())),
+                ListItem::new(format!(
+                    "[{}] {}",
+                    if app_state.show_tabs { "•" } else { " " },
+                    Msg::ShowTabs.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "[{}] Отображать вкладки",
-                    " " // Пока не реализовано
+                    "[{}] {}",
+                    " ", // Пока не реализовано
+                    Msg::ShowFooter.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "[{}] Отображать нижнюю панель",
-                    " " // Пока не реализовано
+                    "[{}] {}",
+                    if app_state.show_hidden_files { "•" } else { " " },
+                    Msg::ShowHiddenFiles.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "[{}] Показывать скрытые файлы",
-                    if app_state.show_hidden_files { "•" } else { " " }
+                    "[{}] {}",
+                    if app_state.show_file_details { "•" } else { " " },
+                    Msg::ShowFileDetails.text(language)
+                )).style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background())),
+                ListItem::new(Msg::ColorSchemeValue.fill(
+                    language,
+                    &[&app_state.config.theme.color_scheme.as_deref().unwrap_or("Dracula")],
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "Цветовая схема: {}",
-                    app_state.config.theme.color_scheme.as_deref().unwrap_or("Dracula")
+                    "[{}] {}",
+                    if app_state.show_key_hints { "•" } else { " " },
+                    Msg::ShowKeyHints.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
+                ListItem::new(Msg::LanguageValue.fill(language, &[&language.native_name()]))
+                    .style(Style::default()
+                        .fg(color_scheme.text_color())
+                        .bg(color_scheme.background())),
+                ListItem::new(Msg::SizeUnitsValue.fill(language, &[&match app_state.size_units {
+                    SizeUnits::Binary => "KiB, MiB (1024)",
+                    SizeUnits::Decimal => "kB, MB (1000)",
+                }]))
+                    .style(Style::default()
+                        .fg(color_scheme.text_color())
+                        .bg(color_scheme.background())),
             ]
         }
         1 => {
@@ -292,21 +459,35 @@ fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut S
         2 => {
             // Сортировка
             vec![
-                ListItem::new(format!(
-                    "Поле сортировки: {}",
-                    "По имени" // Пока не реализовано
+                ListItem::new(Msg::SortFieldValue.fill(
+                    language,
+                    &[&match app_state.sort_settings.field {
+                        SortField::Name => Msg::SortByName,
+                        SortField::Size => Msg::SortBySize,
+                        SortField::Modified => Msg::SortByDate,
+                    }.text(language)],
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
-                ListItem::new(format!(
-                    "Порядок: {}",
-                    "По возрастанию" // Пока не реализовано
+                ListItem::new(Msg::SortOrderValue.fill(
+                    language,
+                    &[&match app_state.sort_settings.order {
+                        SortOrder::Ascending => Msg::Ascending,
+                        SortOrder::Descending => Msg::Descending,
+                    }.text(language)],
+                )).style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background())),
+                ListItem::new(Msg::SeparateDirsValue.fill(
+                    language,
+                    &[&if app_state.sort_settings.separate_dirs { Msg::Yes } else { Msg::No }.text(language)],
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "Разделение файлов и каталогов: {}",
-                    "Да" // Пока не реализовано
+                    "[{}] {}",
+                    if app_state.sort_settings.natural { "•" } else { " " },
+                    Msg::NaturalSort.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
@@ -316,20 +497,42 @@ fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut S
             // Предпросмотр
             vec![
                 ListItem::new(format!(
-                    "[{}] Включить предпросмотр",
-                    "•" // Пока не реализовано
+                    "[{}] {}",
+                    if app_state.preview_settings.enabled { "•" } else { " " },
+                    Msg::EnablePreview.text(language)
+                )).style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background())),
+                ListItem::new(Msg::MaxPreviewSizeValue.fill(
+                    language,
+                    &[&format_size_with_config(app_state.preview_settings.max_preview_size, app_state.size_units)],
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "Максимальный размер файла: {} байт",
-                    1024 * 1024 // Пока не реализовано
+                    "[{}] {}",
+                    if app_state.preview_settings.directory_sizes { "•" } else { " " },
+                    Msg::DirectorySizes.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
+                ListItem::new(Msg::ImageResolutionValue.fill(
+                    language,
+                    &[
+                        &app_state.preview_settings.image_preview_resolution.0,
+                        &app_state.preview_settings.image_preview_resolution.1,
+                    ],
+                )).style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background())),
+                ListItem::new(Msg::PreviewLinesValue.fill(language, &[&app_state.preview_settings.max_lines]))
+                    .style(Style::default()
+                        .fg(color_scheme.text_color())
+                        .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "Разрешение изображений: {}x{}",
-                    800, 600 // Пока не реализовано
+                    "[{}] {}",
+                    if app_state.preview_settings.tail_logs { "•" } else { " " },
+                    Msg::TailLogs.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
@@ -339,29 +542,38 @@ fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut S
             // Поведение
             vec![
                 ListItem::new(format!(
-                    "[{}] Подтверждение удаления файлов",
-                    "•" // Пока не реализовано
+                    "[{}] {}",
+                    if app_state.behavior_settings.confirm_delete { "•" } else { " " },
+                    Msg::ConfirmDelete.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "[{}] Подтверждение перезаписи",
-                    "•" // Пока не реализовано
+                    "[{}] {}",
+                    if app_state.behavior_settings.confirm_overwrite { "•" } else { " " },
+                    Msg::ConfirmOverwrite.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
                 ListItem::new(format!(
-                    "[{}] Автоматическое обновление",
-                    "•" // Пока не реализовано
+                    "[{}] {}",
+                    "•", // Пока не реализовано
+                    Msg::AutoRefresh.text(language)
                 )).style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background())),
+                ListItem::new(Msg::DefaultArchiveFormatValue.fill(language, &[&app_state.archive_settings.default_format])).style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background())),
+                ListItem::new(Msg::DefaultCompressionValue.fill(language, &[&app_state.archive_settings.compression_level])).style(Style::default()
+                    .fg(color_scheme.text_color())
+                    .bg(color_scheme.background())),
             ]
         }
         5 => {
             // Закладки
             vec![
-                ListItem::new("Управление закладками...")
+                ListItem::new(Msg::ManageBookmarks.text(language))
                     .style(Style::default()
                         .fg(color_scheme.text_color())
                         .bg(color_scheme.background())),
@@ -370,11 +582,23 @@ fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut S
         6 => {
             // Поиск
             vec![
-                ListItem::new("Поиск по содержимому файлов по умолчанию")
+                ListItem::new(Msg::ContentSearchDefault.text(language))
+                    .style(Style::default()
+                        .fg(color_scheme.text_color())
+                        .bg(color_scheme.background())),
+                ListItem::new(format!(
+                    "[{}] {}",
+                    if app_state.config.search.case_sensitive { "•" } else { " " },
+                    Msg::CaseSensitivity.text(language)
+                ))
                     .style(Style::default()
                         .fg(color_scheme.text_color())
                         .bg(color_scheme.background())),
-                ListItem::new("Чувствительность к регистру")
+                ListItem::new(Msg::IndexRootsValue.fill(language, &[&index_roots_label(app_state)]))
+                    .style(Style::default()
+                        .fg(color_scheme.text_color())
+                        .bg(color_scheme.background())),
+                ListItem::new(Msg::IndexStatusValue.fill(language, &[&index_status_label(app_state)]))
                     .style(Style::default()
                         .fg(color_scheme.text_color())
                         .bg(color_scheme.background())),
@@ -383,36 +607,48 @@ fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut S
         7 => {
             // Горячие клавиши
             vec![
-                ListItem::new("Нажмите Enter для просмотра полного списка...")
+                ListItem::new(Msg::HotkeysPrompt.text(language))
                     .style(Style::default()
                         .fg(color_scheme.text_color())
                         .bg(color_scheme.background())),
             ]
         }
         8 => {
-            if app_state.plugins.is_empty() {
-                vec![ListItem::new("No plugins found.").style(
-                    Style::default()
-                        .fg(color_scheme.text_color())
-                        .bg(color_scheme.background()),
-                )]
+            let style = Style::default()
+                .fg(color_scheme.text_color())
+                .bg(color_scheme.background());
+            let mut items: Vec<ListItem> = if app_state.plugins.is_empty() {
+                vec![ListItem::new(Msg::NoPlugins.text(language)).style(style)]
             } else {
                 app_state
                     .plugins
                     .iter()
                     .map(|plugin| {
                         let marker = if plugin.enabled { "•" } else { " " };
-                        let text = format!("[{}] {}", marker, plugin.manifest.name);
-                        ListItem::new(text).style(
-                            Style::default()
-                                .fg(color_scheme.text_color())
-                                .bg(color_scheme.background()),
-                        )
+                        let mut state = match &plugin.state {
+                            PluginState::Running => Msg::PluginRunning.text(language).to_string(),
+                            PluginState::Stopped => Msg::PluginStopped.text(language).to_string(),
+                            PluginState::NeedsConsent => Msg::PluginNeedsConsent.text(language).to_string(),
+                            PluginState::Failed(error) => Msg::PluginFailed.fill(language, &[error]),
+                        };
+                        if plugin.stats.events_dropped > 0 {
+                            state.push_str(", ");
+                            state.push_str(&Msg::PluginEventsDropped.fill(language, &[&plugin.stats.events_dropped.to_string()]));
+                        }
+                        let text = format!("[{}] {} ({})", marker, plugin.manifest.name, state);
+                        ListItem::new(text).style(style)
                     })
                     .collect()
+            };
+            // Подсказка и итог последней установки не выбираются курсором
+            items.push(ListItem::new(""));
+            items.push(ListItem::new(Msg::PluginActions.text(language)).style(style));
+            if let Some(message) = &app_state.plugin_message {
+                items.push(ListItem::new(message.as_str()).style(style.fg(color_scheme.accent())));
             }
+            items
         }
-        _ => vec![ListItem::new("Выберите категорию")
+        _ => vec![ListItem::new(Msg::ChooseCategory.text(language))
             .style(Style::default()
                 .fg(color_scheme.text_color())
                 .bg(color_scheme.background()))],
@@ -420,7 +656,7 @@ fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut S
     
     let list = List::new(items)
         .block(Block::default()
-            .title("Настройки")
+            .title(Msg::SettingsItems.text(language))
             .borders(Borders::ALL)
             .style(Style::default()
                 .fg(color_scheme.text_color())
@@ -429,16 +665,23 @@ fn render_category_details(frame: &mut Frame, area: Rect, settings_state: &mut S
             .bg(color_scheme.highlight_bg())
             .fg(color_scheme.text_color()));
 
+    // После удаления плагина курсор не должен остаться за концом списка
+    let last_item = settings_state.get_current_category_item_count(app_state).saturating_sub(1);
+    if settings_state.selected_item > last_item {
+        settings_state.selected_item = last_item;
+        settings_state.items_state.select(Some(last_item));
+    }
     frame.render_stateful_widget(list, area, &mut settings_state.items_state);
 }
 
 /// Рендеринг полного списка горячих клавиш
-fn render_hotkeys_list(frame: &mut Frame, area: Rect, color_scheme: &ColorScheme) {
-    let hotkeys = get_all_hotkeys();
-    let items: Vec<ListItem> = hotkeys
-        .iter()
-        .map(|(key, description)| {
-            ListItem::new(format!("{:<15} - {}", key, description))
+/// Список всех горячих клавиш, начиная со строки `scroll`. Рисуется и в
+/// настройках, и поверх обычного интерфейса по `?`.
+pub fn render_hotkeys_list(frame: &mut Frame, area: Rect, color_scheme: &ColorScheme, language: Language, scroll: usize) {
+    let items: Vec<ListItem> = hotkeys::hotkeys()
+        .skip(scroll)
+        .map(|hotkey| {
+            ListItem::new(format!("{:<15} - {}", hotkey.keys, hotkey.description(language)))
                 .style(Style::default()
                     .fg(color_scheme.text_color())
                     .bg(color_scheme.background()))
@@ -447,7 +690,7 @@ fn render_hotkeys_list(frame: &mut Frame, area: Rect, color_scheme: &ColorScheme
     
     let list = List::new(items)
         .block(Block::default()
-            .title("Горячие клавиши")
+            .title(Msg::HotkeysTitle.text(language))
             .borders(Borders::ALL)
             .style(Style::default()
                 .fg(color_scheme.text_color())
@@ -460,9 +703,66 @@ fn render_hotkeys_list(frame: &mut Frame, area: Rect, color_scheme: &ColorScheme
     frame.render_stateful_widget(list, area, &mut state);
 }
 
+/// Журнал stderr плагина со временем каждой строки. Внизу — последние
+/// строки, если журнал не пролистан назад.
+fn render_plugin_log(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+    let Some(view) = &app_state.plugin_log_view else {
+        return;
+    };
+    let language = app_state.language;
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+    let name = app_state
+        .plugins
+        .iter()
+        .find(|plugin| plugin.path == view.plugin)
+        .map_or_else(|| view.plugin.display().to_string(), |plugin| plugin.manifest.name.clone());
+    let lines = app_state.plugin_host.log_lines(&view.plugin);
+    let height = area.height.saturating_sub(2) as usize;
+    let end = lines.len().saturating_sub(view.scroll);
+    let items: Vec<ListItem> = if lines.is_empty() {
+        vec![ListItem::new(Msg::PluginLogEmpty.text(language)).style(style)]
+    } else {
+        lines[end.saturating_sub(height)..end]
+            .iter()
+            .map(|line| ListItem::new(format!("{} {}", line.time.format("%H:%M:%S"), line.text)).style(style))
+            .collect()
+    };
+    let list = List::new(items).block(
+        Block::default()
+            .title(Msg::PluginLogTitle.fill(language, &[&name, &lines.len()]))
+            .borders(Borders::ALL)
+            .style(style),
+    );
+    frame.render_widget(list, area);
+}
+
+/// Клавиши журнала плагина: листание и выход к списку плагинов
+fn handle_plugin_log_key(key_code: crossterm::event::KeyCode, app_state: &mut AppState) {
+    use crossterm::event::KeyCode;
+    match key_code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => app_state.close_plugin_log(),
+        KeyCode::Up | KeyCode::Char('k') => app_state.scroll_plugin_log(1),
+        KeyCode::Down | KeyCode::Char('j') => app_state.scroll_plugin_log(-1),
+        KeyCode::PageUp => app_state.scroll_plugin_log(PLUGIN_LOG_PAGE),
+        KeyCode::PageDown => app_state.scroll_plugin_log(-PLUGIN_LOG_PAGE),
+        KeyCode::Char('g') | KeyCode::Home => app_state.scroll_plugin_log(isize::MAX),
+        KeyCode::Char('G') | KeyCode::End => app_state.scroll_plugin_log(isize::MIN),
+        _ => {}
+    }
+}
+
+/// На сколько строк листают PageUp и PageDown в журнале плагина
+const PLUGIN_LOG_PAGE: isize = 20;
+
 /// Handle key events for the settings screen
 /// Returns true if the settings screen should be closed
 pub fn handle_settings_key(key_code: crossterm::event::KeyCode, app_state: &mut AppState) -> bool {
+    if app_state.plugin_log_view.is_some() {
+        handle_plugin_log_key(key_code, app_state);
+        return false;
+    }
     unsafe {
         if let Some(settings_state) = SETTINGS_STATE.as_mut() {
             match key_code {
@@ -503,6 +803,12 @@ pub fn handle_settings_key(key_code: crossterm::event::KeyCode, app_state: &mut
                         }
                     }
                 }
+                crossterm::event::KeyCode::Char('l')
+                    if settings_state.selected_category == 8
+                        && settings_state.navigation_mode == SettingsNavigationMode::Items =>
+                {
+                    app_state.open_plugin_log(settings_state.selected_item);
+                }
                 crossterm::event::KeyCode::Char('l') => {
                     settings_state.navigation_mode = SettingsNavigationMode::Items;
                 }
@@ -512,10 +818,25 @@ pub fn handle_settings_key(key_code: crossterm::event::KeyCode, app_state: &mut
                 crossterm::event::KeyCode::Tab => {
                     settings_state.toggle_navigation_mode();
                 }
+                // Установка и удаление плагинов
+                crossterm::event::KeyCode::Char('a') if settings_state.selected_category == 8 => {
+                    app_state.start_plugin_install();
+                }
+                crossterm::event::KeyCode::Char('d')
+                    if settings_state.selected_category == 8
+                        && settings_state.navigation_mode == SettingsNavigationMode::Items =>
+                {
+                    app_state.remove_plugin(settings_state.selected_item);
+                }
                 crossterm::event::KeyCode::Enter => {
                     // Если выбрана категория "Горячие клавиши", показываем полный список
                     if settings_state.selected_category == 7 {
                         settings_state.show_hotkeys_list = true;
+                    } else if settings_state.selected_category == 8
+                        && settings_state.navigation_mode == SettingsNavigationMode::Categories
+                    {
+                        // Enter на плагинах переходит к списку, а не переключает первый из них
+                        settings_state.navigation_mode = SettingsNavigationMode::Items;
                     } else {
                         settings_state.select_item(app_state);
                     }
@@ -526,58 +847,3 @@ pub fn handle_settings_key(key_code: crossterm::event::KeyCode, app_state: &mut
     }
     false
 }
-
-/// Получить список всех горячих клавиш с описаниями
-fn get_all_hotkeys() -> Vec<(&'static str, &'static str)> {
-    vec![
-        // Навигация
-        ("j или ↓", "Переместить курсор вниз"),
-        ("k или ↑", "Переместить курсор вверх"),
-        ("h или ←", "Перейти в родительский каталог"),
-        ("l или → или Enter", "Войти в каталог"),
-        ("J (Shift+j)", "Переместить курсор вниз и выделить файл"),
-        ("K (Shift+k)", "Переместить курсор вверх и выделить файл"),
-        
-        // Вкладки
-        ("Ctrl+n", "Создать новую вкладку"),
-        ("Ctrl+w", "Закрыть текущую вкладку"),
-        ("Ctrl+Tab", "Перейти к следующей вкладке"),
-        ("Ctrl+Shift+Tab", "Перейти к предыдущей вкладке"),
-        ("Alt+1-9", "Перейти к вкладке по номеру"),
-        ("Alt+t", "Показать/скрыть панель вкладок"),
-        
-        // Файловые операции
-        ("y", "Копировать файл(ы)"),
-        ("x", "Вырезать файл(ы)"),
-        ("d", "Удалить файл(ы)"),
-        ("p", "Вставить файл(ы)"),
-        ("m", "Добавить текущий каталог в закладки"),
-        ("r", "Переименовать файл"),
-        ("n", "Открыть меню создания (файл/каталог)"),
-        ("  n+f", "Создать новый файл"),
-        ("  n+d", "Создать новый каталог"),
-        ("i", "Показать информацию о файле"),
-        ("u", "Отмонтировать диск (в панели дисков) или удалить закладку"),
-        ("c+m", "Изменить права доступа (chmod)"),
-        ("c+o", "Изменить владельца (chown)"),
-        
-        // Выделение
-        ("Shift+Space", "Снять выделение с текущего файла"),
-        ("Esc", "Отменить все выделения"),
-        
-        // Поиск
-        ("/", "Открыть диалог поиска"),
-        
-        // Настройки
-        ("F2", "Открыть настройки"),
-        
-        // Терминал
-        ("Ctrl+`", "Показать/скрыть встроенный терминал"),
-        
-        // Скрытие файлов
-        (".", "Показать/скрыть скрытые файлы"),
-        
-        // Выход
-        ("q", "Выход из приложения"),
-    ]
-}
\ No newline at end of file
@@ -0,0 +1,55 @@
+use ratatui::{
+    prelude::{Constraint, Direction, Layout, Rect, Style},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use corvus_core::app_state::{AppState, DEFAULT_OPENER};
+use crate::i18n::Msg;
+
+/// Экран первичной настройки, показывается один раз, пока нет config.toml
+pub fn render_setup_screen(frame: &mut Frame, area: Rect, app_state: &AppState) {
+    let color_scheme = app_state.get_current_color_scheme();
+    let language = app_state.language;
+    let style = Style::default()
+        .fg(color_scheme.text_color())
+        .bg(color_scheme.background());
+
+    let block = Block::default()
+        .title(Msg::SetupTitle.text(language))
+        .borders(Borders::ALL)
+        .style(style);
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(inner_area);
+
+    let intro = Paragraph::new(Msg::SetupIntro.text(language))
+        .wrap(Wrap { trim: true })
+        .style(style);
+    frame.render_widget(intro, chunks[0]);
+
+    let behavior = &app_state.config.behavior;
+    let check = |enabled: bool| if enabled { "•" } else { " " };
+    let items = vec![
+        ListItem::new(Msg::ColorSchemeValue.fill(language, &[&color_scheme.name()])),
+        ListItem::new(format!("[{}] {}", check(app_state.show_hidden_files), Msg::ShowHiddenFiles.text(language))),
+        ListItem::new(format!("[{}] {}", check(app_state.behavior_settings.confirm_delete), Msg::SetupConfirmDelete.text(language))),
+        ListItem::new(Msg::SetupOpener.fill(language, &[
+            &behavior.opener.as_deref().unwrap_or(DEFAULT_OPENER),
+            &if behavior.opener_in_terminal { Msg::SetupInTerminal.text(language) } else { "" },
+        ])),
+        ListItem::new(format!("[{}] {}", check(behavior.use_trash), Msg::SetupUseTrash.text(language))),
+        ListItem::new(Msg::SetupDone.text(language)),
+    ];
+    let list = List::new(items)
+        .style(style)
+        .highlight_style(Style::default()
+            .bg(color_scheme.highlight_bg())
+            .fg(color_scheme.text_color()));
+    let mut list_state = ListState::default();
+    list_state.select(Some(app_state.setup_cursor));
+    frame.render_stateful_widget(list, chunks[1], &mut list_state);
+}
@@ -0,0 +1,74 @@
+use ratatui::{
+    prelude::{Alignment, Line, Rect, Span, Style},
+    widgets::Paragraph,
+    Frame,
+};
+
+use corvus_core::app_state::{AppState, ListingSource};
+use corvus_core::settings::{format_size_with_config, ColorScheme, SortField, SortOrder};
+use crate::i18n::Msg;
+
+/// Строка состояния под списками: позиция курсора, скрытые файлы,
+/// выделение, сортировка, фильтр и свободное место для активной вкладки
+pub fn render_status_bar(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+    if area.height == 0 {
+        return;
+    }
+    let language = app_state.language;
+    let tab = app_state.get_active_tab();
+    let units = app_state.size_units;
+
+    let position = if tab.filtered_entries.is_empty() { 0 } else { tab.cursor + 1 };
+    let mut parts = vec![format!("{}/{}", position, tab.filtered_entries.len())];
+    if tab.hidden_count > 0 && matches!(tab.listing_source, ListingSource::Directory) {
+        parts.push(Msg::StatusHidden.fill(language, &[&tab.hidden_count]));
+    }
+    let selection = &tab.selected_entries;
+    if !selection.is_empty() {
+        let mut selected = Msg::StatusSelected.fill(
+            language,
+            &[&selection.len(), &format_size_with_config(selection.bytes(), units)],
+        );
+        if selection.dirs() > 0 {
+            selected.push(' ');
+            selected.push_str(&Msg::StatusSelectedDirs.fill(language, &[&selection.dirs()]));
+        }
+        parts.push(selected);
+    }
+
+    let mut view = app_state.current_view();
+    if let Some(overrides) = &tab.dir_overrides {
+        overrides.apply(&mut view);
+    }
+    let field = match view.sort.field {
+        SortField::Name => Msg::SortByName,
+        SortField::Size => Msg::SortBySize,
+        SortField::Modified => Msg::SortByDate,
+    };
+    let order = match view.sort.order {
+        SortOrder::Ascending => "↑",
+        SortOrder::Descending => "↓",
+    };
+    parts.push(Msg::StatusSort.fill(language, &[&field.text(language), &order]));
+    if let Some(filter) = &tab.glob_filter {
+        parts.push(Msg::StatusFilter.fill(language, &[&filter.pattern]));
+    }
+
+    let text_style = Style::default().fg(color_scheme.text_color()).bg(color_scheme.background());
+    let separator_style = text_style.fg(color_scheme.accent());
+    let mut spans = Vec::new();
+    for (index, part) in parts.into_iter().enumerate() {
+        if index > 0 {
+            spans.push(Span::styled(" │ ", separator_style));
+        }
+        spans.push(Span::styled(part, text_style));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)).style(text_style), area);
+
+    // Свободное место — у правого края
+    if let Some(free) = tab.free_space {
+        let free = Msg::StatusFree.fill(language, &[&format_size_with_config(free, units)]);
+        let line = Line::from(Span::styled(format!("{} ", free), text_style));
+        frame.render_widget(Paragraph::new(line).alignment(Alignment::Right), area);
+    }
+}
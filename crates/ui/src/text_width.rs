@@ -0,0 +1,51 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Сколько колонок терминала занимает строка: иероглифы и эмодзи — по две
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Обрезает строку до `max_width` колонок, добавляя `…`. Режет только по
+/// границам графем, так что буква с диакритикой или эмодзи из нескольких
+/// символов не распадается на части.
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    let mut result = String::new();
+    let mut width = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if width + grapheme_width + 1 > max_width {
+            break;
+        }
+        width += grapheme_width;
+        result.push_str(grapheme);
+    }
+    if max_width > 0 {
+        result.push('…');
+    }
+    result
+}
+
+/// Дополняет строку пробелами до `width` колонок
+pub fn pad_to_width(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+/// Разбивает строку на части не шире `width` колонок, не разрывая графем
+pub fn wrap_to_width(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut current_width = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if current_width + grapheme_width > width && current_width > 0 {
+            lines.push(String::new());
+            current_width = 0;
+        }
+        lines.last_mut().unwrap().push_str(grapheme);
+        current_width += grapheme_width;
+    }
+    lines
+}
@@ -7,22 +7,33 @@ use ratatui::{
 
 use corvus_core::app_state::AppState;
 use corvus_core::settings::ColorScheme;
+use crate::text_width::display_width;
 
-pub fn render_top_bar(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
-    let titles: Vec<String> = app_state
+fn tab_titles(app_state: &AppState) -> Vec<String> {
+    app_state
         .tabs
         .iter()
-        .map(|tab| {
-            format!(
-                "{} {}",
-                tab.id + 1,
-                tab.current_dir
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-            )
-        })
-        .collect();
+        .map(|tab| format!("{} {}", tab.id + 1, tab.title()))
+        .collect()
+}
+
+/// Вкладка под столбцом `column` полосы `area`. `Tabs` рисует каждую
+/// подпись с пробелом по бокам и ставит между ними разделитель в один столбец.
+pub fn tab_at_column(app_state: &AppState, area: Rect, column: u16) -> Option<usize> {
+    let column = column.checked_sub(area.x)? as usize;
+    let mut start = 0;
+    for (index, title) in tab_titles(app_state).iter().enumerate() {
+        let end = start + display_width(title) + 2;
+        if column < end {
+            return (column >= start).then_some(index);
+        }
+        start = end + 1;
+    }
+    None
+}
+
+pub fn render_top_bar(frame: &mut Frame, area: Rect, app_state: &AppState, color_scheme: &ColorScheme) {
+    let titles = tab_titles(app_state);
 
     let tabs = Tabs::new(titles)
         .block(Block::default()
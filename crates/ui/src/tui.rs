@@ -1,35 +1,261 @@
 use crossterm::{
-    event::{KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use ratatui::prelude::{CrosstermBackend, Terminal};
+use ratatui::prelude::{CrosstermBackend, Rect, Terminal};
 use std::io::{self, stdout, Stdout};
-use corvus_core::app_state::{AppState, InputMode, CreateFileType, RightPaneView};
+use corvus_core::app_state::{ActionToConfirm, AppState, InputMode, CreateFileType, PanelSide, RightPaneView, TerminalLifecycle};
+use corvus_core::clipboard::ClipboardMode;
+use crate::{breadcrumb, hotkeys, left_pane, top_bar};
 
 pub struct Tui {
     pub terminal: Terminal<CrosstermBackend<Stdout>>,
+    /// Включён ли сейчас захват мыши
+    mouse_captured: bool,
 }
 
 impl Tui {
     pub fn new() -> io::Result<Self> {
         let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-        Ok(Self { terminal })
+        Ok(Self { terminal, mouse_captured: false })
     }
 
     pub fn enter(&mut self) -> io::Result<()> {
         enable_raw_mode()?;
         stdout().execute(EnterAlternateScreen)?;
+        stdout().execute(EnableBracketedPaste)?;
+        Ok(())
+    }
+
+    /// Включает или выключает захват мыши, если он отличается от нужного
+    pub fn set_mouse_capture(&mut self, enabled: bool) -> io::Result<()> {
+        if enabled != self.mouse_captured {
+            if enabled {
+                stdout().execute(EnableMouseCapture)?;
+            } else {
+                stdout().execute(DisableMouseCapture)?;
+            }
+            self.mouse_captured = enabled;
+        }
         Ok(())
     }
 
     pub fn exit(&mut self) -> io::Result<()> {
         disable_raw_mode()?;
+        stdout().execute(DisableBracketedPaste)?;
+        stdout().execute(DisableMouseCapture)?;
         stdout().execute(LeaveAlternateScreen)?;
         Ok(())
     }
 }
 
+/// На сколько записей или строк сдвигает один шаг колеса мыши
+const SCROLL_STEP: isize = 3;
+
+/// Щелчок ставит курсор на запись списка, двойной щелчок открывает её, а
+/// колесо листает список или предпросмотр под указателем. Щелчок по месту
+/// в левой панели сразу открывает его, по названию вкладки — переключает на
+/// неё. Пока открыт диалог или другой режим ввода, мышь ничего не делает.
+pub fn handle_mouse(mouse: MouseEvent, app_state: &mut AppState) {
+    if app_state.input_mode != InputMode::Normal
+        || app_state.show_input_dialog
+        || app_state.show_confirmation
+        || app_state.show_search_dialog
+    {
+        return;
+    }
+    let areas = app_state.pane_areas.get();
+    // Строка под указателем относительно начала области
+    let row_in = |area: Rect| {
+        let inside = (area.x..area.x + area.width).contains(&mouse.column)
+            && (area.y..area.y + area.height).contains(&mouse.row);
+        inside.then(|| mouse.row - area.y)
+    };
+    let entry = [(PanelSide::Left, areas.left_list), (PanelSide::Right, areas.right_list)]
+        .into_iter()
+        .find_map(|(side, list)| {
+            let (area, offset) = list?;
+            row_in(area).map(|row| (side, offset + row as usize))
+        });
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let tab = areas
+                .tabs
+                .filter(|&area| row_in(area) == Some(0))
+                .and_then(|area| top_bar::tab_at_column(app_state, area, mouse.column));
+            let path = [(PanelSide::Left, areas.left_path), (PanelSide::Right, areas.right_path)]
+                .into_iter()
+                .find_map(|(side, area)| {
+                    let area = area.filter(|&area| row_in(area).is_some())?;
+                    let panel = match side {
+                        PanelSide::Left => app_state.get_selected_tab(),
+                        PanelSide::Right => app_state.second_panel.as_ref()?,
+                    };
+                    breadcrumb::target_at_column(&panel.current_dir, area.x, area.width, mouse.column).map(|dir| (side, dir))
+                });
+            let place = areas.places.into_iter().flatten().zip(left_pane::PLACE_BLOCKS).find_map(|((area, offset), block)| {
+                let row = row_in(area)?;
+                left_pane::place_at_row(app_state, block, offset, row as usize).map(|index| (block, index))
+            });
+            if let Some((side, index)) = entry {
+                app_state.click_entry(side, index);
+            } else if let Some((side, dir)) = path {
+                app_state.click_breadcrumb(side, dir);
+            } else if let Some(index) = tab {
+                app_state.click_tab(index);
+            } else if let Some((block, index)) = place {
+                app_state.click_place(block, index);
+            }
+        }
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+            let delta = if mouse.kind == MouseEventKind::ScrollDown { SCROLL_STEP } else { -SCROLL_STEP };
+            if let Some((side, _)) = entry {
+                app_state.scroll_entries(side, delta);
+            } else if areas.preview.and_then(row_in).is_some() {
+                app_state.scroll_preview(delta as i16);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Клавиши режима копирования терминала: движение в духе vi, `v` начинает
+/// выделение, `y` копирует его (или строку под курсором) в буфер обмена
+fn handle_copy_mode_key(key: KeyEvent, app_state: &mut AppState) {
+    let terminal = &mut app_state.get_active_tab_mut().terminal;
+    let (rows, cols) = (terminal.pty_size.0 as isize, terminal.pty_size.1 as isize);
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('u') if ctrl => terminal.move_copy_cursor(-rows / 2, 0),
+        KeyCode::Char('d') if ctrl => terminal.move_copy_cursor(rows / 2, 0),
+        KeyCode::PageUp => terminal.move_copy_cursor(-rows, 0),
+        KeyCode::PageDown => terminal.move_copy_cursor(rows, 0),
+        KeyCode::Char('h') | KeyCode::Left => terminal.move_copy_cursor(0, -1),
+        KeyCode::Char('l') | KeyCode::Right => terminal.move_copy_cursor(0, 1),
+        KeyCode::Char('k') | KeyCode::Up => terminal.move_copy_cursor(-1, 0),
+        KeyCode::Char('j') | KeyCode::Down => terminal.move_copy_cursor(1, 0),
+        KeyCode::Char('0') | KeyCode::Home => terminal.move_copy_cursor(0, -cols),
+        KeyCode::Char('$') | KeyCode::End => terminal.move_copy_cursor(0, cols),
+        KeyCode::Char('g') => terminal.move_copy_cursor(isize::MIN / 2, 0),
+        KeyCode::Char('G') => terminal.move_copy_cursor(isize::MAX / 2, 0),
+        KeyCode::Char('v') | KeyCode::Char(' ') => terminal.toggle_selection(),
+        KeyCode::Char('y') | KeyCode::Enter => {
+            if let Some(text) = terminal.copy_selection() {
+                copy_to_system_clipboard(&text);
+                app_state.notification = Some(format!("Copied {} lines from the terminal", text.lines().count().max(1)));
+                app_state.notification_timer = Some(std::time::Instant::now());
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => terminal.exit_copy_mode(),
+        _ => {}
+    }
+}
+
+/// Кладёт текст в системный буфер обмена запросом OSC 52. Его понимает
+/// большинство эмуляторов терминала, в том числе через ssh.
+fn copy_to_system_clipboard(text: &str) {
+    use std::io::Write;
+    let request = format!("\x1b]52;c;{}\x07", utils::base64::encode(text.as_bytes()));
+    let mut out = stdout();
+    if let Err(e) = out.write_all(request.as_bytes()).and_then(|_| out.flush()) {
+        log::error!("Failed to copy to the clipboard: {}", e);
+    }
+}
+
+/// Байты, которые клавиша посылает программе в терминале, как в xterm.
+/// `application_cursor` — программа включила режим курсорных клавиш
+/// приложения (DECCKM), тогда стрелки, Home и End посылаются как `ESC O x`.
+fn terminal_key_bytes(key: KeyEvent, application_cursor: bool) -> Vec<u8> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    // Параметр модификаторов в `CSI 1;m x`: 1 + Shift + 2·Alt + 4·Ctrl
+    let modifier = 1 + shift as u8 + 2 * alt as u8 + 4 * ctrl as u8;
+    let cursor_key = |letter: u8| -> Vec<u8> {
+        if modifier > 1 {
+            format!("\x1b[1;{}{}", modifier, letter as char).into_bytes()
+        } else if application_cursor {
+            vec![0x1b, b'O', letter]
+        } else {
+            vec![0x1b, b'[', letter]
+        }
+    };
+    let tilde_key = |number: u8| -> Vec<u8> {
+        if modifier > 1 {
+            format!("\x1b[{};{}~", number, modifier).into_bytes()
+        } else {
+            format!("\x1b[{}~", number).into_bytes()
+        }
+    };
+
+    let mut bytes = match key.code {
+        KeyCode::Char(c) if ctrl => match c.to_ascii_lowercase() {
+            c @ 'a'..='z' => vec![c as u8 - b'a' + 1],
+            '@' | ' ' | '2' => vec![0],
+            '[' | '3' => vec![0x1b],
+            '\\' | '4' => vec![0x1c],
+            ']' | '5' => vec![0x1d],
+            '^' | '6' => vec![0x1e],
+            '_' | '/' | '7' => vec![0x1f],
+            '?' | '8' => vec![0x7f],
+            _ => return Vec::new(),
+        },
+        KeyCode::Char(c) => c.to_string().into_bytes(),
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace if ctrl => vec![0x08],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => return cursor_key(b'A'),
+        KeyCode::Down => return cursor_key(b'B'),
+        KeyCode::Right => return cursor_key(b'C'),
+        KeyCode::Left => return cursor_key(b'D'),
+        KeyCode::Home => return cursor_key(b'H'),
+        KeyCode::End => return cursor_key(b'F'),
+        KeyCode::Insert => return tilde_key(2),
+        KeyCode::Delete => return tilde_key(3),
+        KeyCode::PageUp => return tilde_key(5),
+        KeyCode::PageDown => return tilde_key(6),
+        KeyCode::F(n @ 1..=4) if modifier > 1 => return format!("\x1b[1;{}{}", modifier, (b'O' + n) as char).into_bytes(),
+        KeyCode::F(n @ 1..=4) => return vec![0x1b, b'O', b'O' + n],
+        KeyCode::F(n @ 5..=12) => return tilde_key([15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5]),
+        _ => return Vec::new(),
+    };
+    // Alt с обычной клавишей посылает её с префиксом ESC
+    if alt {
+        bytes.insert(0, 0x1b);
+    }
+    bytes
+}
+
+/// Вставка из буфера обмена терминала. Во встроенном терминале текст
+/// уходит оболочке целиком, в остальных местах вводится как нажатия
+/// клавиш, как было до включения bracketed paste. Возвращает `false`,
+/// если приложение нужно закрыть.
+pub fn handle_paste(text: &str, app_state: &mut AppState) -> bool {
+    let tab = app_state.get_active_tab();
+    if tab.right_pane_view == RightPaneView::Terminal
+        && tab.terminal.lifecycle == TerminalLifecycle::Running
+        && tab.terminal.copy_mode.is_none()
+    {
+        app_state.paste_to_terminal(text);
+        return true;
+    }
+    for c in text.chars() {
+        let code = if c == '\n' || c == '\r' { KeyCode::Enter } else { KeyCode::Char(c) };
+        if !handle_key_press(KeyEvent::new(code, KeyModifiers::NONE), app_state) {
+            return false;
+        }
+    }
+    true
+}
+
 /// Handles key presses and returns `false` if the app should quit.
 pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
     let active_tab_view = app_state.get_active_tab().right_pane_view.clone();
@@ -41,43 +267,44 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
             return true;
         }
 
-        if let Some(terminal) = &mut app_state.terminal {
-            let mut bytes = Vec::new();
-            match key.code {
-                KeyCode::Char(c) => {
-                    bytes.extend_from_slice(c.to_string().as_bytes());
-                }
-                KeyCode::Enter => {
-                    bytes.push(b'\r');
-                }
-                KeyCode::Backspace => {
-                    bytes.push(8); // Backspace character
-                }
-                KeyCode::Left => {
-                    bytes.extend_from_slice(b"\x1b[D");
-                }
-                KeyCode::Right => {
-                    bytes.extend_from_slice(b"\x1b[C");
-                }
-                KeyCode::Up => {
-                    bytes.extend_from_slice(b"\x1b[A");
-                }
-                KeyCode::Down => {
-                    bytes.extend_from_slice(b"\x1b[B");
-                }
-                KeyCode::Tab => {
-                    bytes.push(b'\t');
-                }
-                _ => {}
+        if app_state.get_active_tab().terminal.copy_mode.is_some() {
+            handle_copy_mode_key(key, app_state);
+            return true;
+        }
+        // Пока оболочка не запущена, Enter запускает новую в каталоге вкладки
+        if app_state.get_active_tab().terminal.lifecycle != TerminalLifecycle::Running {
+            if key.code == KeyCode::Enter {
+                app_state.start_terminal();
             }
-            if !bytes.is_empty() {
-                use std::io::Write;
-                if let Ok(mut writer) = terminal.pty_writer.as_mut().take_writer() {
-                    if let Err(e) = writer.write_all(&bytes) {
-                        log::error!("Failed to write to pty: {}", e);
-                    }
-                }
+            return true;
+        }
+        let terminal = &mut app_state.get_active_tab_mut().terminal;
+        // `Ctrl+b [` открывает режим копирования, как в tmux. После `Ctrl+b`
+        // с другой клавишей оболочка получает обе.
+        if std::mem::take(&mut terminal.prefix_pending) {
+            if key.code == KeyCode::Char('[') {
+                terminal.enter_copy_mode();
+                return true;
+            }
+            app_state.send_to_terminal(&[0x02]);
+        } else if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('b') {
+            terminal.prefix_pending = true;
+            return true;
+        }
+        let terminal = &mut app_state.get_active_tab_mut().terminal;
+        // Прокрутка истории не доходит до оболочки, остальные клавиши
+        // возвращают к экрану
+        let page = terminal.pty_size.0 as isize / 2;
+        match key.code {
+            KeyCode::PageUp | KeyCode::PageDown if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                terminal.scroll(if key.code == KeyCode::PageUp { page } else { -page });
+                return true;
             }
+            _ => terminal.parser.set_scrollback(0),
+        }
+        let bytes = terminal_key_bytes(key, terminal.parser.screen().application_cursor());
+        if !bytes.is_empty() {
+            app_state.send_to_terminal(&bytes);
         }
         return true;
     }
@@ -101,8 +328,7 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
             KeyCode::Char('t') => {
                 let active_tab = app_state.get_active_tab_mut();
                 if active_tab.right_pane_view == RightPaneView::Preview {
-                    active_tab.right_pane_view = RightPaneView::Terminal;
-                    app_state.focus = FocusBlock::Terminal;
+                    app_state.show_terminal();
                 } else {
                     active_tab.right_pane_view = RightPaneView::Preview;
                     app_state.focus = FocusBlock::Middle;
@@ -129,6 +355,21 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
          return true;
     }
 
+    // Ctrl-Shift-Left/Right to move the active tab
+    if key.modifiers.contains(KeyModifiers::CONTROL | KeyModifiers::SHIFT) {
+        match key.code {
+            KeyCode::Left => {
+                app_state.move_tab(false);
+                return true;
+            }
+            KeyCode::Right => {
+                app_state.move_tab(true);
+                return true;
+            }
+            _ => {}
+        }
+    }
+
     // Alt-number for tab switching
     if key.modifiers.contains(KeyModifiers::ALT) {
         match key.code {
@@ -143,6 +384,11 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                 app_state.toggle_tabs();
                 return true;
             }
+            // Ctrl+Shift+n терминалы передают так же, как Ctrl+n
+            KeyCode::Char('n') => {
+                app_state.duplicate_tab();
+                return true;
+            }
             _ => {}
         }
     }
@@ -157,6 +403,29 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
         match key.code {
             KeyCode::Char('y') => {
                 app_state.confirm();
+                return !app_state.quit_requested;
+            }
+            KeyCode::Char('v') => {
+                app_state.toggle_plan_preview();
+                return true;
+            }
+            KeyCode::Down | KeyCode::Char('j') if app_state.plan_preview.is_some() => {
+                app_state.scroll_plan_preview(1);
+                return true;
+            }
+            KeyCode::Up | KeyCode::Char('k') if app_state.plan_preview.is_some() => {
+                app_state.scroll_plan_preview(-1);
+                return true;
+            }
+            // Для распаковки `n` означает «в текущий каталог», отмена — Esc
+            KeyCode::Char('n') if matches!(app_state.action_to_confirm, Some(ActionToConfirm::Extract)) => {
+                app_state.confirm_extract(false);
+                return true;
+            }
+            // Сессия занята другим экземпляром: `n` предлагает другое имя
+            KeyCode::Char('n') if matches!(app_state.action_to_confirm, Some(ActionToConfirm::SessionInUse)) => {
+                app_state.cancel();
+                app_state.start_session_save();
                 return true;
             }
             KeyCode::Char('n') | KeyCode::Esc => {
@@ -169,6 +438,12 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
 
     if app_state.show_input_dialog {
         match key.code {
+            KeyCode::Char('d')
+                if key.modifiers.contains(KeyModifiers::CONTROL) && app_state.input_mode == InputMode::Archive =>
+            {
+                app_state.toggle_archive_remove_sources();
+                return true;
+            }
             KeyCode::Char(c) => {
                 app_state.input_buffer.push(c);
                 return true;
@@ -184,21 +459,53 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                 }
                 return true;
             }
+            KeyCode::Down if app_state.input_mode == InputMode::Archive => {
+                app_state.select_archive_format();
+                return true;
+            }
+            KeyCode::Up if app_state.input_mode == InputMode::Archive => {
+                app_state.select_previous_archive_format();
+                return true;
+            }
+            KeyCode::Left if app_state.input_mode == InputMode::Archive => {
+                app_state.adjust_compression_level(-1);
+                return true;
+            }
+            KeyCode::Right if app_state.input_mode == InputMode::Archive => {
+                app_state.adjust_compression_level(1);
+                return true;
+            }
             KeyCode::Enter => {
+                app_state.input_dialog_error = None;
                 match app_state.input_mode {
                     InputMode::Rename => app_state.rename_item(),
                     InputMode::Chmod => app_state.chmod_item(),
                     InputMode::Chown => app_state.chown_item(),
                     InputMode::Archive => app_state.archive_item(),
+                    InputMode::TabName => app_state.set_tab_name(),
+                    InputMode::Bookmark => app_state.save_bookmark(),
+                    InputMode::PreviewCommand => app_state.run_preview_command(),
+                    InputMode::ShellCommand => app_state.run_shell_command(),
+                    InputMode::GlobFilter => app_state.apply_glob_filter(),
+                    InputMode::SessionName => app_state.save_session_as(),
+                    InputMode::RemoteConnect => app_state.connect_remote_from_input(),
+                    InputMode::PluginInstall => app_state.install_plugin(),
                     _ => app_state.create_item(),
                 }
-                app_state.show_input_dialog = false;
+                // При ошибке диалог остаётся открытым, чтобы её было видно
+                if app_state.input_dialog_error.is_none() {
+                    app_state.show_input_dialog = false;
+                }
                 return true;
             }
             KeyCode::Esc => {
                 app_state.show_input_dialog = false;
                 app_state.input_buffer.clear();
-                app_state.input_mode = InputMode::Normal;
+                // Установка плагина открыта из настроек и возвращает к ним
+                app_state.input_mode = match app_state.input_mode {
+                    InputMode::PluginInstall => InputMode::Settings,
+                    _ => InputMode::Normal,
+                };
                 return true;
             }
             _ => {}
@@ -208,6 +515,27 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
     // Handle search dialog
     if app_state.show_search_dialog {
         match key.code {
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.materialize_search_results();
+                return true;
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.toggle_search_recursive();
+                return true;
+            }
+            KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.export_search_results();
+                return true;
+            }
+            KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.select_all_search_results();
+                return true;
+            }
+            // Пробел без Ctrl — часть запроса
+            KeyCode::Char(' ') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.toggle_search_result();
+                return true;
+            }
             KeyCode::Char(c) => {
                 app_state.search_query.push(c);
                 app_state.update_search();
@@ -219,9 +547,14 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                 return true;
             }
             KeyCode::Enter => {
-                if !app_state.search_results.is_empty() {
+                if !app_state.search_selection.is_empty() {
+                    app_state.select_marked_search_results();
+                } else if !app_state.search_results.is_empty() {
                     app_state.select_search_result();
-                    app_state.cancel_search();
+                    // Рекурсивный поиск остаётся открытым, чтобы перейти к следующему совпадению
+                    if !app_state.search_recursive {
+                        app_state.cancel_search();
+                    }
                 }
                 return true;
             }
@@ -229,6 +562,14 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                 app_state.cancel_search();
                 return true;
             }
+            KeyCode::Tab => {
+                app_state.cycle_search_syntax();
+                return true;
+            }
+            KeyCode::BackTab => {
+                app_state.cycle_search_mode();
+                return true;
+            }
             KeyCode::Down => {
                 app_state.move_search_cursor_down();
                 return true;
@@ -245,7 +586,35 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
     use corvus_core::app_state::FocusBlock;
     match app_state.input_mode {
         InputMode::Normal => {
+            // Пока идёт переход по первым буквам, буквы дописываются к имени, а не выполняют команды
+            if app_state.is_typing_ahead() {
+                match key.code {
+                    KeyCode::Char(c) => {
+                        app_state.type_ahead_char(c);
+                        return true;
+                    }
+                    KeyCode::Esc | KeyCode::Enter => {
+                        app_state.cancel_type_ahead();
+                        return true;
+                    }
+                    _ => app_state.cancel_type_ahead(),
+                }
+            }
             match key.code {
+                KeyCode::Char('`') => {
+                    app_state.input_mode = InputMode::BookmarkJump;
+                    return true;
+                }
+                KeyCode::Char('s')
+                    if key.modifiers.contains(KeyModifiers::CONTROL) && app_state.focus == FocusBlock::Middle =>
+                {
+                    app_state.send_selection_to_terminal();
+                    return true;
+                }
+                KeyCode::Char('\'') if app_state.focus == FocusBlock::Middle => {
+                    app_state.start_type_ahead();
+                    return true;
+                }
                 KeyCode::Char('c') => {
                     app_state.input_mode = InputMode::Command;
                     return true;
@@ -254,9 +623,28 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                     app_state.input_mode = InputMode::Create;
                     return true;
                 }
-                KeyCode::Char('q') => return false, // Signal to quit
+                KeyCode::Char('z') if app_state.focus == FocusBlock::Middle => {
+                    app_state.input_mode = InputMode::Analyze;
+                    return true;
+                }
+                KeyCode::Char('q') => return !app_state.request_quit(), // Signal to quit
                 KeyCode::Tab => app_state.cycle_focus(),
-                KeyCode::Char('.') => app_state.toggle_hidden_files(),
+                KeyCode::Char('.') => app_state.toggle_view_hidden_files(),
+                KeyCode::Char('D') => app_state.toggle_view_file_details(),
+                KeyCode::Char('s') => app_state.cycle_view_sort_field(),
+                KeyCode::Char('S') => app_state.reverse_view_sort_order(),
+                KeyCode::Char('Z') => app_state.toggle_remember_view(),
+                KeyCode::Char('P') if app_state.focus == FocusBlock::Middle => app_state.toggle_pin(),
+                KeyCode::Char('C') => app_state.clear_finished_tasks(),
+                KeyCode::Char('F') if app_state.focus == FocusBlock::Middle => app_state.start_glob_filter(),
+                KeyCode::Char('|') => app_state.toggle_layout_mode(),
+                KeyCode::F(5) => app_state.transfer_to_other_panel(ClipboardMode::Copy),
+                KeyCode::F(6) => app_state.transfer_to_other_panel(ClipboardMode::Move),
+                KeyCode::F(8) => app_state.toggle_footer(),
+                KeyCode::F(9) => app_state.toggle_left_pane(),
+                // Граница между списком и предпросмотром двигается вслед за стрелкой
+                KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.resize_preview(true),
+                KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => app_state.resize_preview(false),
                 KeyCode::Char('j') => {
                     match app_state.focus {
                         FocusBlock::Middle => {
@@ -264,7 +652,7 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                             app_state.get_active_tab_mut().move_cursor_down(show_hidden);
                             app_state.show_info_panel = false;
                         },
-                        _ => { // Covers Xdg, Bookmarks, Disks
+                        _ => { // Covers Xdg, Bookmarks, Disks, Remotes
                             app_state.move_left_pane_cursor_down();
                         }
                     }
@@ -285,7 +673,7 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                             app_state.get_active_tab_mut().move_cursor_up(show_hidden);
                             app_state.show_info_panel = false;
                         },
-                        _ => { // Covers Xdg, Bookmarks, Disks
+                        _ => { // Covers Xdg, Bookmarks, Disks, Remotes
                             app_state.move_left_pane_cursor_up();
                         }
                     }
@@ -299,13 +687,19 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                         app_state.get_active_tab_mut().select_current();
                     }
                 },
-                KeyCode::Char('h') | KeyCode::Left => {
+                KeyCode::Left if key.modifiers.contains(KeyModifiers::ALT) => app_state.navigate_history(false),
+                KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => app_state.navigate_history(true),
+                KeyCode::Char('H') if app_state.focus == FocusBlock::Middle => app_state.open_ancestor_picker(),
+                KeyCode::Char('h') | KeyCode::Left | KeyCode::Backspace => {
                     if app_state.focus == FocusBlock::Middle {
                         let show_hidden = app_state.show_hidden_files;
                         app_state.get_active_tab_mut().leave_directory(show_hidden);
                         app_state.show_info_panel = false;
                     }
                 },
+                KeyCode::Char('l') | KeyCode::Right | KeyCode::Enter if app_state.focus == FocusBlock::Remotes => {
+                    app_state.activate_remote();
+                }
                 KeyCode::Char('l') | KeyCode::Right | KeyCode::Enter => {
                     match app_state.focus {
                         FocusBlock::Middle => {
@@ -313,7 +707,7 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                             app_state.get_active_tab_mut().enter_directory(show_hidden);
                             app_state.show_info_panel = false;
                         },
-                        _ => { // Covers Xdg, Bookmarks, Disks
+                        _ => { // Covers Xdg, Bookmarks, Disks, Remotes
                             app_state.update_middle_pane_from_left_pane_selection();
                             app_state.focus = FocusBlock::Middle;
                         }
@@ -326,7 +720,7 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                             app_state.get_active_tab_mut().move_cursor_down(show_hidden);
                             app_state.show_info_panel = false;
                         },
-                        _ => { // Covers Xdg, Bookmarks, Disks
+                        _ => { // Covers Xdg, Bookmarks, Disks, Remotes
                             app_state.move_left_pane_cursor_down();
                         }
                     }
@@ -338,7 +732,7 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                             app_state.get_active_tab_mut().move_cursor_up(show_hidden);
                             app_state.show_info_panel = false;
                         },
-                        _ => { // Covers Xdg, Bookmarks, Disks
+                        _ => { // Covers Xdg, Bookmarks, Disks, Remotes
                             app_state.move_left_pane_cursor_up();
                         }
                     }
@@ -367,20 +761,25 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                 KeyCode::Char('u') => {
                     match app_state.focus {
                         FocusBlock::Disks => app_state.unmount_selection(),
+                        FocusBlock::Remotes => app_state.disconnect_remote(),
                         FocusBlock::Bookmarks => app_state.remove_bookmark(),
                         _ => {} // Do nothing in other panes
                     }
                 },
+                KeyCode::Char('e') if app_state.focus == FocusBlock::Disks => app_state.eject_selection(),
                 KeyCode::Char('i') => app_state.show_info_panel(),
                 KeyCode::Char('/') => app_state.start_search(), // Add search activation
                 KeyCode::F(2) => {
                     app_state.input_mode = InputMode::Settings;
                     return true;
                 },
-                KeyCode::Esc => {
-                    // Отмена выделения
-                    app_state.get_active_tab_mut().selected_entries.clear();
-                },
+                KeyCode::Char('?') => {
+                    app_state.help_scroll = 0;
+                    app_state.help_filter.clear();
+                    app_state.input_mode = InputMode::Help;
+                    return true;
+                }
+                KeyCode::Esc => app_state.escape(),
                 _ => {}
             }
         },
@@ -434,6 +833,55 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
                     app_state.archive_selection();
                     return true;
                 }
+                KeyCode::Char('e') => {
+                    app_state.input_mode = InputMode::Normal;
+                    app_state.extract_selection();
+                    return true;
+                }
+                KeyCode::Char(':') => {
+                    app_state.open_command_palette();
+                    return true;
+                }
+                KeyCode::Char('p') => {
+                    app_state.start_preview_command();
+                    return true;
+                }
+                KeyCode::Char('!') => {
+                    app_state.start_shell_command();
+                    return true;
+                }
+                KeyCode::Char('v') => {
+                    app_state.input_mode = InputMode::Normal;
+                    app_state.write_dir_overrides();
+                    return true;
+                }
+                KeyCode::Char('t') => {
+                    app_state.start_tab_rename();
+                    return true;
+                }
+                KeyCode::Char('s') => {
+                    app_state.start_session_save();
+                    return true;
+                }
+                KeyCode::Char('w') => {
+                    app_state.open_session_picker();
+                    return true;
+                }
+                KeyCode::Char('T') => {
+                    app_state.open_external_terminal();
+                    return true;
+                }
+                KeyCode::Char('d') => app_state.toggle_dry_run(),
+                KeyCode::Char('y') => {
+                    let dir = app_state.get_active_tab().current_dir.to_string_lossy().into_owned();
+                    copy_to_system_clipboard(&dir);
+                    app_state.notification = Some(format!("Copied {}", dir));
+                    app_state.notification_timer = Some(std::time::Instant::now());
+                }
+                KeyCode::Char('r') => {
+                    app_state.start_remote_connect();
+                    return true;
+                }
                 KeyCode::Char('o') => {
                     if let Some(path) = app_state.get_active_tab().get_selected_entry_path() {
                         if let Ok(metadata) = std::fs::metadata(&path) {
@@ -453,12 +901,124 @@ pub fn handle_key_press(key: KeyEvent, app_state: &mut AppState) -> bool {
             }
             app_state.input_mode = InputMode::Normal;
         }
-        InputMode::Chmod | InputMode::Chown | InputMode::Archive => {
+        InputMode::CommandPalette => {
+            match key.code {
+                KeyCode::Esc => app_state.input_mode = InputMode::Normal,
+                KeyCode::Down => app_state.move_command_palette_cursor(1),
+                KeyCode::Up => app_state.move_command_palette_cursor(-1),
+                KeyCode::Enter => app_state.run_custom_command(app_state.command_palette_cursor),
+                KeyCode::Char(c) => {
+                    app_state.run_custom_command_by_key(c);
+                }
+                _ => {}
+            }
+            return true;
+        }
+        InputMode::SessionPicker => {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => app_state.input_mode = InputMode::Normal,
+                KeyCode::Down | KeyCode::Char('j') => app_state.move_session_cursor(1),
+                KeyCode::Up | KeyCode::Char('k') => app_state.move_session_cursor(-1),
+                KeyCode::Enter | KeyCode::Char('l') => app_state.load_selected_session(),
+                _ => {}
+            }
+            return true;
+        }
+        InputMode::AncestorPicker => {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q' | 'H') => app_state.input_mode = InputMode::Normal,
+                KeyCode::Down | KeyCode::Char('j') => app_state.move_ancestor_cursor(1),
+                KeyCode::Up | KeyCode::Char('k') => app_state.move_ancestor_cursor(-1),
+                KeyCode::Enter | KeyCode::Char('l') => app_state.jump_to_ancestor(app_state.ancestor_cursor),
+                // Цифра сразу переходит на столько уровней вверх
+                KeyCode::Char(digit @ '1'..='9') => app_state.jump_to_ancestor(digit as usize - '1' as usize),
+                _ => {}
+            }
+            return true;
+        }
+        InputMode::Help => {
+            // Пока фильтр пуст, j/k/q/? управляют списком, а остальные буквы
+            // начинают фильтр; с непустым фильтром все буквы идут в него
+            let filtering = !app_state.help_filter.is_empty();
+            let delta = match key.code {
+                KeyCode::Esc if filtering => {
+                    app_state.help_filter.clear();
+                    app_state.help_scroll = 0;
+                    return true;
+                }
+                KeyCode::Esc => {
+                    app_state.input_mode = InputMode::Normal;
+                    return true;
+                }
+                KeyCode::Char('?' | 'q') if !filtering => {
+                    app_state.input_mode = InputMode::Normal;
+                    return true;
+                }
+                KeyCode::Char('j') if !filtering => 1,
+                KeyCode::Char('k') if !filtering => -1,
+                KeyCode::Down => 1,
+                KeyCode::Up => -1,
+                KeyCode::PageDown => 10,
+                KeyCode::PageUp => -10,
+                KeyCode::Char(c) => {
+                    app_state.help_filter.push(c);
+                    app_state.help_scroll = 0;
+                    return true;
+                }
+                KeyCode::Backspace => {
+                    app_state.help_filter.pop();
+                    app_state.help_scroll = 0;
+                    return true;
+                }
+                _ => return true,
+            };
+            let last = hotkeys::help_rows(app_state).len().saturating_sub(1);
+            app_state.help_scroll = app_state.help_scroll.saturating_add_signed(delta).min(last);
+            return true;
+        }
+        InputMode::Analyze => {
+            match key.code {
+                KeyCode::Char('u') => app_state.start_disk_usage(),
+                _ => app_state.input_mode = InputMode::Normal,
+            }
+            return true;
+        }
+        InputMode::BookmarkJump => {
+            app_state.input_mode = InputMode::Normal;
+            if let KeyCode::Char(c) = key.code {
+                app_state.jump_to_bookmark(c);
+            }
+            return true;
+        }
+        InputMode::Chmod
+        | InputMode::Chown
+        | InputMode::Archive
+        | InputMode::TabName
+        | InputMode::Bookmark
+        | InputMode::PreviewCommand
+        | InputMode::ShellCommand
+        | InputMode::GlobFilter
+        | InputMode::SessionName
+        | InputMode::RemoteConnect
+        | InputMode::PluginInstall => {
             // Handled by the `show_input_dialog` block
         }
+        InputMode::Setup => {
+            match key.code {
+                KeyCode::Down | KeyCode::Char('j') => app_state.move_setup_cursor(1),
+                KeyCode::Up | KeyCode::Char('k') => app_state.move_setup_cursor(-1),
+                KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('l') => app_state.change_setup_item(),
+                KeyCode::Esc => app_state.finish_setup(),
+                _ => {}
+            }
+            return true;
+        }
         InputMode::Settings => {
-            // Обработка клавиш в режиме настроек
-            crate::settings::handle_settings_key(key.code, app_state);
+            // Обработка клавиш в режиме настроек; пока открыт вопрос об
+            // удалении плагина, клавиши достаются только ему
+            if !app_state.show_confirmation {
+                crate::settings::handle_settings_key(key.code, app_state);
+            }
             return true;
         }
     }
@@ -1,13 +1,45 @@
 use std::path::Path;
 use walkdir::WalkDir;
 
+/// A file or directory met while walking a tree. Only files have a size,
+/// directories and symlinks count as zero.
+#[derive(Debug, Clone, Copy)]
+pub struct WalkedEntry<'a> {
+    pub path: &'a Path,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
 pub fn get_directory_size(path: &Path) -> u64 {
-    WalkDir::new(path)
-        .min_depth(1)
-        .into_iter()
-        .filter_map(|entry| entry.ok())
-        .filter_map(|entry| entry.metadata().ok())
-        .filter(|metadata| metadata.is_file())
-        .map(|metadata| metadata.len())
-        .sum()
+    get_directory_size_until(path, || false).unwrap_or_default()
+}
+
+/// Like `get_directory_size`, but gives up and returns `None` as soon as
+/// `cancelled` reports true. It is checked once per visited entry.
+pub fn get_directory_size_until(path: &Path, cancelled: impl Fn() -> bool) -> Option<u64> {
+    walk_directory_size(path, cancelled, |_, _| {})
+}
+
+/// Walks the tree under `path` parents first and calls `visit` with every
+/// entry below it and the total counted so far, so callers can show
+/// progress or build their own per-directory sums. Returns the total, or
+/// `None` once `cancelled` reports true.
+pub fn walk_directory_size(
+    path: &Path,
+    cancelled: impl Fn() -> bool,
+    mut visit: impl FnMut(WalkedEntry, u64),
+) -> Option<u64> {
+    let mut total = 0;
+    for entry in WalkDir::new(path).min_depth(1).into_iter().filter_map(|entry| entry.ok()) {
+        if cancelled() {
+            return None;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let size = if metadata.is_file() { metadata.len() } else { 0 };
+        total += size;
+        visit(WalkedEntry { path: entry.path(), is_dir: metadata.is_dir(), size }, total);
+    }
+    Some(total)
 }
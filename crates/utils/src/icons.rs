@@ -10,11 +10,85 @@ pub enum IconColor {
     Gray,
 }
 
+/// Broad file category used to tint names in the listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileCategory {
+    Directory,
+    Symlink,
+    Executable,
+    Archive,
+    Image,
+    Audio,
+    Video,
+    Document,
+    Other,
+}
+
+impl FileCategory {
+    /// Key used for per-category overrides in `config.toml`
+    pub fn key(&self) -> &'static str {
+        match self {
+            FileCategory::Directory => "directory",
+            FileCategory::Symlink => "symlink",
+            FileCategory::Executable => "executable",
+            FileCategory::Archive => "archive",
+            FileCategory::Image => "image",
+            FileCategory::Audio => "audio",
+            FileCategory::Video => "video",
+            FileCategory::Document => "document",
+            FileCategory::Other => "other",
+        }
+    }
+
+    pub fn all() -> [FileCategory; 9] {
+        [
+            FileCategory::Directory,
+            FileCategory::Symlink,
+            FileCategory::Executable,
+            FileCategory::Archive,
+            FileCategory::Image,
+            FileCategory::Audio,
+            FileCategory::Video,
+            FileCategory::Document,
+            FileCategory::Other,
+        ]
+    }
+}
+
+/// Text after the last dot of `name`, or `None` when there is no dot, so a
+/// file called `zip` is not taken for an archive. Dotfiles such as
+/// `.gitignore` count the part after the dot as the extension.
+fn extension(name: &str) -> Option<&str> {
+    name.rsplit_once('.').map(|(_, extension)| extension)
+}
+
+/// Classifies a file. Symlinks take precedence, executables are detected by
+/// permission bit, everything else by extension.
+pub fn get_file_category(name: &str, is_dir: bool, is_executable: bool, is_symlink: bool) -> FileCategory {
+    if is_symlink {
+        return FileCategory::Symlink;
+    }
+    if is_dir {
+        return FileCategory::Directory;
+    }
+    if is_executable {
+        return FileCategory::Executable;
+    }
+    match extension(name).map(|ext| ext.to_lowercase()).as_deref() {
+        Some("zip") | Some("rar") | Some("7z") | Some("tar") | Some("gz") | Some("bz2") | Some("xz") | Some("tgz") => FileCategory::Archive,
+        Some("png") | Some("jpg") | Some("jpeg") | Some("gif") | Some("webp") | Some("ico") | Some("svg") => FileCategory::Image,
+        Some("mp3") | Some("wav") | Some("flac") | Some("ogg") => FileCategory::Audio,
+        Some("mp4") | Some("avi") | Some("mkv") | Some("mov") | Some("webm") => FileCategory::Video,
+        Some("pdf") | Some("md") | Some("txt") | Some("doc") | Some("docx") | Some("odt") => FileCategory::Document,
+        _ => FileCategory::Other,
+    }
+}
+
 pub fn get_icon_for_file(name: &str, is_dir: bool) -> &'static str {
     if is_dir {
         return ""; // Folder icon
     }
-    match name.split('.').last() {
+    match extension(name) {
         Some("rs") => "",   // Rust
         Some("js") => "",   // JavaScript
         Some("html") => "", // HTML
@@ -36,11 +110,16 @@ pub fn get_icon_for_file(name: &str, is_dir: bool) -> &'static str {
     }
 }
 
+pub const SYMLINK_ICON: &str = "\u{f0c1}";
+
+/// Marker after the name of a pinned directory
+pub const PIN_ICON: &str = "\u{f08d}";
+
 pub fn get_color_for_file(name: &str, is_dir: bool) -> IconColor {
     if is_dir {
         return IconColor::Blue;
     }
-    match name.split('.').last() {
+    match extension(name) {
         Some("rs") => IconColor::Rgb(220, 100, 80),   // Rust
         Some("js") => IconColor::Rgb(240, 220, 130),  // JavaScript
         Some("html") => IconColor::Rgb(227, 79, 38), // HTML
@@ -61,3 +140,20 @@ pub fn get_color_for_file(name: &str, is_dir: bool) -> IconColor {
         _ => IconColor::Gray,           // Default file
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_real_extensions_are_classified() {
+        assert_eq!(get_file_category("backup.ZIP", false, false, false), FileCategory::Archive);
+        assert_eq!(get_file_category("song.mp3", false, false, false), FileCategory::Audio);
+        for name in ["zip", "tar", "mp3", "rs"] {
+            assert_eq!(get_file_category(name, false, false, false), FileCategory::Other, "{}", name);
+            assert_eq!(get_icon_for_file(name, false), get_icon_for_file("README", false), "{}", name);
+        }
+        assert_eq!(get_icon_for_file("main.rs", false), get_icon_for_file("lib.rs", false));
+        assert_ne!(get_icon_for_file("main.rs", false), get_icon_for_file("rs", false));
+    }
+}
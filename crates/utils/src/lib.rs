@@ -1,5 +1,6 @@
 pub mod icons;
 pub mod fs;
+pub mod base64;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right